@@ -0,0 +1,55 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use shulkerscript_cli::testing::TestProject;
+
+/// Create a project with the number of scripts, each containing a few functions.
+fn project(scripts: usize) -> TestProject {
+    let project = TestProject::init("bench");
+    for index in 0..scripts {
+        project.write(
+            format!("src/module_{index}.shu"),
+            format!(
+                r#"namespace "bench";
+
+fn function_{index}_a() {{
+    /say a
+    /scoreboard players add @s bench 1
+}}
+
+fn function_{index}_b() {{
+    /say b
+    function_{index}_a();
+}}
+
+#[tick]
+fn tick_{index}() {{
+    function_{index}_b();
+}}
+"#
+            ),
+        );
+    }
+    project
+}
+
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    group.sample_size(20);
+    for scripts in [1, 10, 100] {
+        let project = project(scripts);
+        group.throughput(Throughput::Elements(scripts as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(scripts),
+            &project,
+            |b, project| {
+                b.iter(|| {
+                    let output = project.run(["build"]);
+                    assert!(output.success, "{output:?}");
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, build);
+criterion_main!(benches);
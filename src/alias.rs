@@ -0,0 +1,327 @@
+//! Expansion of command aliases defined in the `[alias]` section of the pack.toml file or the
+//! global config.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use clap::CommandFactory as _;
+
+use crate::{
+    cli::Args,
+    config::{AliasConfig, GlobalConfig},
+    error::Error,
+    terminal_output::{print_error, print_warning},
+    util,
+    workspace::Workspace,
+};
+
+/// Expand an alias used as subcommand in the command line arguments.
+///
+/// Returns the command lines to run one after another, which is just the given arguments if the
+/// subcommand is not an alias. Built-in subcommands cannot be overridden by aliases.
+///
+/// The aliases are looked up in the project targeted by the first positional argument after the
+/// alias, which is resolved against the base directory.
+pub fn expand_args(args: Vec<OsString>, base: &Path) -> Result<Vec<Vec<OsString>>> {
+    match subcommand_position(&args).and_then(|position| args[position].to_str()) {
+        Some(name) if !is_builtin(name) => {
+            let aliases = load_aliases(&target_path(&args, base), discovers(&args));
+            expand(args, &aliases, is_builtin)
+        }
+        _ => Ok(vec![args]),
+    }
+}
+
+/// Load the aliases of the global config, the workspace and the project containing the path.
+///
+/// Aliases of the project take precedence over those of the workspace, which take precedence over
+/// the global ones.
+fn load_aliases(path: &Path, discover: bool) -> BTreeMap<String, AliasConfig> {
+    let mut aliases = match GlobalConfig::load() {
+        Ok(config) => config.alias,
+        Err(err) => {
+            print_warning(format!("Could not read the global config: {err}"));
+            BTreeMap::new()
+        }
+    };
+
+    let workspace_root = Workspace::find_root(path, discover);
+    let project_root = util::get_project_path(path, discover);
+    for root in [workspace_root, project_root].into_iter().flatten() {
+        aliases.extend(read_aliases(&root.join("pack.toml")));
+    }
+
+    aliases
+}
+
+/// Read the `[alias]` section of a pack.toml file.
+fn read_aliases(toml_path: &Path) -> BTreeMap<String, AliasConfig> {
+    fs::read_to_string(toml_path)
+        .ok()
+        .and_then(|content| toml::from_str::<toml::Table>(&content).ok())
+        .and_then(|mut table| table.remove("alias"))
+        .and_then(|alias| alias.try_into().ok())
+        .unwrap_or_default()
+}
+
+fn is_builtin(name: &str) -> bool {
+    name == "help" || Args::command().find_subcommand(name).is_some()
+}
+
+/// Get the position of the subcommand, which is the first argument that is neither an option
+/// nor the value of a global option.
+pub(crate) fn subcommand_position(args: &[OsString]) -> Option<usize> {
+    let command = Args::command();
+    let mut args = args.iter().enumerate().skip(1);
+    while let Some((position, arg)) = args.next() {
+        let arg = arg.to_string_lossy();
+        if !arg.starts_with('-') {
+            return Some(position);
+        }
+        if takes_separate_value(&command, &arg) {
+            args.next();
+        }
+    }
+    None
+}
+
+/// Get the path targeted by the command line, which is the first positional argument of the
+/// (nested) subcommand resolved against the base, or the base itself without one.
+pub(crate) fn target_path(args: &[OsString], base: &Path) -> PathBuf {
+    let root = Args::command();
+    let Some(position) = subcommand_position(args) else {
+        return base.to_path_buf();
+    };
+    let mut command = args[position]
+        .to_str()
+        .and_then(|name| root.find_subcommand(name))
+        .unwrap_or(&root);
+    let mut rest = args[position + 1..].iter();
+    while let Some(arg) = rest.next() {
+        let arg = arg.to_string_lossy();
+        if arg == "--" {
+            break;
+        }
+        if arg.starts_with('-') {
+            if takes_separate_value(command, &arg) || takes_separate_value(&root, &arg) {
+                rest.next();
+            }
+            continue;
+        }
+        match command.find_subcommand(arg.as_ref()) {
+            Some(subcommand) => command = subcommand,
+            None => return base.join(arg.as_ref()),
+        }
+    }
+    base.to_path_buf()
+}
+
+/// Check whether projects are discovered in the parent directories, i.e. neither
+/// `--no-discover` nor its environment variable is given.
+pub(crate) fn discovers(args: &[OsString]) -> bool {
+    let falsy = |value: &OsString| {
+        ["", "0", "n", "no", "f", "false", "off"]
+            .iter()
+            .any(|falsy| value.eq_ignore_ascii_case(falsy))
+    };
+    !args.iter().any(|arg| arg == "--no-discover")
+        && env::var_os("SHULKERSCRIPT_NO_DISCOVER").is_none_or(|value| falsy(&value))
+}
+
+/// Check whether the option is followed by its value as separate argument, e.g.
+/// `--message-format json`.
+fn takes_separate_value(command: &clap::Command, option: &str) -> bool {
+    let takes_value = |arg: &clap::Arg| {
+        arg.get_action().takes_values()
+            && !arg.is_require_equals_set()
+            && arg
+                .get_num_args()
+                .is_none_or(|range| range.min_values() > 0)
+    };
+
+    if let Some(long) = option.strip_prefix("--") {
+        !long.contains('=')
+            && command
+                .get_arguments()
+                .find(|arg| arg.get_long() == Some(long))
+                .is_some_and(takes_value)
+    } else {
+        // in a group of short flags, the rest of the group after the first option taking a value
+        // is its value
+        let shorts = option.chars().skip(1).collect::<Vec<_>>();
+        shorts
+            .iter()
+            .position(|&short| {
+                command
+                    .get_arguments()
+                    .find(|arg| arg.get_short() == Some(short))
+                    .is_some_and(takes_value)
+            })
+            .is_some_and(|position| position + 1 == shorts.len())
+    }
+}
+
+fn expand<F>(
+    args: Vec<OsString>,
+    aliases: &BTreeMap<String, AliasConfig>,
+    is_builtin: F,
+) -> Result<Vec<Vec<OsString>>>
+where
+    F: Fn(&str) -> bool + Copy,
+{
+    expand_recursive(args, aliases, is_builtin, &mut Vec::new())
+}
+
+fn expand_recursive<F>(
+    args: Vec<OsString>,
+    aliases: &BTreeMap<String, AliasConfig>,
+    is_builtin: F,
+    expanding: &mut Vec<String>,
+) -> Result<Vec<Vec<OsString>>>
+where
+    F: Fn(&str) -> bool + Copy,
+{
+    let Some(position) = subcommand_position(&args) else {
+        return Ok(vec![args]);
+    };
+    let Some((name, alias)) = args[position]
+        .to_str()
+        .filter(|name| !is_builtin(name))
+        .and_then(|name| aliases.get_key_value(name))
+    else {
+        return Ok(vec![args]);
+    };
+
+    let commands = alias.commands();
+    if commands.is_empty() || expanding.contains(name) {
+        print_error(format!(
+            "The alias {name} is empty or refers to itself, check the [alias] section."
+        ));
+        Err(Error::InvalidAliasError(name.clone()))?;
+    }
+
+    expanding.push(name.clone());
+    let last = commands.len() - 1;
+    let mut expanded = Vec::new();
+    for (index, command) in commands.into_iter().enumerate() {
+        let mut command_args = args[..position].to_vec();
+        command_args.extend(command.into_iter().map(OsString::from));
+        if index == last {
+            command_args.extend(args[position + 1..].iter().cloned());
+        }
+        expanded.extend(expand_recursive(
+            command_args,
+            aliases,
+            is_builtin,
+            expanding,
+        )?);
+    }
+    expanding.pop();
+
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    fn args(line: &str) -> Vec<OsString> {
+        line.split_whitespace().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_expand() {
+        let aliases = BTreeMap::from([
+            (
+                "release".to_string(),
+                AliasConfig::Command("build --zip --release".to_string()),
+            ),
+            (
+                "ci".to_string(),
+                AliasConfig::Chain(vec!["clean".to_string(), "release".to_string()]),
+            ),
+            ("loop".to_string(), AliasConfig::Command("loop".to_string())),
+        ]);
+        let is_builtin = |name: &str| matches!(name, "build" | "clean");
+
+        assert_eq!(
+            expand(args("shu --trace release ."), &aliases, is_builtin).unwrap(),
+            vec![args("shu --trace build --zip --release .")]
+        );
+        assert_eq!(
+            expand(
+                args("shu --message-format json release ."),
+                &aliases,
+                is_builtin
+            )
+            .unwrap(),
+            vec![args("shu --message-format json build --zip --release .")]
+        );
+        assert_eq!(
+            expand(args("shu --trace=debug release"), &aliases, is_builtin).unwrap(),
+            vec![args("shu --trace=debug build --zip --release")]
+        );
+        assert_eq!(
+            expand(args("shu ci ."), &aliases, is_builtin).unwrap(),
+            vec![args("shu clean"), args("shu build --zip --release .")]
+        );
+        assert_eq!(
+            expand(args("shu build ."), &aliases, is_builtin).unwrap(),
+            vec![args("shu build .")]
+        );
+        assert!(expand(args("shu loop"), &aliases, is_builtin).is_err());
+    }
+
+    #[test]
+    fn test_expand_args_of_target() {
+        let project = TestProject::init("aliased");
+        let pack_toml = project.read("pack.toml").unwrap();
+        project.write(
+            "pack.toml",
+            format!("{pack_toml}\n[alias]\nrelease = \"build --zip\"\n"),
+        );
+        let path = project.path().display();
+
+        assert_eq!(
+            expand_args(args(&format!("shu release {path}")), Path::new(".")).unwrap(),
+            vec![args(&format!("shu build --zip {path}"))]
+        );
+        assert_eq!(
+            expand_args(args("shu release"), project.path()).unwrap(),
+            vec![args("shu build --zip")]
+        );
+    }
+
+    #[test]
+    fn test_target_path() {
+        let base = Path::new("base");
+
+        assert_eq!(target_path(&args("shu build"), base), base);
+        assert_eq!(
+            target_path(&args("shu build -o dist project"), base),
+            base.join("project")
+        );
+        assert_eq!(
+            target_path(
+                &args("shu --message-format json build --message-format json project"),
+                base
+            ),
+            base.join("project")
+        );
+        assert_eq!(
+            target_path(&args("shu config fmt project"), base),
+            base.join("project")
+        );
+        assert_eq!(
+            target_path(&args("shu release project"), base),
+            base.join("project")
+        );
+    }
+}
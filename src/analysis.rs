@@ -0,0 +1,733 @@
+//! Static analysis of the compiled datapack.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use shulkerscript::shulkerbox::virtual_fs::VFolder;
+
+use crate::optimize::{function_id, is_generated, text};
+
+/// The estimated number of commands run by the functions of a function tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandEstimate {
+    /// The total number of commands run by all functions of the tag.
+    pub total: usize,
+    /// The functions of the tag with the number of commands they run, most expensive first.
+    pub functions: Vec<(String, usize)>,
+    /// Whether a recursive function call was found, making the estimate a lower bound.
+    pub recursive: bool,
+}
+
+/// The functions and function tags of a datapack.
+struct Functions {
+    bodies: BTreeMap<String, Vec<String>>,
+    tags: BTreeMap<String, Vec<String>>,
+}
+
+impl Functions {
+    fn new(folder: &VFolder) -> Self {
+        let mut bodies = BTreeMap::new();
+        let mut tags = BTreeMap::new();
+        for (path, file) in folder.flatten() {
+            if let Some(id) = function_id(&path) {
+                let lines = text(file)
+                    .unwrap_or_default()
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string)
+                    .collect();
+                bodies.insert(id, lines);
+            } else if let Some(tag) = function_tag_id(&path) {
+                let values = serde_json::from_slice::<serde_json::Value>(file.as_bytes())
+                    .ok()
+                    .and_then(|json| json.get("values").cloned())
+                    .and_then(|values| values.as_array().cloned())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|value| match value {
+                        serde_json::Value::String(id) => Some(id),
+                        serde_json::Value::Object(entry) => entry
+                            .get("id")
+                            .and_then(|id| id.as_str())
+                            .map(str::to_string),
+                        _ => None,
+                    })
+                    .collect();
+                tags.insert(tag, values);
+            }
+        }
+
+        Self { bodies, tags }
+    }
+
+    /// Get the functions referenced by a function id or `#tag` reference.
+    fn resolve(&self, reference: &str) -> Vec<String> {
+        let mut resolved = Vec::new();
+        let mut visited = BTreeSet::new();
+        self.resolve_into(reference, &mut resolved, &mut visited);
+        resolved
+    }
+
+    fn resolve_into(
+        &self,
+        reference: &str,
+        resolved: &mut Vec<String>,
+        visited: &mut BTreeSet<String>,
+    ) {
+        match reference.strip_prefix('#') {
+            Some(tag) => {
+                if !visited.insert(tag.to_string()) {
+                    return;
+                }
+                for value in self.tags.get(&normalize(tag)).into_iter().flatten() {
+                    self.resolve_into(value, resolved, visited);
+                }
+            }
+            None => resolved.push(normalize(reference)),
+        }
+    }
+
+    /// Count the commands run by a function, including the commands of called functions.
+    fn count(
+        &self,
+        id: &str,
+        memo: &mut BTreeMap<String, usize>,
+        stack: &mut Vec<String>,
+        recursive: &mut bool,
+    ) -> usize {
+        if let Some(count) = memo.get(id) {
+            return *count;
+        }
+        if stack.iter().any(|entry| entry == id) {
+            *recursive = true;
+            return 0;
+        }
+        let Some(lines) = self.bodies.get(id) else {
+            return 0;
+        };
+
+        stack.push(id.to_string());
+        let mut count = 0;
+        for line in lines {
+            count += 1;
+            for callee in called_functions(line) {
+                for function in self.resolve(callee) {
+                    count += self.count(&function, memo, stack, recursive);
+                }
+            }
+        }
+        stack.pop();
+
+        memo.insert(id.to_string(), count);
+        count
+    }
+}
+
+/// Estimate the number of commands run by the functions of a function tag, e.g. `minecraft:tick`.
+///
+/// Calls of functions and function tags are followed, scheduled functions are not counted.
+/// Returns `None` if the tag does not exist.
+pub fn estimate_commands(folder: &VFolder, tag: &str) -> Option<CommandEstimate> {
+    let functions = Functions::new(folder);
+    if !functions.tags.contains_key(tag) {
+        return None;
+    }
+
+    let mut memo = BTreeMap::new();
+    let mut recursive = false;
+    let mut counts = functions
+        .resolve(&format!("#{tag}"))
+        .into_iter()
+        .map(|function| {
+            let count = functions.count(&function, &mut memo, &mut Vec::new(), &mut recursive);
+            (function, count)
+        })
+        .collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    Some(CommandEstimate {
+        total: counts.iter().map(|(_, count)| count).sum(),
+        functions: counts,
+        recursive,
+    })
+}
+
+/// The scoreboard objectives and data storages used by the commands of a datapack.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// The names of the used scoreboard objectives.
+    pub objectives: BTreeSet<String>,
+    /// The ids (`namespace:path`) of the used data storages.
+    pub storages: BTreeSet<String>,
+}
+
+impl ResourceUsage {
+    /// Get the used objectives and storages that are not prefixed with one of the namespaces.
+    pub fn unprefixed<'a>(&'a self, namespaces: &[&str]) -> (Vec<&'a str>, Vec<&'a str>) {
+        let objectives = self
+            .objectives
+            .iter()
+            .filter(|objective| {
+                !namespaces
+                    .iter()
+                    .any(|namespace| objective.starts_with(namespace))
+            })
+            .map(String::as_str)
+            .collect();
+        let storages = self
+            .storages
+            .iter()
+            .filter(|storage| {
+                storage
+                    .split_once(':')
+                    .is_none_or(|(namespace, _)| !namespaces.contains(&namespace))
+            })
+            .map(String::as_str)
+            .collect();
+        (objectives, storages)
+    }
+}
+
+/// Collect the scoreboard objectives and data storages used by the functions of a datapack.
+pub fn used_resources(folder: &VFolder) -> ResourceUsage {
+    let mut usage = ResourceUsage::default();
+    for (path, file) in folder.flatten() {
+        if function_id(&path).is_none() {
+            continue;
+        }
+        for line in text(file).unwrap_or_default().lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let tokens = line
+                .trim_start_matches('$')
+                .split_whitespace()
+                .collect::<Vec<_>>();
+            usage.objectives.extend(
+                objectives(&tokens)
+                    .into_iter()
+                    .filter(|objective| !objective.contains("$("))
+                    .map(str::to_string),
+            );
+            usage.storages.extend(
+                tokens
+                    .windows(2)
+                    .filter(|window| window[0] == "storage" && !window[1].contains("$("))
+                    .map(|window| normalize(window[1])),
+            );
+        }
+    }
+    usage
+}
+
+/// A broken reference between the functions and function tags of a datapack.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FunctionTagIssue {
+    /// A function tag or a function references a function that does not exist.
+    MissingFunction {
+        output_path: String,
+        function: String,
+    },
+    /// A function tag or a function references a function tag that does not exist.
+    MissingTag { output_path: String, tag: String },
+    /// A `tick` or `load` function is neither in the function tag of the same name nor run by the
+    /// functions of the `minecraft:tick` and `minecraft:load` tags.
+    NotRun {
+        output_path: String,
+        function: String,
+        tag: String,
+    },
+}
+
+/// Get the functions run by a function tag, `None` if the tag does not exist.
+pub fn tag_functions(folder: &VFolder, tag: &str) -> Option<Vec<String>> {
+    let functions = Functions::new(folder);
+    functions
+        .tags
+        .contains_key(tag)
+        .then(|| functions.resolve(&format!("#{tag}")))
+}
+
+/// Find the references of function tags and functions to missing functions and function tags,
+/// and the `tick` and `load` functions that are never run.
+///
+/// Only references to namespaces with functions or function tags in the datapack are checked, as
+/// the others may be provided by other packs, as well as the vanilla tags of the `minecraft`
+/// namespace. Entries of tags with `"required": false` are allowed to be missing.
+pub fn function_tag_issues(folder: &VFolder) -> Vec<FunctionTagIssue> {
+    let functions = Functions::new(folder);
+    let namespaces = functions
+        .bodies
+        .keys()
+        .chain(functions.tags.keys())
+        .filter_map(|id| id.split_once(':').map(|(namespace, _)| namespace))
+        .collect::<BTreeSet<_>>();
+
+    let mut issues = BTreeSet::new();
+    let mut function_paths = BTreeMap::new();
+    for (path, file) in folder.flatten() {
+        let references = if let Some(id) = function_id(&path) {
+            function_paths.insert(id, path.clone());
+            text(file)
+                .unwrap_or_default()
+                .lines()
+                .map(str::trim)
+                // references of macro lines are only known when they run
+                .filter(|line| !line.starts_with('#') && !line.starts_with('$'))
+                .flat_map(referenced_functions)
+                .filter(|reference| reference.contains(':'))
+                .map(str::to_string)
+                .collect()
+        } else if function_tag_id(&path).is_some() {
+            required_tag_values(file.as_bytes())
+        } else {
+            continue;
+        };
+
+        for reference in references {
+            let (is_tag, id) = match reference.strip_prefix('#') {
+                Some(tag) => (true, normalize(tag)),
+                None => (false, normalize(&reference)),
+            };
+            let namespace = id.split_once(':').map_or("", |(namespace, _)| namespace);
+            if !namespaces.contains(namespace) || (is_tag && namespace == "minecraft") {
+                continue;
+            }
+            if is_tag && !functions.tags.contains_key(&id) {
+                issues.insert(FunctionTagIssue::MissingTag {
+                    output_path: path.clone(),
+                    tag: id,
+                });
+            } else if !is_tag && !functions.bodies.contains_key(&id) {
+                issues.insert(FunctionTagIssue::MissingFunction {
+                    output_path: path.clone(),
+                    function: id,
+                });
+            }
+        }
+    }
+
+    // scheduled functions are followed, as `tick` functions are often scheduled by `load`
+    let mut run = BTreeSet::new();
+    let mut pending = functions.resolve("#minecraft:tick");
+    pending.extend(functions.resolve("#minecraft:load"));
+    while let Some(id) = pending.pop() {
+        if !run.insert(id.clone()) {
+            continue;
+        }
+        for line in functions.bodies.get(&id).into_iter().flatten() {
+            for reference in referenced_functions(line) {
+                pending.extend(functions.resolve(reference));
+            }
+        }
+    }
+    for (id, output_path) in function_paths {
+        let name = id.rsplit(['/', ':']).next().unwrap_or_default();
+        if matches!(name, "tick" | "load") && !is_generated(&id) && !run.contains(&id) {
+            issues.insert(FunctionTagIssue::NotRun {
+                output_path,
+                tag: format!("minecraft:{name}"),
+                function: id,
+            });
+        }
+    }
+
+    issues.into_iter().collect()
+}
+
+/// A conflict between a datapack and another datapack installed in the same world.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PackConflict {
+    /// Both datapacks contain files in the namespace.
+    Namespace(String),
+    /// Both datapacks define the function tag and at least one of them replaces the entries of
+    /// the other, or the tag is not a shared tag of the `minecraft` namespace.
+    FunctionTag { tag: String, replace: bool },
+    /// Both datapacks use the scoreboard objective.
+    Objective(String),
+}
+
+/// Find the conflicts of a datapack with another datapack.
+///
+/// The `minecraft` namespace is shared by all datapacks, its function tags only conflict if one
+/// of the datapacks replaces the entries of the other.
+pub fn pack_conflicts(pack: &VFolder, other: &VFolder) -> Vec<PackConflict> {
+    let mut conflicts = BTreeSet::new();
+
+    let (namespaces, other_namespaces) = (data_namespaces(pack), data_namespaces(other));
+    conflicts.extend(
+        namespaces
+            .intersection(&other_namespaces)
+            .filter(|namespace| *namespace != "minecraft")
+            .map(|namespace| PackConflict::Namespace(namespace.clone())),
+    );
+
+    let (tags, other_tags) = (function_tags(pack), function_tags(other));
+    for (tag, replace) in &tags {
+        if let Some(other_replace) = other_tags.get(tag) {
+            let replace = *replace || *other_replace;
+            if replace || !tag.starts_with("minecraft:") {
+                conflicts.insert(PackConflict::FunctionTag {
+                    tag: tag.clone(),
+                    replace,
+                });
+            }
+        }
+    }
+
+    conflicts.extend(
+        used_resources(pack)
+            .objectives
+            .intersection(&used_resources(other).objectives)
+            .map(|objective| PackConflict::Objective(objective.clone())),
+    );
+
+    conflicts.into_iter().collect()
+}
+
+/// Get the namespaces of the `data` folder of a datapack.
+fn data_namespaces(folder: &VFolder) -> BTreeSet<String> {
+    folder
+        .flatten()
+        .into_iter()
+        .filter_map(|(path, _)| {
+            let mut parts = path.splitn(3, '/');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("data"), Some(namespace), Some(_)) => Some(namespace.to_string()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Get the function tags of a datapack and whether they replace the entries of other datapacks.
+fn function_tags(folder: &VFolder) -> BTreeMap<String, bool> {
+    folder
+        .flatten()
+        .into_iter()
+        .filter_map(|(path, file)| {
+            let tag = function_tag_id(&path)?;
+            let replace = serde_json::from_slice::<serde_json::Value>(file.as_bytes())
+                .ok()
+                .and_then(|json| json.get("replace").and_then(|replace| replace.as_bool()))
+                .unwrap_or(false);
+            Some((tag, replace))
+        })
+        .collect()
+}
+
+/// Get the values of a tag file that are required to exist.
+fn required_tag_values(content: &[u8]) -> Vec<String> {
+    serde_json::from_slice::<serde_json::Value>(content)
+        .ok()
+        .and_then(|json| json.get("values").cloned())
+        .and_then(|values| values.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|value| match value {
+            serde_json::Value::String(id) => Some(id),
+            serde_json::Value::Object(entry)
+                if entry
+                    .get("required")
+                    .and_then(|required| required.as_bool())
+                    != Some(false) =>
+            {
+                entry
+                    .get("id")
+                    .and_then(|id| id.as_str())
+                    .map(str::to_string)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Get the scoreboard objectives referenced by the tokens of a command.
+fn objectives<'a>(tokens: &[&'a str]) -> Vec<&'a str> {
+    let token = |index: usize| tokens.get(index).copied();
+    let mut objectives = Vec::new();
+    for (index, current) in tokens.iter().enumerate() {
+        match *current {
+            "scoreboard" => match (token(index + 1), token(index + 2)) {
+                (Some("objectives"), Some("add" | "remove" | "modify")) => {
+                    objectives.extend(token(index + 3));
+                }
+                (Some("objectives"), Some("setdisplay")) => objectives.extend(token(index + 4)),
+                (Some("players"), Some("set" | "add" | "remove" | "get" | "enable" | "reset")) => {
+                    objectives.extend(token(index + 4))
+                }
+                (Some("players"), Some("operation")) => {
+                    objectives.extend(token(index + 4));
+                    objectives.extend(token(index + 7));
+                }
+                (Some("players"), Some("display")) => objectives.extend(token(index + 5)),
+                _ => {}
+            },
+            "score" if index > 0 => match tokens[index - 1] {
+                "result" | "success" => objectives.extend(token(index + 2)),
+                "if" | "unless" => {
+                    objectives.extend(token(index + 2));
+                    if token(index + 3).is_some_and(|operator| operator != "matches") {
+                        objectives.extend(token(index + 5));
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    objectives
+}
+
+/// Get the id of a function tag file path in the datapack.
+fn function_tag_id(path: &str) -> Option<String> {
+    let mut parts = path.splitn(5, '/');
+    match (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) {
+        (
+            Some("data"),
+            Some(namespace),
+            Some("tags"),
+            Some("function" | "functions"),
+            Some(tag),
+        ) => tag
+            .strip_suffix(".json")
+            .map(|tag| format!("{namespace}:{tag}")),
+        _ => None,
+    }
+}
+
+/// Add the default `minecraft` namespace to a resource location without namespace.
+fn normalize(id: &str) -> String {
+    if id.contains(':') {
+        id.to_string()
+    } else {
+        format!("minecraft:{id}")
+    }
+}
+
+/// Get the functions called in the current tick by a command.
+fn called_functions(line: &str) -> Vec<&str> {
+    let tokens = line
+        .trim_start_matches('$')
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    tokens
+        .windows(2)
+        .enumerate()
+        .filter(|(index, window)| {
+            window[0] == "function" && (*index == 0 || tokens[index - 1] != "schedule")
+        })
+        .map(|(_, window)| window[1].split('{').next().unwrap_or(window[1]))
+        .collect()
+}
+
+/// Get the functions and function tags referenced by a command, including scheduled ones.
+fn referenced_functions(line: &str) -> Vec<&str> {
+    line.trim_start_matches('$')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .windows(2)
+        .filter(|window| window[0] == "function")
+        .map(|window| window[1].split('{').next().unwrap_or(window[1]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use shulkerscript::shulkerbox::virtual_fs::VFile;
+
+    use super::*;
+
+    #[test]
+    fn test_estimate_commands() {
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/minecraft/tags/function/tick.json",
+            VFile::Text(r##"{"values":["test:main", "#test:extra"]}"##.to_string()),
+        );
+        folder.add_file(
+            "data/test/tags/function/extra.json",
+            VFile::Text(r#"{"values":[{"id":"test:extra","required":false}]}"#.to_string()),
+        );
+        folder.add_file(
+            "data/test/function/main.mcfunction",
+            VFile::Text(
+                "say a\nexecute if entity @p run function test:helper\nschedule function test:helper 1t\nfunction test:main"
+                    .to_string(),
+            ),
+        );
+        folder.add_file(
+            "data/test/function/helper.mcfunction",
+            VFile::Text("# comment\nsay b\nsay c".to_string()),
+        );
+        folder.add_file(
+            "data/test/function/extra.mcfunction",
+            VFile::Text("say d".to_string()),
+        );
+
+        let estimate = estimate_commands(&folder, "minecraft:tick").unwrap();
+        assert_eq!(
+            estimate.functions,
+            vec![("test:main".to_string(), 6), ("test:extra".to_string(), 1)]
+        );
+        assert_eq!(estimate.total, 7);
+        assert!(estimate.recursive);
+        assert!(estimate_commands(&folder, "minecraft:load").is_none());
+    }
+
+    #[test]
+    fn test_function_tag_issues() {
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/minecraft/tags/function/load.json",
+            VFile::Text(
+                r##"{"values":["test:load", "test:missing", "#test:missing", {"id":"other:optional","required":false}, "other:foo"]}"##
+                    .to_string(),
+            ),
+        );
+        folder.add_file(
+            "data/test/function/load.mcfunction",
+            VFile::Text(
+                "schedule function test:loop 1t
+function #minecraft:tick"
+                    .to_string(),
+            ),
+        );
+        folder.add_file(
+            "data/test/function/loop.mcfunction",
+            VFile::Text(
+                "function test:tick
+say function foo
+$function $(name)"
+                    .to_string(),
+            ),
+        );
+        folder.add_file(
+            "data/test/function/tick.mcfunction",
+            VFile::Text("function test:gone".to_string()),
+        );
+        folder.add_file(
+            "data/test/function/lib/load.mcfunction",
+            VFile::Text("say unused".to_string()),
+        );
+
+        assert_eq!(
+            function_tag_issues(&folder),
+            vec![
+                FunctionTagIssue::MissingFunction {
+                    output_path: "data/minecraft/tags/function/load.json".to_string(),
+                    function: "test:missing".to_string(),
+                },
+                FunctionTagIssue::MissingFunction {
+                    output_path: "data/test/function/tick.mcfunction".to_string(),
+                    function: "test:gone".to_string(),
+                },
+                FunctionTagIssue::MissingTag {
+                    output_path: "data/minecraft/tags/function/load.json".to_string(),
+                    tag: "test:missing".to_string(),
+                },
+                FunctionTagIssue::NotRun {
+                    output_path: "data/test/function/lib/load.mcfunction".to_string(),
+                    function: "test:lib/load".to_string(),
+                    tag: "minecraft:load".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            tag_functions(&folder, "minecraft:load").unwrap()[..2],
+            ["test:load", "test:missing"]
+        );
+        assert!(tag_functions(&folder, "minecraft:tick").is_none());
+    }
+
+    #[test]
+    fn test_pack_conflicts() {
+        let mut pack = VFolder::new();
+        pack.add_file(
+            "data/minecraft/tags/function/load.json",
+            VFile::Text(r#"{"values":["game:load"]}"#.to_string()),
+        );
+        pack.add_file(
+            "data/minecraft/tags/function/tick.json",
+            VFile::Text(r#"{"values":["game:tick"]}"#.to_string()),
+        );
+        pack.add_file(
+            "data/game/function/load.mcfunction",
+            VFile::Text("scoreboard objectives add points dummy".to_string()),
+        );
+        pack.add_file(
+            "data/lib/tags/function/init.json",
+            VFile::Text(r#"{"values":[]}"#.to_string()),
+        );
+
+        let mut other = VFolder::new();
+        other.add_file(
+            "data/minecraft/tags/function/load.json",
+            VFile::Text(r#"{"values":["other:load"]}"#.to_string()),
+        );
+        other.add_file(
+            "data/minecraft/tags/function/tick.json",
+            VFile::Text(r#"{"replace":true,"values":["other:tick"]}"#.to_string()),
+        );
+        other.add_file(
+            "data/other/function/load.mcfunction",
+            VFile::Text("scoreboard players set #max points 10".to_string()),
+        );
+        other.add_file(
+            "data/lib/tags/function/init.json",
+            VFile::Text(r#"{"values":[]}"#.to_string()),
+        );
+
+        assert_eq!(
+            pack_conflicts(&pack, &other),
+            vec![
+                PackConflict::Namespace("lib".to_string()),
+                PackConflict::FunctionTag {
+                    tag: "lib:init".to_string(),
+                    replace: false,
+                },
+                PackConflict::FunctionTag {
+                    tag: "minecraft:tick".to_string(),
+                    replace: true,
+                },
+                PackConflict::Objective("points".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_used_resources() {
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/test/function/main.mcfunction",
+            VFile::Text(
+                "scoreboard objectives add test.points dummy\n\
+                 execute store result score @s points if data storage test:data values\n\
+                 execute if score @s test.points > #max other run data modify storage foo values set from storage test:data values\n\
+                 scoreboard players operation @s test.points += #bonus test.bonus"
+                    .to_string(),
+            ),
+        );
+
+        let usage = used_resources(&folder);
+        assert_eq!(
+            usage.objectives.iter().collect::<Vec<_>>(),
+            vec!["other", "points", "test.bonus", "test.points"]
+        );
+        assert_eq!(
+            usage.storages.iter().collect::<Vec<_>>(),
+            vec!["minecraft:foo", "test:data"]
+        );
+        assert_eq!(
+            usage.unprefixed(&["test"]),
+            (vec!["other", "points"], vec!["minecraft:foo"])
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! Running commands from other programs, e.g. graphical frontends and editor extensions.
+//!
+//! The functions run the commands on a background thread and stream their messages, compiler
+//! diagnostics and progress as typed [`Event`]s instead of printing them, so frontends do not
+//! need to parse the text output.
+//!
+//! ```no_run
+//! use shulkerscript_cli::{api::{self, Event}, subcommands::BuildArgs};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let task = api::build_project(BuildArgs {
+//!     path: "path/to/project".into(),
+//!     ..Default::default()
+//! })?;
+//! for event in task.events() {
+//!     match event {
+//!         Event::Progress { percentage, .. } => println!("{percentage}%"),
+//!         Event::Diagnostic(diagnostic) => eprintln!("{diagnostic}"),
+//!         _ => {}
+//!     }
+//! }
+//! let artifact = task.join()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Async frontends use the functions of the [`nonblocking`] module instead, which stream the
+//! events over a tokio channel.
+
+use std::{
+    io,
+    path::PathBuf,
+    sync::mpsc,
+    thread::{self, JoinHandle},
+};
+
+use anyhow::Result;
+
+use crate::{
+    cancel::CancellationToken,
+    cli::GlobalOptions,
+    subcommands::{self, BuildArgs, InitArgs},
+    terminal_output::{self, Message},
+};
+
+/// An event of a command running in a [`Task`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A message the command line would print with its level.
+    Message(Message),
+    /// A diagnostic of the compiler, e.g. a syntax error with the affected source code.
+    Diagnostic(String),
+    /// An operation with progress started.
+    ProgressBegin { token: String, title: String },
+    /// An operation reached a step, with the percentage of the operation that is done.
+    Progress {
+        token: String,
+        message: String,
+        percentage: u32,
+    },
+    /// An operation finished, with a final message if it succeeded.
+    ProgressEnd {
+        token: String,
+        message: Option<String>,
+    },
+}
+
+/// The channel the events of a thread are forwarded to, see [`terminal_output::forward`].
+#[derive(Debug, Clone)]
+pub enum EventSender {
+    /// A channel of the standard library, used by [`Task`].
+    Sync(mpsc::Sender<Event>),
+    /// A tokio channel, used by [`nonblocking::Task`].
+    Async(tokio::sync::mpsc::UnboundedSender<Event>),
+}
+
+impl EventSender {
+    pub(crate) fn send(&self, event: Event) {
+        // the receiver may have stopped listening, the event is dropped then
+        let _ = match self {
+            Self::Sync(sender) => sender.send(event).map_err(drop),
+            Self::Async(sender) => sender.send(event).map_err(drop),
+        };
+    }
+}
+
+impl From<mpsc::Sender<Event>> for EventSender {
+    fn from(sender: mpsc::Sender<Event>) -> Self {
+        Self::Sync(sender)
+    }
+}
+
+impl From<tokio::sync::mpsc::UnboundedSender<Event>> for EventSender {
+    fn from(sender: tokio::sync::mpsc::UnboundedSender<Event>) -> Self {
+        Self::Async(sender)
+    }
+}
+
+/// A command running on a background thread.
+#[derive(Debug)]
+pub struct Task<T> {
+    events: mpsc::Receiver<Event>,
+    cancel: CancellationToken,
+    thread: JoinHandle<Result<T>>,
+}
+
+impl<T: Send + 'static> Task<T> {
+    fn spawn(
+        name: &str,
+        cancel: CancellationToken,
+        f: impl FnOnce(&CancellationToken) -> Result<T> + Send + 'static,
+    ) -> io::Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let token = cancel.clone();
+        let thread = thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || terminal_output::forward(tx, || f(&token)))?;
+        Ok(Self {
+            events,
+            cancel,
+            thread,
+        })
+    }
+}
+
+impl<T> Task<T> {
+    /// Iterate over the events, blocking until the next one. Ends when the command finished.
+    pub fn events(&self) -> mpsc::Iter<'_, Event> {
+        self.events.iter()
+    }
+
+    /// Iterate over the events that are available without blocking.
+    pub fn try_events(&self) -> mpsc::TryIter<'_, Event> {
+        self.events.try_iter()
+    }
+
+    /// Cancel the command, which stops at the next checkpoint.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Whether the command finished.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Wait for the command to finish and get its result.
+    ///
+    /// # Errors
+    /// - If the command failed.
+    pub fn join(self) -> Result<T> {
+        self.thread
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("The command panicked.")))
+    }
+}
+
+/// Build the project at the path of the arguments, returning the path of the artifact.
+///
+/// Builds a single project, the workspace and remote options are ignored.
+///
+/// # Errors
+/// - If the thread cannot be spawned.
+pub fn build_project(args: BuildArgs) -> io::Result<Task<PathBuf>> {
+    Task::spawn("build", CancellationToken::new(), move |cancel| {
+        subcommands::build_artifact(&args, &GlobalOptions::default(), cancel)
+    })
+}
+
+/// Initialize a new project at the path of the arguments.
+///
+/// Runs in batch mode, as there is no terminal to prompt in.
+///
+/// # Errors
+/// - If the thread cannot be spawned.
+pub fn init_project(args: InitArgs) -> io::Result<Task<()>> {
+    let args = InitArgs {
+        batch: true,
+        ..args
+    };
+    Task::spawn("init", CancellationToken::new(), move |_| {
+        subcommands::init(&args, &GlobalOptions::default())
+    })
+}
+
+/// Watch the project of the builder until the task is cancelled.
+///
+/// Returns the controller of the watcher to trigger runs. The paths of the commands are resolved
+/// against the project root, the working directory of the process is not changed.
+///
+/// # Errors
+/// - If the watcher cannot be built, e.g. because of invalid commands.
+/// - If the thread cannot be spawned.
+#[cfg(feature = "watch")]
+pub fn watch_project(
+    builder: crate::watcher::WatcherBuilder,
+) -> Result<(Task<()>, crate::watcher::WatchController)> {
+    let cancel = CancellationToken::new();
+    let (tx, events) = mpsc::channel();
+    // messages of building the watcher are forwarded as well
+    let watcher =
+        terminal_output::forward(tx.clone(), || builder.cancellation(cancel.clone()).build())?;
+    let controller = watcher.controller();
+    let thread = thread::Builder::new()
+        .name("watcher".to_string())
+        .spawn(move || terminal_output::forward(tx, || watcher.run()))?;
+    Ok((
+        Task {
+            events,
+            cancel,
+            thread,
+        },
+        controller,
+    ))
+}
+
+/// Async variants of the functions, for frontends running on a tokio runtime.
+///
+/// The commands still run on a background thread, but their events and results are awaited
+/// instead of blocking the executor.
+pub mod nonblocking {
+    use std::{io, path::PathBuf, thread};
+
+    use anyhow::Result;
+    use tokio::sync::{mpsc, oneshot};
+
+    use super::Event;
+    use crate::{
+        cancel::CancellationToken,
+        cli::GlobalOptions,
+        subcommands::{self, BuildArgs, InitArgs},
+        terminal_output,
+    };
+
+    /// A command running on a background thread, whose events and result are awaited.
+    #[derive(Debug)]
+    pub struct Task<T> {
+        events: mpsc::UnboundedReceiver<Event>,
+        cancel: CancellationToken,
+        result: oneshot::Receiver<Result<T>>,
+    }
+
+    impl<T: Send + 'static> Task<T> {
+        fn spawn(
+            name: &str,
+            cancel: CancellationToken,
+            f: impl FnOnce(&CancellationToken) -> Result<T> + Send + 'static,
+        ) -> io::Result<Self> {
+            let (tx, events) = mpsc::unbounded_channel();
+            let (result_tx, result) = oneshot::channel();
+            let token = cancel.clone();
+            thread::Builder::new()
+                .name(name.to_string())
+                .spawn(move || {
+                    let _ = result_tx.send(terminal_output::forward(tx, || f(&token)));
+                })?;
+            Ok(Self {
+                events,
+                cancel,
+                result,
+            })
+        }
+    }
+
+    impl<T> Task<T> {
+        /// Wait for the next event, `None` once the command finished and all events are received.
+        pub async fn next_event(&mut self) -> Option<Event> {
+            self.events.recv().await
+        }
+
+        /// Get the next event if one is available without waiting.
+        pub fn try_next_event(&mut self) -> Option<Event> {
+            self.events.try_recv().ok()
+        }
+
+        /// Cancel the command, which stops at the next checkpoint.
+        pub fn cancel(&self) {
+            self.cancel.cancel();
+        }
+
+        /// Wait for the command to finish and get its result.
+        ///
+        /// # Errors
+        /// - If the command failed.
+        pub async fn join(self) -> Result<T> {
+            self.result
+                .await
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("The command panicked.")))
+        }
+    }
+
+    /// Build the project at the path of the arguments, see [`super::build_project`].
+    ///
+    /// # Errors
+    /// - If the thread cannot be spawned.
+    pub async fn build_project(args: BuildArgs) -> io::Result<Task<PathBuf>> {
+        Task::spawn("build", CancellationToken::new(), move |cancel| {
+            subcommands::build_artifact(&args, &GlobalOptions::default(), cancel)
+        })
+    }
+
+    /// Initialize a new project at the path of the arguments, see [`super::init_project`].
+    ///
+    /// # Errors
+    /// - If the thread cannot be spawned.
+    pub async fn init_project(args: InitArgs) -> io::Result<Task<()>> {
+        let args = InitArgs {
+            batch: true,
+            ..args
+        };
+        Task::spawn("init", CancellationToken::new(), move |_| {
+            subcommands::init(&args, &GlobalOptions::default())
+        })
+    }
+
+    /// Watch the project of the builder until the task is cancelled, see
+    /// [`super::watch_project`].
+    ///
+    /// The watcher is built on the background thread, so the messages of building it are
+    /// received as events of the task.
+    ///
+    /// # Errors
+    /// - If the watcher cannot be built, e.g. because of invalid commands.
+    /// - If the thread cannot be spawned.
+    #[cfg(feature = "watch")]
+    pub async fn watch_project(
+        builder: crate::watcher::WatcherBuilder,
+    ) -> Result<(Task<()>, crate::watcher::WatchController)> {
+        let cancel = CancellationToken::new();
+        let (tx, events) = mpsc::unbounded_channel();
+        let (controller_tx, controller) = oneshot::channel();
+        let (result_tx, result) = oneshot::channel();
+        let token = cancel.clone();
+        thread::Builder::new()
+            .name("watcher".to_string())
+            .spawn(move || {
+                terminal_output::forward(tx, || match builder.cancellation(token).build() {
+                    Ok(watcher) => {
+                        let _ = controller_tx.send(Ok(watcher.controller()));
+                        let _ = result_tx.send(watcher.run());
+                    }
+                    Err(err) => {
+                        let _ = controller_tx.send(Err(err));
+                    }
+                });
+            })?;
+        let controller = controller
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("The watcher panicked.")))?;
+
+        Ok((
+            Task {
+                events,
+                cancel,
+                result,
+            },
+            controller,
+        ))
+    }
+}
@@ -0,0 +1,245 @@
+//! Writing zip files of the output and reading them back.
+//!
+//! The files are streamed to the zip file through a buffer instead of building the archive in
+//! memory. Archives with more than 65535 entries get a Zip64 end of central directory and entries
+//! of 4 GiB and more are written as Zip64 entries, as large adventure maps may exceed the limits
+//! of the plain zip format.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufWriter, Read, Seek, Write},
+    path::{Component, Path},
+};
+
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+use zip::{
+    result::{ZipError, ZipResult},
+    write::SimpleFileOptions,
+    CompressionMethod, ZipArchive, ZipWriter,
+};
+
+use crate::walk::{StreamedFiles, STREAM_THRESHOLD};
+
+/// The size from which entries must be written as Zip64 entries.
+const ZIP64_SIZE: u64 = u32::MAX as u64;
+
+/// Set the options of an entry with the uncompressed size, enabling Zip64 for large entries.
+pub fn entry_options(options: SimpleFileOptions, size: u64) -> SimpleFileOptions {
+    options.large_file(size >= ZIP64_SIZE)
+}
+
+/// The default options of entries, compressed with deflate.
+pub fn default_options() -> SimpleFileOptions {
+    SimpleFileOptions::default().compression_method(CompressionMethod::Deflated)
+}
+
+/// Write the folder to a zip file at the path with the comment, omitted if it is empty.
+///
+/// Streamed files are copied from disk.
+///
+/// # Errors
+/// - If the zip file cannot be written.
+pub fn write_folder(
+    folder: &VFolder,
+    streamed: &StreamedFiles,
+    path: &Path,
+    comment: &str,
+) -> ZipResult<()> {
+    let mut writer = ZipWriter::new(BufWriter::new(File::create(path)?));
+    let mut files = folder.flatten();
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (name, file) in files {
+        if let Some(source) = streamed.source_of(folder, &name) {
+            let mut source = File::open(source)?;
+            let size = source.metadata()?.len();
+            copy_entry(&mut writer, &name, default_options(), size, &mut source)?;
+            continue;
+        }
+        let content = file.as_bytes();
+        writer.start_file(name, entry_options(default_options(), content.len() as u64))?;
+        writer.write_all(content)?;
+    }
+    finish(writer, comment)
+}
+
+/// Read the files of a zip file, e.g. of a datapack, into a folder.
+///
+/// # Errors
+/// - If the zip file cannot be read.
+/// - If the path of an entry is absolute or leaves the folder, e.g. with `../`.
+pub fn read_folder(path: &Path) -> ZipResult<VFolder> {
+    let (files, _) = read_files(path)?;
+    let mut folder = VFolder::new();
+    for (name, content) in files {
+        folder.add_file(&name, VFile::Binary(content));
+    }
+    Ok(folder)
+}
+
+/// Read the files of a zip file by their paths joined with `/`, and the comment of the zip file.
+///
+/// # Errors
+/// - If the zip file cannot be read.
+/// - If the path of an entry is absolute or leaves the archive, e.g. with `../`.
+pub fn read_files(path: &Path) -> ZipResult<(BTreeMap<String, Vec<u8>>, String)> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut files = BTreeMap::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file
+            .enclosed_name()
+            .as_deref()
+            .and_then(enclosed_path)
+            .ok_or(ZipError::InvalidArchive(
+                "Entry path is outside of the archive",
+            ))?;
+        // the size in the header is untrusted, only use it as a bounded hint
+        let mut content = Vec::with_capacity(file.size().min(STREAM_THRESHOLD) as usize);
+        file.read_to_end(&mut content)?;
+        files.insert(name, content);
+    }
+    let comment = String::from_utf8_lossy(archive.comment()).into_owned();
+    Ok((files, comment))
+}
+
+/// Join the components of an enclosed entry path with `/`, resolving `.` and `..`.
+fn enclosed_path(path: &Path) -> Option<String> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_str()?),
+            Component::ParentDir => {
+                components.pop()?;
+            }
+            Component::CurDir => {}
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!components.is_empty()).then(|| components.join("/"))
+}
+
+/// Copy the entry read from `reader` with the uncompressed size to the zip file.
+///
+/// # Errors
+/// - If the entry cannot be read or written.
+pub fn copy_entry<W: Write + Seek>(
+    writer: &mut ZipWriter<W>,
+    name: &str,
+    options: SimpleFileOptions,
+    size: u64,
+    reader: &mut impl Read,
+) -> ZipResult<()> {
+    writer.start_file(name, entry_options(options, size))?;
+    io::copy(reader, writer)?;
+    Ok(())
+}
+
+/// Set the comment, if it is not empty, and write the central directory.
+///
+/// # Errors
+/// - If the central directory cannot be written.
+pub fn finish<W: Write + Seek>(mut writer: ZipWriter<W>, comment: &str) -> ZipResult<()> {
+    if !comment.is_empty() {
+        writer.set_comment(comment);
+    }
+    writer.finish()?.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_write_many_entries() {
+        let temp = TestProject::empty("archive");
+        let dir = temp.path();
+
+        // more entries than the plain zip format supports
+        let count = u16::MAX as usize + 100;
+        let mut folder = VFolder::new();
+        for index in 0..count {
+            folder.add_file(
+                &format!("data/pack/structure/part_{index}.nbt"),
+                VFile::Binary(vec![index as u8; 16]),
+            );
+        }
+        let path = dir.join("large.zip");
+        write_folder(&folder, &StreamedFiles::default(), &path, "comment").unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(archive.len(), count);
+        assert_eq!(archive.comment(), b"comment");
+        let mut content = Vec::new();
+        archive
+            .by_name(&format!("data/pack/structure/part_{}.nbt", count - 1))
+            .unwrap()
+            .read_to_end(&mut content)
+            .unwrap();
+        assert_eq!(content, vec![(count - 1) as u8; 16]);
+
+        let read = read_folder(&path).unwrap();
+        assert_eq!(read.flatten().len(), count);
+        assert_eq!(
+            read.get_file("data/pack/structure/part_1.nbt")
+                .unwrap()
+                .as_bytes(),
+            [1; 16]
+        );
+    }
+
+    #[test]
+    fn test_read_entry_outside() {
+        let temp = TestProject::empty("archive-outside");
+        let dir = temp.path();
+
+        let write_zip = |path: &Path, names: &[&str]| {
+            let mut writer = ZipWriter::new(File::create(path).unwrap());
+            for name in names {
+                writer.start_file(*name, default_options()).unwrap();
+                writer.write_all(b"content").unwrap();
+            }
+            writer.finish().unwrap();
+        };
+
+        let evil = dir.join("evil.zip");
+        write_zip(&evil, &["pack.mcmeta", "../evil.txt"]);
+        assert!(matches!(
+            read_folder(&evil),
+            Err(ZipError::InvalidArchive(_))
+        ));
+
+        let nested = dir.join("nested.zip");
+        write_zip(&nested, &["./data/../pack.mcmeta"]);
+        let folder = read_folder(&nested).unwrap();
+        assert_eq!(
+            folder.get_file("pack.mcmeta").unwrap().as_bytes(),
+            b"content"
+        );
+    }
+
+    #[test]
+    #[ignore = "compresses 4 GiB of data"]
+    fn test_write_large_entry() {
+        let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
+        copy_entry(
+            &mut writer,
+            "data/pack/structure/large.nbt",
+            default_options(),
+            ZIP64_SIZE,
+            &mut io::repeat(0).take(ZIP64_SIZE),
+        )
+        .unwrap();
+        let buffer = writer.finish().unwrap();
+
+        let mut archive = ZipArchive::new(buffer).unwrap();
+        let file = archive.by_index(0).unwrap();
+        assert_eq!(file.name(), "data/pack/structure/large.nbt");
+        assert_eq!(file.size(), ZIP64_SIZE);
+    }
+}
@@ -0,0 +1,191 @@
+//! Rhai build scripts generating files of the output at build time.
+//!
+//! The script only has access to the output folder and read access to the files of the project,
+//! it cannot run commands or write files outside of the output.
+//!
+//! Available functions:
+//! - `write_file(path, content)`  Add a text file to the output, replacing an existing one
+//! - `read_file(path)`            The content of a text file of the output or `()`
+//! - `file_exists(path)`          Whether a file exists in the output
+//! - `list_files()`               The paths of all files of the output
+//! - `read_project_file(path)`    The content of a text file of the project directory
+//!
+//! The constants `NAMESPACE`, `PACK_NAME`, `PACK_VERSION` and `PACK_FORMAT` describe the pack.
+
+use std::{cell::RefCell, collections::BTreeSet, fs, path::Path, rc::Rc};
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope};
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+use crate::{
+    cancel::CancellationToken,
+    config::ProjectConfig,
+    error::Error,
+    terminal_output::{print_error, print_info},
+    util,
+};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Run the build script on the output and return the paths of the written files.
+pub fn run(
+    script: &Path,
+    output: &mut VFolder,
+    project_root: &Path,
+    config: &ProjectConfig,
+    cancel: &CancellationToken,
+) -> anyhow::Result<BTreeSet<String>> {
+    let folder = Rc::new(RefCell::new(std::mem::take(output)));
+    let written = Rc::new(RefCell::new(BTreeSet::new()));
+    let root = project_root.canonicalize()?;
+
+    let mut engine = Engine::new();
+    engine.on_print(|text| print_info(text));
+    engine.on_debug(|text, _, pos| print_info(format!("{text} ({pos})")));
+    let cancel = cancel.clone();
+    engine.on_progress(move |_| cancel.is_cancelled().then_some(Dynamic::UNIT));
+
+    {
+        let folder = folder.clone();
+        let written = written.clone();
+        engine.register_fn(
+            "write_file",
+            move |path: &str, content: &str| -> ScriptResult<()> {
+                if !util::is_enclosed_path(path) {
+                    return Err(format!(
+                        "The path {path} is not a relative path inside of the output"
+                    )
+                    .into());
+                }
+                folder
+                    .borrow_mut()
+                    .add_file(path, VFile::Text(content.to_string()));
+                written.borrow_mut().insert(path.to_string());
+                Ok(())
+            },
+        );
+    }
+    {
+        let folder = folder.clone();
+        engine.register_fn("read_file", move |path: &str| -> Dynamic {
+            folder
+                .borrow()
+                .get_file(path)
+                .and_then(|file| file.as_text())
+                .map_or(Dynamic::UNIT, |text| text.to_string().into())
+        });
+    }
+    {
+        let folder = folder.clone();
+        engine.register_fn("file_exists", move |path: &str| -> bool {
+            folder.borrow().get_file(path).is_some()
+        });
+    }
+    {
+        let folder = folder.clone();
+        engine.register_fn("list_files", move || -> Array {
+            folder
+                .borrow()
+                .flatten()
+                .into_iter()
+                .map(|(path, _)| path.into())
+                .collect()
+        });
+    }
+    engine.register_fn(
+        "read_project_file",
+        move |path: &str| -> ScriptResult<String> {
+            let file = root
+                .join(path)
+                .canonicalize()
+                .map_err(|err| format!("Cannot read {path}: {err}"))?;
+            if !file.starts_with(&root) {
+                return Err(
+                    format!("Cannot read {path}: the file is outside of the project").into(),
+                );
+            }
+            fs::read_to_string(&file).map_err(|err| format!("Cannot read {path}: {err}").into())
+        },
+    );
+
+    let mut scope = Scope::new();
+    scope.push_constant("NAMESPACE", config.pack.namespace().into_owned());
+    scope.push_constant("PACK_NAME", config.pack.name.clone());
+    scope.push_constant("PACK_VERSION", config.pack.version.clone());
+    scope.push_constant("PACK_FORMAT", i64::from(config.pack.pack_format));
+
+    let result = engine.run_file_with_scope(&mut scope, script.to_path_buf());
+    drop(engine);
+    *output = folder.take();
+
+    match result {
+        Ok(()) => Ok(written.take()),
+        Err(err) if matches!(*err, EvalAltResult::ErrorTerminated(..)) => {
+            Err(Error::CancelledError.into())
+        }
+        Err(err) => {
+            print_error(format!(
+                "The build script {} failed: {err}",
+                script.display()
+            ));
+            Err(Error::BuildScriptError(script.to_path_buf()).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_build_script() {
+        let temp = TestProject::empty("build-script");
+        let dir = temp.path();
+        fs::write(dir.join("items.txt"), "diamond\nemerald").unwrap();
+        fs::write(
+            dir.join("build.rhai"),
+            r#"
+                for item in read_project_file("items.txt").split("\n") {
+                    let table = #{ pools: [#{ rolls: 1, entries: [#{ type: "minecraft:item", name: item }] }] };
+                    write_file(`data/${NAMESPACE}/loot_table/${item}.json`, table.to_json());
+                }
+                write_file("count.txt", `${list_files().len()}`);
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("escape.rhai"),
+            r#"write_file("../escape.txt", "")"#,
+        )
+        .unwrap();
+
+        let config = toml::from_str::<ProjectConfig>(
+            "[pack]\nname = \"test\"\ndescription = \"\"\npack_format = 48\nversion = \"0.1.0\"",
+        )
+        .unwrap();
+        let cancel = CancellationToken::new();
+
+        let mut output = VFolder::new();
+        let written = run(&dir.join("build.rhai"), &mut output, dir, &config, &cancel).unwrap();
+        assert_eq!(
+            written,
+            BTreeSet::from([
+                "count.txt".to_string(),
+                "data/test/loot_table/diamond.json".to_string(),
+                "data/test/loot_table/emerald.json".to_string(),
+            ])
+        );
+        assert!(output
+            .get_file("data/test/loot_table/emerald.json")
+            .and_then(|file| file.as_text())
+            .is_some_and(|json| json.contains(r#""name":"emerald""#)));
+        assert_eq!(
+            output.get_file("count.txt").and_then(|file| file.as_text()),
+            Some("2")
+        );
+
+        assert!(run(&dir.join("escape.rhai"), &mut output, dir, &config, &cancel).is_err());
+        assert!(output.get_file("count.txt").is_some());
+    }
+}
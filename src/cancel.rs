@@ -0,0 +1,131 @@
+//! Cooperative cancellation of builds, e.g. when Ctrl-C is pressed.
+//!
+//! Long running operations check a [`CancellationToken`] between their stages and stop with
+//! [`Error::CancelledError`], so partial output can be rolled back instead of exiting mid-write.
+//! Async tasks wait for [`CancellationToken::cancelled`] instead, e.g. to stop listening for
+//! connections.
+
+use std::{
+    io, process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Once, OnceLock,
+    },
+};
+
+use tokio::sync::Notify;
+
+use crate::{error::Error, runtime, terminal_output::print_warning, util};
+
+/// A flag shared between the operation and the code requesting its cancellation.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<TokenState>);
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the cancellation of all operations using this token.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the cancellation is requested.
+    pub async fn cancelled(&self) {
+        // the waiter is registered before checking the flag, so a concurrent cancel is not missed
+        let notified = self.0.notify.notified();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+
+    /// Return [`Error::CancelledError`] if the cancellation was requested.
+    pub fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            Err(Error::CancelledError)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The token cancelled by the signal handler installed with [`install_shutdown_handler`].
+pub fn global() -> &'static CancellationToken {
+    static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+    TOKEN.get_or_init(CancellationToken::new)
+}
+
+/// Install a handler of Ctrl-C and SIGTERM cancelling the [`global`] token, if not installed yet.
+///
+/// The handler runs as task of the shared [`runtime`], so all subsystems waiting for the token
+/// are stopped by the same signal.
+/// The running shell commands are terminated together with the processes started by them, as
+/// they run in their own process groups and do not receive the Ctrl-C themselves.
+/// A second signal exits immediately.
+pub fn install_shutdown_handler() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        runtime::handle().spawn(async {
+            loop {
+                if shutdown_signal().await.is_err() {
+                    print_warning("Failed to listen for Ctrl-C, builds cannot be cancelled.");
+                    return;
+                }
+                util::kill_running_commands();
+                let token = global();
+                if token.is_cancelled() {
+                    process::exit(130);
+                }
+                token.cancel();
+                print_warning("Cancelling... Press Ctrl-C again to exit immediately.");
+            }
+        });
+    });
+}
+
+/// Wait for Ctrl-C or, on Unix, SIGTERM.
+async fn shutdown_signal() -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(Error::CancelledError)));
+        runtime::block_on(token.cancelled());
+    }
+}
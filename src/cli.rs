@@ -1,4 +1,16 @@
-use crate::subcommands::{self, BuildArgs, CleanArgs, InitArgs};
+use crate::{
+    subcommands::{
+        self, BuildArgs, CleanArgs, CloneArgs, FixArgs, InitArgs, InstallArgs, NbtArgs,
+        RunScriptArgs, UpgradeArgs,
+    },
+    terminal_output::MessageFormat,
+};
+
+#[cfg(feature = "watch")]
+use crate::subcommands::{ConfigCommand, DatapackCommand, NbtCommand};
+
+#[cfg(feature = "watch")]
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use clap::{Parser, Subcommand, ValueEnum};
@@ -29,6 +41,39 @@ pub struct Args {
         value_name = "LEVEL"
     )]
     trace: Option<TracingLevel>,
+    #[command(flatten)]
+    options: GlobalOptions,
+    /// The format of the printed messages.
+    ///
+    /// `json` prints the messages and the progress of builds as JSON-RPC notifications of the
+    /// language server protocol, one per line, e.g. for editor integrations. Applies to the whole
+    /// process, the commands run by `watch` print in the format of the watcher.
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        default_value = "human",
+        env = "SHULKERSCRIPT_MESSAGE_FORMAT"
+    )]
+    message_format: MessageFormat,
+}
+
+/// The global options passed to the subcommands.
+#[derive(Debug, Clone, Copy, Default, clap::Args)]
+pub struct GlobalOptions {
+    /// Fail instead of warning when the pack.toml file contains unknown keys.
+    #[arg(long, global = true, env = "SHULKERSCRIPT_STRICT_CONFIG")]
+    pub strict_config: bool,
+    /// Only use the given path as project instead of searching the parent directories.
+    #[arg(long, global = true, env = "SHULKERSCRIPT_NO_DISCOVER")]
+    pub no_discover: bool,
+}
+
+impl GlobalOptions {
+    /// Whether projects are searched in the parent directories of the given paths.
+    pub fn discover(&self) -> bool {
+        !self.no_discover
+    }
 }
 
 #[derive(Debug, Clone, Subcommand)]
@@ -37,15 +82,59 @@ pub enum Command {
     Init(InitArgs),
     /// Build the project.
     Build(BuildArgs),
+    /// Build the project repeatedly and report statistics of the build durations.
+    #[command(hide = true)]
+    BenchProject(subcommands::BenchProjectArgs),
     /// Clean build artifacts.
     /// This will remove the output directory.
     Clean(CleanArgs),
+    /// Edit the pack.toml file of the project.
+    Config(subcommands::ConfigArgs),
+    /// Report the conflicts of the project with the datapacks installed in worlds and servers.
+    Conflicts(subcommands::ConflictsArgs),
+    /// Enable, disable and list the datapacks of worlds and servers.
+    Datapack(subcommands::DatapackArgs),
+    /// Build the project and deploy it to worlds and servers.
+    Deploy(subcommands::DeployArgs),
+    /// Clone an existing project from a git repository.
+    Clone(CloneArgs),
+    /// Replace deprecated keys and options in the pack.toml file.
+    Fix(FixArgs),
+    /// Install a library as a dependency of the project.
+    Install(InstallArgs),
+    /// Print the messages, diagnostics and statistics of the last build again.
+    Last(subcommands::LastArgs),
     #[cfg(feature = "lang-debug")]
     /// Build the project and dump the intermediate state.
     LangDebug(subcommands::LangDebugArgs),
+    #[cfg(feature = "mcdata")]
+    /// Query the vanilla registries (blocks, items, entities, ...) of a Minecraft version.
+    Mcdata(subcommands::McdataArgs),
     #[cfg(feature = "migrate")]
     /// Migrate a regular datapack to a Shulkerscript project.
     Migrate(subcommands::MigrateArgs),
+    /// Convert and inspect SNBT, NBT and JSON files.
+    Nbt(NbtArgs),
+    #[cfg(feature = "zip")]
+    /// Build the project to a zip file with packaging options.
+    Package(subcommands::PackageArgs),
+    #[cfg(feature = "zip")]
+    /// Create a patch with the differences between two releases.
+    Patch(subcommands::PatchArgs),
+    #[cfg(feature = "zip")]
+    /// Apply a patch created with the `patch` subcommand to a release.
+    Apply(subcommands::ApplyArgs),
+    #[cfg(feature = "publish")]
+    /// Build the project and publish it to Modrinth.
+    Publish(subcommands::PublishArgs),
+    /// Run a script defined in the `[scripts]` section of the pack.toml file.
+    RunScript(RunScriptArgs),
+    /// Summarize the recorded build statistics of the project.
+    Stats(subcommands::StatsArgs),
+    /// List the TODO, FIXME and migration markers of the scripts of the project.
+    Todo(subcommands::TodoArgs),
+    /// Migrate the pack.toml file of the project across breaking changes of the cli.
+    Upgrade(UpgradeArgs),
     #[cfg(feature = "watch")]
     /// Watch for changes and execute commands.
     Watch(subcommands::WatchArgs),
@@ -67,26 +156,160 @@ impl Args {
             setup_tracing(level)?;
         }
 
-        self.cmd.run()
+        self.cmd.run(&self.options)
+    }
+
+    /// The format of the printed messages, set once for the process with
+    /// [`crate::terminal_output::set_message_format`].
+    pub fn message_format(&self) -> MessageFormat {
+        self.message_format
+    }
+
+    /// Resolve the relative paths of the arguments against the directory, so the command runs
+    /// as if it was started in it without changing the working directory of the process.
+    #[cfg(feature = "watch")]
+    pub(crate) fn resolve_paths(&mut self, base: &Path) {
+        self.cmd.resolve_paths(base);
     }
 }
 
 impl Command {
-    pub fn run(&self) -> Result<()> {
+    pub fn run(&self, options: &GlobalOptions) -> Result<()> {
         match self {
-            Command::Init(args) => subcommands::init(args)?,
-            Command::Build(args) => subcommands::build(args)?,
-            Command::Clean(args) => subcommands::clean(args)?,
+            Command::Init(args) => subcommands::init(args, options)?,
+            Command::Build(args) => subcommands::build(args, options)?,
+            Command::BenchProject(args) => subcommands::bench_project(args, options)?,
+            Command::Clean(args) => subcommands::clean(args, options)?,
+            Command::Config(args) => subcommands::config(args, options)?,
+            Command::Conflicts(args) => subcommands::conflicts(args, options)?,
+            Command::Datapack(args) => subcommands::datapack(args)?,
+            Command::Deploy(args) => subcommands::deploy(args, options)?,
+            Command::Clone(args) => subcommands::clone(args, options)?,
+            Command::Fix(args) => subcommands::fix(args, options)?,
+            Command::Install(args) => subcommands::install(args, options)?,
+            Command::Last(args) => subcommands::last(args, options)?,
             #[cfg(feature = "lang-debug")]
-            Command::LangDebug(args) => subcommands::lang_debug(args)?,
+            Command::LangDebug(args) => subcommands::lang_debug(args, options)?,
+            #[cfg(feature = "mcdata")]
+            Command::Mcdata(args) => subcommands::mcdata(args, options)?,
             #[cfg(feature = "migrate")]
             Command::Migrate(args) => subcommands::migrate(args)?,
+            Command::Nbt(args) => subcommands::nbt(args)?,
+            #[cfg(feature = "zip")]
+            Command::Package(args) => subcommands::package(args, options)?,
+            #[cfg(feature = "zip")]
+            Command::Patch(args) => subcommands::patch(args)?,
+            #[cfg(feature = "zip")]
+            Command::Apply(args) => subcommands::apply(args)?,
+            #[cfg(feature = "publish")]
+            Command::Publish(args) => subcommands::publish(args, options)?,
+            Command::RunScript(args) => subcommands::run_script(args, options)?,
+            Command::Stats(args) => subcommands::stats(args, options)?,
+            Command::Todo(args) => subcommands::todo(args, options)?,
+            Command::Upgrade(args) => subcommands::upgrade(args, options)?,
             #[cfg(feature = "watch")]
-            Command::Watch(args) => subcommands::watch(args)?,
+            Command::Watch(args) => subcommands::watch(args, options)?,
         }
 
         Ok(())
     }
+
+    #[cfg(feature = "watch")]
+    fn resolve_paths(&mut self, base: &Path) {
+        let resolve = |path: &mut PathBuf| {
+            if path.is_relative() {
+                *path = base.join(&*path);
+            }
+        };
+        let resolve_build = |args: &mut BuildArgs| {
+            resolve(&mut args.path);
+            args.output.iter_mut().for_each(resolve);
+            args.assets.iter_mut().for_each(resolve);
+        };
+
+        match self {
+            Command::Init(args) => {
+                resolve(&mut args.path);
+                args.icon_path.iter_mut().for_each(resolve);
+            }
+            Command::Build(args) => resolve_build(args),
+            Command::BenchProject(args) => resolve(&mut args.path),
+            Command::Clean(args) => {
+                resolve(&mut args.path);
+                args.output.iter_mut().for_each(resolve);
+            }
+            Command::Config(args) => match &mut args.command {
+                ConfigCommand::Fmt(args) => resolve(&mut args.path),
+            },
+            Command::Conflicts(args) => {
+                resolve(&mut args.path);
+                args.worlds.iter_mut().for_each(resolve);
+            }
+            Command::Datapack(args) => match &mut args.command {
+                DatapackCommand::Enable(args) | DatapackCommand::Disable(args) => {
+                    resolve(&mut args.target.target);
+                }
+                DatapackCommand::List(args) => resolve(&mut args.target),
+            },
+            Command::Deploy(args) => {
+                resolve_build(&mut args.build);
+                args.worlds.iter_mut().for_each(resolve);
+                args.launcher.iter_mut().for_each(resolve);
+            }
+            Command::Clone(args) => args.path.iter_mut().for_each(resolve),
+            Command::Fix(args) => resolve(&mut args.path),
+            Command::Install(args) => resolve(&mut args.path),
+            Command::Last(args) => resolve(&mut args.path),
+            #[cfg(feature = "lang-debug")]
+            Command::LangDebug(args) => resolve(&mut args.path),
+            #[cfg(feature = "mcdata")]
+            Command::Mcdata(args) => resolve(&mut args.path),
+            #[cfg(feature = "migrate")]
+            Command::Migrate(args) => {
+                resolve(&mut args.path);
+                resolve(&mut args.target);
+            }
+            Command::Nbt(args) => match &mut args.command {
+                NbtCommand::Convert(args) => {
+                    resolve(&mut args.input);
+                    resolve(&mut args.output);
+                }
+                NbtCommand::Print(args) => resolve(&mut args.input),
+            },
+            #[cfg(feature = "zip")]
+            Command::Package(args) => {
+                resolve(&mut args.path);
+                args.output.iter_mut().for_each(resolve);
+                args.assets.iter_mut().for_each(resolve);
+            }
+            #[cfg(feature = "zip")]
+            Command::Patch(args) => {
+                resolve(&mut args.old);
+                resolve(&mut args.new);
+                args.output.iter_mut().for_each(resolve);
+            }
+            #[cfg(feature = "zip")]
+            Command::Apply(args) => {
+                resolve(&mut args.target);
+                resolve(&mut args.patch);
+                args.output.iter_mut().for_each(resolve);
+            }
+            #[cfg(feature = "publish")]
+            Command::Publish(args) => resolve(&mut args.path),
+            Command::RunScript(args) => {
+                resolve(&mut args.path);
+                args.output.iter_mut().for_each(resolve);
+            }
+            Command::Stats(args) => resolve(&mut args.path),
+            Command::Todo(args) => resolve(&mut args.path),
+            Command::Upgrade(args) => resolve(&mut args.path),
+            #[cfg(feature = "watch")]
+            Command::Watch(args) => {
+                resolve(&mut args.path);
+                args.watch.iter_mut().for_each(resolve);
+            }
+        }
+    }
 }
 
 impl From<TracingLevel> for Level {
@@ -125,4 +348,21 @@ mod tests {
     fn verify_cli() {
         Args::command().debug_assert();
     }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn test_resolve_paths() {
+        let base = Path::new("/project");
+        let mut args =
+            Args::try_parse_from(["shulkerscript", "build", "--output", "dist", "/absolute"])
+                .unwrap();
+        args.resolve_paths(base);
+
+        let Command::Build(build) = &args.cmd else {
+            panic!("expected the build subcommand");
+        };
+        assert_eq!(build.path, Path::new("/absolute"));
+        assert_eq!(build.output.as_deref(), Some(base.join("dist").as_path()));
+        assert_eq!(build.assets, None);
+    }
 }
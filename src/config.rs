@@ -1,42 +1,1027 @@
-use std::path::PathBuf;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use colored::Colorize;
+use path_absolutize::Absolutize;
 use serde::{Deserialize, Serialize};
 use shulkerscript::shulkerbox;
 
+use crate::{
+    error::Error,
+    fetch::Sha256Pin,
+    terminal_output::{print_error, print_warning},
+    upgrade, util,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProjectConfig {
     pub pack: PackConfig,
     pub compiler: Option<CompilerConfig>,
+    pub publish: Option<PublishConfig>,
+    /// Other Shulkerscript projects whose scripts are compiled into this pack.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependencies: BTreeMap<String, DependencyConfig>,
+    /// Command aliases available in the project.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub alias: BTreeMap<String, AliasConfig>,
+    /// Default options of subcommands in the project, by subcommand and option name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub defaults: BTreeMap<String, toml::Table>,
+    /// Shell commands that can be run with `shulkerscript run-script <name>`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub scripts: BTreeMap<String, String>,
+    /// Translations converted into lang files and text components.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<LangConfig>,
+    /// Additional paths watched by the `watch` subcommand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch: Option<WatchConfig>,
+    /// Limits of the shell commands run by `watch` and `run-script`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<LimitsConfig>,
+    /// Plugins run on the output between compiling and placing it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<PluginConfig>,
+    /// Files generated from a template for every row of a data file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generate: Vec<GenerateConfig>,
+    /// The public surface of a library pack consumed as dependency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lib: Option<LibConfig>,
+    /// Overlays of the pack by directory name, read from `assets-overlays/<name>`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overlays: BTreeMap<String, OverlayConfig>,
+    /// Warnings of the build that are suppressed, everywhere or for some paths.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lints: Option<LintsConfig>,
+}
+
+impl ProjectConfig {
+    /// Read the config of a pack.toml file, merged over the config files it `extends`.
+    ///
+    /// Tables are merged key by key, all other values (including arrays) of the extending file
+    /// replace the ones of the extended file. Multiple extended files are merged in order.
+    ///
+    /// Returns the config and the keys that are not part of the config.
+    pub fn load(toml_path: &Path) -> anyhow::Result<(Self, Vec<UnknownKey>)> {
+        let content = fs::read_to_string(toml_path)?;
+        let mut table = parse_table(toml_path, &content)?;
+        for deprecated in upgrade::deprecated_keys(&table) {
+            print_warning(format!("{}: {deprecated}", toml_path.display()));
+        }
+
+        let mut unknown = Vec::new();
+        let mut record = |path: serde_ignored::Path| unknown.push(UnknownKey::new(&path));
+        let extends = table.contains_key("extends");
+        let result: Result<Self, _> = if extends {
+            let mut chain = Vec::new();
+            table = resolve_extends(toml_path, table, &mut chain)?;
+            serde_ignored::deserialize(toml::Value::Table(table), &mut record)
+        } else {
+            serde_ignored::deserialize(toml::Deserializer::new(&content), &mut record)
+        };
+        unknown.retain(|key| key.segments != ["workspace"]);
+
+        match result {
+            Ok(mut config) => {
+                if let Some(format) = config.pack.unstable_format {
+                    config.pack.set_unstable_format(format);
+                }
+                Ok((config, unknown))
+            }
+            Err(err) => {
+                // the merged config of extending files has no locations
+                let content = (!extends).then_some(content.as_str());
+                print_error(render_toml_error(toml_path, content, &err, &unknown));
+                Err(Error::InvalidConfigError(toml_path.to_path_buf()).into())
+            }
+        }
+    }
+
+    /// Get the config files the pack.toml file extends, directly or indirectly.
+    ///
+    /// Files that cannot be read are skipped.
+    pub fn extended_files(toml_path: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut pending = vec![toml_path.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            let Some(table) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| toml::from_str::<toml::Table>(&content).ok())
+            else {
+                continue;
+            };
+            for base in extends_paths(&path, &table).unwrap_or_default() {
+                if base != toml_path && !files.contains(&base) {
+                    files.push(base.clone());
+                    pending.push(base);
+                }
+            }
+        }
+        files
+    }
+}
+
+/// A key of a config file that is not part of the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey {
+    /// The keys of the tables containing the key and the key itself, array indices are included
+    /// as numbers.
+    pub segments: Vec<String>,
+    /// A known key with a similar name.
+    pub suggestion: Option<&'static str>,
+}
+
+impl UnknownKey {
+    fn new(path: &serde_ignored::Path) -> Self {
+        fn collect(path: &serde_ignored::Path, segments: &mut Vec<String>) {
+            match path {
+                serde_ignored::Path::Root => {}
+                serde_ignored::Path::Seq { parent, index } => {
+                    collect(parent, segments);
+                    segments.push(index.to_string());
+                }
+                serde_ignored::Path::Map { parent, key } => {
+                    collect(parent, segments);
+                    segments.push(key.clone());
+                }
+                serde_ignored::Path::Some { parent }
+                | serde_ignored::Path::NewtypeStruct { parent }
+                | serde_ignored::Path::NewtypeVariant { parent } => collect(parent, segments),
+            }
+        }
+
+        let mut segments = Vec::new();
+        collect(path, &mut segments);
+        let suggestion = segments.split_last().and_then(|(key, parents)| {
+            known_keys(parents)
+                .iter()
+                .map(|known| (strsim::jaro(key, known), *known))
+                .filter(|(similarity, _)| *similarity > 0.8)
+                .max_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(_, known)| known)
+        });
+
+        Self {
+            segments,
+            suggestion,
+        }
+    }
+
+    /// The dotted path of the key, e.g. `compiler.optimise` or `generate[0].tempalte`.
+    pub fn path(&self) -> String {
+        self.segments
+            .iter()
+            .fold(String::new(), |mut path, segment| {
+                if segment.parse::<usize>().is_ok() {
+                    path.push_str(&format!("[{segment}]"));
+                } else {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(segment);
+                }
+                path
+            })
+    }
+}
+
+impl std::fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown key `{}`", self.path())?;
+        if let Some(suggestion) = self.suggestion {
+            write!(f, ", did you mean `{suggestion}`?")?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the keys of the table at the path of a project config.
+fn known_keys(parents: &[String]) -> &'static [&'static str] {
+    let parents = parents.iter().map(String::as_str).collect::<Vec<_>>();
+    match parents.as_slice() {
+        [] => struct_fields::<ProjectConfig>(),
+        ["pack"] => struct_fields::<PackConfig>(),
+        ["compiler"] => struct_fields::<CompilerConfig>(),
+        ["publish"] => struct_fields::<PublishConfig>(),
+        ["publish", "modrinth"] => struct_fields::<ModrinthPublishConfig>(),
+        ["dependencies", _] => struct_fields::<DependencyConfig>(),
+        ["lang"] => struct_fields::<LangConfig>(),
+        ["watch"] => struct_fields::<WatchConfig>(),
+        ["limits"] => struct_fields::<LimitsConfig>(),
+        ["plugins", _] => struct_fields::<PluginConfig>(),
+        ["generate", _] => struct_fields::<GenerateConfig>(),
+        _ => &[],
+    }
+}
+
+/// Get the field names of a struct by letting it deserialize itself from a deserializer that only
+/// records the requested fields.
+fn struct_fields<T: serde::de::DeserializeOwned>() -> &'static [&'static str] {
+    struct FieldsDeserializer<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> serde::Deserializer<'de> for FieldsDeserializer<'_> {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(
+            self,
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("only structs are supported"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(serde::de::Error::custom("only the fields are recorded"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+            ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldsDeserializer(&mut fields));
+    fields
+}
+
+/// Merge the table over the tables it extends.
+fn resolve_extends(
+    path: &Path,
+    mut table: toml::Table,
+    chain: &mut Vec<PathBuf>,
+) -> Result<toml::Table, Error> {
+    let bases = extends_paths(path, &table)?;
+    table.remove("extends");
+    chain.push(path.to_path_buf());
+
+    let mut merged = toml::Table::new();
+    for base in bases {
+        if chain.contains(&base) {
+            return Err(Error::CyclicExtendsError(base));
+        }
+        let content =
+            fs::read_to_string(&base).map_err(|_| Error::InvalidExtendsError(base.clone()))?;
+        let base_table = parse_table(&base, &content)?;
+        merge_tables(&mut merged, resolve_extends(&base, base_table, chain)?);
+    }
+
+    chain.pop();
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+/// Parse a config file into a table, printing syntax errors.
+fn parse_table(path: &Path, content: &str) -> Result<toml::Table, Error> {
+    toml::from_str(content).map_err(|err| {
+        print_error(render_toml_error(path, Some(content), &err, &[]));
+        Error::InvalidConfigError(path.to_path_buf())
+    })
+}
+
+/// Render a TOML error with its location, the offending line with a caret below the span and a
+/// hint how to fix it.
+///
+/// The content of the file is required to show the offending line.
+pub fn render_toml_error(
+    path: &Path,
+    content: Option<&str>,
+    err: &toml::de::Error,
+    unknown: &[UnknownKey],
+) -> String {
+    let message = err.message().trim_end();
+    let mut key = None;
+    let mut gutter = String::new();
+
+    let mut rendered = match content.zip(err.span()) {
+        Some((content, span)) => {
+            let line_start = content[..span.start]
+                .rfind('\n')
+                .map_or(0, |index| index + 1);
+            let line_end = content[span.start..]
+                .find('\n')
+                .map_or(content.len(), |index| span.start + index);
+            let line = content[line_start..line_end].trim_end_matches('\r');
+            let line_number = content[..span.start].matches('\n').count() + 1;
+            let column = content[line_start..span.start].chars().count() + 1;
+            let width = content[span]
+                .chars()
+                .take_while(|c| *c != '\n')
+                .count()
+                .max(1);
+            key = line
+                .split_once('=')
+                .map(|(key, _)| key.trim().trim_matches('"'));
+            gutter = " ".repeat(line_number.to_string().len());
+
+            format!(
+                "{}:{line_number}:{column}: {message}\n{gutter} {bar}\n{} {bar} {line}\n{gutter} {bar} {}{}",
+                path.display(),
+                line_number.to_string().blue(),
+                " ".repeat(column - 1),
+                "^".repeat(width).red(),
+                bar = "|".blue(),
+            )
+        }
+        None => format!("{}: {message}", path.display()),
+    };
+
+    if let Some(hint) = toml_hint(message, key, unknown) {
+        rendered.push_str(&format!("\n{gutter} {} {hint}", "= hint:".bold()));
+    }
+    rendered
+}
+
+/// Get a hint how to fix the value causing the error message.
+fn toml_hint(message: &str, key: Option<&str>, unknown: &[UnknownKey]) -> Option<String> {
+    if let Some(field) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split('`').next())
+    {
+        return Some(
+            match unknown.iter().find(|key| key.suggestion == Some(field)) {
+                Some(typo) => format!("`{}` looks like a misspelling of `{field}`", typo.path()),
+                None => format!("add the required field `{field}`"),
+            },
+        );
+    }
+
+    let (_, expected) = message.split_once(", expected ")?;
+    let key = key.map_or_else(|| "the value".to_string(), |key| format!("`{key}`"));
+    let integer = ["u8", "u16", "u32", "u64", "usize", "i64", "integer"]
+        .iter()
+        .any(|integer| expected.contains(integer));
+    let hint = if expected == "u8" && message.starts_with("invalid value") {
+        format!("{key} must be between 0 and 255")
+    } else if integer && message.starts_with("invalid value") {
+        format!("{key} is out of range ({expected})")
+    } else if integer {
+        format!("{key} must be an integer without quotes")
+    } else if expected.contains("a boolean") {
+        format!("{key} must be `true` or `false`")
+    } else if expected.contains("a string") || expected.contains("path string") {
+        format!("{key} must be a string in quotes")
+    } else if expected.contains("a sequence") {
+        format!("{key} must be an array, e.g. `[\"a\", \"b\"]`")
+    } else if expected.contains("struct") || expected.contains("a map") {
+        format!("{key} must be a table")
+    } else {
+        return None;
+    };
+    Some(hint)
+}
+
+/// Get the paths of the `extends` field, relative to the directory of the config file.
+fn extends_paths(path: &Path, table: &toml::Table) -> Result<Vec<PathBuf>, Error> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let resolve = |value: &toml::Value| {
+        value
+            .as_str()
+            .map(|base| dir.join(base).absolutize().map(|base| base.into_owned()))
+            .and_then(Result::ok)
+            .ok_or_else(|| Error::InvalidExtendsError(path.to_path_buf()))
+    };
+    match table.get("extends") {
+        None => Ok(Vec::new()),
+        Some(toml::Value::Array(bases)) => bases.iter().map(resolve).collect(),
+        Some(base) => Ok(vec![resolve(base)?]),
+    }
+}
+
+/// Merge the overlay into the base table, replacing all values but tables.
+pub fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The user-wide configuration, stored in the `config.toml` file in the [`GlobalConfig::dir`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GlobalConfig {
+    /// Command aliases available in all projects.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub alias: BTreeMap<String, AliasConfig>,
+    /// Default options of subcommands in all projects, by subcommand and option name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub defaults: BTreeMap<String, toml::Table>,
+    /// Defaults of the `init` subcommand.
+    #[serde(default)]
+    pub init: InitDefaults,
+    /// Settings of the `clean` subcommand.
+    #[serde(default)]
+    pub clean: CleanDefaults,
+}
+
+/// Defaults of the `init` subcommand in the global config.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct InitDefaults {
+    /// Whether to create a pack.png file with the default icon if no icon is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<bool>,
+    /// The name of the author of the initial commits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    /// The email of the author of the initial commits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_email: Option<String>,
+    /// The name of the initial branch of new repositories.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_branch: Option<String>,
+    /// Whether to create a single initial commit instead of two.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub squash: Option<bool>,
+}
+
+/// Settings of the `clean` subcommand in the global config.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CleanDefaults {
+    /// Output directories outside of projects that `clean --all` may delete without typing their
+    /// name, including their subdirectories.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_outputs: Vec<PathBuf>,
+}
+
+impl GlobalConfig {
+    /// Get the directory of the global config.
+    ///
+    /// Can be overridden with the `SHULKERSCRIPT_CONFIG_DIR` environment variable.
+    pub fn dir() -> PathBuf {
+        if let Some(dir) = env::var_os("SHULKERSCRIPT_CONFIG_DIR") {
+            return PathBuf::from(dir);
+        }
+
+        let base = if cfg!(target_os = "windows") {
+            env::var_os("APPDATA").map(PathBuf::from)
+        } else {
+            env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        };
+
+        base.unwrap_or_else(env::temp_dir).join("shulkerscript")
+    }
+
+    /// Load the global config, falling back to the default if it does not exist.
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::dir().join("config.toml");
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// A command alias.
+///
+/// Either a single command line or a list of command lines that are run one after another.
+/// Command lines may also be chained with `&&`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AliasConfig {
+    Command(String),
+    Chain(Vec<String>),
+}
+
+impl AliasConfig {
+    /// Get the command lines of the alias, split into their arguments.
+    pub fn commands(&self) -> Vec<Vec<String>> {
+        let lines = match self {
+            Self::Command(command) => vec![command.as_str()],
+            Self::Chain(commands) => commands.iter().map(String::as_str).collect(),
+        };
+
+        lines
+            .into_iter()
+            .flat_map(|line| line.split("&&"))
+            .map(|command| command.split_whitespace().map(str::to_string).collect())
+            .filter(|command: &Vec<String>| !command.is_empty())
+            .collect()
+    }
+}
+
+/// The parts of a pack.toml file describing a workspace.
+///
+/// Parsed separately from the [`ProjectConfig`], as the root of a workspace does not need to be
+/// a project itself.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WorkspaceManifest {
+    pub workspace: Option<WorkspaceConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WorkspaceConfig {
+    /// The paths of the member projects, relative to the workspace root.
+    ///
+    /// A trailing `*` includes all subdirectories containing a pack.toml file.
+    pub members: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackConfig {
     pub name: String,
+    /// The namespace of the datapack.
+    ///
+    /// Defaults to the namespace derived from the pack name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
     pub description: String,
     #[serde(rename = "format", alias = "pack_format")]
     pub pack_format: u8,
+    /// The version of the pack.
+    ///
+    /// Supports the placeholders `{name}`, `{namespace}`, `{git_hash}` and `{git_tag}`.
     pub version: String,
+    /// The Minecraft version used for the vanilla registries.
+    ///
+    /// Defaults to the newest Minecraft version using the pack format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minecraft_version: Option<String>,
+    /// The experimental vanilla features the pack requires, e.g. `minecraft:trade_rebalance`.
+    ///
+    /// Emitted into the `features` block of the pack.mcmeta file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_features: Vec<String>,
+    /// The pack format of a snapshot, e.g. `"61.1"`, which the pack is built for instead of
+    /// `format`.
+    ///
+    /// The major format replaces `format` for the validation, the full format is emitted as
+    /// `min_format` and `max_format` into the pack.mcmeta file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unstable_format: Option<UnstableFormat>,
 }
 
 impl PackConfig {
     pub const DEFAULT_NAME: &'static str = "shulkerscript-pack";
     pub const DEFAULT_DESCRIPTION: &'static str = "A Minecraft datapack created with shulkerscript";
     pub const DEFAULT_PACK_FORMAT: u8 = shulkerbox::datapack::Datapack::LATEST_FORMAT;
+
+    /// Build the pack for the pack format of a snapshot, using its major format as pack format.
+    pub fn set_unstable_format(&mut self, format: UnstableFormat) {
+        self.pack_format = format.major;
+        self.unstable_format = Some(format);
+    }
+
+    /// Get the namespace of the pack, either the configured one or the one derived from the name.
+    pub fn namespace(&self) -> Cow<'_, str> {
+        self.namespace
+            .as_deref()
+            .map(Cow::Borrowed)
+            .unwrap_or_else(|| Cow::Owned(util::name_to_namespace(&self.name)))
+    }
 }
 
 impl Default for PackConfig {
     fn default() -> Self {
         Self {
             name: Self::DEFAULT_NAME.to_string(),
+            namespace: None,
             description: Self::DEFAULT_DESCRIPTION.to_string(),
             pack_format: Self::DEFAULT_PACK_FORMAT,
             version: "0.1.0".to_string(),
+            minecraft_version: None,
+            required_features: Vec::new(),
+            unstable_format: None,
         }
     }
 }
 
+/// A pack format of a snapshot consisting of the major format and a minor version, e.g. `61.1`.
+///
+/// Recent snapshots identify their pack formats this way instead of by a single number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UnstableFormat {
+    pub major: u8,
+    pub minor: u16,
+}
+
+impl std::str::FromStr for UnstableFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor) = s.split_once('.').unwrap_or((s, "0"));
+        match (major.parse(), minor.parse()) {
+            (Ok(major), Ok(minor)) => Ok(Self { major, minor }),
+            _ => Err(format!(
+                "invalid pack format \"{s}\", expected `<MAJOR>.<MINOR>`, e.g. `61.1`"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for UnstableFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<UnstableFormat> for String {
+    fn from(value: UnstableFormat) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for UnstableFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompilerConfig {
     /// The path of a folder which files and subfolders will be copied to the root of the datapack.
     pub assets: Option<PathBuf>,
+    /// The name of the output folder/zip file, defaults to the pack name.
+    ///
+    /// Supports the placeholders `{name}`, `{namespace}`, `{version}`, `{git_hash}` and `{git_tag}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_name: Option<String>,
+    /// The comment of built zip files, an empty string omits the comment.
+    ///
+    /// Supports the placeholders of `output_name` and `{description}`, defaults to
+    /// `{description} - v{version}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zip_comment: Option<String>,
+    /// Embed the build metadata as `META-INF/pack-info.json` in built zip files.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub embed_pack_info: bool,
+    /// Strip comments and empty lines from the emitted `.mcfunction` files.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strip_comments: bool,
+    /// Merge trivially chained generated functions and collapse duplicate generated functions.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optimize: bool,
+    /// Give generated functions readable names derived from the script and function name instead
+    /// of hashes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deobfuscate_all: bool,
+    /// Write a mapping file linking the generated function names to the source functions.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mapping: bool,
+    /// Fail on symlinks in the source, assets and structures folders instead of following them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub refuse_symlinks: bool,
+    /// Record the statistics of every build in `.shulkerscript/stats.jsonl`, summarized by the
+    /// `stats` subcommand.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub record_stats: bool,
+    /// The number of commands that may run per tick before a warning is shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tick_budget: Option<usize>,
+    /// The number of commands that may run on load before a warning is shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_budget: Option<usize>,
+    /// The path of the Rhai script generating files of the output, relative to the project root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_script: Option<PathBuf>,
+    /// The number of builds whose messages, diagnostics and statistics are kept in
+    /// `.shulkerscript/history` for the `last` subcommand, `0` to keep none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<usize>,
+}
+
+impl CompilerConfig {
+    pub const DEFAULT_ZIP_COMMENT: &'static str = "{description} - v{version}";
+    pub const DEFAULT_TICK_BUDGET: usize = 1000;
+    pub const DEFAULT_LOAD_BUDGET: usize = 10000;
+    pub const DEFAULT_BUILD_SCRIPT: &'static str = "build.rhai";
+    pub const DEFAULT_HISTORY: usize = 10;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LibConfig {
+    /// The namespaces the library may emit besides `minecraft`, e.g. for function tags.
+    ///
+    /// Building fails if the output contains files of other namespaces.
+    #[serde(default)]
+    pub exported_namespaces: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LangConfig {
+    /// The directory containing the translation files, defaults to `lang`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PathBuf>,
+    /// The language used for the text components in the datapack, defaults to `en_us`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// The directory of a resource pack that is placed next to the datapack with the generated
+    /// lang files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_pack: Option<PathBuf>,
+}
+
+impl LangConfig {
+    pub const DEFAULT_SOURCE: &'static str = "lang";
+    pub const DEFAULT_LANGUAGE: &'static str = "en_us";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct WatchConfig {
+    /// Paths relative to the project root that are watched in addition to the default ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathBuf>,
+    /// Glob patterns of paths relative to the project root whose changes are ignored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// The shulkerscript commands run when changes are detected, unless `--execute` is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub execute: Vec<String>,
+    /// The shell commands run when changes are detected, unless `--shell` is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shell: Vec<String>,
+    /// The time in ms to wait after changes of the paths matching the glob patterns, relative to
+    /// the project root, instead of the debounce time.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub debounce: BTreeMap<String, u64>,
+}
+
+impl WatchConfig {
+    pub const DEFAULT_EXECUTE: &'static str = "build .";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct LimitsConfig {
+    /// The time in seconds after which shell commands are killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// The number of bytes of stdout and stderr each that are printed of shell commands.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output: Option<usize>,
+}
+
+impl LimitsConfig {
+    /// Get the limits of the config, overridden by the values given on the command line.
+    pub fn command_limits(
+        config: Option<&Self>,
+        timeout: Option<Duration>,
+        max_output: Option<usize>,
+    ) -> util::CommandLimits {
+        util::CommandLimits {
+            timeout: timeout.or_else(|| {
+                config
+                    .and_then(|config| config.timeout)
+                    .map(Duration::from_secs)
+            }),
+            max_output: max_output.or_else(|| config.and_then(|config| config.max_output)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GenerateConfig {
+    /// The path of the template file, relative to the project root.
+    pub template: PathBuf,
+    /// The path of the `.csv`, `.json` or `.toml` file with the rows, relative to the project root.
+    pub data: PathBuf,
+    /// The path of the generated files in the output, with placeholders for the columns.
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct LintsConfig {
+    /// The names of the warnings suppressed everywhere.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// The names of the warnings suppressed for paths matching a glob pattern, e.g.
+    /// `"src/legacy/**" = ["unknown-id"]`.
+    ///
+    /// Patterns are matched against the scripts relative to the project root and against the
+    /// files of the output, e.g. `data/pack/function/main.mcfunction`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<String, Vec<String>>,
+}
+
+/// An overlay directory of the pack, applied on top of the pack by the game versions using one of
+/// its pack formats.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OverlayConfig {
+    /// The pack formats the overlay is applied for, a single format or `[min, max]`.
+    pub formats: FormatRange,
+}
+
+/// A range of pack formats as used by the pack.mcmeta file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum FormatRange {
+    Single(u8),
+    Range([u8; 2]),
+}
+
+impl FormatRange {
+    /// The lowest and highest format of the range.
+    pub fn bounds(self) -> (u8, u8) {
+        match self {
+            Self::Single(format) => (format, format),
+            Self::Range([min, max]) => (min, max),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginConfig {
+    /// The path of the dynamic library of the plugin, relative to the project root.
+    pub path: PathBuf,
+    /// The options passed to the plugin.
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub options: toml::Table,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishConfig {
+    /// Publish the pack to Modrinth.
+    pub modrinth: Option<ModrinthPublishConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModrinthPublishConfig {
+    /// The id or slug of the Modrinth project.
+    pub project: String,
+    /// The Minecraft versions the pack supports.
+    pub game_versions: Vec<String>,
+    /// The type of the version (`release`, `beta` or `alpha`), defaults to `release`.
+    pub version_type: Option<String>,
+    /// The path of the changelog file, defaults to `CHANGELOG.md`.
+    pub changelog: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DependencyConfig {
+    /// The URL of the git repository of the dependency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The branch, tag or commit of the git repository to use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// The local path of the dependency, relative to the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// The SHA-256 pin of the content of the dependency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<Sha256Pin>,
+    /// A prefix prepended to all namespaces of the dependency that are not renamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace_prefix: Option<String>,
+    /// Namespaces of the dependency to rename, mapping the original to the new namespace.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rename_namespaces: BTreeMap<String, String>,
+}
+
+impl DependencyConfig {
+    /// Get the namespace that the given namespace of the dependency is merged into.
+    pub fn map_namespace<'a>(&'a self, namespace: &'a str) -> Cow<'a, str> {
+        if let Some(renamed) = self.rename_namespaces.get(namespace) {
+            Cow::Borrowed(renamed)
+        } else if let Some(prefix) = &self.namespace_prefix {
+            Cow::Owned(format!("{prefix}{namespace}"))
+        } else {
+            Cow::Borrowed(namespace)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_extends() {
+        let temp = TestProject::empty("extends");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("pack")).unwrap();
+        fs::write(
+            dir.join("base.toml"),
+            "[pack]\ndescription = \"Base\"\npack_format = 48\nversion = \"1.0.0\"\n\n[compiler]\noptimize = true\nmapping = true\n\n[watch]\nignore = [\"*.tmp\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("pack/pack.toml"),
+            "extends = \"../base.toml\"\n\n[pack]\nname = \"child\"\n\n[compiler]\nmapping = false\n\n[watch]\nignore = [\"*.bak\"]\n",
+        )
+        .unwrap();
+        fs::write(dir.join("a.toml"), "extends = \"b.toml\"").unwrap();
+        fs::write(dir.join("b.toml"), "extends = [\"a.toml\"]").unwrap();
+
+        let (config, unknown) = ProjectConfig::load(&dir.join("pack/pack.toml")).unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(config.pack.name, "child");
+        assert_eq!(config.pack.description, "Base");
+        let compiler = config.compiler.unwrap();
+        assert!(compiler.optimize);
+        assert!(!compiler.mapping);
+        assert_eq!(config.watch.unwrap().ignore, ["*.bak"]);
+        assert_eq!(
+            ProjectConfig::extended_files(&dir.join("pack/pack.toml")),
+            [dir.join("base.toml")]
+        );
+
+        let err = ProjectConfig::load(&dir.join("a.toml")).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::CyclicExtendsError(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let temp = TestProject::empty("unknown-keys");
+        let dir = temp.path();
+        fs::write(
+            dir.join("pack.toml"),
+            "[pack]\nname = \"test\"\ndescription = \"\"\npack_format = 48\nversion = \"0.1.0\"\nminecraft_verison = \"1.21\"\n\n[compiler]\noptimise = true\n\n[[generate]]\ntemplate = \"a\"\ndata = \"b\"\noutput = \"c\"\nunrelated = 1\n",
+        )
+        .unwrap();
+
+        let (_, unknown) = ProjectConfig::load(&dir.join("pack.toml")).unwrap();
+        assert_eq!(
+            unknown.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "Unknown key `pack.minecraft_verison`, did you mean `minecraft_version`?",
+                "Unknown key `compiler.optimise`, did you mean `optimize`?",
+                "Unknown key `generate[0].unrelated`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unstable_format() {
+        let temp = TestProject::empty("unstable-format");
+        let dir = temp.path();
+        fs::write(
+            dir.join("pack.toml"),
+            "[pack]\nname = \"test\"\ndescription = \"\"\nformat = 48\nversion = \"0.1.0\"\nunstable_format = \"61.1\"\n",
+        )
+        .unwrap();
+
+        let (config, unknown) = ProjectConfig::load(&dir.join("pack.toml")).unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(config.pack.pack_format, 61);
+        assert_eq!(
+            config.pack.unstable_format,
+            Some(UnstableFormat {
+                major: 61,
+                minor: 1
+            })
+        );
+        assert_eq!("62".parse::<UnstableFormat>().unwrap().to_string(), "62.0");
+        assert!("61.x".parse::<UnstableFormat>().is_err());
+    }
+
+    #[test]
+    fn test_toml_hint() {
+        let unknown = [UnknownKey {
+            segments: vec!["pack".to_string(), "fromat".to_string()],
+            suggestion: Some("format"),
+        }];
+        assert_eq!(
+            toml_hint("missing field `format`", None, &unknown).as_deref(),
+            Some("`pack.fromat` looks like a misspelling of `format`")
+        );
+        assert_eq!(
+            toml_hint("missing field `version`", None, &unknown).as_deref(),
+            Some("add the required field `version`")
+        );
+        assert_eq!(
+            toml_hint(
+                "invalid type: string \"48\", expected u8",
+                Some("format"),
+                &[]
+            )
+            .as_deref(),
+            Some("`format` must be an integer without quotes")
+        );
+        assert_eq!(
+            toml_hint(
+                "invalid value: integer `480`, expected u8",
+                Some("format"),
+                &[]
+            )
+            .as_deref(),
+            Some("`format` must be between 0 and 255")
+        );
+        assert_eq!(toml_hint("invalid basic string", Some("name"), &[]), None);
+    }
 }
@@ -0,0 +1,306 @@
+//! Recipes and advancements written as `.recipe.toml` and `.advancement.toml` files in the source
+//! directory of a project.
+//!
+//! The files are converted into the vanilla JSON format of the pack format and checked for the
+//! fields required by their type. Item stacks and ingredients may be written as plain ids.
+
+use serde_json::{json, Map, Value};
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+use crate::layout;
+
+/// The kind of a data file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Recipe,
+    Advancement,
+}
+
+impl Kind {
+    fn directory(self, pack_format: u8) -> &'static str {
+        match self {
+            Self::Recipe => layout::directory("recipe", pack_format),
+            Self::Advancement => layout::directory("advancement", pack_format),
+        }
+    }
+}
+
+/// Convert the recipe and advancement files of the source directory into datapack files.
+///
+/// Returns the datapack files and the problems found in the files.
+pub fn convert(src: &VFolder, namespace: &str, pack_format: u8) -> (VFolder, Vec<String>) {
+    let mut output = VFolder::new();
+    let mut problems = Vec::new();
+    let mut files = src.flatten();
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (path, file) in files {
+        let (name, kind) = if let Some(name) = path.strip_suffix(".recipe.toml") {
+            (name, Kind::Recipe)
+        } else if let Some(name) = path.strip_suffix(".advancement.toml") {
+            (name, Kind::Advancement)
+        } else {
+            continue;
+        };
+
+        let parsed = std::str::from_utf8(file.as_bytes())
+            .map_err(|err| err.to_string())
+            .and_then(|content| toml::from_str::<Value>(content).map_err(|err| err.to_string()));
+        let mut value = match parsed {
+            Ok(value) => value,
+            Err(err) => {
+                problems.push(format!("{path}: {err}"));
+                continue;
+            }
+        };
+
+        let file_problems = match kind {
+            Kind::Recipe => convert_recipe(&mut value, pack_format),
+            Kind::Advancement => convert_advancement(&mut value, pack_format),
+        };
+        if file_problems.is_empty() {
+            output.add_file(
+                &format!(
+                    "data/{namespace}/{}/{name}.json",
+                    kind.directory(pack_format)
+                ),
+                VFile::Text(serde_json::to_string_pretty(&value).unwrap_or_default()),
+            );
+        } else {
+            problems.extend(
+                file_problems
+                    .into_iter()
+                    .map(|problem| format!("{path}: {problem}")),
+            );
+        }
+    }
+
+    (output, problems)
+}
+
+fn convert_recipe(value: &mut Value, pack_format: u8) -> Vec<String> {
+    let Some(recipe) = value.as_object_mut() else {
+        return vec!["expected a table".to_string()];
+    };
+    let mut problems = Vec::new();
+
+    let Some(recipe_type) = recipe
+        .get("type")
+        .and_then(Value::as_str)
+        .map(|recipe_type| {
+            let recipe_type = recipe_type
+                .strip_prefix("minecraft:")
+                .unwrap_or(recipe_type);
+            recipe_type.to_string()
+        })
+    else {
+        return vec!["missing `type`".to_string()];
+    };
+
+    let (required, ingredients): (&[&str], &[&str]) = match recipe_type.as_str() {
+        "crafting_shaped" => (&["pattern", "key", "result"], &[]),
+        "crafting_shapeless" => (&["ingredients", "result"], &[]),
+        "smelting" | "blasting" | "smoking" | "campfire_cooking" | "stonecutting" => {
+            (&["ingredient", "result"], &["ingredient"])
+        }
+        "smithing_transform" => (
+            &["template", "base", "addition", "result"],
+            &["template", "base", "addition"],
+        ),
+        "smithing_trim" => (
+            &["template", "base", "addition"],
+            &["template", "base", "addition"],
+        ),
+        other if other.starts_with("crafting_special_") || other == "crafting_decorated_pot" => {
+            (&[], &[])
+        }
+        other => {
+            return vec![format!("unknown recipe type `{other}`")];
+        }
+    };
+    for field in required {
+        if !recipe.contains_key(*field) {
+            problems.push(format!("missing `{field}` for recipe type `{recipe_type}`"));
+        }
+    }
+
+    for field in ingredients {
+        if let Some(ingredient) = recipe.get_mut(*field) {
+            convert_ingredient(ingredient, pack_format);
+        }
+    }
+    if let Some(Value::Array(values)) = recipe.get_mut("ingredients") {
+        values
+            .iter_mut()
+            .for_each(|ingredient| convert_ingredient(ingredient, pack_format));
+    }
+    if let Some(Value::Object(key)) = recipe.get_mut("key") {
+        key.values_mut()
+            .for_each(|ingredient| convert_ingredient(ingredient, pack_format));
+    }
+
+    if recipe_type == "crafting_shaped" {
+        problems.extend(check_pattern(recipe));
+    }
+
+    if let Some(result) = recipe.get_mut("result") {
+        // cooking and stonecutting results were plain ids before item stacks were introduced
+        let plain_id = pack_format < 41
+            && matches!(
+                recipe_type.as_str(),
+                "smelting" | "blasting" | "smoking" | "campfire_cooking" | "stonecutting"
+            );
+        if !plain_id {
+            convert_item_stack(result, pack_format);
+        }
+    }
+
+    problems
+}
+
+/// Check that the pattern of a shaped recipe is a list of up to three rows of the same length
+/// with up to three keys, which are all defined.
+fn check_pattern(recipe: &Map<String, Value>) -> Vec<String> {
+    let mut problems = Vec::new();
+    let Some(pattern) = recipe.get("pattern").and_then(Value::as_array) else {
+        return problems;
+    };
+    let rows = pattern.iter().filter_map(Value::as_str).collect::<Vec<_>>();
+    if rows.len() != pattern.len() || rows.is_empty() || rows.len() > 3 {
+        problems.push("`pattern` must be a list of one to three strings".to_string());
+        return problems;
+    }
+    let width = rows[0].chars().count();
+    if width == 0 || width > 3 || rows.iter().any(|row| row.chars().count() != width) {
+        problems
+            .push("the rows of `pattern` must have the same length of one to three".to_string());
+    }
+
+    let key = recipe.get("key").and_then(Value::as_object);
+    for symbol in rows.iter().flat_map(|row| row.chars()) {
+        if symbol != ' ' && key.is_none_or(|key| !key.contains_key(&symbol.to_string())) {
+            problems.push(format!(
+                "the symbol `{symbol}` of `pattern` is not defined in `key`"
+            ));
+        }
+    }
+    problems.dedup();
+    problems
+}
+
+fn convert_advancement(value: &mut Value, pack_format: u8) -> Vec<String> {
+    let Some(advancement) = value.as_object_mut() else {
+        return vec!["expected a table".to_string()];
+    };
+    let mut problems = Vec::new();
+
+    match advancement.get("criteria").and_then(Value::as_object) {
+        Some(criteria) if !criteria.is_empty() => {
+            for (name, criterion) in criteria {
+                if criterion.get("trigger").and_then(Value::as_str).is_none() {
+                    problems.push(format!("missing `trigger` of criterion `{name}`"));
+                }
+            }
+        }
+        _ => problems.push("missing `criteria`".to_string()),
+    }
+
+    if let Some(display) = advancement.get_mut("display") {
+        for field in ["icon", "title", "description"] {
+            if display.get(field).is_none() {
+                problems.push(format!("missing `{field}` of `display`"));
+            }
+        }
+        if let Some(icon) = display.get_mut("icon") {
+            convert_item_stack(icon, pack_format);
+        }
+    }
+
+    problems
+}
+
+/// Convert an ingredient written as `"<item>"` or `"#<tag>"` into the format of the pack format.
+fn convert_ingredient(ingredient: &mut Value, pack_format: u8) {
+    match ingredient {
+        // plain ids and tags are the native format since 1.21.2
+        Value::String(id) if pack_format < 57 => {
+            *ingredient = match id.strip_prefix('#') {
+                Some(tag) => json!({ "tag": tag }),
+                None => json!({ "item": id }),
+            };
+        }
+        Value::Array(alternatives) => alternatives
+            .iter_mut()
+            .for_each(|alternative| convert_ingredient(alternative, pack_format)),
+        _ => {}
+    }
+}
+
+/// Convert an item stack written as `"<item>"` into an object with the id.
+fn convert_item_stack(stack: &mut Value, pack_format: u8) {
+    let id_field = if pack_format < 41 { "item" } else { "id" };
+    if let Value::String(id) = stack {
+        let mut object = Map::new();
+        object.insert(id_field.to_string(), Value::String(id.clone()));
+        *stack = Value::Object(object);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let mut src = VFolder::new();
+        src.add_file(
+            "tools/pick.recipe.toml",
+            VFile::Text(
+                r##"
+                type = "crafting_shaped"
+                pattern = ["DDD", " S ", " S "]
+                key = { D = "minecraft:diamond", S = "#minecraft:sticks" }
+                result = "minecraft:diamond_pickaxe"
+                "##
+                .to_string(),
+            ),
+        );
+        src.add_file(
+            "broken.recipe.toml",
+            VFile::Text(
+                "type = \"crafting_shaped\"\npattern = [\"AB\"]\nkey = { A = \"stone\" }"
+                    .to_string(),
+            ),
+        );
+        src.add_file(
+            "root.advancement.toml",
+            VFile::Text(
+                "[display]\nicon = \"minecraft:stone\"\ntitle = \"Root\"\ndescription = \"\"\n[criteria.tick]\ntrigger = \"minecraft:tick\""
+                    .to_string(),
+            ),
+        );
+
+        let (output, problems) = convert(&src, "test", 48);
+
+        assert_eq!(
+            problems,
+            vec![
+                "broken.recipe.toml: missing `result` for recipe type `crafting_shaped`",
+                "broken.recipe.toml: the symbol `B` of `pattern` is not defined in `key`",
+            ]
+        );
+        let recipe = output
+            .get_file("data/test/recipe/tools/pick.json")
+            .map(|file| serde_json::from_slice::<Value>(file.as_bytes()).unwrap());
+        assert_eq!(
+            recipe
+                .as_ref()
+                .map(|recipe| (&recipe["key"], &recipe["result"])),
+            Some((
+                &json!({ "D": { "item": "minecraft:diamond" }, "S": { "tag": "minecraft:sticks" } }),
+                &json!({ "id": "minecraft:diamond_pickaxe" })
+            ))
+        );
+        assert!(output.get_file("data/test/advancement/root.json").is_some());
+    }
+}
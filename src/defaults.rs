@@ -0,0 +1,220 @@
+//! Default options of subcommands defined in the `[defaults.<subcommand>]` sections of the
+//! pack.toml file or the global config, e.g. `zip = true` in `[defaults.build]`.
+//!
+//! The defaults are inserted into the command line after the subcommand. Options given on the
+//! command line, options conflicting with them and options set by their environment variable are
+//! not inserted, so they always take precedence over the defaults.
+
+use std::{collections::BTreeMap, env, ffi::OsString, fs, path::Path};
+
+use clap::{Arg, CommandFactory as _};
+
+use crate::{
+    alias, cli::Args, config::GlobalConfig, terminal_output::print_warning, util,
+    workspace::Workspace,
+};
+
+/// Load the defaults of the global config, the workspace and the project containing the path.
+///
+/// The defaults are merged option by option, the ones of the project take precedence over those
+/// of the workspace, which take precedence over the global ones.
+fn load(path: &Path, discover: bool) -> BTreeMap<String, toml::Table> {
+    let mut defaults = GlobalConfig::load()
+        .map(|config| config.defaults)
+        .unwrap_or_default();
+
+    let workspace_root = Workspace::find_root(path, discover);
+    let project_root = util::get_project_path(path, discover);
+    for root in [workspace_root, project_root].into_iter().flatten() {
+        for (subcommand, options) in read_defaults(&root.join("pack.toml")) {
+            defaults.entry(subcommand).or_default().extend(options);
+        }
+    }
+
+    defaults
+}
+
+/// Read the `[defaults]` section of a pack.toml file.
+fn read_defaults(toml_path: &Path) -> BTreeMap<String, toml::Table> {
+    fs::read_to_string(toml_path)
+        .ok()
+        .and_then(|content| toml::from_str::<toml::Table>(&content).ok())
+        .and_then(|mut table| table.remove("defaults"))
+        .and_then(|defaults| defaults.try_into().ok())
+        .unwrap_or_default()
+}
+
+/// Insert the default options of the subcommand into the command line arguments.
+///
+/// The defaults are loaded for the project targeted by the path argument of the subcommand,
+/// which is resolved against the base directory.
+pub fn apply(args: Vec<OsString>, base: &Path) -> Vec<OsString> {
+    let defaults = load(&alias::target_path(&args, base), alias::discovers(&args));
+    insert(args, &defaults, &Args::command())
+}
+
+fn insert(
+    mut args: Vec<OsString>,
+    defaults: &BTreeMap<String, toml::Table>,
+    command: &clap::Command,
+) -> Vec<OsString> {
+    let Some(position) = alias::subcommand_position(&args) else {
+        return args;
+    };
+    let Some(subcommand) = args[position]
+        .to_str()
+        .and_then(|name| command.find_subcommand(name))
+    else {
+        return args;
+    };
+    let name = subcommand.get_name();
+    let Some(options) = defaults.get(name) else {
+        return args;
+    };
+
+    let given = args[position + 1..]
+        .iter()
+        .take_while(|arg| *arg != "--")
+        .filter_map(|arg| arg.to_str())
+        .collect::<Vec<_>>();
+    let mut inserted = Vec::new();
+    for (key, value) in options {
+        let long = key.replace('_', "-");
+        let Some(arg) = subcommand
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+        else {
+            print_warning(format!(
+                "Ignoring the unknown option `{key}` in the [defaults.{name}] section."
+            ));
+            continue;
+        };
+        let overridden = is_given(arg, &given)
+            || subcommand
+                .get_arg_conflicts_with(arg)
+                .into_iter()
+                .any(|conflict| is_given(conflict, &given))
+            || arg
+                .get_env()
+                .is_some_and(|variable| env::var_os(variable).is_some());
+        if overridden {
+            continue;
+        }
+
+        let values = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        if arg.get_action().takes_values() {
+            for value in values {
+                let value = match value {
+                    toml::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                inserted.push(OsString::from(format!("--{long}={value}")));
+            }
+        } else {
+            match value {
+                toml::Value::Boolean(true) => inserted.push(OsString::from(format!("--{long}"))),
+                toml::Value::Boolean(false) => {}
+                _ => print_warning(format!(
+                    "Ignoring the option `{key}` in the [defaults.{name}] section, as it must be `true` or `false`."
+                )),
+            }
+        }
+    }
+
+    args.splice(position + 1..position + 1, inserted);
+    args
+}
+
+/// Whether the option is given in the arguments, by its long or short name.
+fn is_given(arg: &Arg, given: &[&str]) -> bool {
+    given.iter().any(|given| {
+        let long = given.strip_prefix("--").is_some_and(|given| {
+            arg.get_long().is_some_and(|long| {
+                given == long
+                    || given
+                        .strip_prefix(long)
+                        .is_some_and(|rest| rest.starts_with('='))
+            })
+        });
+        let short = given
+            .strip_prefix('-')
+            .filter(|given| !given.starts_with('-'))
+            .and_then(|given| given.chars().next())
+            .is_some_and(|first| arg.get_short() == Some(first));
+        long || short
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    fn args(line: &str) -> Vec<OsString> {
+        line.split_whitespace().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let defaults = toml::from_str::<BTreeMap<String, toml::Table>>(
+            r#"
+            [build]
+            zip = true
+            no_validate = false
+
+            [watch]
+            debounce_time = 500
+            "#,
+        )
+        .unwrap();
+        let command = Args::command();
+
+        assert_eq!(
+            insert(args("shu build ."), &defaults, &command),
+            args("shu build --zip .")
+        );
+        assert_eq!(
+            insert(args("shu build -z ."), &defaults, &command),
+            args("shu build -z .")
+        );
+        assert_eq!(
+            insert(args("shu watch"), &defaults, &command),
+            args("shu watch --debounce-time=500")
+        );
+        assert_eq!(
+            insert(args("shu watch -d 100"), &defaults, &command),
+            args("shu watch -d 100")
+        );
+        assert_eq!(
+            insert(args("shu clean"), &defaults, &command),
+            args("shu clean")
+        );
+    }
+
+    #[test]
+    fn test_apply_defaults_of_target() {
+        let project = TestProject::init("defaulted");
+        let pack_toml = project.read("pack.toml").unwrap();
+        project.write(
+            "pack.toml",
+            format!("{pack_toml}\n[defaults.build]\nzip = true\n"),
+        );
+        let path = project.path().display();
+
+        assert_eq!(
+            apply(args(&format!("shu build {path}")), Path::new(".")),
+            args(&format!("shu build --zip {path}"))
+        );
+        assert_eq!(
+            apply(args("shu build ."), project.path()),
+            args("shu build --zip .")
+        );
+        assert_eq!(
+            apply(args("shu --no-discover build src"), project.path()),
+            args("shu --no-discover build src")
+        );
+    }
+}
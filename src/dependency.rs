@@ -0,0 +1,668 @@
+//! Resolution of the dependencies declared in the `pack.toml` file.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use path_absolutize::Absolutize as _;
+use shulkerscript::{
+    base::{source_file::SourceElement, FileProvider, FileProviderError, SilentHandler},
+    syntax::syntax_tree::{declaration::Declaration, program::ProgramFile},
+};
+
+use crate::{
+    config::{DependencyConfig, ProjectConfig},
+    error::Error,
+    fetch, lints,
+    subcommands::{get_pack_config, get_script_paths},
+    terminal_output::{print_error, print_info, print_warning},
+    util,
+    walk::WalkOptions,
+};
+
+/// The cache paths of the git sources used by this process, each locked while it is fetched and
+/// verified and holding whether it is ready, i.e. fetched or verified by this process.
+static FETCHED_SOURCES: Mutex<BTreeMap<PathBuf, Arc<Mutex<bool>>>> = Mutex::new(BTreeMap::new());
+
+/// A dependency that is available on the local file system.
+#[derive(Debug, Clone)]
+pub struct ResolvedDependency {
+    /// The name of the dependency in the `pack.toml` file.
+    pub name: String,
+    /// The root directory of the dependency project.
+    pub path: PathBuf,
+    /// The config of the dependency project.
+    pub config: ProjectConfig,
+    /// The entry of the dependency in the `pack.toml` file of the depending project.
+    pub dependency: DependencyConfig,
+    /// Whether the dependency was fetched because it was not cached, `None` for path dependencies.
+    pub fetched: Option<bool>,
+    /// The name of the dependency depending on it, `None` for dependencies of the project.
+    pub required_by: Option<String>,
+}
+
+impl ResolvedDependency {
+    /// Get the scripts of the dependency.
+    ///
+    /// The identifiers are prefixed with the dependency name, so they can be imported
+    /// with `from "/<name>/<script>" import ...`.
+    pub fn script_paths(&self) -> std::io::Result<Vec<(String, PathBuf)>> {
+        Ok(
+            get_script_paths(&self.path.join("src"), WalkOptions::default())?
+                .into_iter()
+                .map(|(identifier, path)| (format!("{}/{identifier}", self.name), path))
+                .collect(),
+        )
+    }
+
+    /// Get the public functions of the dependency, grouped by the namespace they are merged into.
+    pub fn exported_functions(&self) -> Result<BTreeMap<String, Vec<String>>> {
+        let mut exported = BTreeMap::<String, Vec<String>>::new();
+        let file_provider = ScriptProvider::default();
+
+        for (identifier, path) in self.script_paths()? {
+            let program =
+                shulkerscript::parse(&SilentHandler::new(), &file_provider, &path, identifier)?;
+            let namespace = self
+                .dependency
+                .map_namespace(&program.namespace().namespace_name().str_content())
+                .into_owned();
+
+            let functions = exported.entry(namespace).or_default();
+            for declaration in program.declarations() {
+                if let Declaration::Function(function) = declaration {
+                    if function.is_public() {
+                        functions.push(function.identifier().span.str().to_string());
+                    }
+                }
+            }
+        }
+
+        Ok(exported)
+    }
+
+    /// Register the scripts of the dependency whose namespace is renamed in the file provider.
+    ///
+    /// # Errors
+    /// - If a script cannot be read or parsed.
+    /// - If a namespace is renamed to an invalid namespace.
+    pub fn register_renamed_scripts(&self, file_provider: &mut ScriptProvider) -> Result<()> {
+        let dependency = &self.dependency;
+        if dependency.namespace_prefix.is_none() && dependency.rename_namespaces.is_empty() {
+            return Ok(());
+        }
+
+        let mut unused_renames = dependency
+            .rename_namespaces
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        for (identifier, path) in self.script_paths()? {
+            let content = fs::read_to_string(&path)?;
+            let program = shulkerscript::parse(
+                &SilentHandler::new(),
+                &ScriptProvider::default(),
+                &path,
+                identifier,
+            )?;
+            let literal = program.namespace().namespace_name();
+            let original = literal.str_content();
+            unused_renames.retain(|name| *name != original);
+
+            let mapped = dependency.map_namespace(&original);
+            if mapped == original {
+                continue;
+            }
+            if !util::is_valid_namespace(&mapped) {
+                print_error(format!(
+                    "Dependency {} renames the namespace \"{original}\" to the invalid namespace \"{mapped}\".",
+                    self.name
+                ));
+                return Err(Error::InvalidNamespaceError(mapped.into_owned()))?;
+            }
+
+            let location = literal.span.start_location();
+            let start = byte_offset(&content, location.line, location.column).ok_or_else(|| {
+                anyhow::anyhow!("Invalid namespace location in {}", path.display())
+            })?;
+            let end = start + literal.span.str().len();
+
+            let rewritten = format!("{}\"{mapped}\"{}", &content[..start], &content[end..]);
+            file_provider.insert(path, rewritten);
+        }
+
+        for name in unused_renames {
+            print_warning(format!(
+                "Dependency {} does not declare the namespace \"{name}\" that is configured to be renamed.",
+                self.name
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Find imports of the dependency scripts that refer to scripts outside of the dependency and
+    /// its own dependencies.
+    pub fn foreign_imports(&self) -> Result<Vec<ForeignImport>> {
+        let allowed_prefixes = std::iter::once(&self.name)
+            .chain(self.config.dependencies.keys())
+            .map(|name| format!("{name}/"))
+            .collect::<Vec<_>>();
+        let mut foreign = Vec::new();
+
+        for (identifier, path) in self.script_paths()? {
+            let program = shulkerscript::parse(
+                &SilentHandler::new(),
+                &ScriptProvider::default(),
+                &path,
+                identifier.clone(),
+            )?;
+            for declaration in program.declarations() {
+                if let Declaration::Import(import) = declaration {
+                    let module = import.module().str_content();
+                    let imported = import_identifier(&identifier, &module);
+                    if !allowed_prefixes
+                        .iter()
+                        .any(|prefix| imported.starts_with(prefix))
+                    {
+                        foreign.push(ForeignImport {
+                            dependency: self.name.clone(),
+                            path: path.clone(),
+                            line: import.span().start_location().line,
+                            module: module.into_owned(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(foreign)
+    }
+}
+
+/// Resolve all dependencies of a project and the dependencies of its dependencies, fetching git
+/// dependencies that are not cached yet.
+///
+/// Path dependencies of dependencies are relative to the depending dependency. Dependencies of the
+/// project take precedence over dependencies of dependencies with the same name.
+///
+/// # Errors
+/// - If a dependency cannot be resolved.
+/// - If dependencies of dependencies with the same name refer to different sources.
+pub fn resolve_dependencies(
+    config: &ProjectConfig,
+    project_root: &Path,
+    locked: bool,
+    strict: bool,
+) -> Result<Vec<ResolvedDependency>> {
+    let mut queue = config
+        .dependencies
+        .iter()
+        .map(|(name, dependency)| {
+            (
+                name.clone(),
+                dependency.clone(),
+                project_root.to_path_buf(),
+                None,
+            )
+        })
+        .collect::<VecDeque<_>>();
+    let mut sources = BTreeMap::<String, (String, Option<String>)>::new();
+    let mut resolved = Vec::new();
+
+    while let Some((name, dependency, root, required_by)) = queue.pop_front() {
+        let source = source_of(&dependency, &root)?;
+        if let Some((first_source, first_required_by)) = sources.get(&name) {
+            if first_required_by.is_some() && *first_source != source {
+                print_error(format!(
+                    "The dependencies {} and {} depend on different sources named {name}: {first_source} and {source}. Add {name} to the [dependencies] section of the pack.toml file to choose one.",
+                    first_required_by.as_deref().unwrap_or_default(),
+                    required_by.as_deref().unwrap_or_default(),
+                ));
+                return Err(Error::DependencyConflictError(name).into());
+            }
+            continue;
+        }
+
+        let mut resolved_dependency =
+            resolve_dependency(&name, &dependency, &root, locked, false, strict)?;
+        resolved_dependency.required_by = required_by.clone();
+        queue.extend(resolved_dependency.config.dependencies.iter().map(
+            |(nested, nested_dependency)| {
+                (
+                    nested.clone(),
+                    nested_dependency.clone(),
+                    resolved_dependency.path.clone(),
+                    Some(name.clone()),
+                )
+            },
+        ));
+        sources.insert(name, (source, required_by));
+        resolved.push(resolved_dependency);
+    }
+
+    Ok(resolved)
+}
+
+/// Describe the source of a dependency declared in the project at the root, used to compare
+/// dependencies without fetching them.
+fn source_of(dependency: &DependencyConfig, root: &Path) -> Result<String> {
+    Ok(match (&dependency.git, &dependency.path) {
+        (Some(git), _) => match &dependency.rev {
+            Some(rev) => format!("{}#{rev}", fetch::resolve_git_url(git)),
+            None => fetch::resolve_git_url(git),
+        },
+        (None, Some(path)) => root.join(path).absolutize()?.display().to_string(),
+        (None, None) => String::new(),
+    })
+}
+
+/// Resolve a single dependency.
+///
+/// Git dependencies are fetched if they are not cached yet or `update` is set and verified
+/// against their pin.
+pub fn resolve_dependency(
+    name: &str,
+    dependency: &DependencyConfig,
+    project_root: &Path,
+    locked: bool,
+    update: bool,
+    strict: bool,
+) -> Result<ResolvedDependency> {
+    let mut fetched = None;
+    let path = match (&dependency.git, &dependency.path) {
+        (Some(git), None) => {
+            let url = fetch::resolve_git_url(git);
+            let path = fetch::git_cache_path(&url, dependency.rev.as_deref());
+
+            // parallel workspace builds share the cache, so every source is fetched at most once,
+            // only builds using the same source wait for each other
+            let source = FETCHED_SOURCES
+                .lock()
+                .unwrap()
+                .entry(path.clone())
+                .or_default()
+                .clone();
+            let mut ready = source.lock().unwrap();
+            let fetch = !*ready && (update || !path.join(".git").exists());
+            if fetch {
+                print_info(format!("Fetching dependency {name} from {url}"));
+                fetch::fetch_git(&url, dependency.rev.as_deref(), &path).inspect_err(|err| {
+                    print_error(format!("Could not fetch dependency {name}: {err}"));
+                })?;
+            }
+            fetched = Some(fetch);
+            fetch::verify(
+                &path,
+                dependency.sha256.as_ref(),
+                locked,
+                &format!("dependency {name}"),
+            )
+            .inspect_err(|err| print_error(err))?;
+            *ready = true;
+            path
+        }
+        (None, Some(path)) => project_root.join(path).absolutize()?.into_owned(),
+        _ => {
+            print_error(format!(
+                "Dependency {name} must specify exactly one of `git` or `path`."
+            ));
+            return Err(anyhow::anyhow!("Invalid dependency {name}"));
+        }
+    };
+
+    let (config, _) = get_pack_config(&path, strict)?;
+
+    Ok(ResolvedDependency {
+        name: name.to_string(),
+        path,
+        config,
+        dependency: dependency.clone(),
+        fetched,
+        required_by: None,
+    })
+}
+
+/// Provides the contents of scripts, replacing registered scripts with rewritten contents.
+///
+/// The `#[allow(...)]` annotations of the build are blanked out, as the compiler does not know them.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptProvider {
+    rewritten: HashMap<PathBuf, String>,
+}
+
+impl ScriptProvider {
+    /// Replace the content of the script at the given path.
+    pub fn insert(&mut self, path: PathBuf, content: String) {
+        self.rewritten.insert(path, content);
+    }
+}
+
+impl FileProvider for ScriptProvider {
+    fn read_bytes<P: AsRef<Path>>(&self, path: P) -> Result<Cow<'_, [u8]>, FileProviderError> {
+        match self.rewritten.get(path.as_ref()) {
+            Some(content) => Ok(Cow::Borrowed(content.as_bytes())),
+            None => fs::read(path)
+                .map(Cow::Owned)
+                .map_err(FileProviderError::from),
+        }
+    }
+
+    fn read_str<P: AsRef<Path>>(&self, path: P) -> Result<Cow<'_, str>, FileProviderError> {
+        let content = match self.rewritten.get(path.as_ref()) {
+            Some(content) => Cow::Borrowed(content.as_str()),
+            None => Cow::Owned(fs::read_to_string(path).map_err(FileProviderError::from)?),
+        };
+        Ok(match lints::strip_allow_annotations(&content) {
+            Some(stripped) => Cow::Owned(stripped),
+            None => content,
+        })
+    }
+}
+
+/// The scripts of a pack that is merged into the datapack.
+#[derive(Debug, Clone)]
+pub struct PackScripts {
+    /// A human readable description of the pack, e.g. `dependency mylib`.
+    pub origin: String,
+    /// The identifiers and paths of the scripts of the pack.
+    pub scripts: Vec<(String, PathBuf)>,
+}
+
+/// The location of a declaration in a script of a pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclarationOrigin {
+    /// The description of the pack declaring it.
+    pub pack: String,
+    /// The path of the script declaring it.
+    pub path: PathBuf,
+    /// The line of the declaration.
+    pub line: usize,
+}
+
+impl Display for DeclarationOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}:{})", self.pack, self.path.display(), self.line)
+    }
+}
+
+/// A collision between declarations of different packs that are merged into the same datapack.
+///
+/// Collisions prevent the datapack from being built. A namespace collision is resolved by moving
+/// the dependency to another namespace with `namespace_prefix` or `rename_namespaces`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Collision {
+    /// Multiple packs declare the same namespace.
+    Namespace {
+        namespace: String,
+        first: DeclarationOrigin,
+        second: DeclarationOrigin,
+    },
+    /// Multiple packs declare functions with the same resource location.
+    Function {
+        location: String,
+        first: DeclarationOrigin,
+        second: DeclarationOrigin,
+    },
+}
+
+impl Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Namespace {
+                namespace,
+                first,
+                second,
+            } => write!(
+                f,
+                "Namespace \"{namespace}\" is declared by {first} and by {second}"
+            ),
+            Self::Function {
+                location,
+                first,
+                second,
+            } => write!(
+                f,
+                "Function {location} is declared by {first} and by {second}"
+            ),
+        }
+    }
+}
+
+/// An import of a dependency script that refers to a script outside of the dependency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignImport {
+    /// The name of the dependency.
+    pub dependency: String,
+    /// The path of the importing script.
+    pub path: PathBuf,
+    /// The line of the import.
+    pub line: usize,
+    /// The imported module path.
+    pub module: String,
+}
+
+impl Display for ForeignImport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Dependency {} imports \"{}\" from outside of the dependency ({}:{})",
+            self.dependency,
+            self.module,
+            self.path.display(),
+            self.line
+        )
+    }
+}
+
+/// Find namespaces and functions that are declared by more than one pack.
+///
+/// Only functions with a fixed name (`#[deobfuscate]`) can collide, as the names of the other
+/// functions are derived from the unique script identifiers.
+pub fn find_collisions(
+    packs: &[PackScripts],
+    file_provider: &ScriptProvider,
+) -> Result<Vec<Collision>> {
+    let mut namespaces = BTreeMap::<String, DeclarationOrigin>::new();
+    let mut functions = BTreeMap::<String, DeclarationOrigin>::new();
+    let mut reported_namespaces = BTreeSet::new();
+    let mut collisions = Vec::new();
+
+    for pack in packs {
+        for (identifier, path) in &pack.scripts {
+            let program = shulkerscript::parse(
+                &SilentHandler::new(),
+                file_provider,
+                path,
+                identifier.clone(),
+            )?;
+            let origin_at = |line| DeclarationOrigin {
+                pack: pack.origin.clone(),
+                path: path.clone(),
+                line,
+            };
+
+            let namespace = program
+                .namespace()
+                .namespace_name()
+                .str_content()
+                .into_owned();
+            let namespace_origin = origin_at(program.namespace().span().start_location().line);
+            match namespaces.get(&namespace) {
+                Some(first) if first.pack != pack.origin => {
+                    if reported_namespaces.insert((namespace.clone(), pack.origin.clone())) {
+                        collisions.push(Collision::Namespace {
+                            namespace: namespace.clone(),
+                            first: first.clone(),
+                            second: namespace_origin,
+                        });
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    namespaces.insert(namespace.clone(), namespace_origin);
+                }
+            }
+
+            for (location, line) in fixed_function_locations(&program, &namespace) {
+                match functions.get(&location) {
+                    Some(first) if first.pack != pack.origin => {
+                        collisions.push(Collision::Function {
+                            location,
+                            first: first.clone(),
+                            second: origin_at(line),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        functions.insert(location, origin_at(line));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(collisions)
+}
+
+/// Get the resource locations and lines of the functions with a fixed name in the program.
+fn fixed_function_locations(program: &ProgramFile, namespace: &str) -> Vec<(String, usize)> {
+    program
+        .declarations()
+        .iter()
+        .filter_map(|declaration| match declaration {
+            Declaration::Function(function) => {
+                let annotation = function
+                    .annotations()
+                    .iter()
+                    .find(|annotation| annotation.identifier().span.str() == "deobfuscate")?;
+                let name = annotation
+                    .value()
+                    .as_ref()
+                    .map(|(_, value)| value.str_content().into_owned())
+                    .unwrap_or_else(|| function.identifier().span.str().to_string());
+                Some((
+                    format!("{namespace}:{name}"),
+                    function.span().start_location().line,
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Resolve the identifier of an imported script, relative to the importing script.
+pub(crate) fn import_identifier(current: &str, module: &str) -> String {
+    let mut elements = Vec::new();
+    if let Some(absolute) = module.strip_prefix('/') {
+        elements.extend(absolute.split('/'));
+    } else {
+        elements.extend(current.split('/'));
+        elements.pop();
+        elements.extend(module.split('/'));
+    }
+
+    let mut normalized = Vec::new();
+    for element in elements {
+        match element {
+            "" | "." => {}
+            ".." => {
+                normalized.pop();
+            }
+            element => normalized.push(element),
+        }
+    }
+    normalized.join("/")
+}
+
+/// Get the byte offset of a line and column (both starting at 1, the column counted in chars).
+fn byte_offset(content: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = content
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum::<usize>();
+    let line_content = content.get(line_start..)?;
+    line_content
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(line_content.len()))
+        .nth(column - 1)
+        .map(|offset| line_start + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_import_identifier() {
+        assert_eq!(import_identifier("lib/util/math", "vec"), "lib/util/vec");
+        assert_eq!(import_identifier("lib/util/math", "../main"), "lib/main");
+        assert_eq!(import_identifier("lib/util/math", "/app/main"), "app/main");
+        assert_eq!(import_identifier("lib/main", "../../app/main"), "app/main");
+    }
+
+    #[test]
+    fn test_resolve_nested_dependencies() {
+        let temp = TestProject::empty("nested-dependencies");
+        let dir = temp.path();
+        let write_pack = |name: &str, dependencies: &str| {
+            fs::create_dir_all(dir.join(name).join("src")).unwrap();
+            fs::write(
+                dir.join(name).join("pack.toml"),
+                format!("[pack]\nname = \"{name}\"\ndescription = \"\"\npack_format = 48\nversion = \"0.1.0\"\n\n[dependencies]\n{dependencies}"),
+            )
+            .unwrap();
+        };
+        write_pack("app", "ui = { path = \"../ui\" }\n");
+        write_pack("ui", "math = { path = \"../math\" }\n");
+        write_pack("math", "");
+        write_pack("other_math", "");
+
+        let (config, _) = get_pack_config(&dir.join("app"), false).unwrap();
+        let resolved = resolve_dependencies(&config, &dir.join("app"), false, false).unwrap();
+        let names = resolved
+            .iter()
+            .map(|dependency| (dependency.name.as_str(), dependency.required_by.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(names, [("ui", None), ("math", Some("ui"))]);
+        assert_eq!(resolved[1].path, dir.join("math"));
+
+        // dependencies of dependencies with the same name must refer to the same source
+        write_pack(
+            "app",
+            "ui = { path = \"../ui\" }\nlib = { path = \"../lib\" }\n",
+        );
+        write_pack("lib", "math = { path = \"../other_math\" }\n");
+        let (config, _) = get_pack_config(&dir.join("app"), false).unwrap();
+        assert!(resolve_dependencies(&config, &dir.join("app"), false, false).is_err());
+
+        // unless the project chooses one
+        write_pack(
+            "app",
+            "ui = { path = \"../ui\" }\nlib = { path = \"../lib\" }\nmath = { path = \"../other_math\" }\n",
+        );
+        let (config, _) = get_pack_config(&dir.join("app"), false).unwrap();
+        let resolved = resolve_dependencies(&config, &dir.join("app"), false, false).unwrap();
+        assert_eq!(resolved.len(), 3);
+        assert!(resolved.iter().any(
+            |dependency| dependency.name == "math" && dependency.path == dir.join("other_math")
+        ));
+    }
+
+    #[test]
+    fn test_byte_offset() {
+        let content = "// ä comment\nnamespace \"lib\";\n";
+        let offset = byte_offset(content, 2, 11).unwrap();
+        assert_eq!(&content[offset..offset + 5], "\"lib\"");
+    }
+}
@@ -0,0 +1,750 @@
+//! Detection of the worlds datapacks are deployed to.
+//!
+//! Besides `datapacks` folders themselves, output directories can be singleplayer worlds
+//! (containing a `level.dat` file) or server directories (containing a `server.properties` file),
+//! whose active world is read from the `level-name` property. Vanilla, Paper, Spigot/Bukkit,
+//! Fabric, Forge and NeoForge servers are recognized by their files.
+//!
+//! Datapacks are enabled and disabled with the `/datapack` command on running servers and in the
+//! `level.dat` file of worlds that are not loaded.
+//!
+//! Before deploying, the replaced datapack or the whole `datapacks` folder can be backed up into
+//! the `shulkerscript-backups` folder of the world, from which the last deploy is rolled back.
+
+use std::{
+    env,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{nbt, rcon::Rcon, versions};
+
+/// The folder of the backups in the world folder, which is ignored by the game.
+pub const BACKUP_DIR: &str = "shulkerscript-backups";
+/// The file describing a backup in its folder.
+const BACKUP_MANIFEST: &str = "backup.json";
+
+/// The software of a server directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerKind {
+    Vanilla,
+    Paper,
+    /// Spigot or CraftBukkit.
+    Spigot,
+    Fabric,
+    Forge,
+    NeoForge,
+}
+
+impl Display for ServerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Vanilla => write!(f, "vanilla"),
+            Self::Paper => write!(f, "Paper"),
+            Self::Spigot => write!(f, "Spigot/Bukkit"),
+            Self::Fabric => write!(f, "Fabric"),
+            Self::Forge => write!(f, "Forge"),
+            Self::NeoForge => write!(f, "NeoForge"),
+        }
+    }
+}
+
+/// A world that datapacks can be deployed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeployTarget {
+    /// A singleplayer world or a world folder of a server.
+    World { path: PathBuf },
+    /// The active world of a server.
+    Server {
+        root: PathBuf,
+        kind: ServerKind,
+        world: PathBuf,
+    },
+}
+
+impl DeployTarget {
+    /// Detect the world or server at the path, `None` if it is neither.
+    ///
+    /// Worlds in a server directory, e.g. `world_nether`, are detected as world of the server and
+    /// `datapacks` folders as their world.
+    pub fn detect(path: &Path) -> Option<Self> {
+        if path.file_name().is_some_and(|name| name == "datapacks") {
+            return path.parent().and_then(Self::detect);
+        }
+
+        if path.join("server.properties").is_file() {
+            Some(Self::Server {
+                root: path.to_path_buf(),
+                kind: server_kind(path),
+                world: path.join(level_name(path)),
+            })
+        } else if let Some(root) = path
+            .parent()
+            .filter(|parent| parent.join("server.properties").is_file())
+            .filter(|_| path.join("level.dat").is_file())
+        {
+            Some(Self::Server {
+                root: root.to_path_buf(),
+                kind: server_kind(root),
+                world: path.to_path_buf(),
+            })
+        } else if path.join("level.dat").is_file() {
+            Some(Self::World {
+                path: path.to_path_buf(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The world folder.
+    pub fn world(&self) -> &Path {
+        match self {
+            Self::World { path } => path,
+            Self::Server { world, .. } => world,
+        }
+    }
+
+    /// The `datapacks` folder of the world.
+    pub fn datapacks_dir(&self) -> PathBuf {
+        self.world().join("datapacks")
+    }
+
+    /// Get the Minecraft version of the world or server.
+    ///
+    /// The version the world was last saved with is preferred, as it also covers servers that
+    /// have not been recognized. Servers that did not create their world yet are detected by
+    /// their files.
+    pub fn minecraft_version(&self) -> Option<String> {
+        world_version(self.world()).or_else(|| match self {
+            Self::World { .. } => None,
+            Self::Server { root, kind, .. } => server_version(root, *kind),
+        })
+    }
+
+    /// The RCON port and password of the server, `None` for worlds and servers without RCON
+    /// enabled.
+    pub fn rcon(&self) -> Option<(u16, String)> {
+        let Self::Server { root, .. } = self else {
+            return None;
+        };
+        if server_property(root, "enable-rcon").as_deref() != Some("true") {
+            return None;
+        }
+        let port = server_property(root, "rcon.port")
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(25575);
+        let password = server_property(root, "rcon.password").unwrap_or_default();
+        Some((port, password))
+    }
+}
+
+impl Display for DeployTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::World { path } => write!(f, "the world {}", path.display()),
+            Self::Server { root, kind, world } => write!(
+                f,
+                "the world {} of the {kind} server at {}",
+                world.strip_prefix(root).unwrap_or(world).display(),
+                root.display()
+            ),
+        }
+    }
+}
+
+/// Get the `datapacks` folder of the world or server at the path, or the path itself if it is
+/// neither.
+pub fn resolve_output_dir(path: &Path) -> PathBuf {
+    DeployTarget::detect(path).map_or_else(|| path.to_path_buf(), |target| target.datapacks_dir())
+}
+
+/// Get the `saves` folder of the official launcher, `None` if it does not exist.
+pub fn minecraft_saves_dir() -> Option<PathBuf> {
+    let game_dir = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join(".minecraft"))
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join("Library/Application Support/minecraft"))
+    } else {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join(".minecraft"))
+    };
+    game_dir
+        .map(|dir| dir.join("saves"))
+        .filter(|saves| saves.is_dir())
+}
+
+/// Get the worlds in the `saves` folder, the most recently played first.
+///
+/// # Errors
+/// - If the folder cannot be read.
+pub fn saved_worlds(saves: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut worlds = fs::read_dir(saves)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter_map(|path| {
+            let played = fs::metadata(path.join("level.dat"))
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            Some((played, path))
+        })
+        .collect::<Vec<_>>();
+    worlds.sort_by(|(a, a_path), (b, b_path)| b.cmp(a).then_with(|| a_path.cmp(b_path)));
+    Ok(worlds.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Copy the artifact folder or zip file to the destination.
+///
+/// # Errors
+/// - If a file cannot be copied.
+pub fn copy_artifact(source: &Path, destination: &Path) -> io::Result<()> {
+    if source.is_dir() {
+        fs::create_dir_all(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_artifact(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(source, destination).map(|_| ())
+    }
+}
+
+/// What is backed up before deploying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupScope {
+    /// The datapack replaced by the deploy.
+    Pack,
+    /// The whole `datapacks` folder.
+    All,
+}
+
+/// The datapack replaced by a deploy and what was backed up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backup {
+    /// The name of the deployed datapack in the `datapacks` folder.
+    pub datapack: String,
+    pub scope: BackupScope,
+    /// The time of the backup, in seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// Back up the datapack of the target that is replaced by deploying the datapack with the name,
+/// or the whole `datapacks` folder, removing the oldest backups exceeding the number to keep.
+///
+/// A backup of a datapack that does not exist yet is empty, rolling it back removes the datapack.
+/// Returns the folder of the backup.
+///
+/// # Errors
+/// - If the datapacks cannot be copied or the outdated backups cannot be removed.
+pub fn backup(
+    target: &DeployTarget,
+    datapack: &str,
+    scope: BackupScope,
+    keep: usize,
+) -> io::Result<PathBuf> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let backups_dir = target.world().join(BACKUP_DIR);
+    // deploys in the same millisecond, e.g. of several projects, are numbered after the newest
+    // one, as the numbers of pruned backups must not be reused
+    let stamp = format!("{:015}", now.as_millis());
+    let index = fs::read_dir(&backups_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()?
+                .strip_prefix(&stamp)?
+                .strip_prefix('-')?
+                .parse::<u32>()
+                .ok()
+        })
+        .max()
+        .map_or(0, |index| index + 1);
+    let dir = backups_dir.join(format!("{stamp}-{index:03}"));
+    fs::create_dir_all(&dir)?;
+
+    let datapacks = target.datapacks_dir();
+    let (source, destination) = match scope {
+        BackupScope::Pack => (datapacks.join(datapack), dir.join(datapack)),
+        BackupScope::All => (datapacks, dir.join("datapacks")),
+    };
+    if source.exists() {
+        copy_artifact(&source, &destination)?;
+    }
+    let backup = Backup {
+        datapack: datapack.to_string(),
+        scope,
+        timestamp: now.as_secs(),
+    };
+    fs::write(
+        dir.join(BACKUP_MANIFEST),
+        serde_json::to_string_pretty(&backup)?,
+    )?;
+
+    for outdated in backups(target)?.into_iter().skip(keep) {
+        fs::remove_dir_all(outdated)?;
+    }
+    Ok(dir)
+}
+
+/// Restore the datapacks of the target from the backup of the last deploy and remove the backup.
+///
+/// Returns the restored backup, `None` if there is none.
+///
+/// # Errors
+/// - If the backup cannot be read or restored.
+pub fn rollback(target: &DeployTarget) -> io::Result<Option<Backup>> {
+    let Some(dir) = backups(target)?.into_iter().next() else {
+        return Ok(None);
+    };
+    let backup = serde_json::from_str::<Backup>(&fs::read_to_string(dir.join(BACKUP_MANIFEST))?)?;
+
+    let datapacks = target.datapacks_dir();
+    let (saved, restored) = match backup.scope {
+        BackupScope::Pack => (dir.join(&backup.datapack), datapacks.join(&backup.datapack)),
+        BackupScope::All => (dir.join("datapacks"), datapacks),
+    };
+    remove_path(&restored)?;
+    if saved.exists() {
+        copy_artifact(&saved, &restored)?;
+    }
+    fs::remove_dir_all(dir)?;
+    Ok(Some(backup))
+}
+
+/// The backup folders of the target, newest first.
+fn backups(target: &DeployTarget) -> io::Result<Vec<PathBuf>> {
+    let backups_dir = target.world().join(BACKUP_DIR);
+    if !backups_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(backups_dir)? {
+        let path = entry?.path();
+        if path.join(BACKUP_MANIFEST).is_file() {
+            backups.push(path);
+        }
+    }
+    backups.sort();
+    backups.reverse();
+    Ok(backups)
+}
+
+/// Remove the file or folder at the path, if it exists.
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that the pack format is the one used by the Minecraft version.
+///
+/// Returns `Ok` for versions whose pack format is unknown.
+///
+/// # Errors
+/// - If the version uses another pack format, with a message describing the mismatch.
+pub fn check_compatibility(version: &str, pack_format: u8) -> Result<(), String> {
+    match versions::pack_format_of_version(version) {
+        Some(entry) if entry.format != pack_format => Err(format!(
+            "Minecraft {version} uses the pack format {}, but the pack has the format {pack_format}",
+            entry.format
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Reload the datapacks of the server and enable the datapack if it is not enabled yet.
+///
+/// The name is the name of the folder or zip file in the `datapacks` folder. Returns whether the
+/// datapack is listed as enabled afterwards.
+///
+/// # Errors
+/// - If the commands cannot be sent.
+pub fn enable_datapack(rcon: &mut Rcon, name: &str) -> io::Result<bool> {
+    // discovers new datapacks and loads the new content of enabled ones
+    rcon.command("reload")?;
+    set_enabled_on_server(rcon, &format!("file/{name}"), true)
+}
+
+/// Enable or disable the datapack with the id, e.g. `file/<name>`, on the server.
+///
+/// Returns whether the datapack is listed as enabled or not afterwards, as requested.
+///
+/// # Errors
+/// - If the commands cannot be sent.
+pub fn set_enabled_on_server(rcon: &mut Rcon, id: &str, enabled: bool) -> io::Result<bool> {
+    let is_enabled =
+        |list: &str| list.contains(&format!("[{id}]")) || list.contains(&format!("[{id} ("));
+
+    if is_enabled(&rcon.command("datapack list enabled")?) == enabled {
+        return Ok(true);
+    }
+    rcon.command(&format!(
+        "datapack {} \"{}\"",
+        if enabled { "enable" } else { "disable" },
+        id.replace('\\', "\\\\").replace('"', "\\\"")
+    ))?;
+    Ok(is_enabled(&rcon.command("datapack list enabled")?) == enabled)
+}
+
+/// The id of a datapack of the world: `file/<name>` for the folders and zip files of its
+/// `datapacks` folder, otherwise the name itself, e.g. for built-in packs like `bundle`.
+pub fn datapack_id(world: &Path, name: &str) -> String {
+    if !name.contains('/') && world.join("datapacks").join(name).exists() {
+        format!("file/{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// The enabled and disabled datapacks of a world, in the order of their priority.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatapackState {
+    pub enabled: Vec<String>,
+    pub disabled: Vec<String>,
+}
+
+/// Read the enabled and disabled datapacks from the `level.dat` file of the world.
+///
+/// # Errors
+/// - If the `level.dat` file cannot be read.
+pub fn read_world_datapacks(world: &Path) -> io::Result<DatapackState> {
+    let (_, level) = nbt::read(&fs::read(world.join("level.dat"))?)?;
+    let data_packs = level.get("Data").and_then(|data| data.get("DataPacks"));
+    let ids = |key: &str| {
+        data_packs
+            .and_then(|data_packs| data_packs.get(key))
+            .and_then(nbt::Tag::as_list)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|tag| match tag {
+                nbt::Tag::String(id) => Some(id.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+    Ok(DatapackState {
+        enabled: ids("Enabled"),
+        disabled: ids("Disabled"),
+    })
+}
+
+/// Enable or disable the datapack with the id in the `level.dat` file of the world.
+///
+/// Enabled datapacks are added with the highest priority. The previous file is kept as
+/// `level.dat_old`, as the game does. The game overwrites the file when saving a loaded world, so
+/// the world must not be open. Returns whether the file was changed.
+///
+/// # Errors
+/// - If the `level.dat` file cannot be read, is invalid or cannot be written.
+pub fn set_enabled_in_world(world: &Path, id: &str, enabled: bool) -> io::Result<bool> {
+    let path = world.join("level.dat");
+    let (name, mut level) = nbt::read(&fs::read(&path)?)?;
+    let data = level
+        .get_mut("Data")
+        .ok_or_else(|| invalid_level("the Data compound is missing"))?;
+    let data_packs = entry_or_insert(data, "DataPacks", nbt::Tag::Compound(Vec::new()))?;
+
+    let id = nbt::Tag::String(id.to_string());
+    let (add_to, remove_from) = if enabled {
+        ("Enabled", "Disabled")
+    } else {
+        ("Disabled", "Enabled")
+    };
+    let removed = match entry_or_insert(data_packs, remove_from, nbt::Tag::List(Vec::new()))? {
+        nbt::Tag::List(ids) => {
+            let len = ids.len();
+            ids.retain(|tag| *tag != id);
+            ids.len() != len
+        }
+        _ => return Err(invalid_level("the datapack lists are not lists")),
+    };
+    let added = match entry_or_insert(data_packs, add_to, nbt::Tag::List(Vec::new()))? {
+        nbt::Tag::List(ids) if ids.contains(&id) => false,
+        nbt::Tag::List(ids) => {
+            ids.push(id);
+            true
+        }
+        _ => return Err(invalid_level("the datapack lists are not lists")),
+    };
+    if !removed && !added {
+        return Ok(false);
+    }
+
+    let new = world.join("level.dat_new");
+    fs::write(&new, nbt::write_compressed(&name, &level)?)?;
+    fs::copy(&path, world.join("level.dat_old"))?;
+    fs::rename(new, path)?;
+    Ok(true)
+}
+
+/// Get the entry of the compound, inserting the default if it is missing.
+fn entry_or_insert<'a>(
+    compound: &'a mut nbt::Tag,
+    key: &str,
+    default: nbt::Tag,
+) -> io::Result<&'a mut nbt::Tag> {
+    let nbt::Tag::Compound(entries) = compound else {
+        return Err(invalid_level(&format!("{key} is not in a compound")));
+    };
+    let index = match entries.iter().position(|(name, _)| name == key) {
+        Some(index) => index,
+        None => {
+            entries.push((key.to_string(), default));
+            entries.len() - 1
+        }
+    };
+    Ok(&mut entries[index].1)
+}
+
+fn invalid_level(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid level.dat file: {message}"),
+    )
+}
+
+/// Read the `level-name` property of the `server.properties` file, defaulting to `world`.
+fn level_name(server_root: &Path) -> String {
+    server_property(server_root, "level-name")
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "world".to_string())
+}
+
+/// Read a property of the `server.properties` file.
+fn server_property(server_root: &Path, key: &str) -> Option<String> {
+    let content = fs::read_to_string(server_root.join("server.properties")).ok()?;
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| line.split_once(['=', ':']))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| value.trim().to_string())
+}
+
+fn server_kind(root: &Path) -> ServerKind {
+    let has_jar = |prefix: &str| {
+        fs::read_dir(root).is_ok_and(|entries| {
+            entries.filter_map(Result::ok).any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                name.starts_with(prefix) && name.ends_with(".jar")
+            })
+        })
+    };
+
+    if root.join("config/paper-global.yml").is_file()
+        || root.join("paper.yml").is_file()
+        || has_jar("paper")
+    {
+        ServerKind::Paper
+    } else if root.join("spigot.yml").is_file() || root.join("bukkit.yml").is_file() {
+        ServerKind::Spigot
+    } else if root.join(".fabric").is_dir()
+        || root.join("fabric-server-launcher.properties").is_file()
+    {
+        ServerKind::Fabric
+    } else if root.join("libraries/net/neoforged").is_dir() {
+        ServerKind::NeoForge
+    } else if root.join("libraries/net/minecraftforge").is_dir() || has_jar("forge") {
+        ServerKind::Forge
+    } else {
+        ServerKind::Vanilla
+    }
+}
+
+/// Read the version the world was last saved with from its `level.dat` file.
+fn world_version(world: &Path) -> Option<String> {
+    let (_, level) = nbt::read(&fs::read(world.join("level.dat")).ok()?).ok()?;
+    match level.get("Data")?.get("Version")?.get("Name")? {
+        nbt::Tag::String(name) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// Detect the Minecraft version of a server by the files of its software.
+fn server_version(root: &Path, kind: ServerKind) -> Option<String> {
+    let dir_names = |dir: &str| -> Vec<String> {
+        let mut names = fs::read_dir(root.join(dir))
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    };
+
+    match kind {
+        // e.g. {"currentVersion": "git-Paper-196 (MC: 1.21.1)"}
+        ServerKind::Paper | ServerKind::Spigot => {
+            let history = fs::read_to_string(root.join("version_history.json")).ok()?;
+            let start = history.find("MC: ")? + 4;
+            let end = start + history[start..].find(')')?;
+            Some(history[start..end].to_string())
+        }
+        // e.g. .fabric/server/1.21.1-server.jar
+        ServerKind::Fabric => dir_names(".fabric/server")
+            .into_iter()
+            .find_map(|name| name.strip_suffix("-server.jar").map(str::to_string)),
+        // e.g. libraries/net/minecraftforge/forge/1.21.1-52.0.16
+        ServerKind::Forge => dir_names("libraries/net/minecraftforge/forge")
+            .into_iter()
+            .find_map(|name| name.split_once('-').map(|(version, _)| version.to_string())),
+        // e.g. libraries/net/neoforged/neoforge/21.1.77 for Minecraft 1.21.1
+        ServerKind::NeoForge => dir_names("libraries/net/neoforged/neoforge")
+            .into_iter()
+            .find_map(|name| {
+                let mut parts = name.split('.');
+                let major = parts.next()?;
+                match parts.next()? {
+                    "0" => Some(format!("1.{major}")),
+                    minor => Some(format!("1.{major}.{minor}")),
+                }
+            }),
+        // e.g. versions/1.21.1/server-1.21.1.jar of the bundled server jar
+        ServerKind::Vanilla => dir_names("versions").into_iter().next(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_detect_server() {
+        let temp = TestProject::empty("deploy");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join(".fabric/server")).unwrap();
+        fs::write(dir.join(".fabric/server/1.21.1-server.jar"), "").unwrap();
+        fs::write(
+            dir.join("server.properties"),
+            "# Minecraft server properties\nlevel-name=survival\nenable-rcon=true\nrcon.password=secret\n",
+        )
+        .unwrap();
+
+        let target = DeployTarget::detect(dir).unwrap();
+        assert_eq!(
+            target,
+            DeployTarget::Server {
+                root: dir.to_path_buf(),
+                kind: ServerKind::Fabric,
+                world: dir.join("survival"),
+            }
+        );
+        assert_eq!(target.datapacks_dir(), dir.join("survival/datapacks"));
+        assert_eq!(target.minecraft_version().as_deref(), Some("1.21.1"));
+        assert_eq!(target.rcon(), Some((25575, "secret".to_string())));
+
+        fs::create_dir_all(dir.join("survival_nether/datapacks")).unwrap();
+        fs::write(dir.join("survival_nether/level.dat"), "").unwrap();
+        let nether = DeployTarget::detect(&dir.join("survival_nether/datapacks")).unwrap();
+        assert_eq!(nether.world(), dir.join("survival_nether"));
+        assert!(matches!(nether, DeployTarget::Server { root, .. } if root == dir));
+        assert!(check_compatibility("1.21.1", 48).is_ok());
+        assert!(check_compatibility("1.21.1", 57).is_err());
+
+        assert_eq!(resolve_output_dir(&dir.join("dist")), dir.join("dist"));
+    }
+
+    #[test]
+    fn test_saved_worlds() {
+        let temp = TestProject::empty("deploy-saves");
+        let dir = temp.path();
+        let now = SystemTime::now();
+        for (world, hours_ago) in [("a", 2), ("b", 0), ("c", 1)] {
+            fs::create_dir_all(dir.join(world)).unwrap();
+            fs::File::create(dir.join(world).join("level.dat"))
+                .unwrap()
+                .set_modified(now - Duration::from_secs(hours_ago * 3600))
+                .unwrap();
+        }
+        fs::create_dir_all(dir.join("not a world")).unwrap();
+
+        // the most recently played world first
+        let worlds = saved_worlds(dir).unwrap();
+        assert_eq!(worlds, [dir.join("b"), dir.join("c"), dir.join("a")]);
+    }
+
+    #[test]
+    fn test_backup_and_rollback() {
+        let temp = TestProject::empty("deploy-backup");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("datapacks/pack")).unwrap();
+        fs::write(dir.join("level.dat"), "").unwrap();
+        fs::write(dir.join("datapacks/pack/pack.mcmeta"), "old").unwrap();
+        let target = DeployTarget::detect(dir).unwrap();
+
+        for _ in 0..3 {
+            backup(&target, "pack", BackupScope::Pack, 2).unwrap();
+        }
+        assert_eq!(backups(&target).unwrap().len(), 2);
+        backup(&target, "new.zip", BackupScope::Pack, 2).unwrap();
+        fs::write(dir.join("datapacks/new.zip"), "new").unwrap();
+        fs::write(dir.join("datapacks/pack/pack.mcmeta"), "broken").unwrap();
+
+        // the datapack that did not exist before is removed
+        let restored = rollback(&target).unwrap().unwrap();
+        assert_eq!(restored.datapack, "new.zip");
+        assert!(!dir.join("datapacks/new.zip").exists());
+
+        let restored = rollback(&target).unwrap().unwrap();
+        assert_eq!(restored.datapack, "pack");
+        assert_eq!(
+            fs::read_to_string(dir.join("datapacks/pack/pack.mcmeta")).unwrap(),
+            "old"
+        );
+        assert!(rollback(&target).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_world_datapacks() {
+        let temp = TestProject::empty("deploy-level");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("datapacks/pack")).unwrap();
+        let level = nbt::parse_snbt(
+            r#"{Data: {DataPacks: {Enabled: ["vanilla", "file/old.zip"], Disabled: []}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("level.dat"),
+            nbt::write_compressed("", &level).unwrap(),
+        )
+        .unwrap();
+
+        let id = datapack_id(dir, "pack");
+        assert_eq!(id, "file/pack");
+        assert_eq!(datapack_id(dir, "bundle"), "bundle");
+        assert!(set_enabled_in_world(dir, &id, true).unwrap());
+        assert!(!set_enabled_in_world(dir, &id, true).unwrap());
+        assert!(set_enabled_in_world(dir, "file/old.zip", false).unwrap());
+        assert!(dir.join("level.dat_old").is_file());
+
+        assert_eq!(
+            read_world_datapacks(dir).unwrap(),
+            DatapackState {
+                enabled: vec!["vanilla".to_string(), "file/pack".to_string()],
+                disabled: vec!["file/old.zip".to_string()],
+            }
+        );
+    }
+}
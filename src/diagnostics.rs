@@ -0,0 +1,160 @@
+//! Diagnostics of scripts cached by the hash of their content.
+//!
+//! The cache is kept in `.shulkerscript/diagnostics.json` next to the build statistics, so
+//! checking a project again only parses the scripts that changed since the last check. The
+//! cache is discarded when the language version changes.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt::Display,
+    fs, io,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shulkerscript::base::Handler;
+
+use crate::{stats, terminal_output::print_diagnostic};
+
+/// The file of the cache, relative to the project root.
+pub const CACHE_FILE: &str = ".shulkerscript/diagnostics.json";
+
+/// The diagnostics of the scripts of a project by script path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticsCache {
+    /// The version of the language that produced the diagnostics.
+    version: String,
+    scripts: BTreeMap<String, CachedScript>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedScript {
+    hash: String,
+    diagnostics: Vec<String>,
+}
+
+impl DiagnosticsCache {
+    /// Load the cache of the project, empty if it does not exist or is outdated.
+    pub fn load(project_root: &Path) -> Self {
+        fs::read_to_string(project_root.join(CACHE_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|cache| cache.version == shulkerscript::VERSION)
+            .unwrap_or_else(|| Self {
+                version: shulkerscript::VERSION.to_string(),
+                scripts: BTreeMap::new(),
+            })
+    }
+
+    /// Get the cached diagnostics of the script if its content did not change.
+    pub fn get(&self, path: &str, content: &[u8]) -> Option<&[String]> {
+        self.scripts
+            .get(path)
+            .filter(|script| script.hash == hash(content))
+            .map(|script| script.diagnostics.as_slice())
+    }
+
+    /// Cache the diagnostics of the script with the given content.
+    pub fn insert(&mut self, path: String, content: &[u8], diagnostics: Vec<String>) {
+        self.scripts.insert(
+            path,
+            CachedScript {
+                hash: hash(content),
+                diagnostics,
+            },
+        );
+    }
+
+    /// Remove the scripts that are not in the list, e.g. deleted ones.
+    pub fn retain<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        let paths = paths.into_iter().collect::<Vec<_>>();
+        self.scripts
+            .retain(|path, _| paths.contains(&path.as_str()));
+    }
+
+    /// Write the cache to the project.
+    ///
+    /// # Errors
+    /// - If the cache file cannot be written.
+    pub fn save(&self, project_root: &Path) -> io::Result<()> {
+        stats::create_state_dir(project_root)?;
+        fs::write(project_root.join(CACHE_FILE), serde_json::to_string(self)?)
+    }
+}
+
+fn hash(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+/// A handler collecting the diagnostics as text instead of printing them.
+#[derive(Debug, Default)]
+pub struct CollectingHandler {
+    diagnostics: RefCell<Vec<String>>,
+}
+
+impl CollectingHandler {
+    /// The collected diagnostics.
+    pub fn into_diagnostics(self) -> Vec<String> {
+        self.diagnostics.into_inner()
+    }
+}
+
+impl<T: Display> Handler<T> for CollectingHandler {
+    fn receive<E: Into<T>>(&self, error: E) {
+        self.diagnostics.borrow_mut().push(error.into().to_string());
+    }
+
+    fn has_received(&self) -> bool {
+        !self.diagnostics.borrow().is_empty()
+    }
+}
+
+/// A handler printing the diagnostics like the `PrintHandler` of the compiler, but forwarding
+/// them as events if the output is forwarded.
+#[derive(Debug, Default)]
+pub struct OutputHandler {
+    received: Cell<bool>,
+}
+
+impl<T: Display> Handler<T> for OutputHandler {
+    fn receive<E: Into<T>>(&self, error: E) {
+        print_diagnostic(error.into());
+        self.received.set(true);
+    }
+
+    fn has_received(&self) -> bool {
+        self.received.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_cache() {
+        let temp = TestProject::empty("diagnostics");
+        let dir = temp.path();
+
+        let mut cache = DiagnosticsCache::load(dir);
+        cache.insert(
+            "src/main.shu".to_string(),
+            b"fn (",
+            vec!["error".to_string()],
+        );
+        cache.insert("src/old.shu".to_string(), b"", Vec::new());
+        cache.retain(["src/main.shu"]);
+        cache.save(dir).unwrap();
+
+        let cache = DiagnosticsCache::load(dir);
+        assert_eq!(
+            cache.get("src/main.shu", b"fn ("),
+            Some(&["error".to_string()][..])
+        );
+        assert_eq!(cache.get("src/main.shu", b"fn () {}"), None);
+        assert_eq!(cache.get("src/old.shu", b""), None);
+    }
+}
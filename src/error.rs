@@ -15,6 +15,96 @@ pub enum Error {
     FeatureNotEnabledError(String),
     #[error("An error occured because the pack version does not support a used feature")]
     IncompatiblePackVersionError,
+    #[error("An error occured because the pack format is not used by Minecraft {0} of the deploy target.")]
+    IncompatibleTargetError(String),
+    #[error("An error occured because the path {0} is neither a world nor a server directory.")]
+    NoDeployTargetError(PathBuf),
+    #[error("An error occured because deploying to {0} of the targets failed.")]
+    DeployError(usize),
+    #[error("An error occured because the datapack {0} could not be enabled or disabled.")]
+    DatapackStateError(String),
+    #[error("An error occured because the server could not be reached via RCON on {0}.")]
+    RconError(String),
+    #[error("An error occured because the option {0} is not supported by this subcommand.")]
+    UnsupportedOptionError(String),
+    #[error("An error occured because the required feature {0} is not available in the pack format of the pack.")]
+    UnavailableFeatureError(String),
+    #[error("An error occured because the namespace \"{0}\" is invalid.")]
+    InvalidNamespaceError(String),
+    #[error("An error occured because the project name \"{0}\" cannot be used as file name.")]
+    InvalidProjectNameError(String),
+    #[error("An error occured because {0} scripts contain syntax errors.")]
+    ScriptSyntaxError(usize),
+    #[error("An error occured because the datapack contains invalid resource locations.")]
+    InvalidResourceLocationError,
+    #[error("An error occured because the content of {source_name} does not match its pin (expected {expected}, got {actual}).")]
+    ChecksumMismatchError {
+        source_name: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("An error occured because the content of {0} is not pinned, which is required in locked mode.")]
+    UnpinnedSourceError(String),
+    #[error("An error occured because multiple packs declare the same namespaces or functions.")]
+    PackCollisionError,
+    #[error("An error occured because the library emits namespaces that are not exported.")]
+    NamespaceLeakError,
+    #[error("An error occured because the dependency {0} imports scripts from outside of the dependency.")]
+    ForeignImportError(String),
+    #[error("An error occured because dependencies depend on different sources named {0}.")]
+    DependencyConflictError(String),
+    #[error("An error occured because the pack.toml file at {0} does not define a workspace.")]
+    NoWorkspaceError(PathBuf),
+    #[error("An error occured because the workspace members {0} depend on each other.")]
+    CyclicWorkspaceDependencyError(String),
+    #[error("An error occured because the task of the workspace member {0} panicked: {1}")]
+    MemberPanicError(String, String),
+    #[error("An error occured because the alias {0} is empty or refers to itself.")]
+    InvalidAliasError(String),
+    #[error("An error occured because the translation keys {0} are not defined.")]
+    UnknownTranslationKeyError(String),
+    #[error("An error occured because recipe or advancement files are invalid.")]
+    InvalidDataFileError,
+    #[error("An error occured because the operation was cancelled.")]
+    CancelledError,
+    #[error("An error occured because the plugin at {0} could not be loaded.")]
+    InvalidPluginError(PathBuf),
+    #[error("An error occured because the plugins were not allowed to be loaded.")]
+    PluginsNotAllowedError,
+    #[error("An error occured because the plugin {0} failed.")]
+    PluginError(String),
+    #[error("An error occured because the build script {0} failed.")]
+    BuildScriptError(PathBuf),
+    #[error("An error occured because the overlays of the pack.toml file are invalid.")]
+    InvalidOverlayError,
+    #[error("An error occured because the generators of the pack.toml file are invalid.")]
+    InvalidGeneratorError,
+    #[error("An error occured because the config file {0} cannot be extended, check the `extends` field.")]
+    InvalidExtendsError(PathBuf),
+    #[error("An error occured because the config file {0} extends itself.")]
+    CyclicExtendsError(PathBuf),
+    #[error("An error occured because the config file {0} contains unknown keys.")]
+    UnknownConfigKeyError(PathBuf),
+    #[error("An error occured because the config file {0} is invalid.")]
+    InvalidConfigError(PathBuf),
+    #[error("An error occured because the config file {0} is not formatted.")]
+    UnformattedConfigError(PathBuf),
+    #[error("An error occured because the artifact {0} could not be signed.")]
+    SigningError(PathBuf),
+    #[error("An error occured because the last run of the watched commands failed.")]
+    WatchRunError,
+    #[error("An error occured because the output directory {0} is not safe to delete.")]
+    UnsafeCleanError(PathBuf),
+    #[error("An error occured because the launcher {0} could not be started.")]
+    LaunchError(PathBuf),
+    #[error("An error occured because {0} worldgen files do not match the pack format.")]
+    InvalidWorldgenError(usize),
+    #[error("An error occured because the template {0} does not exist.")]
+    UnknownTemplateError(String),
+    #[error("An error occured because {0} conflicts with installed datapacks were found.")]
+    PackConflictError(usize),
+    #[error("An error occured because {0} datapacks could not be migrated.")]
+    MigrationError(usize),
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,313 @@
+//! Fetching of remote sources like templates and dependencies.
+//!
+//! Fetched content can be pinned with a SHA-256 hash of its files, which is verified after each
+//! fetch. In locked mode (`--locked` or the `CI` environment variable being set), unpinned content
+//! is refused. Signatures of fetched content are not verified, the pins are the only check.
+
+use std::{
+    env,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::Result;
+use git2::{build::RepoBuilder, FetchOptions as GitFetchOptions, Repository as GitRepository};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{cancel, error::Error, lock::PathLock, terminal_output::print_warning};
+
+/// A SHA-256 pin of the content of a fetched source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Sha256Pin(String);
+
+impl FromStr for Sha256Pin {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let hex = s.strip_prefix("sha256:").unwrap_or(s).to_ascii_lowercase();
+        if hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(Self(hex))
+        } else {
+            Err(format!(
+                "Invalid SHA-256 pin \"{s}\", expected \"sha256:\" followed by 64 hex digits"
+            ))
+        }
+    }
+}
+
+impl TryFrom<String> for Sha256Pin {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Sha256Pin> for String {
+    fn from(value: Sha256Pin) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for Sha256Pin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sha256:{}", self.0)
+    }
+}
+
+/// Check whether unpinned content should be refused.
+///
+/// This is the case if the `--locked` flag is given or the `CI` environment variable is set.
+pub fn is_locked(locked_flag: bool) -> bool {
+    locked_flag || env::var("CI").is_ok_and(|ci| !ci.is_empty() && ci != "0" && ci != "false")
+}
+
+/// Resolve a git source to a URL.
+///
+/// Accepts full URLs, local paths and the GitHub shorthands `gh:owner/repo` and `owner/repo`.
+pub fn resolve_git_url(source: &str) -> String {
+    if let Some(repo) = source.strip_prefix("gh:") {
+        format!("https://github.com/{repo}.git")
+    } else if source.contains("://") || source.starts_with("git@") || Path::new(source).exists() {
+        source.to_string()
+    } else if source.split('/').count() == 2 && !source.starts_with('.') {
+        format!("https://github.com/{source}.git")
+    } else {
+        source.to_string()
+    }
+}
+
+/// Get the name of a repository from its git URL, which is the last path segment without the
+/// `.git` extension.
+pub fn repo_name(url: &str) -> &str {
+    let trimmed = url.trim_end_matches('/').trim_end_matches(".git");
+    trimmed.rsplit(['/', ':', '\\']).next().unwrap_or(trimmed)
+}
+
+/// Get the directory used for caching fetched sources.
+///
+/// Can be overridden with the `SHULKERSCRIPT_CACHE_DIR` environment variable.
+pub fn cache_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("SHULKERSCRIPT_CACHE_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+    };
+
+    base.unwrap_or_else(env::temp_dir).join("shulkerscript")
+}
+
+/// Get the cache path of a git source at a specific reference.
+pub fn git_cache_path(url: &str, reference: Option<&str>) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    hasher.update([0]);
+    hasher.update(reference.unwrap_or_default().as_bytes());
+    let hash = hex::encode(hasher.finalize());
+
+    let name = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or("repo")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect::<String>();
+
+    cache_dir()
+        .join("git")
+        .join(format!("{name}-{}", &hash[..16]))
+}
+
+/// Fetch a git repository into the destination, checking out the given reference.
+///
+/// If the destination already contains a clone, it is updated instead.
+pub fn fetch_git(url: &str, reference: Option<&str>, destination: &Path) -> Result<()> {
+    let _lock = PathLock::acquire(destination, cancel::global())?;
+    let repo = if destination.join(".git").exists() {
+        let repo = GitRepository::open(destination)?;
+        repo.find_remote("origin")?.fetch(
+            &[
+                "+refs/heads/*:refs/remotes/origin/*",
+                "+refs/tags/*:refs/tags/*",
+            ],
+            None,
+            None,
+        )?;
+        repo
+    } else {
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut fetch_options = GitFetchOptions::new();
+        // the local transport does not support shallow fetches
+        let is_remote =
+            (url.contains("://") && !url.starts_with("file://")) || url.starts_with("git@");
+        if reference.is_none() && is_remote {
+            fetch_options.depth(1);
+        }
+        RepoBuilder::new()
+            .fetch_options(fetch_options)
+            .clone(url, destination)?
+    };
+
+    let target = match reference {
+        Some(reference) => repo
+            .revparse_single(&format!("origin/{reference}"))
+            .or_else(|_| repo.revparse_single(reference))?,
+        None => repo
+            .revparse_single("refs/remotes/origin/HEAD")
+            .or_else(|_| repo.revparse_single("HEAD"))?,
+    };
+    let commit = target.peel_to_commit()?;
+
+    repo.checkout_tree(
+        commit.as_object(),
+        Some(git2::build::CheckoutBuilder::new().force()),
+    )?;
+    repo.set_head_detached(commit.id())?;
+
+    Ok(())
+}
+
+/// Compute the SHA-256 pin of all files in a directory, ignoring the `.git` directory.
+pub fn content_hash(dir: &Path) -> io::Result<Sha256Pin> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for (relative, path) in files {
+        let content = fs::read(path)?;
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+
+    Ok(Sha256Pin(hex::encode(hasher.finalize())))
+}
+
+/// Compute the SHA-256 pin of in-memory files in the same way as [`content_hash`].
+///
+/// The files must be sorted by their relative path.
+pub fn entries_hash<'a, I>(entries: I) -> Sha256Pin
+where
+    I: IntoIterator<Item = (&'a str, &'a [u8])>,
+{
+    let mut hasher = Sha256::new();
+    for (relative, content) in entries {
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(content);
+    }
+
+    Sha256Pin(hex::encode(hasher.finalize()))
+}
+
+/// Compute the SHA-256 pin of a single file.
+pub fn file_hash(path: &Path) -> io::Result<Sha256Pin> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+
+    Ok(Sha256Pin(hex::encode(hasher.finalize())))
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            files.push((relative, path));
+        }
+    }
+    Ok(())
+}
+
+/// Verify the content of a fetched source against its pin.
+///
+/// Returns the computed pin of the content.
+///
+/// # Errors
+/// - If the content does not match the pin.
+/// - If no pin is given and `locked` is set.
+pub fn verify(
+    dir: &Path,
+    pin: Option<&Sha256Pin>,
+    locked: bool,
+    source: &str,
+) -> Result<Sha256Pin> {
+    let actual = content_hash(dir)?;
+
+    match pin {
+        Some(expected) if expected != &actual => Err(Error::ChecksumMismatchError {
+            source_name: source.to_string(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        })?,
+        Some(_) => {}
+        None if locked => Err(Error::UnpinnedSourceError(source.to_string()))?,
+        None => print_warning(format!(
+            "The content of {source} is not pinned. Pin it with sha256 = \"{actual}\" to verify future downloads."
+        )),
+    }
+
+    Ok(actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_pin() {
+        let hex = "a".repeat(64);
+        assert_eq!(
+            format!("sha256:{hex}").parse::<Sha256Pin>().unwrap(),
+            Sha256Pin(hex.clone())
+        );
+        assert_eq!(
+            hex.to_uppercase().parse::<Sha256Pin>().unwrap().to_string(),
+            format!("sha256:{hex}")
+        );
+        assert!("sha256:abc".parse::<Sha256Pin>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_git_url() {
+        assert_eq!(
+            resolve_git_url("gh:owner/repo"),
+            "https://github.com/owner/repo.git"
+        );
+        assert_eq!(
+            resolve_git_url("owner/repo"),
+            "https://github.com/owner/repo.git"
+        );
+        assert_eq!(
+            resolve_git_url("https://example.com/repo.git"),
+            "https://example.com/repo.git"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Files generated from a template and the rows of a data file, configured in the `[[generate]]`
+//! sections of the pack.toml file.
+//!
+//! Every row of the data file produces one file. The `{column}` placeholders of the template and
+//! of the output path are replaced with the values of the row, as well as `{name}` and
+//! `{namespace}` with the ones of the pack unless the data file has columns with these names.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+use crate::{config::GenerateConfig, lang, util};
+
+/// Generate the files of all generators.
+///
+/// Returns the generated files and the problems found in the generators.
+pub fn generate(
+    generators: &[GenerateConfig],
+    project_root: &Path,
+    pack_values: &[(&str, String)],
+) -> (VFolder, Vec<String>) {
+    let mut folder = VFolder::new();
+    let mut problems = Vec::new();
+    let mut origins = BTreeMap::new();
+
+    for generator in generators {
+        if let Some(path) = [&generator.template, &generator.data]
+            .into_iter()
+            .find(|path| !util::is_enclosed_path(path))
+        {
+            problems.push(format!(
+                "The path {} is not a relative path inside of the project",
+                path.display()
+            ));
+            continue;
+        }
+        let template = match fs::read_to_string(project_root.join(&generator.template)) {
+            Ok(template) => template,
+            Err(err) => {
+                problems.push(format!(
+                    "Cannot read the template {}: {err}",
+                    generator.template.display()
+                ));
+                continue;
+            }
+        };
+        let rows = match read_rows(&project_root.join(&generator.data)) {
+            Ok(rows) => rows,
+            Err(err) => {
+                problems.push(format!(
+                    "Cannot read the data file {}: {err}",
+                    generator.data.display()
+                ));
+                continue;
+            }
+        };
+
+        for (index, row) in rows.iter().enumerate() {
+            let mut values = row
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>();
+            values.extend(
+                pack_values
+                    .iter()
+                    .filter(|(key, _)| !row.contains_key(*key))
+                    .map(|(key, value)| (*key, value.as_str())),
+            );
+
+            let path = util::expand_placeholders(&generator.output, &values);
+            if let Some(placeholder) = unknown_placeholder(&path) {
+                problems.push(format!(
+                    "The output path of row {} of {} contains the placeholder {{{placeholder}}}, which is not a column of the data file",
+                    index + 1,
+                    generator.data.display()
+                ));
+                continue;
+            }
+            if !util::is_enclosed_path(&path) {
+                problems.push(format!(
+                    "The output path {path} of row {} of {} is not a relative path inside of the output",
+                    index + 1,
+                    generator.data.display()
+                ));
+                continue;
+            }
+            if let Some(other) = origins.insert(path.clone(), &generator.data) {
+                problems.push(format!(
+                    "The file {path} is generated multiple times (by {} and {})",
+                    other.display(),
+                    generator.data.display()
+                ));
+                continue;
+            }
+
+            folder.add_file(
+                &path,
+                VFile::Text(util::expand_placeholders(&template, &values)),
+            );
+        }
+    }
+
+    (folder, problems)
+}
+
+/// Read the rows of a `.csv` file with a header row, a `.json` file with an array of objects or a
+/// `.toml` file with a `rows` array of tables.
+fn read_rows(path: &Path) -> Result<Vec<BTreeMap<String, String>>, String> {
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => {
+            let mut rows = lang::parse_csv(&content).into_iter();
+            let header = rows.next().unwrap_or_default();
+            Ok(rows
+                .map(|row| header.iter().cloned().zip(row).collect())
+                .collect())
+        }
+        Some("json") => {
+            let rows =
+                serde_json::from_str::<Vec<serde_json::Map<String, serde_json::Value>>>(&content)
+                    .map_err(|err| err.to_string())?;
+            Ok(rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|(key, value)| match value {
+                            serde_json::Value::String(value) => (key, value),
+                            value => (key, value.to_string()),
+                        })
+                        .collect()
+                })
+                .collect())
+        }
+        Some("toml") => {
+            let mut table =
+                toml::from_str::<toml::Table>(&content).map_err(|err| err.to_string())?;
+            let Some(toml::Value::Array(rows)) = table.remove("rows") else {
+                return Err("the file has no `rows` array".to_string());
+            };
+            rows.into_iter()
+                .map(|row| match row {
+                    toml::Value::Table(row) => Ok(row
+                        .into_iter()
+                        .map(|(key, value)| match value {
+                            toml::Value::String(value) => (key, value),
+                            value => (key, value.to_string()),
+                        })
+                        .collect()),
+                    _ => Err("the `rows` array may only contain tables".to_string()),
+                })
+                .collect()
+        }
+        _ => Err("only .csv, .json and .toml data files are supported".to_string()),
+    }
+}
+
+/// Get the first `{placeholder}` that was not replaced.
+fn unknown_placeholder(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let end = start + text[start..].find('}')?;
+    Some(&text[start + 1..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_generate() {
+        let temp = TestProject::empty("generate");
+        let dir = temp.path();
+        fs::write(
+            dir.join("give.mcfunction"),
+            "give @s minecraft:{id} {count}",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("items.csv"),
+            "id,count\ndiamond,1\n\"emerald\",64\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("items.toml"),
+            "[[rows]]\nid = \"stone\"\ncount = 16\n",
+        )
+        .unwrap();
+
+        let generators = [
+            GenerateConfig {
+                template: "give.mcfunction".into(),
+                data: "items.csv".into(),
+                output: "data/{namespace}/function/give/{id}.mcfunction".to_string(),
+            },
+            GenerateConfig {
+                template: "give.mcfunction".into(),
+                data: "items.toml".into(),
+                output: "data/{namespace}/function/give/{id}.mcfunction".to_string(),
+            },
+            GenerateConfig {
+                template: "give.mcfunction".into(),
+                data: "items.toml".into(),
+                output: "data/{namespace}/function/{unknown}.mcfunction".to_string(),
+            },
+            GenerateConfig {
+                template: "give.mcfunction".into(),
+                data: "items.csv".into(),
+                output: "../{id}.mcfunction".to_string(),
+            },
+            GenerateConfig {
+                template: "../give.mcfunction".into(),
+                data: "items.csv".into(),
+                output: "data/{namespace}/function/{id}.mcfunction".to_string(),
+            },
+        ];
+        let (folder, problems) = generate(&generators, dir, &[("namespace", "test".to_string())]);
+
+        assert_eq!(
+            folder
+                .get_file("data/test/function/give/emerald.mcfunction")
+                .and_then(|file| file.as_text()),
+            Some("give @s minecraft:emerald 64")
+        );
+        assert_eq!(
+            folder
+                .get_file("data/test/function/give/stone.mcfunction")
+                .and_then(|file| file.as_text()),
+            Some("give @s minecraft:stone 16")
+        );
+        assert_eq!(folder.flatten().len(), 3);
+        assert_eq!(problems.len(), 4);
+        assert!(problems[0].contains("{unknown}"));
+        assert!(problems[1].contains("../diamond.mcfunction"));
+        assert!(problems[3].contains("../give.mcfunction"));
+    }
+}
@@ -0,0 +1,157 @@
+//! The history of the recent builds of a project.
+//!
+//! Each build writes its messages, the diagnostics of the compiler, its statistics and the path
+//! of the artifact to a file in `.shulkerscript/history`, so the `last` subcommand can print them
+//! again without rebuilding. Only the configured number of builds is kept.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    stats::{self, BuildStats},
+    terminal_output::{print_warning, Message, Transcript},
+};
+
+/// The directory of the history, relative to the project root.
+pub const HISTORY_DIR: &str = ".shulkerscript/history";
+
+/// The result of a build kept in the history.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildRecord {
+    pub stats: BuildStats,
+    /// The path of the built folder/zip file, if the build succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<PathBuf>,
+    /// The error the build failed with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The messages printed during the build.
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    /// The diagnostics of the compiler printed during the build.
+    #[serde(default)]
+    pub diagnostics: Vec<String>,
+}
+
+impl BuildRecord {
+    pub fn new(stats: BuildStats, result: Result<&Path, String>, transcript: Transcript) -> Self {
+        let (artifact, error) = match result {
+            Ok(artifact) => (Some(artifact.to_path_buf()), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            stats,
+            artifact,
+            error,
+            messages: transcript.messages,
+            diagnostics: transcript.diagnostics,
+        }
+    }
+}
+
+/// Add the build to the history of the project, removing the oldest builds exceeding the limit.
+///
+/// # Errors
+/// - If the history directory cannot be written.
+pub fn save(project_root: &Path, record: &BuildRecord, limit: usize) -> io::Result<()> {
+    if limit == 0 {
+        return Ok(());
+    }
+    stats::create_state_dir(project_root)?;
+    let history_dir = project_root.join(HISTORY_DIR);
+    fs::create_dir_all(&history_dir)?;
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis());
+    // builds finishing in the same millisecond, e.g. of a workspace, are numbered
+    let path = (0..)
+        .map(|index| history_dir.join(format!("{millis:015}-{index:03}.json")))
+        .find(|path| !path.exists())
+        .expect("an unused file name");
+    fs::write(path, serde_json::to_string_pretty(record)?)?;
+
+    let files = files(project_root)?;
+    for outdated in files.iter().skip(limit) {
+        fs::remove_file(outdated)?;
+    }
+    Ok(())
+}
+
+/// Load the builds in the history of the project, newest first.
+///
+/// Files that cannot be parsed are skipped with a warning.
+///
+/// # Errors
+/// - If the history directory exists but cannot be read.
+pub fn load(project_root: &Path) -> io::Result<Vec<BuildRecord>> {
+    Ok(files(project_root)?
+        .into_iter()
+        .filter_map(|path| {
+            fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|content| serde_json::from_str(&content).map_err(|err| err.to_string()))
+                .inspect_err(|err| {
+                    print_warning(format!("Skipping {}: {err}", path.display()));
+                })
+                .ok()
+        })
+        .collect())
+}
+
+/// The files of the history, newest first.
+fn files(project_root: &Path) -> io::Result<Vec<PathBuf>> {
+    let history_dir = project_root.join(HISTORY_DIR);
+    if !history_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(history_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files.reverse();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{terminal_output::Level, testing::TestProject};
+
+    #[test]
+    fn test_history() {
+        let temp = TestProject::empty("history");
+        let dir = temp.path();
+
+        for index in 0..4 {
+            let transcript = Transcript {
+                messages: vec![Message {
+                    level: Level::Warning,
+                    text: format!("warning {index}"),
+                }],
+                diagnostics: Vec::new(),
+            };
+            let record = BuildRecord::new(
+                BuildStats::default(),
+                Err(format!("error {index}")),
+                transcript,
+            );
+            save(dir, &record, 3).unwrap();
+        }
+
+        let records = load(dir).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].error.as_deref(), Some("error 3"));
+        assert_eq!(records[2].messages[0].text, "warning 1");
+    }
+}
@@ -0,0 +1,283 @@
+//! Translations of the `[lang]` pipeline.
+//!
+//! Translations are read from `<code>.toml` files (nested tables are joined with `.`) and from
+//! `.csv` files with a `key` column followed by one column per language. They are converted into
+//! the lang files of a resource pack and replace `{{lang:<key>}}` placeholders in the functions of
+//! the datapack with JSON text components.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::json;
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+use crate::optimize::{function_id, text};
+
+const PLACEHOLDER_START: &str = "{{lang:";
+const PLACEHOLDER_END: &str = "}}";
+
+/// The translations of a project, mapping language codes to keys and their translations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Translations {
+    pub languages: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Translations {
+    /// Load all `.toml` and `.csv` translation files of a directory.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut translations = Self::default();
+
+        let mut paths = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read the lang directory {}", dir.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.sort();
+
+        for path in paths {
+            let extension = path.extension().and_then(|ext| ext.to_str());
+            match extension {
+                Some("toml") => {
+                    let code = path
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_lowercase())
+                        .unwrap_or_default();
+                    let table = toml::from_str::<toml::Table>(&fs::read_to_string(&path)?)
+                        .with_context(|| format!("Failed to parse {}", path.display()))?;
+                    let language = translations.languages.entry(code).or_default();
+                    flatten_table(&table, "", language);
+                }
+                Some("csv") => {
+                    translations
+                        .add_csv(&fs::read_to_string(&path)?)
+                        .with_context(|| format!("Failed to parse {}", path.display()))?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(translations)
+    }
+
+    /// Add the translations of a csv file with a `key` column followed by one column per language.
+    fn add_csv(&mut self, content: &str) -> Result<()> {
+        let mut rows = parse_csv(content).into_iter();
+        let header = rows.next().unwrap_or_default();
+        if header.first().map(|key| key.trim()) != Some("key") {
+            return Err(anyhow!("The first column must be named `key`"));
+        }
+        let codes = header[1..]
+            .iter()
+            .map(|code| code.trim().to_lowercase())
+            .collect::<Vec<_>>();
+
+        for row in rows {
+            let Some((key, values)) = row.split_first() else {
+                continue;
+            };
+            let key = key.trim();
+            if key.is_empty() {
+                continue;
+            }
+            for (code, value) in codes.iter().zip(values) {
+                if !value.is_empty() {
+                    self.languages
+                        .entry(code.clone())
+                        .or_default()
+                        .insert(key.to_string(), value.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the keys of the default language that are missing in other languages.
+    pub fn missing_keys(&self, default: &str) -> Vec<(&str, &str)> {
+        let Some(default_keys) = self.languages.get(default) else {
+            return Vec::new();
+        };
+        self.languages
+            .iter()
+            .filter(|(code, _)| code.as_str() != default)
+            .flat_map(|(code, keys)| {
+                default_keys
+                    .keys()
+                    .filter(|key| !keys.contains_key(*key))
+                    .map(|key| (code.as_str(), key.as_str()))
+            })
+            .collect()
+    }
+
+    /// Get the lang files of the resource pack, mapping the language codes to their json content.
+    pub fn lang_files(&self) -> BTreeMap<&str, String> {
+        self.languages
+            .iter()
+            .map(|(code, keys)| {
+                (
+                    code.as_str(),
+                    serde_json::to_string_pretty(keys).unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    /// Replace the `{{lang:<key>}}` placeholders in the functions of the datapack with JSON text
+    /// components of the default language.
+    ///
+    /// With `translate`, the components are translatable and fall back to the default language if
+    /// the resource pack is not loaded.
+    /// Returns the number of replaced placeholders or the unknown keys.
+    pub fn replace_placeholders(
+        &self,
+        folder: &VFolder,
+        default: &str,
+        translate: bool,
+    ) -> std::result::Result<(VFolder, usize), BTreeSet<String>> {
+        let empty = BTreeMap::new();
+        let keys = self.languages.get(default).unwrap_or(&empty);
+
+        let mut replaced = 0;
+        let mut unknown = BTreeSet::new();
+        let mut output = VFolder::new();
+        for (path, file) in folder.flatten() {
+            let content = text(file).filter(|_| function_id(&path).is_some());
+            let Some(content) = content.filter(|content| content.contains(PLACEHOLDER_START))
+            else {
+                output.add_file(&path, file.clone());
+                continue;
+            };
+
+            let mut result = String::with_capacity(content.len());
+            let mut rest = content;
+            while let Some(start) = rest.find(PLACEHOLDER_START) {
+                let Some(end) = rest[start..].find(PLACEHOLDER_END) else {
+                    break;
+                };
+                let key = &rest[start + PLACEHOLDER_START.len()..start + end];
+                result.push_str(&rest[..start]);
+                match keys.get(key) {
+                    Some(value) if translate => {
+                        result.push_str(&json!({"translate": key, "fallback": value}).to_string())
+                    }
+                    Some(value) => result.push_str(&json!({ "text": value }).to_string()),
+                    None => {
+                        unknown.insert(key.to_string());
+                    }
+                }
+                replaced += 1;
+                rest = &rest[start + end + PLACEHOLDER_END.len()..];
+            }
+            result.push_str(rest);
+            output.add_file(&path, VFile::Text(result));
+        }
+
+        if unknown.is_empty() {
+            Ok((output, replaced))
+        } else {
+            Err(unknown)
+        }
+    }
+}
+
+fn flatten_table(table: &toml::Table, prefix: &str, keys: &mut BTreeMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::Table(table) => flatten_table(table, &key, keys),
+            toml::Value::String(value) => {
+                keys.insert(key, value.clone());
+            }
+            other => {
+                keys.insert(key, other.to_string());
+            }
+        }
+    }
+}
+
+/// Parse the rows of a csv file, supporting quoted fields with `""` as escaped quote.
+pub(crate) fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| row.iter().any(|field| !field.is_empty()));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translations() {
+        let mut translations = Translations::default();
+        translations
+            .add_csv("key,en_us,de_de\ngreeting,\"Hello, \"\"world\"\"\",Hallo\nbye,Bye,\n")
+            .unwrap();
+
+        assert_eq!(
+            translations.languages["en_us"]["greeting"],
+            r#"Hello, "world""#
+        );
+        assert_eq!(translations.missing_keys("en_us"), vec![("de_de", "bye")]);
+
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/test/function/main.mcfunction",
+            VFile::Text("tellraw @a {{lang:bye}}".to_string()),
+        );
+
+        let (replaced, count) = translations
+            .replace_placeholders(&folder, "en_us", true)
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            replaced
+                .get_file("data/test/function/main.mcfunction")
+                .and_then(VFile::as_text),
+            Some(r#"tellraw @a {"fallback":"Bye","translate":"bye"}"#)
+        );
+
+        folder.add_file(
+            "data/test/function/other.mcfunction",
+            VFile::Text("tellraw @a {{lang:unknown}}".to_string()),
+        );
+        assert_eq!(
+            translations
+                .replace_placeholders(&folder, "en_us", false)
+                .err(),
+            Some(BTreeSet::from(["unknown".to_string()]))
+        );
+    }
+}
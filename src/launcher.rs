@@ -0,0 +1,167 @@
+//! Launching Minecraft with a singleplayer world after deploying to it.
+//!
+//! Worlds in the `saves` folder of a Prism Launcher or MultiMC instance are opened by starting the
+//! instance with the launcher's command line interface. Prism Launcher also joins the world
+//! directly, MultiMC only starts the game.
+
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// A launcher managing instances of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LauncherKind {
+    Prism,
+    MultiMc,
+}
+
+impl LauncherKind {
+    /// The names of the executable, without the `.exe` extension on Windows.
+    fn executable_names(self) -> &'static [&'static str] {
+        match self {
+            Self::Prism => &["prismlauncher", "PrismLauncher"],
+            Self::MultiMc => &["MultiMC", "multimc"],
+        }
+    }
+}
+
+impl Display for LauncherKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Prism => write!(f, "Prism Launcher"),
+            Self::MultiMc => write!(f, "MultiMC"),
+        }
+    }
+}
+
+/// The launcher instance a world belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance {
+    pub launcher: LauncherKind,
+    /// The data folder of the launcher, containing the `instances` folder.
+    pub launcher_root: PathBuf,
+    /// The id of the instance, which is the name of its folder.
+    pub id: String,
+    /// The name of the world folder in the `saves` folder.
+    pub world: String,
+}
+
+impl Instance {
+    /// Detect the instance of the world at the path, `None` if it is not in the `saves` folder of
+    /// a launcher instance.
+    ///
+    /// Instances are recognized by their `instance.cfg` file and the launcher by the config file
+    /// in its data folder, defaulting to Prism Launcher.
+    pub fn of_world(world: &Path) -> Option<Self> {
+        let saves = world
+            .parent()
+            .filter(|saves| saves.file_name().is_some_and(|name| name == "saves"))?;
+        let game_dir = saves.parent().filter(|game_dir| {
+            game_dir
+                .file_name()
+                .is_some_and(|name| name == ".minecraft" || name == "minecraft")
+        })?;
+        let instance = game_dir
+            .parent()
+            .filter(|instance| instance.join("instance.cfg").is_file())?;
+        let launcher_root = instance.parent().and_then(Path::parent)?;
+
+        let launcher = if launcher_root.join("multimc.cfg").is_file() {
+            LauncherKind::MultiMc
+        } else {
+            LauncherKind::Prism
+        };
+
+        Some(Self {
+            launcher,
+            launcher_root: launcher_root.to_path_buf(),
+            id: instance.file_name()?.to_string_lossy().into_owned(),
+            world: world.file_name()?.to_string_lossy().into_owned(),
+        })
+    }
+
+    /// The executable of the launcher, preferring the one in the data folder of portable
+    /// installations over the one on the `PATH`.
+    pub fn executable(&self) -> PathBuf {
+        let names = self.launcher.executable_names();
+        names
+            .iter()
+            .map(|name| {
+                self.launcher_root
+                    .join(name)
+                    .with_extension(std::env::consts::EXE_EXTENSION)
+            })
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| PathBuf::from(names[0]))
+    }
+
+    /// The arguments starting the instance and, for Prism Launcher, joining the world.
+    pub fn launch_args(&self) -> Vec<String> {
+        let mut args = vec!["--launch".to_string(), self.id.clone()];
+        if self.launcher == LauncherKind::Prism {
+            args.extend(["--world".to_string(), self.world.clone()]);
+        }
+        args
+    }
+
+    /// Start the instance with the executable, without waiting for the launcher to exit.
+    ///
+    /// # Errors
+    /// - If the launcher cannot be started.
+    pub fn launch(&self, executable: &Path) -> io::Result<()> {
+        spawn_detached(Command::new(executable).args(self.launch_args()))
+    }
+}
+
+/// Start the launcher at the path without arguments, e.g. the official Minecraft launcher which
+/// cannot open worlds directly.
+///
+/// # Errors
+/// - If the launcher cannot be started.
+pub fn start(executable: &Path) -> io::Result<()> {
+    spawn_detached(&mut Command::new(executable))
+}
+
+fn spawn_detached(command: &mut Command) -> io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_instance_of_world() {
+        let temp = TestProject::empty("launcher");
+        let dir = temp.path();
+        let world = dir.join("instances/1.21.1/minecraft/saves/Test World");
+        fs::create_dir_all(&world).unwrap();
+        fs::write(dir.join("instances/1.21.1/instance.cfg"), "").unwrap();
+
+        let instance = Instance::of_world(&world).unwrap();
+        assert_eq!(instance.launcher, LauncherKind::Prism);
+        assert_eq!(instance.launcher_root, dir);
+        assert_eq!(
+            instance.launch_args(),
+            ["--launch", "1.21.1", "--world", "Test World"]
+        );
+
+        fs::write(dir.join("multimc.cfg"), "").unwrap();
+        let instance = Instance::of_world(&world).unwrap();
+        assert_eq!(instance.launcher, LauncherKind::MultiMc);
+        assert_eq!(instance.launch_args(), ["--launch", "1.21.1"]);
+
+        assert!(Instance::of_world(&dir.join("saves/world")).is_none());
+    }
+}
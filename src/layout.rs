@@ -0,0 +1,235 @@
+//! The names of the directories of a namespace, which are singular since pack format 45
+//! (Minecraft 1.21) and plural before, e.g. `function` and `functions`.
+//!
+//! The output is laid out with the names of the pack format, regardless of the names used by the
+//! compiler, the assets or the build script. When overlays make the pack load in game versions on
+//! both sides of the rename, the files are written to the directories of both names.
+
+use std::collections::BTreeMap;
+
+use shulkerscript::shulkerbox::virtual_fs::VFolder;
+
+use crate::config::OverlayConfig;
+
+/// The first pack format using the singular directory names.
+pub const SINGULAR_FORMAT: u8 = 45;
+
+/// The renamed directories of a namespace, singular and plural.
+const RENAMED: [(&str, &str); 13] = [
+    ("function", "functions"),
+    ("structure", "structures"),
+    ("advancement", "advancements"),
+    ("recipe", "recipes"),
+    ("loot_table", "loot_tables"),
+    ("predicate", "predicates"),
+    ("item_modifier", "item_modifiers"),
+    ("tags/function", "tags/functions"),
+    ("tags/block", "tags/blocks"),
+    ("tags/item", "tags/items"),
+    ("tags/entity_type", "tags/entity_types"),
+    ("tags/fluid", "tags/fluids"),
+    ("tags/game_event", "tags/game_events"),
+];
+
+/// The directory names read by a range of pack formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Singular,
+    Plural,
+    /// The range contains formats before and after the rename.
+    Both,
+}
+
+impl Layout {
+    pub fn of_formats(min: u8, max: u8) -> Self {
+        match (min < SINGULAR_FORMAT, max < SINGULAR_FORMAT) {
+            (false, _) => Self::Singular,
+            (true, true) => Self::Plural,
+            (true, false) => Self::Both,
+        }
+    }
+
+    /// The names of the renamed directory used by the layout.
+    fn names(self, (singular, plural): (&'static str, &'static str)) -> Vec<&'static str> {
+        match self {
+            Self::Singular => vec![singular],
+            Self::Plural => vec![plural],
+            Self::Both => vec![singular, plural],
+        }
+    }
+}
+
+/// The name of the directory in the pack format, given by its singular name, e.g. `function` or
+/// `tags/function`.
+pub fn directory(singular: &'static str, pack_format: u8) -> &'static str {
+    if pack_format >= SINGULAR_FORMAT {
+        return singular;
+    }
+    RENAMED
+        .iter()
+        .find(|(name, _)| *name == singular)
+        .map_or(singular, |&(_, plural)| plural)
+}
+
+/// The singular name of a tag type directory, e.g. `block` for `blocks`.
+pub fn singular_tag_type(tag_type: &str) -> &str {
+    RENAMED
+        .iter()
+        .filter_map(|&(singular, plural)| {
+            Some((
+                singular.strip_prefix("tags/")?,
+                plural.strip_prefix("tags/")?,
+            ))
+        })
+        .find(|(_, plural)| *plural == tag_type)
+        .map_or(tag_type, |(singular, _)| singular)
+}
+
+/// Move the files of renamed directories to the names read by the game versions loading them.
+///
+/// The files of the pack are laid out for the pack format and the formats of all overlays, the
+/// files of an overlay directory for the formats of the overlay. Files existing with the target
+/// name are kept instead of being replaced.
+///
+/// Returns the output and the moved or copied files as `(from, to)`.
+pub fn apply(
+    output: &VFolder,
+    pack_format: u8,
+    overlays: &BTreeMap<String, OverlayConfig>,
+) -> (VFolder, Vec<(String, String)>) {
+    // the files of the pack are loaded by all game versions the overlays are made for
+    let (min, max) = overlays
+        .values()
+        .map(|overlay| overlay.formats.bounds())
+        .fold((pack_format, pack_format), |(min, max), (low, high)| {
+            (min.min(low), max.max(high))
+        });
+    let base_layout = Layout::of_formats(min, max);
+
+    let mut result = VFolder::new();
+    let mut moved = Vec::new();
+    for (path, file) in output.flatten() {
+        let (prefix, layout) = match path.split_once('/') {
+            Some((name, _)) if overlays.contains_key(name) => {
+                let (low, high) = overlays[name].formats.bounds();
+                (format!("{name}/"), Layout::of_formats(low, high))
+            }
+            _ => (String::new(), base_layout),
+        };
+        let Some((namespace, renamed, rest)) = split_renamed(&path[prefix.len()..]) else {
+            result.add_file(&path, file.clone());
+            continue;
+        };
+
+        for name in layout.names(renamed) {
+            let target = format!("{prefix}data/{namespace}/{name}/{rest}");
+            if target != path {
+                if output.get_file(&target).is_some() {
+                    continue;
+                }
+                moved.push((path.clone(), target.clone()));
+            }
+            result.add_file(&target, file.clone());
+        }
+    }
+
+    (result, moved)
+}
+
+/// Split a path of the data directory into the namespace, the renamed directory and the path in it.
+fn split_renamed(path: &str) -> Option<(&str, (&'static str, &'static str), &str)> {
+    let (namespace, path) = path.strip_prefix("data/")?.split_once('/')?;
+    RENAMED.iter().find_map(|&(singular, plural)| {
+        [singular, plural].into_iter().find_map(|name| {
+            let rest = path.strip_prefix(name)?.strip_prefix('/')?;
+            Some((namespace, (singular, plural), rest))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use shulkerscript::shulkerbox::virtual_fs::VFile;
+
+    use crate::config::FormatRange;
+
+    use super::*;
+
+    fn folder(paths: &[&str]) -> VFolder {
+        let mut folder = VFolder::new();
+        for path in paths {
+            folder.add_file(path, VFile::Text((*path).to_string()));
+        }
+        folder
+    }
+
+    fn paths(folder: &VFolder) -> Vec<String> {
+        let mut paths = folder
+            .flatten()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_directory() {
+        assert_eq!(directory("function", 41), "functions");
+        assert_eq!(directory("function", 48), "function");
+        assert_eq!(directory("tags/function", 26), "tags/functions");
+        assert_eq!(directory("worldgen/biome", 26), "worldgen/biome");
+        assert_eq!(singular_tag_type("blocks"), "block");
+        assert_eq!(singular_tag_type("worldgen/biome"), "worldgen/biome");
+    }
+
+    #[test]
+    fn test_apply() {
+        let output = folder(&[
+            "pack.mcmeta",
+            "data/pack/function/main.mcfunction",
+            "data/minecraft/tags/function/load.json",
+            "data/pack/functions/legacy.mcfunction",
+            "data/pack/worldgen/biome/a.json",
+        ]);
+
+        let (plural, moved) = apply(&output, 41, &BTreeMap::new());
+        assert_eq!(
+            paths(&plural),
+            [
+                "data/minecraft/tags/functions/load.json",
+                "data/pack/functions/legacy.mcfunction",
+                "data/pack/functions/main.mcfunction",
+                "data/pack/worldgen/biome/a.json",
+                "pack.mcmeta",
+            ]
+        );
+        assert_eq!(moved.len(), 2);
+        assert_eq!(
+            plural
+                .get_file("data/pack/functions/main.mcfunction")
+                .map(VFile::as_bytes),
+            Some("data/pack/function/main.mcfunction".as_bytes())
+        );
+
+        let overlays = BTreeMap::from([(
+            "modern".to_string(),
+            OverlayConfig {
+                formats: FormatRange::Range([45, 48]),
+            },
+        )]);
+        let output = folder(&[
+            "data/pack/function/main.mcfunction",
+            "modern/data/pack/functions/main.mcfunction",
+        ]);
+        let (both, _) = apply(&output, 41, &overlays);
+        assert_eq!(
+            paths(&both),
+            [
+                "data/pack/function/main.mcfunction",
+                "data/pack/functions/main.mcfunction",
+                "modern/data/pack/function/main.mcfunction",
+            ]
+        );
+    }
+}
@@ -28,9 +28,58 @@
 //! shulkerscript watch [OPTIONS] [PATH]
 //! ```
 
+pub mod alias;
+pub mod analysis;
+pub mod api;
+#[cfg(feature = "zip")]
+pub mod archive;
+#[cfg(feature = "build-script")]
+pub mod build_script;
+pub mod cancel;
 pub mod cli;
 pub mod config;
+pub mod data_files;
+pub mod defaults;
+pub mod dependency;
+pub mod deploy;
+pub mod diagnostics;
 pub mod error;
+pub mod fetch;
+pub mod generate;
+pub mod history;
+pub mod lang;
+pub mod launcher;
+pub mod layout;
+pub mod lints;
+pub mod lock;
+pub mod mcdata;
+pub mod nbt;
+pub mod obfuscation;
+pub mod optimize;
+pub mod overlay;
+pub mod plan;
+pub mod plugin;
+pub mod profiling;
+pub mod prompt;
+pub mod rcon;
+pub mod runtime;
+pub mod stats;
+pub mod structure;
 pub mod subcommands;
+pub mod sync;
+pub mod template;
 pub mod terminal_output;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod toml_format;
+pub mod units;
+pub mod upgrade;
 pub mod util;
+pub mod validation;
+pub mod vcs;
+pub mod versions;
+pub mod walk;
+#[cfg(feature = "watch")]
+pub mod watcher;
+pub mod workspace;
+pub mod worldgen;
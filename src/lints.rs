@@ -0,0 +1,330 @@
+//! Suppression of the warnings of the build.
+//!
+//! Warnings are suppressed by their name everywhere with the `allow` field of the `[lints]`
+//! section, for paths matching a glob pattern with `[lints.overrides]`, and for single scripts
+//! with `#[allow(...)]` annotations in them:
+//!
+//! ```toml
+//! [lints]
+//! allow = ["command-budget"]
+//!
+//! [lints.overrides]
+//! "src/legacy/**" = ["unknown-id", "unprefixed-resource"]
+//! ```
+
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::LintsConfig,
+    layout,
+    terminal_output::{print_info, print_warning},
+    util,
+    validation::{Origin, ScriptIndex},
+};
+
+/// The name allowing all warnings.
+const ALL: &str = "all";
+
+/// A warning of the build that can be suppressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Lint {
+    /// The estimated commands run per tick or on load exceed the budget.
+    CommandBudget,
+    /// A block, item or entity id is not in the registries of the Minecraft version.
+    UnknownId,
+    /// A scoreboard objective or data storage is not prefixed with a namespace of the pack.
+    UnprefixedResource,
+    /// A worldgen file uses a field removed in the pack format.
+    RemovedWorldgenField,
+    /// A file of the output is replaced by another one.
+    ReplacedFile,
+    /// A translation key is missing in a language.
+    MissingTranslation,
+    /// A function or function tag is missing or a `tick` or `load` function is never run.
+    FunctionTag,
+}
+
+impl Lint {
+    pub const ALL: &'static [Self] = &[
+        Self::CommandBudget,
+        Self::UnknownId,
+        Self::UnprefixedResource,
+        Self::RemovedWorldgenField,
+        Self::ReplacedFile,
+        Self::MissingTranslation,
+        Self::FunctionTag,
+    ];
+
+    /// The name of the lint used in the config and annotations.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CommandBudget => "command-budget",
+            Self::UnknownId => "unknown-id",
+            Self::UnprefixedResource => "unprefixed-resource",
+            Self::RemovedWorldgenField => "removed-worldgen-field",
+            Self::ReplacedFile => "replaced-file",
+            Self::MissingTranslation => "missing-translation",
+            Self::FunctionTag => "function-tag",
+        }
+    }
+
+    /// Get the lint with the name, `_` and `-` are interchangeable.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().replace('_', "-");
+        Self::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The suppressed warnings of a project.
+#[derive(Debug, Default)]
+pub struct Lints {
+    allow: BTreeSet<Lint>,
+    overrides: Vec<(String, BTreeSet<Lint>)>,
+    /// The lints allowed by the annotations of each script.
+    annotations: BTreeMap<PathBuf, BTreeSet<Lint>>,
+    index: ScriptIndex,
+    project_root: PathBuf,
+    suppressed: Cell<usize>,
+}
+
+impl Lints {
+    /// Read the suppressions of the config and the annotations of the scripts.
+    ///
+    /// Unknown lint names are warned about.
+    pub fn new<P>(
+        config: Option<&LintsConfig>,
+        project_root: &Path,
+        script_paths: &[(String, P)],
+    ) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let allow = config
+            .map(|config| parse_names(&config.allow, "the [lints] section"))
+            .unwrap_or_default();
+        let overrides = config
+            .map(|config| {
+                config
+                    .overrides
+                    .iter()
+                    .map(|(pattern, names)| {
+                        let context = format!("the override \"{pattern}\" of the [lints] section");
+                        (pattern.clone(), parse_names(names, &context))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let annotations = script_paths
+            .iter()
+            .filter_map(|(_, path)| {
+                let path = path.as_ref();
+                let content = fs::read_to_string(path).ok()?;
+                let names = allowed_by_annotations(&content);
+                if names.is_empty() {
+                    return None;
+                }
+                let context = format!("the annotations of {}", path.display());
+                Some((path.to_path_buf(), parse_names(&names, &context)))
+            })
+            .collect();
+
+        Self {
+            allow,
+            overrides,
+            annotations,
+            index: ScriptIndex::new(script_paths),
+            project_root: project_root.to_path_buf(),
+            suppressed: Cell::new(0),
+        }
+    }
+
+    /// Check whether the lint is suppressed for the file of the output, or everywhere if no file
+    /// is given.
+    ///
+    /// Files of the output are also attributed to the script that most likely produced them.
+    pub fn is_allowed(&self, lint: Lint, output_path: Option<&str>) -> bool {
+        if self.allow.contains(&lint) {
+            return true;
+        }
+        let Some(output_path) = output_path else {
+            return false;
+        };
+
+        let mut paths = vec![output_path.to_string()];
+        if let Origin::Script { path, .. } = self.index.origin_of(output_path, output_path) {
+            if self
+                .annotations
+                .get(&path)
+                .is_some_and(|lints| lints.contains(&lint))
+            {
+                return true;
+            }
+            let relative = path.strip_prefix(&self.project_root).unwrap_or(&path);
+            paths.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+
+        self.overrides.iter().any(|(pattern, lints)| {
+            lints.contains(&lint) && paths.iter().any(|path| util::matches_glob(pattern, path))
+        })
+    }
+
+    /// Print the warning with the name of the lint unless it is suppressed.
+    ///
+    /// Returns whether the warning was printed, so details can be printed after it.
+    pub fn warn(&self, lint: Lint, output_path: Option<&str>, message: impl Display) -> bool {
+        if self.is_allowed(lint, output_path) {
+            self.suppressed.set(self.suppressed.get() + 1);
+            return false;
+        }
+        print_warning(format!("{message} [{lint}]"));
+        true
+    }
+
+    /// Print the number of suppressed warnings, if any.
+    pub fn print_summary(&self) {
+        let suppressed = self.suppressed.get();
+        if suppressed > 0 {
+            print_info(format!(
+                "{suppressed} warning(s) suppressed by the [lints] section and #[allow] annotations."
+            ));
+        }
+    }
+}
+
+/// The path of the function in the output, e.g. to check suppressions of warnings about a
+/// function.
+pub fn function_path(function: &str, pack_format: u8) -> String {
+    let (namespace, path) = function.split_once(':').unwrap_or(("minecraft", function));
+    let directory = layout::directory("function", pack_format);
+    format!("data/{namespace}/{directory}/{path}.mcfunction")
+}
+
+/// Parse lint names, `all` expanding to all lints.
+fn parse_names(names: &[String], context: &str) -> BTreeSet<Lint> {
+    let mut lints = BTreeSet::new();
+    for name in names {
+        if name.trim() == ALL {
+            lints.extend(Lint::ALL);
+        } else if let Some(lint) = Lint::from_name(name) {
+            lints.insert(lint);
+        } else {
+            print_warning(format!(
+                "Unknown lint \"{name}\" in {context}. Known lints are: {}, {ALL}",
+                Lint::ALL
+                    .iter()
+                    .map(|lint| lint.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+    lints
+}
+
+/// Get the lint names of the `#[allow(...)]` annotations of a script.
+fn allowed_by_annotations(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| allow_annotation(line).map(|(_, names)| names))
+        .flat_map(|names| names.split(','))
+        .map(|name| name.trim().trim_matches('"').to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Blank out the `#[allow(...)]` annotations of a script, which the compiler does not know.
+///
+/// The annotations are replaced with spaces, so the lines and columns of diagnostics are kept.
+/// Returns `None` if the script has no annotations.
+pub fn strip_allow_annotations(content: &str) -> Option<String> {
+    if !content.contains("#[allow(") {
+        return None;
+    }
+    let stripped = content
+        .split_inclusive('\n')
+        .map(|line| match allow_annotation(line) {
+            Some((range, _)) => format!(
+                "{}{}{}",
+                &line[..range.start],
+                " ".repeat(range.len()),
+                &line[range.end..]
+            ),
+            None => line.to_string(),
+        })
+        .collect();
+    Some(stripped)
+}
+
+/// Find the `#[allow(...)]` annotation at the start of a line, returning its byte range and the
+/// lint names.
+fn allow_annotation(line: &str) -> Option<(Range<usize>, &str)> {
+    let start = line.len() - line.trim_start().len();
+    let (names, _) = line[start..].strip_prefix("#[allow(")?.split_once(")]")?;
+    let end = start + "#[allow(".len() + names.len() + ")]".len();
+    Some((start..end, names))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_lints() {
+        let temp = TestProject::empty("lints");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("src/legacy")).unwrap();
+        let main = dir.join("src/main.shu");
+        fs::write(
+            &main,
+            "namespace \"pack\";\n\n#[allow(unknown_id)]\n#[tick]\nfn main() {}\n",
+        )
+        .unwrap();
+        let old = dir.join("src/legacy/old.shu");
+        fs::write(&old, "namespace \"legacy\";\n\nfn old() {}\n").unwrap();
+
+        let config = LintsConfig {
+            allow: vec!["replaced-file".to_string()],
+            overrides: BTreeMap::from([("src/legacy/**".to_string(), vec![ALL.to_string()])]),
+        };
+        let lints = Lints::new(
+            Some(&config),
+            dir,
+            &[("main".to_string(), main), ("legacy/old".to_string(), old)],
+        );
+
+        assert!(lints.is_allowed(Lint::ReplacedFile, None));
+        assert!(!lints.is_allowed(Lint::UnknownId, None));
+        assert!(lints.is_allowed(Lint::UnknownId, Some("data/pack/function/main.mcfunction")));
+        assert!(!lints.is_allowed(
+            Lint::CommandBudget,
+            Some("data/pack/function/main.mcfunction")
+        ));
+        assert!(lints.is_allowed(Lint::CommandBudget, Some(&function_path("legacy:old", 48))));
+    }
+
+    #[test]
+    fn test_strip_allow_annotations() {
+        assert_eq!(
+            strip_allow_annotations(
+                "#[allow(unknown_id)]\r\n  #[allow(all)] #[tick]\nfn main() {}\n"
+            )
+            .as_deref(),
+            Some("                    \r\n                #[tick]\nfn main() {}\n")
+        );
+        assert_eq!(strip_allow_annotations("fn main() {}\n"), None);
+    }
+}
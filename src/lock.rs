@@ -0,0 +1,119 @@
+//! Advisory locks serializing writes of multiple processes to the same directory.
+//!
+//! The lock files are kept in the cache directory instead of the locked directory, so output
+//! directories like the `datapacks` folder of a world stay free of them and can be removed while
+//! other processes wait for the lock.
+
+use std::{
+    borrow::Cow,
+    env,
+    fs::{self, File, TryLockError},
+    io::{Seek as _, Write as _},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+use path_absolutize::Absolutize as _;
+use sha2::{Digest as _, Sha256};
+
+use crate::{cancel::CancellationToken, fetch, terminal_output::print_info};
+
+/// The interval in which a held lock is checked again.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An exclusive lock on writing to a directory, released when dropped.
+#[derive(Debug)]
+pub struct PathLock {
+    _file: File,
+}
+
+impl PathLock {
+    /// Lock the directory, waiting while another process holds the lock.
+    ///
+    /// # Errors
+    /// - If the lock file cannot be created or locked.
+    /// - If the token is cancelled while waiting.
+    pub fn acquire(dir: &Path, cancel: &CancellationToken) -> Result<Self> {
+        let lock_path = lock_path(dir)?;
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)?;
+
+        let mut waiting = false;
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => {
+                    if !waiting {
+                        waiting = true;
+                        let holder = fs::read_to_string(&lock_path)
+                            .ok()
+                            .filter(|holder| !holder.is_empty())
+                            .unwrap_or_else(|| "another process".to_string());
+                        print_info(format!(
+                            "Waiting for {holder} to finish writing to {}, press Ctrl-C to cancel",
+                            dir.display()
+                        ));
+                    }
+                    cancel.check()?;
+                    thread::sleep(POLL_INTERVAL);
+                }
+                Err(TryLockError::Error(err)) => return Err(err.into()),
+            }
+        }
+
+        // describe the holder for waiting processes, failing to do so is not fatal
+        let command = env::args()
+            .skip(1)
+            .fold("shulkerscript".to_string(), |command, arg| {
+                command + " " + arg.as_str()
+            });
+        let _ = file
+            .set_len(0)
+            .and_then(|()| file.rewind())
+            .and_then(|()| write!(file, "process {} (`{command}`)", std::process::id()));
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// Get the path of the lock file of the directory.
+fn lock_path(dir: &Path) -> Result<PathBuf> {
+    let dir = match dir.canonicalize() {
+        Ok(dir) => Cow::Owned(dir),
+        Err(_) => dir.absolutize()?,
+    };
+    let hash = hex::encode(Sha256::digest(dir.as_os_str().as_encoded_bytes()));
+    Ok(fetch::cache_dir()
+        .join("locks")
+        .join(format!("{}.lock", &hash[..16])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_path_lock() {
+        let temp = TestProject::empty("lock");
+        let dir = temp.path();
+        let cancel = CancellationToken::new();
+
+        let lock = PathLock::acquire(dir, &cancel).unwrap();
+        let cancelled = CancellationToken::new();
+        cancelled.cancel();
+        assert!(PathLock::acquire(dir, &cancelled).is_err());
+
+        drop(lock);
+        assert!(PathLock::acquire(dir, &cancelled).is_ok());
+    }
+}
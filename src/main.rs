@@ -1,8 +1,14 @@
-use std::process::ExitCode;
+use std::{env, path::Path, process::ExitCode};
 
 use clap::Parser;
 
-use shulkerscript_cli::{cli::Args, terminal_output::print_info};
+use shulkerscript_cli::{
+    alias,
+    cli::Args,
+    defaults,
+    terminal_output::{self, print_info, print_warning},
+    upgrade,
+};
 
 fn main() -> ExitCode {
     human_panic::setup_panic!();
@@ -10,10 +16,34 @@ fn main() -> ExitCode {
         print_info("Using environment variables from .env file");
     }
 
-    let args = Args::parse();
+    let Ok(commands) = alias::expand_args(env::args_os().collect(), Path::new(".")) else {
+        return ExitCode::FAILURE;
+    };
+    let chained = commands.len() > 1;
 
-    match args.run() {
-        Ok(_) => ExitCode::SUCCESS,
-        Err(_) => ExitCode::FAILURE,
+    for command in commands {
+        if chained {
+            print_info(format!(
+                "Running `{}`",
+                command[1..]
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        }
+
+        let command = defaults::apply(command, Path::new("."));
+        for deprecated in upgrade::deprecated_flags(&command) {
+            print_warning(deprecated);
+        }
+
+        let args = Args::parse_from(command);
+        terminal_output::set_message_format(args.message_format());
+        if args.run().is_err() {
+            return ExitCode::FAILURE;
+        }
     }
+
+    ExitCode::SUCCESS
 }
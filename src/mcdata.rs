@@ -0,0 +1,176 @@
+//! Vanilla registry reports (blocks, items, entities, ...) of Minecraft versions.
+//!
+//! The reports are fetched from the summaries of the data generator output published in the
+//! [mcmeta](https://github.com/misode/mcmeta) repository and cached per version, so lints can use
+//! them without network access.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+};
+
+use shulkerscript::shulkerbox::virtual_fs::VFolder;
+
+use crate::{
+    config::PackConfig,
+    fetch,
+    optimize::{function_id, text},
+    versions,
+};
+
+/// The URL of the registry summary, `{version}` is replaced with the Minecraft version.
+///
+/// Can be overridden with the `SHULKERSCRIPT_MCDATA_URL` environment variable.
+pub const DEFAULT_URL: &str =
+    "https://raw.githubusercontent.com/misode/mcmeta/{version}-summary/registries/data.min.json";
+
+/// The entries of the vanilla registries of a Minecraft version, without the `minecraft` namespace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Registries(pub BTreeMap<String, BTreeSet<String>>);
+
+impl Registries {
+    /// Get the cache path of the registries of a Minecraft version.
+    pub fn cache_path(version: &str) -> PathBuf {
+        fetch::cache_dir()
+            .join("mcdata")
+            .join(version)
+            .join("registries.json")
+    }
+
+    /// Load the cached registries of a Minecraft version.
+    pub fn load_cached(version: &str) -> Option<Self> {
+        let content = fs::read(Self::cache_path(version)).ok()?;
+        serde_json::from_slice(&content).ok().map(Self)
+    }
+
+    /// Fetch the registries of a Minecraft version and cache them.
+    #[cfg(feature = "mcdata")]
+    pub fn fetch(version: &str) -> anyhow::Result<Self> {
+        let url = std::env::var("SHULKERSCRIPT_MCDATA_URL")
+            .unwrap_or_else(|_| DEFAULT_URL.to_string())
+            .replace("{version}", version);
+        let registries: BTreeMap<String, BTreeSet<String>> =
+            if let Some(path) = url.strip_prefix("file://") {
+                serde_json::from_slice(&fs::read(path)?)?
+            } else {
+                ureq::get(&url)
+                    .set("User-Agent", crate::util::USER_AGENT)
+                    .call()?
+                    .into_json()?
+            };
+
+        let cache_path = Self::cache_path(version);
+        if let Some(parent) = cache_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&cache_path, serde_json::to_vec(&registries)?)?;
+
+        Ok(Self(registries))
+    }
+
+    /// Check whether the registry contains the id, which may be prefixed with `minecraft:`.
+    ///
+    /// Returns `None` if the registry is unknown.
+    pub fn contains(&self, registry: &str, id: &str) -> Option<bool> {
+        let entries = self.0.get(registry)?;
+        let id = id.strip_prefix("minecraft:").unwrap_or(id);
+        Some(entries.contains(id))
+    }
+}
+
+/// Get the Minecraft version of the pack, either the configured one or the newest version using
+/// the pack format.
+pub fn minecraft_version(pack: &PackConfig) -> Option<String> {
+    pack.minecraft_version.clone().or_else(|| {
+        versions::pack_format(pack.pack_format).map(|format| format.newest_version().to_string())
+    })
+}
+
+/// Find ids of blocks, items and entities in `give`, `summon`, `setblock` and `fill` commands
+/// (also after `execute ... run`) that are not in the registries.
+///
+/// Returns the function ids with the unknown ids and their registry.
+pub fn unknown_ids(folder: &VFolder, registries: &Registries) -> Vec<(String, String, String)> {
+    let mut unknown = Vec::new();
+    for (path, file) in folder.flatten() {
+        let Some(function) = function_id(&path) else {
+            continue;
+        };
+        for line in text(file).unwrap_or_default().lines() {
+            let line = line.trim();
+            if line.starts_with('#') || line.starts_with('$') {
+                continue;
+            }
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            for (index, token) in tokens.iter().enumerate() {
+                if index > 0 && tokens[index - 1] != "run" {
+                    continue;
+                }
+                let argument = match *token {
+                    "give" => tokens.get(index + 2).map(|id| ("item", *id)),
+                    "summon" => tokens.get(index + 1).map(|id| ("entity_type", *id)),
+                    "setblock" => tokens.get(index + 4).map(|id| ("block", *id)),
+                    "fill" => tokens.get(index + 7).map(|id| ("block", *id)),
+                    _ => None,
+                };
+                let Some((registry, id)) = argument else {
+                    continue;
+                };
+                // strip block states, components and nbt
+                let id = id.split(['[', '{']).next().unwrap_or(id);
+                if id.starts_with('#') || (id.contains(':') && !id.starts_with("minecraft:")) {
+                    continue;
+                }
+                if registries.contains(registry, id) == Some(false) {
+                    unknown.push((function.clone(), registry.to_string(), id.to_string()));
+                }
+            }
+        }
+    }
+    unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use shulkerscript::shulkerbox::virtual_fs::VFile;
+
+    use super::*;
+
+    #[test]
+    fn test_unknown_ids() {
+        let registries = Registries(BTreeMap::from([
+            ("item".to_string(), BTreeSet::from(["diamond".to_string()])),
+            ("block".to_string(), BTreeSet::from(["stone".to_string()])),
+        ]));
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/test/function/main.mcfunction",
+            VFile::Text(
+                "give @s minecraft:diamond[custom_name='\"a\"'] 1\n\
+                 give @s diamnod\n\
+                 execute as @a run setblock ~ ~ ~ stone_brick\n\
+                 summon zombie\n\
+                 give @s other:item\n\
+                 say give @s nothing"
+                    .to_string(),
+            ),
+        );
+
+        assert_eq!(
+            unknown_ids(&folder, &registries),
+            vec![
+                (
+                    "test:main".to_string(),
+                    "item".to_string(),
+                    "diamnod".to_string()
+                ),
+                (
+                    "test:main".to_string(),
+                    "block".to_string(),
+                    "stone_brick".to_string()
+                ),
+            ]
+        );
+    }
+}
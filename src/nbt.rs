@@ -0,0 +1,711 @@
+//! Reading and writing of NBT data in the binary (optionally gzip compressed) and SNBT formats.
+
+use std::{
+    fmt::{self, Display, Write as _},
+    io::{self, Read, Write},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+/// A NBT tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    /// The entries of a compound, in the order they were read.
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    fn id(&self) -> u8 {
+        match self {
+            Self::Byte(_) => 1,
+            Self::Short(_) => 2,
+            Self::Int(_) => 3,
+            Self::Long(_) => 4,
+            Self::Float(_) => 5,
+            Self::Double(_) => 6,
+            Self::ByteArray(_) => 7,
+            Self::String(_) => 8,
+            Self::List(_) => 9,
+            Self::Compound(_) => 10,
+            Self::IntArray(_) => 11,
+            Self::LongArray(_) => 12,
+        }
+    }
+
+    /// Get the value of an entry of a compound.
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Self::Compound(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, tag)| tag),
+            _ => None,
+        }
+    }
+
+    /// Get the value of an entry of a compound mutably.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Tag> {
+        match self {
+            Self::Compound(entries) => entries
+                .iter_mut()
+                .find(|(name, _)| name == key)
+                .map(|(_, tag)| tag),
+            _ => None,
+        }
+    }
+
+    /// Get the value of an integer tag of any size.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Byte(value) => Some(i64::from(*value)),
+            Self::Short(value) => Some(i64::from(*value)),
+            Self::Int(value) => Some(i64::from(*value)),
+            Self::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the elements of a list tag.
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Self::List(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Read a named root tag from binary NBT data, decompressing it if it is gzip compressed.
+///
+/// Returns the name of the root tag and the tag.
+pub fn read(data: &[u8]) -> io::Result<(String, Tag)> {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(data).read_to_end(&mut decompressed)?;
+        read_uncompressed(&decompressed)
+    } else {
+        read_uncompressed(data)
+    }
+}
+
+fn read_uncompressed(mut data: &[u8]) -> io::Result<(String, Tag)> {
+    let reader = &mut data;
+    let id = read_u8(reader)?;
+    if id == 0 {
+        return Err(invalid_data("the root tag is empty"));
+    }
+    let name = read_string(reader)?;
+    let tag = read_payload(reader, id, 0)?;
+    if !reader.is_empty() {
+        return Err(invalid_data("unexpected data after the root tag"));
+    }
+    Ok((name, tag))
+}
+
+/// Write a named root tag as gzip compressed binary NBT data.
+pub fn write_compressed(name: &str, tag: &Tag) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&write(name, tag))?;
+    encoder.finish()
+}
+
+/// Write a named root tag as uncompressed binary NBT data.
+pub fn write(name: &str, tag: &Tag) -> Vec<u8> {
+    let mut data = vec![tag.id()];
+    write_string(&mut data, name);
+    write_payload(&mut data, tag);
+    data
+}
+
+/// The maximum nesting depth of compounds and lists, as in Minecraft.
+const MAX_DEPTH: usize = 512;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_bytes<const N: usize>(reader: &mut &[u8]) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u8(reader: &mut &[u8]) -> io::Result<u8> {
+    Ok(read_bytes::<1>(reader)?[0])
+}
+
+fn read_len(reader: &mut &[u8]) -> io::Result<usize> {
+    let len = i32::from_be_bytes(read_bytes(reader)?);
+    usize::try_from(len).map_err(|_| invalid_data("negative length"))
+}
+
+fn read_string(reader: &mut &[u8]) -> io::Result<String> {
+    let len = u16::from_be_bytes(read_bytes(reader)?) as usize;
+    if reader.len() < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let (string, rest) = reader.split_at(len);
+    *reader = rest;
+    String::from_utf8(string.to_vec()).map_err(|_| invalid_data("invalid string"))
+}
+
+fn read_array<T, const N: usize>(
+    reader: &mut &[u8],
+    convert: impl Fn([u8; N]) -> T,
+) -> io::Result<Vec<T>> {
+    let len = read_len(reader)?;
+    if reader.len() < len.saturating_mul(N) {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    (0..len).map(|_| read_bytes(reader).map(&convert)).collect()
+}
+
+fn read_payload(reader: &mut &[u8], id: u8, depth: usize) -> io::Result<Tag> {
+    if depth > MAX_DEPTH {
+        return Err(invalid_data("the tags are nested too deeply"));
+    }
+    Ok(match id {
+        1 => Tag::Byte(i8::from_be_bytes(read_bytes(reader)?)),
+        2 => Tag::Short(i16::from_be_bytes(read_bytes(reader)?)),
+        3 => Tag::Int(i32::from_be_bytes(read_bytes(reader)?)),
+        4 => Tag::Long(i64::from_be_bytes(read_bytes(reader)?)),
+        5 => Tag::Float(f32::from_be_bytes(read_bytes(reader)?)),
+        6 => Tag::Double(f64::from_be_bytes(read_bytes(reader)?)),
+        7 => Tag::ByteArray(read_array(reader, i8::from_be_bytes)?),
+        8 => Tag::String(read_string(reader)?),
+        9 => {
+            let element_id = read_u8(reader)?;
+            let len = read_len(reader)?;
+            if element_id == 0 && len > 0 {
+                return Err(invalid_data("list of empty tags"));
+            }
+            let mut values = Vec::new();
+            for _ in 0..len {
+                values.push(read_payload(reader, element_id, depth + 1)?);
+            }
+            Tag::List(values)
+        }
+        10 => {
+            let mut entries = Vec::new();
+            loop {
+                let id = read_u8(reader)?;
+                if id == 0 {
+                    break;
+                }
+                let name = read_string(reader)?;
+                entries.push((name, read_payload(reader, id, depth + 1)?));
+            }
+            Tag::Compound(entries)
+        }
+        11 => Tag::IntArray(read_array(reader, i32::from_be_bytes)?),
+        12 => Tag::LongArray(read_array(reader, i64::from_be_bytes)?),
+        id => return Err(invalid_data(&format!("unknown tag type {id}"))),
+    })
+}
+
+fn write_string(data: &mut Vec<u8>, string: &str) {
+    data.extend((string.len() as u16).to_be_bytes());
+    data.extend(string.as_bytes());
+}
+
+fn write_payload(data: &mut Vec<u8>, tag: &Tag) {
+    match tag {
+        Tag::Byte(value) => data.extend(value.to_be_bytes()),
+        Tag::Short(value) => data.extend(value.to_be_bytes()),
+        Tag::Int(value) => data.extend(value.to_be_bytes()),
+        Tag::Long(value) => data.extend(value.to_be_bytes()),
+        Tag::Float(value) => data.extend(value.to_be_bytes()),
+        Tag::Double(value) => data.extend(value.to_be_bytes()),
+        Tag::ByteArray(values) => {
+            data.extend((values.len() as i32).to_be_bytes());
+            values
+                .iter()
+                .for_each(|value| data.extend(value.to_be_bytes()));
+        }
+        Tag::String(value) => write_string(data, value),
+        Tag::List(values) => {
+            data.push(values.first().map_or(0, Tag::id));
+            data.extend((values.len() as i32).to_be_bytes());
+            values.iter().for_each(|value| write_payload(data, value));
+        }
+        Tag::Compound(entries) => {
+            for (name, value) in entries {
+                data.push(value.id());
+                write_string(data, name);
+                write_payload(data, value);
+            }
+            data.push(0);
+        }
+        Tag::IntArray(values) => {
+            data.extend((values.len() as i32).to_be_bytes());
+            values
+                .iter()
+                .for_each(|value| data.extend(value.to_be_bytes()));
+        }
+        Tag::LongArray(values) => {
+            data.extend((values.len() as i32).to_be_bytes());
+            values
+                .iter()
+                .for_each(|value| data.extend(value.to_be_bytes()));
+        }
+    }
+}
+
+impl Display for Tag {
+    /// Format the tag as SNBT.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn join<T>(
+            f: &mut fmt::Formatter<'_>,
+            values: &[T],
+            mut write: impl FnMut(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+        ) -> fmt::Result {
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    f.write_char(',')?;
+                }
+                write(f, value)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Self::Byte(value) => write!(f, "{value}b"),
+            Self::Short(value) => write!(f, "{value}s"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Long(value) => write!(f, "{value}L"),
+            Self::Float(value) => write!(f, "{value:?}f"),
+            Self::Double(value) => write!(f, "{value:?}d"),
+            Self::ByteArray(values) => {
+                f.write_str("[B;")?;
+                join(f, values, |f, value| write!(f, "{value}B"))?;
+                f.write_char(']')
+            }
+            Self::String(value) => write_quoted(f, value),
+            Self::List(values) => {
+                f.write_char('[')?;
+                join(f, values, |f, value| write!(f, "{value}"))?;
+                f.write_char(']')
+            }
+            Self::Compound(entries) => {
+                f.write_char('{')?;
+                join(f, entries, |f, (name, value)| {
+                    write!(f, "{}:{value}", snbt_key(name))
+                })?;
+                f.write_char('}')
+            }
+            Self::IntArray(values) => {
+                f.write_str("[I;")?;
+                join(f, values, |f, value| write!(f, "{value}"))?;
+                f.write_char(']')
+            }
+            Self::LongArray(values) => {
+                f.write_str("[L;")?;
+                join(f, values, |f, value| write!(f, "{value}L"))?;
+                f.write_char(']')
+            }
+        }
+    }
+}
+
+/// Format the key of a compound entry, quoting it if necessary.
+fn snbt_key(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(is_unquoted_char) {
+        name.to_string()
+    } else {
+        Tag::String(name.to_string()).to_string()
+    }
+}
+
+fn write_quoted(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char('"')
+}
+
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+impl Tag {
+    /// Format the tag as SNBT with one entry per line for compounds and lists of compounds.
+    pub fn to_pretty_snbt(&self) -> String {
+        let mut output = String::new();
+        self.write_pretty(&mut output, 0);
+        output
+    }
+
+    fn write_pretty(&self, output: &mut String, depth: usize) {
+        let indent = "    ";
+        match self {
+            Self::Compound(entries) if !entries.is_empty() => {
+                output.push_str("{\n");
+                for (index, (name, value)) in entries.iter().enumerate() {
+                    output.push_str(&indent.repeat(depth + 1));
+                    output.push_str(&snbt_key(name));
+                    output.push_str(": ");
+                    value.write_pretty(output, depth + 1);
+                    if index + 1 < entries.len() {
+                        output.push(',');
+                    }
+                    output.push('\n');
+                }
+                output.push_str(&indent.repeat(depth));
+                output.push('}');
+            }
+            Self::List(values)
+                if values
+                    .iter()
+                    .any(|value| matches!(value, Self::Compound(_) | Self::List(_))) =>
+            {
+                output.push_str("[\n");
+                for (index, value) in values.iter().enumerate() {
+                    output.push_str(&indent.repeat(depth + 1));
+                    value.write_pretty(output, depth + 1);
+                    if index + 1 < values.len() {
+                        output.push(',');
+                    }
+                    output.push('\n');
+                }
+                output.push_str(&indent.repeat(depth));
+                output.push(']');
+            }
+            other => output.push_str(&other.to_string()),
+        }
+    }
+
+    /// Convert the tag into JSON.
+    ///
+    /// The conversion is lossy, as JSON does not distinguish between the numeric types and
+    /// between lists and arrays.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+
+        match self {
+            Self::Byte(value) => Value::from(*value),
+            Self::Short(value) => Value::from(*value),
+            Self::Int(value) => Value::from(*value),
+            Self::Long(value) => Value::from(*value),
+            Self::Float(value) => Value::from(*value),
+            Self::Double(value) => Value::from(*value),
+            Self::ByteArray(values) => Value::from(values.clone()),
+            Self::String(value) => Value::from(value.clone()),
+            Self::List(values) => Value::Array(values.iter().map(Self::to_json).collect()),
+            Self::Compound(entries) => Value::Object(
+                entries
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.to_json()))
+                    .collect(),
+            ),
+            Self::IntArray(values) => Value::from(values.clone()),
+            Self::LongArray(values) => Value::from(values.clone()),
+        }
+    }
+
+    /// Convert JSON into a tag.
+    ///
+    /// Integers become ints (or longs if they do not fit), other numbers doubles and booleans
+    /// bytes. Fails for `null` and lists with elements of different types.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        use serde_json::Value;
+
+        Ok(match value {
+            Value::Null => return Err("null cannot be converted to NBT".to_string()),
+            Value::Bool(value) => Self::Byte(i8::from(*value)),
+            Value::Number(number) => match number.as_i64() {
+                Some(value) => i32::try_from(value).map_or(Self::Long(value), Self::Int),
+                None => Self::Double(number.as_f64().unwrap_or_default()),
+            },
+            Value::String(value) => Self::String(value.clone()),
+            Value::Array(values) => {
+                let values = values
+                    .iter()
+                    .map(Self::from_json)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if values
+                    .first()
+                    .is_some_and(|first| values.iter().any(|value| value.id() != first.id()))
+                {
+                    return Err("all elements of a list must have the same type".to_string());
+                }
+                Self::List(values)
+            }
+            Value::Object(entries) => Self::Compound(
+                entries
+                    .iter()
+                    .map(|(name, value)| Ok((name.clone(), Self::from_json(value)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+        })
+    }
+}
+
+/// Parse a SNBT string.
+pub fn parse_snbt(input: &str) -> Result<Tag, String> {
+    let mut parser = SnbtParser { input, position: 0 };
+    let tag = parser.parse_value(0)?;
+    parser.skip_whitespace();
+    if parser.position < input.len() {
+        return Err(parser.error("unexpected trailing data"));
+    }
+    Ok(tag)
+}
+
+struct SnbtParser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl SnbtParser<'_> {
+    fn error(&self, message: &str) -> String {
+        let line = self.input[..self.position].matches('\n').count() + 1;
+        let column = self.input[..self.position]
+            .rsplit('\n')
+            .next()
+            .map_or(0, |line| line.chars().count())
+            + 1;
+        format!("{message} at line {line}, column {column}")
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{expected}`")))
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Tag, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("the tags are nested too deeply"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.parse_compound(depth),
+            Some('[') => self.parse_list(depth),
+            Some('"' | '\'') => self.parse_quoted().map(Tag::String),
+            Some(_) => {
+                let start = self.position;
+                let word = self.parse_unquoted();
+                if word.is_empty() {
+                    self.position = start;
+                    return Err(self.error("expected a value"));
+                }
+                Ok(parse_primitive(word))
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn parse_unquoted(&mut self) -> &str {
+        let start = self.position;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.position += 1;
+        }
+        &self.input[start..self.position]
+    }
+
+    fn parse_quoted(&mut self) -> Result<String, String> {
+        let quote = self.peek().ok_or_else(|| self.error("expected a string"))?;
+        self.position += 1;
+        let mut value = String::new();
+        let mut chars = self.input[self.position..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, escaped)) => value.push(escaped),
+                    None => break,
+                },
+                c if c == quote => {
+                    self.position += offset + 1;
+                    return Ok(value);
+                }
+                c => value.push(c),
+            }
+        }
+        self.position = self.input.len();
+        Err(self.error("unterminated string"))
+    }
+
+    fn parse_key(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"' | '\'') => self.parse_quoted(),
+            _ => {
+                let key = self.parse_unquoted();
+                if key.is_empty() {
+                    Err(self.error("expected a key"))
+                } else {
+                    Ok(key.to_string())
+                }
+            }
+        }
+    }
+
+    /// Parse comma separated elements until the closing character, which is consumed.
+    fn parse_elements(
+        &mut self,
+        close: char,
+        mut element: impl FnMut(&mut Self) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.position += 1;
+            return Ok(());
+        }
+        loop {
+            element(self)?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(c) if c == close => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error(&format!("expected `,` or `{close}`"))),
+            }
+        }
+    }
+
+    fn parse_compound(&mut self, depth: usize) -> Result<Tag, String> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.parse_elements('}', |parser| {
+            let key = parser.parse_key()?;
+            parser.expect(':')?;
+            entries.push((key, parser.parse_value(depth + 1)?));
+            Ok(())
+        })?;
+        Ok(Tag::Compound(entries))
+    }
+
+    fn parse_list(&mut self, depth: usize) -> Result<Tag, String> {
+        self.expect('[')?;
+        let rest = &self.input[self.position..];
+        let array_type = ["B;", "I;", "L;"]
+            .into_iter()
+            .find(|prefix| rest.starts_with(prefix));
+        if let Some(array_type) = array_type {
+            self.position += 2;
+            let mut values = Vec::new();
+            self.parse_elements(']', |parser| {
+                parser.skip_whitespace();
+                let value = parser.parse_unquoted();
+                let parsed = match parse_primitive(value).as_i64() {
+                    Some(value) => value,
+                    None => return Err(parser.error("expected a number")),
+                };
+                values.push(parsed);
+                Ok(())
+            })?;
+            return match array_type {
+                "B;" => Ok(Tag::ByteArray(
+                    values.into_iter().map(|value| value as i8).collect(),
+                )),
+                "I;" => Ok(Tag::IntArray(
+                    values.into_iter().map(|value| value as i32).collect(),
+                )),
+                _ => Ok(Tag::LongArray(values)),
+            };
+        }
+
+        let mut values = Vec::<Tag>::new();
+        self.parse_elements(']', |parser| {
+            let value = parser.parse_value(depth + 1)?;
+            if values.first().is_some_and(|first| first.id() != value.id()) {
+                return Err(parser.error("all elements of a list must have the same type"));
+            }
+            values.push(value);
+            Ok(())
+        })?;
+        Ok(Tag::List(values))
+    }
+}
+
+/// Parse an unquoted SNBT value, which is a number, a boolean or a string.
+fn parse_primitive(word: &str) -> Tag {
+    match word {
+        "true" => return Tag::Byte(1),
+        "false" => return Tag::Byte(0),
+        _ => {}
+    }
+
+    let (number, suffix) = match word.char_indices().last() {
+        Some((index, c)) if c.is_ascii_alphabetic() => (&word[..index], Some(c)),
+        _ => (word, None),
+    };
+    let parsed = match suffix.map(|c| c.to_ascii_lowercase()) {
+        Some('b') => number.parse().ok().map(Tag::Byte),
+        Some('s') => number.parse().ok().map(Tag::Short),
+        Some('l') => number.parse().ok().map(Tag::Long),
+        Some('f') => number.parse().ok().map(Tag::Float),
+        Some('d') => number.parse().ok().map(Tag::Double),
+        Some(_) => None,
+        None => number.parse().ok().map(Tag::Int).or_else(|| {
+            number
+                .contains(['.', 'e', 'E'])
+                .then(|| number.parse().ok().map(Tag::Double))
+                .flatten()
+        }),
+    };
+    parsed.unwrap_or_else(|| Tag::String(word.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snbt_roundtrip() {
+        let snbt = r#"{size:[3,2,1],palette:[{Name:"minecraft:stone"}],"with space":1.5d,flags:[B;1B,0B],name:'it\'s',big:3L,list:[1.0f,2.0f],empty:[]}"#;
+        let tag = parse_snbt(snbt).unwrap();
+
+        assert_eq!(
+            tag.get("size").and_then(Tag::as_list).map(<[Tag]>::len),
+            Some(3)
+        );
+        assert_eq!(tag.get("name"), Some(&Tag::String("it's".to_string())));
+        assert_eq!(tag.get("big"), Some(&Tag::Long(3)));
+        assert_eq!(parse_snbt(&tag.to_string()), Ok(tag.clone()));
+
+        let data = write_compressed("", &tag).unwrap();
+        assert_eq!(read(&data).unwrap(), (String::new(), tag.clone()));
+
+        assert!(parse_snbt("{a:1").is_err());
+        assert!(parse_snbt("[1,2b]").is_err());
+        assert!(read(&[10, 0, 0, 1]).is_err());
+
+        let pretty = tag.to_pretty_snbt();
+        assert!(pretty.starts_with("{\n    size: [3,2,1],\n    palette: [\n        {\n            Name: \"minecraft:stone\"\n"));
+        assert_eq!(parse_snbt(&pretty), Ok(tag.clone()));
+        assert_eq!(
+            Tag::from_json(&tag.to_json()).map(|tag| tag.get("big").cloned()),
+            Ok(Some(Tag::Int(3)))
+        );
+    }
+}
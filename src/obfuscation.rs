@@ -0,0 +1,384 @@
+//! Mapping between the hashed names of generated functions and the functions declared in the
+//! scripts.
+//!
+//! The compiler names every function without a `#[deobfuscate]` annotation
+//! `<namespace>:shu/<hash>`, where the hash is derived from the script identifier and the function
+//! name. The names are therefore stable between builds as long as the script is not moved or the
+//! function renamed. Blocks of a function moved to own functions are placed below
+//! `<namespace>:sb/shu/<hash>/`.
+//!
+//! The compiler does not expose these names, so they are read from its output for probe scripts
+//! declaring functions with the same names under the same script identifiers.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use serde::Serialize;
+use shulkerscript::{
+    base::{source_file::SourceElement, FileProvider, SilentHandler},
+    shulkerbox::datapack::Command,
+    syntax::syntax_tree::declaration::{Declaration, ImportItems},
+};
+
+use crate::dependency;
+
+/// The namespace of the probe scripts compiled to get the paths of functions.
+const PROBE_NAMESPACE: &str = "probe";
+/// The pack format the probe scripts are compiled with, which does not affect the paths.
+const PROBE_PACK_FORMAT: u8 = 48;
+
+/// A function declared in a script.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceFunction {
+    /// The identifier of the script declaring the function.
+    pub script: String,
+    /// The name of the function.
+    pub function: String,
+    /// The path of the script file.
+    pub file: PathBuf,
+    /// The line of the declaration.
+    pub line: usize,
+}
+
+/// An entry of the mapping file, linking a function of the datapack to its source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MappingEntry {
+    #[serde(flatten)]
+    pub source: SourceFunction,
+    /// Whether the function is a block of the source function moved to an own function.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub block: bool,
+}
+
+/// Get the functions declared in the scripts by their hashed function id (`namespace:shu/<hash>`).
+///
+/// The hash is computed from the script the function is first used in, so imported functions are
+/// included under the hashes of all importing scripts. Functions with a fixed name
+/// (`#[deobfuscate]`) are not included.
+pub fn hashed_functions<F>(
+    script_paths: &[(String, PathBuf)],
+    file_provider: &F,
+) -> Result<BTreeMap<String, SourceFunction>>
+where
+    F: FileProvider,
+{
+    let mut declared = BTreeMap::<(String, String), (String, SourceFunction)>::new();
+    let mut imports = Vec::new();
+    for (identifier, path) in script_paths {
+        let program = shulkerscript::parse(
+            &SilentHandler::new(),
+            file_provider,
+            path,
+            identifier.clone(),
+        )?;
+        let namespace = program.namespace().namespace_name().str_content();
+
+        for declaration in program.declarations() {
+            match declaration {
+                Declaration::Function(function) => {
+                    if function
+                        .annotations()
+                        .iter()
+                        .any(|annotation| annotation.identifier().span.str() == "deobfuscate")
+                    {
+                        continue;
+                    }
+
+                    let name = function.identifier().span.str().to_string();
+                    declared.insert(
+                        (identifier.clone(), name.clone()),
+                        (
+                            namespace.to_string(),
+                            SourceFunction {
+                                script: identifier.clone(),
+                                function: name,
+                                file: path.clone(),
+                                line: function.span().start_location().line,
+                            },
+                        ),
+                    );
+                }
+                Declaration::Import(import) => {
+                    let module =
+                        dependency::import_identifier(identifier, &import.module().str_content());
+                    if let ImportItems::Named(items) = import.items() {
+                        for item in items.elements() {
+                            imports.push((
+                                identifier.clone(),
+                                module.clone(),
+                                item.span.str().to_string(),
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut used = imports
+        .iter()
+        .map(|(importing, _, name)| (importing.clone(), name.clone()))
+        .collect::<BTreeSet<_>>();
+    used.extend(declared.keys().cloned());
+    let paths = compiled_paths(&used)?;
+
+    let mut functions = BTreeMap::new();
+    for (importing, module, name) in imports {
+        if let (Some((namespace, source)), Some(path)) = (
+            declared.get(&(module, name.clone())),
+            paths.get(&(importing, name)),
+        ) {
+            functions.insert(format!("{namespace}:{path}"), source.clone());
+        }
+    }
+    for (key, (namespace, source)) in declared {
+        if let Some(path) = paths.get(&key) {
+            functions.insert(format!("{namespace}:{path}"), source);
+        }
+    }
+
+    Ok(functions)
+}
+
+/// Get the paths the compiler gives to functions, keyed by the script identifier and the function
+/// name.
+///
+/// The paths are taken from the output of the compiler for probe scripts with the same
+/// identifiers, which declare empty functions with the same names and call them in order.
+fn compiled_paths(
+    functions: &BTreeSet<(String, String)>,
+) -> Result<BTreeMap<(String, String), String>> {
+    let mut by_script = BTreeMap::<&str, Vec<&str>>::new();
+    for (script, name) in functions {
+        by_script.entry(script).or_default().push(name);
+    }
+
+    let mut file_provider = dependency::ScriptProvider::default();
+    let mut probes = Vec::new();
+    for (index, (script, names)) in by_script.iter().enumerate() {
+        let mut caller = "probe".to_string();
+        while names.contains(&caller.as_str()) {
+            caller.push('_');
+        }
+        let calls = names
+            .iter()
+            .map(|name| format!("    {name}();\n"))
+            .collect::<String>();
+        let declarations = names
+            .iter()
+            .map(|name| format!("fn {name}() {{}}\n"))
+            .collect::<String>();
+        let path = PathBuf::from(format!("<probe {index}>.shu"));
+        file_provider.insert(
+            path.clone(),
+            format!(
+                "namespace \"{PROBE_NAMESPACE}\";\n\n#[deobfuscate = \"probe_{index}\"]\nfn {caller}() {{\n{calls}}}\n\n{declarations}"
+            ),
+        );
+        probes.push((script.to_string(), path));
+    }
+
+    let datapack = shulkerscript::transpile(
+        &SilentHandler::new(),
+        &file_provider,
+        PROBE_PACK_FORMAT,
+        &probes,
+    )?;
+    let namespace = datapack.namespace(PROBE_NAMESPACE);
+    let mut paths = BTreeMap::new();
+    for (index, (script, names)) in by_script.into_iter().enumerate() {
+        let calls = namespace
+            .and_then(|namespace| namespace.function(&format!("probe_{index}")))
+            .map(|caller| caller.get_commands().as_slice())
+            .unwrap_or_default();
+        for (name, call) in names.into_iter().zip(calls) {
+            let Command::Raw(call) = call else {
+                continue;
+            };
+            if let Some((_, path)) = call
+                .strip_prefix("function ")
+                .and_then(|location| location.split_once(':'))
+            {
+                paths.insert((script.to_string(), name.to_string()), path.to_string());
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Split a generated function id into the hashed function id it belongs to and the parts of the
+/// path before and after the hash.
+fn split_generated(id: &str) -> Option<(String, String, String)> {
+    let (namespace, path) = id.split_once(':')?;
+    let segments = path.split('/').collect::<Vec<_>>();
+    let position = segments.windows(2).position(|window| {
+        window[0] == "shu"
+            && window[1].len() == 32
+            && window[1].chars().all(|c| c.is_ascii_hexdigit())
+    })?;
+
+    Some((
+        format!("{namespace}:shu/{}", segments[position + 1]),
+        segments[..position].join("/"),
+        segments[position + 2..].join("/"),
+    ))
+}
+
+/// Link the functions of the datapack to the declared functions they were generated from.
+pub fn mapping(
+    function_ids: &[String],
+    functions: &BTreeMap<String, SourceFunction>,
+) -> BTreeMap<String, MappingEntry> {
+    function_ids
+        .iter()
+        .filter_map(|id| {
+            let (hashed, prefix, rest) = split_generated(id)?;
+            let source = functions.get(&hashed)?;
+            Some((
+                id.clone(),
+                MappingEntry {
+                    source: source.clone(),
+                    block: !prefix.is_empty() || !rest.is_empty(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Get readable names (`namespace:shu/<script>/<function>`) for the generated functions of the
+/// datapack, keyed by their hashed names.
+pub fn readable_names(
+    function_ids: &[String],
+    functions: &BTreeMap<String, SourceFunction>,
+) -> BTreeMap<String, String> {
+    let present = function_ids
+        .iter()
+        .filter_map(|id| split_generated(id).map(|(hashed, _, _)| hashed))
+        .collect::<BTreeSet<_>>();
+    let mut readable_paths = BTreeMap::new();
+    let mut taken = function_ids.iter().cloned().collect::<BTreeSet<_>>();
+    for (hashed, source) in functions
+        .iter()
+        .filter(|(hashed, _)| present.contains(*hashed))
+    {
+        let namespace = hashed
+            .split_once(':')
+            .map_or("", |(namespace, _)| namespace);
+        let mut readable = format!(
+            "{namespace}:shu/{}/{}",
+            sanitize(&source.script),
+            sanitize(&source.function)
+        );
+        if !taken.insert(readable.clone()) {
+            // names differing only in case or invalid characters would collide
+            readable.push('_');
+            readable.push_str(&hashed[hashed.len() - 8..]);
+            taken.insert(readable.clone());
+        }
+        readable_paths.insert(hashed.clone(), readable);
+    }
+
+    function_ids
+        .iter()
+        .filter_map(|id| {
+            let (hashed, prefix, rest) = split_generated(id)?;
+            let readable = readable_paths.get(&hashed)?;
+            let (namespace, readable_path) = readable.split_once(':')?;
+            let path = [prefix.as_str(), readable_path, rest.as_str()]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some((id.clone(), format!("{namespace}:{path}")))
+        })
+        .collect()
+}
+
+/// Replace the characters not allowed in function paths.
+fn sanitize(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use shulkerscript::base::FsProvider;
+
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_readable_names() {
+        let hash = "de1e6826a9782d65816e5fe3950c5d71";
+        let functions = BTreeMap::from([(
+            format!("test:shu/{hash}"),
+            SourceFunction {
+                script: "util/Math".to_string(),
+                function: "addOne".to_string(),
+                file: PathBuf::from("src/util/Math.shu"),
+                line: 3,
+            },
+        )]);
+        let ids = vec![
+            format!("test:shu/{hash}"),
+            format!("test:sb/shu/{hash}/cdd26115efccc7a5"),
+            "test:main".to_string(),
+        ];
+
+        let names = readable_names(&ids, &functions);
+        assert_eq!(names[&ids[0]], "test:shu/util/math/addone");
+        assert_eq!(
+            names[&ids[1]],
+            "test:sb/shu/util/math/addone/cdd26115efccc7a5"
+        );
+        assert!(!names.contains_key("test:main"));
+
+        let mapping = mapping(&ids, &functions);
+        assert!(!mapping[&ids[0]].block);
+        assert!(mapping[&ids[1]].block);
+    }
+
+    #[test]
+    fn test_hashed_functions() {
+        let temp = TestProject::empty("hashed-functions");
+        let dir = temp.path();
+        let path = dir.join("main.shu");
+        fs::write(
+            &path,
+            "namespace \"test\";\n\n#[load]\nfn main() {\n    helper();\n}\n\nfn helper() {\n    /say hi\n}\n",
+        )
+        .unwrap();
+        let script_paths = [("main".to_string(), path)];
+
+        let functions = hashed_functions(&script_paths, &FsProvider::default()).unwrap();
+        let datapack = shulkerscript::transpile(
+            &SilentHandler::new(),
+            &FsProvider::default(),
+            48,
+            &script_paths,
+        )
+        .unwrap();
+        let compiled = datapack.namespace("test").unwrap().get_functions();
+        assert_eq!(functions.len(), 2);
+        for (id, source) in &functions {
+            let (namespace, path) = id.split_once(':').unwrap();
+            assert_eq!(namespace, "test");
+            assert!(compiled.contains_key(path), "{id} of {}", source.function);
+        }
+    }
+}
@@ -0,0 +1,389 @@
+//! Opt-in optimizations of the compiled datapack.
+//!
+//! Only functions generated by the compiler (in the `shu/` and `sb/` folders of a namespace) are
+//! merged or removed, functions written by the user always keep their names.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+/// The optimizations to apply to the compiled datapack.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OptimizeOptions {
+    /// Strip comments and empty lines from `.mcfunction` files.
+    pub strip_comments: bool,
+    /// Replace calls of generated functions that only call another function with calls of the
+    /// other function.
+    pub merge_chains: bool,
+    /// Replace generated functions with identical bodies by a single one.
+    pub collapse_duplicates: bool,
+}
+
+impl OptimizeOptions {
+    /// Check whether any optimization is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.strip_comments || self.merge_chains || self.collapse_duplicates
+    }
+}
+
+/// Statistics about the applied optimizations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OptimizeStats {
+    pub stripped_lines: usize,
+    pub merged_chains: usize,
+    pub collapsed_duplicates: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+/// Apply the optimizations to the compiled datapack.
+pub fn optimize(folder: &VFolder, options: &OptimizeOptions) -> (VFolder, OptimizeStats) {
+    let mut files = folder
+        .flatten()
+        .into_iter()
+        .map(|(path, file)| (path, file.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let mut stats = OptimizeStats {
+        bytes_before: files.values().map(|file| file.as_bytes().len()).sum(),
+        ..Default::default()
+    };
+
+    if options.strip_comments {
+        for (path, file) in files.iter_mut() {
+            if function_id(path).is_some() {
+                if let Some((stripped, removed)) = strip_comments(file) {
+                    *file = VFile::Text(stripped);
+                    stats.stripped_lines += removed;
+                }
+            }
+        }
+    }
+
+    // redirecting calls may produce new chains and duplicates, so repeat until nothing changes
+    for _ in 0..16 {
+        let mut redirects = BTreeMap::new();
+        if options.merge_chains {
+            let chains = find_chains(&files);
+            stats.merged_chains += chains.len();
+            redirects.extend(chains);
+        }
+        if options.collapse_duplicates && redirects.is_empty() {
+            let duplicates = find_duplicates(&files);
+            stats.collapsed_duplicates += duplicates.len();
+            redirects.extend(duplicates);
+        }
+        if redirects.is_empty() {
+            break;
+        }
+        apply_redirects(&mut files, &redirects);
+    }
+
+    stats.bytes_after = files.values().map(|file| file.as_bytes().len()).sum();
+
+    let mut optimized = VFolder::new();
+    for (path, file) in files {
+        optimized.add_file(&path, file);
+    }
+
+    (optimized, stats)
+}
+
+/// Get the function id (`namespace:path`) of a function file path in the datapack.
+pub(crate) fn function_id(path: &str) -> Option<String> {
+    let mut parts = path.splitn(4, '/');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some("data"), Some(namespace), Some("function" | "functions"), Some(function)) => function
+            .strip_suffix(".mcfunction")
+            .map(|function| format!("{namespace}:{function}")),
+        _ => None,
+    }
+}
+
+/// Check whether the function was generated by the compiler.
+pub(crate) fn is_generated(id: &str) -> bool {
+    id.split_once(':')
+        .is_some_and(|(_, path)| path.starts_with("shu/") || path.starts_with("sb/"))
+}
+
+pub(crate) fn text(file: &VFile) -> Option<&str> {
+    match file {
+        VFile::Text(text) => Some(text),
+        VFile::Binary(data) => std::str::from_utf8(data).ok(),
+    }
+}
+
+/// Remove comment and empty lines, returning the new content and the number of removed lines.
+fn strip_comments(file: &VFile) -> Option<(String, usize)> {
+    let content = text(file)?;
+    let mut removed = 0;
+    let kept = content
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim_start();
+            let keep = !trimmed.is_empty() && !trimmed.starts_with('#');
+            if !keep {
+                removed += 1;
+            }
+            keep
+        })
+        .collect::<Vec<_>>();
+
+    (removed > 0).then(|| (kept.join("\n"), removed))
+}
+
+/// Get the significant lines of a function body.
+fn body_lines(file: &VFile) -> Option<Vec<&str>> {
+    Some(
+        text(file)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect(),
+    )
+}
+
+/// Find generated functions that only call another function without arguments.
+///
+/// Functions that are called with macro arguments are skipped, as their target would receive the
+/// arguments instead.
+fn find_chains(files: &BTreeMap<String, VFile>) -> BTreeMap<String, String> {
+    let called_with_arguments = functions_called_with_arguments(files);
+
+    let mut chains = BTreeMap::new();
+    for (path, file) in files {
+        let Some(id) = function_id(path).filter(|id| is_generated(id)) else {
+            continue;
+        };
+        if called_with_arguments.contains(&id) {
+            continue;
+        }
+        if let Some([line]) = body_lines(file).as_deref() {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if let ["function", target] = tokens.as_slice() {
+                if *target != id && !target.starts_with('#') && !chains.contains_key(*target) {
+                    chains.insert(id, target.to_string());
+                }
+            }
+        }
+    }
+
+    // a target may itself be redirected in this round, so only keep redirects to final targets
+    let targets = chains.values().cloned().collect::<BTreeSet<_>>();
+    chains.retain(|id, _| !targets.contains(id));
+    chains
+}
+
+/// Find generated functions with identical bodies, mapping each duplicate to the first one.
+fn find_duplicates(files: &BTreeMap<String, VFile>) -> BTreeMap<String, String> {
+    let mut first_by_body = BTreeMap::<&[u8], String>::new();
+    let mut duplicates = BTreeMap::new();
+    for (path, file) in files {
+        let Some(id) = function_id(path).filter(|id| is_generated(id)) else {
+            continue;
+        };
+        match first_by_body.get(file.as_bytes()) {
+            Some(first) => {
+                duplicates.insert(id, first.clone());
+            }
+            None => {
+                first_by_body.insert(file.as_bytes(), id);
+            }
+        }
+    }
+    duplicates
+}
+
+/// Get the functions that are called with macro arguments anywhere in the datapack.
+fn functions_called_with_arguments(files: &BTreeMap<String, VFile>) -> BTreeSet<String> {
+    let mut called = BTreeSet::new();
+    for (path, file) in files {
+        if function_id(path).is_none() {
+            continue;
+        }
+        let Some(content) = text(file) else {
+            continue;
+        };
+        for line in content.lines() {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            for window in tokens.windows(3) {
+                if window[0] == "function" && (window[2] == "with" || window[2].starts_with('{')) {
+                    called.insert(window[1].to_string());
+                }
+            }
+            for token in &tokens {
+                if let Some((id, _)) = token.split_once('{') {
+                    called.insert(id.to_string());
+                }
+            }
+        }
+    }
+    called
+}
+
+/// Remove the redirected functions and replace all references to them in functions and json files.
+fn apply_redirects(files: &mut BTreeMap<String, VFile>, redirects: &BTreeMap<String, String>) {
+    files.retain(|path, _| function_id(path).is_none_or(|id| !redirects.contains_key(&id)));
+    rewrite_references(files, redirects);
+}
+
+/// Move the functions to new names and replace all references to them in functions and json files.
+///
+/// The renames map function ids (`namespace:path`) to their new ids.
+pub fn rename_functions(folder: &VFolder, renames: &BTreeMap<String, String>) -> VFolder {
+    let mut files = BTreeMap::new();
+    for (path, file) in folder.flatten() {
+        let path = match function_id(&path).and_then(|id| renames.get(&id)) {
+            Some(new_id) => {
+                let (namespace, function) = new_id.split_once(':').unwrap_or(("minecraft", new_id));
+                let directory = path.split('/').nth(2).unwrap_or("function");
+                format!("data/{namespace}/{directory}/{function}.mcfunction")
+            }
+            None => path,
+        };
+        files.insert(path, file.clone());
+    }
+    rewrite_references(&mut files, renames);
+
+    let mut renamed = VFolder::new();
+    for (path, file) in files {
+        renamed.add_file(&path, file);
+    }
+    renamed
+}
+
+/// Get the ids (`namespace:path`) of all functions in the datapack.
+pub fn function_ids(folder: &VFolder) -> Vec<String> {
+    folder
+        .flatten()
+        .into_iter()
+        .filter_map(|(path, _)| function_id(&path))
+        .collect()
+}
+
+/// Replace all references to the functions in functions and json files.
+fn rewrite_references(files: &mut BTreeMap<String, VFile>, redirects: &BTreeMap<String, String>) {
+    for (path, file) in files.iter_mut() {
+        if function_id(path).is_some() {
+            let Some(content) = text(file) else {
+                continue;
+            };
+            let mut changed = false;
+            let lines = content
+                .split('\n')
+                .map(|line| {
+                    line.split(' ')
+                        .map(|token| match redirects.get(token) {
+                            Some(target) => {
+                                changed = true;
+                                target.as_str()
+                            }
+                            None => token,
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>();
+            if changed {
+                *file = VFile::Text(lines.join("\n"));
+            }
+        } else if path.starts_with("data/") && path.ends_with(".json") {
+            let Ok(mut value) = serde_json::from_slice::<serde_json::Value>(file.as_bytes()) else {
+                continue;
+            };
+            if redirect_json(&mut value, redirects) {
+                *file = VFile::Text(value.to_string());
+            }
+        }
+    }
+}
+
+fn redirect_json(value: &mut serde_json::Value, redirects: &BTreeMap<String, String>) -> bool {
+    match value {
+        serde_json::Value::String(string) => match redirects.get(string.as_str()) {
+            Some(target) => {
+                *string = target.clone();
+                true
+            }
+            None => false,
+        },
+        serde_json::Value::Array(values) => {
+            let mut changed = false;
+            for value in values {
+                changed |= redirect_json(value, redirects);
+            }
+            changed
+        }
+        serde_json::Value::Object(map) => {
+            let mut changed = false;
+            for value in map.values_mut() {
+                changed |= redirect_json(value, redirects);
+            }
+            changed
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optimize() {
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/test/function/main.mcfunction",
+            VFile::Text(
+                "# comment\nfunction test:shu/a\n\nexecute if entity @p run function test:shu/c"
+                    .to_string(),
+            ),
+        );
+        folder.add_file(
+            "data/test/function/shu/a.mcfunction",
+            VFile::Text("function test:shu/b".to_string()),
+        );
+        folder.add_file(
+            "data/test/function/shu/b.mcfunction",
+            VFile::Text("say b".to_string()),
+        );
+        folder.add_file(
+            "data/test/function/shu/c.mcfunction",
+            VFile::Text("say b".to_string()),
+        );
+        folder.add_file(
+            "data/minecraft/tags/function/tick.json",
+            VFile::Text(r#"{"values":["test:shu/a"]}"#.to_string()),
+        );
+
+        let (optimized, stats) = optimize(
+            &folder,
+            &OptimizeOptions {
+                strip_comments: true,
+                merge_chains: true,
+                collapse_duplicates: true,
+            },
+        );
+
+        assert_eq!(stats.stripped_lines, 2);
+        assert_eq!(stats.merged_chains, 1);
+        assert_eq!(stats.collapsed_duplicates, 1);
+        assert_eq!(
+            optimized
+                .get_file("data/test/function/main.mcfunction")
+                .and_then(VFile::as_text),
+            Some("function test:shu/b\nexecute if entity @p run function test:shu/b")
+        );
+        assert!(optimized
+            .get_file("data/test/function/shu/a.mcfunction")
+            .is_none());
+        assert!(optimized
+            .get_file("data/test/function/shu/c.mcfunction")
+            .is_none());
+        assert_eq!(
+            optimized
+                .get_file("data/minecraft/tags/function/tick.json")
+                .and_then(VFile::as_text),
+            Some(r#"{"values":["test:shu/b"]}"#)
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Hand-maintained overlays of the `assets-overlays` directory of a project.
+//!
+//! Every subdirectory `assets-overlays/<name>` configured in the `[overlays.<name>]` section is
+//! placed in the `<name>` directory of the output and listed with its pack formats in the
+//! `overlays` block of the pack.mcmeta file.
+
+use std::{collections::BTreeMap, io, path::Path};
+
+use shulkerscript::shulkerbox::virtual_fs::VFolder;
+
+use crate::{
+    config::OverlayConfig,
+    walk::{self, WalkOptions},
+};
+
+/// The directory of the overlays, relative to the project root.
+pub const SOURCE_DIR: &str = "assets-overlays";
+
+/// Read the configured overlays from the `assets-overlays` directory of the project.
+///
+/// Returns the overlay directories as they are placed in the output and problems that fail the
+/// build, e.g. invalid directory names or format ranges. Subdirectories without a configuration
+/// and configured overlays without a directory are ignored with a warning.
+///
+/// # Errors
+/// - If an overlay directory cannot be read.
+pub fn read(
+    project_root: &Path,
+    overlays: &BTreeMap<String, OverlayConfig>,
+    options: WalkOptions,
+) -> io::Result<(VFolder, Vec<String>, Vec<String>)> {
+    let source = project_root.join(SOURCE_DIR);
+    let mut output = VFolder::new();
+    let mut problems = Vec::new();
+    let mut warnings = Vec::new();
+
+    if source.is_dir() {
+        for entry in source.read_dir()? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_dir() && !overlays.contains_key(&name) {
+                warnings.push(format!(
+                    "The overlay directory {SOURCE_DIR}/{name} is not configured and skipped, add an [overlays.{name}] section to the pack.toml file to include it"
+                ));
+            }
+        }
+    }
+
+    for (name, overlay) in overlays {
+        if !is_valid_directory_name(name) {
+            problems.push(format!(
+                "The overlay name \"{name}\" may only contain the characters a-z, 0-9, _ and -"
+            ));
+            continue;
+        }
+        let (min, max) = overlay.formats.bounds();
+        if min > max {
+            problems.push(format!(
+                "The formats of the overlay {name} start after they end ({min} > {max})"
+            ));
+            continue;
+        }
+
+        let directory = source.join(name);
+        if !directory.is_dir() {
+            warnings.push(format!(
+                "The configured overlay {name} has no directory {SOURCE_DIR}/{name}"
+            ));
+            continue;
+        }
+        output.add_existing_folder(name, walk::read_folder(&directory, options)?);
+    }
+
+    Ok((output, problems, warnings))
+}
+
+/// The `overlays` block of the pack.mcmeta file listing the overlays.
+pub fn mcmeta_entries(overlays: &BTreeMap<String, OverlayConfig>) -> serde_json::Value {
+    let entries = overlays
+        .iter()
+        .map(|(name, overlay)| {
+            serde_json::json!({
+                "formats": overlay.formats,
+                "directory": name,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({ "entries": entries })
+}
+
+/// Whether the name can be used as overlay directory.
+fn is_valid_directory_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{config::FormatRange, testing::TestProject};
+
+    #[test]
+    fn test_read() {
+        let temp = TestProject::empty("overlay");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("assets-overlays/v57/data/ns/function")).unwrap();
+        fs::create_dir_all(dir.join("assets-overlays/unused")).unwrap();
+        fs::write(
+            dir.join("assets-overlays/v57/data/ns/function/main.mcfunction"),
+            "say new",
+        )
+        .unwrap();
+
+        let overlays = BTreeMap::from([
+            (
+                "v57".to_string(),
+                OverlayConfig {
+                    formats: FormatRange::Range([57, 61]),
+                },
+            ),
+            (
+                "Invalid".to_string(),
+                OverlayConfig {
+                    formats: FormatRange::Single(48),
+                },
+            ),
+        ]);
+        let (output, problems, warnings) = read(dir, &overlays, WalkOptions::default()).unwrap();
+
+        assert!(output
+            .get_file("v57/data/ns/function/main.mcfunction")
+            .is_some());
+        assert_eq!(problems.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            mcmeta_entries(&overlays)["entries"][1],
+            serde_json::json!({ "formats": [57, 61], "directory": "v57" })
+        );
+    }
+}
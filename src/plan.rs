@@ -0,0 +1,115 @@
+//! Plans of the operations `clean` and `deploy` are about to perform on the file system and on
+//! servers.
+//!
+//! With `--plan-json`, the plan is printed as one line of JSON before executing it, or instead of
+//! executing it with `--dry-run`, so orchestration tools can audit the changes made to production
+//! servers.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::{deploy::BackupScope, terminal_output::print_plain};
+
+/// An operation of a plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum Operation {
+    /// Delete the file or folder.
+    Delete { path: PathBuf },
+    /// Back up the datapack or the datapacks folder of the world.
+    Backup {
+        world: PathBuf,
+        datapack: String,
+        scope: BackupScope,
+        keep: usize,
+    },
+    /// Copy the artifact, `replace` is set if it replaces a file or folder at the destination.
+    Copy {
+        from: PathBuf,
+        to: PathBuf,
+        replace: bool,
+    },
+    /// Enable the datapack on the server via RCON.
+    Enable {
+        server: PathBuf,
+        address: String,
+        datapack: String,
+    },
+}
+
+/// The operations of a command, in the order they are performed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Plan {
+    pub command: &'static str,
+    pub dry_run: bool,
+    pub operations: Vec<Operation>,
+}
+
+impl Plan {
+    pub fn new(command: &'static str, dry_run: bool) -> Self {
+        Self {
+            command,
+            dry_run,
+            operations: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, operation: Operation) {
+        self.operations.push(operation);
+    }
+
+    /// Print the plan as one line of JSON.
+    ///
+    /// # Errors
+    /// - If the plan cannot be serialized.
+    pub fn print(&self) -> serde_json::Result<()> {
+        print_plain(serde_json::to_string(self)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_plan() {
+        let mut plan = Plan::new("deploy", true);
+        plan.push(Operation::Backup {
+            world: PathBuf::from("world"),
+            datapack: "pack.zip".to_string(),
+            scope: BackupScope::Pack,
+            keep: 5,
+        });
+        plan.push(Operation::Copy {
+            from: PathBuf::from("pack.zip"),
+            to: PathBuf::from("world/datapacks/pack.zip"),
+            replace: false,
+        });
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "command": "deploy",
+                "dry_run": true,
+                "operations": [
+                    {
+                        "operation": "backup",
+                        "world": "world",
+                        "datapack": "pack.zip",
+                        "scope": "pack",
+                        "keep": 5
+                    },
+                    {
+                        "operation": "copy",
+                        "from": "pack.zip",
+                        "to": "world/datapacks/pack.zip",
+                        "replace": false
+                    }
+                ]
+            })
+        );
+    }
+}
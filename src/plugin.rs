@@ -0,0 +1,265 @@
+//! Plugins that inspect or modify the output between compiling and placing it.
+//!
+//! Plugins implement the [`Plugin`] trait and are either registered directly on a [`PluginHost`]
+//! or compiled as dynamic libraries exporting their constructor with [`declare_plugin!`] and
+//! listed in the `[[plugins]]` section of the pack.toml file.
+//!
+//! ```ignore
+//! use shulkerscript_cli::plugin::{Plugin, PluginContext};
+//!
+//! #[derive(Default)]
+//! struct Header;
+//!
+//! impl Plugin for Header {
+//!     fn name(&self) -> &str {
+//!         "header"
+//!     }
+//!
+//!     fn process(&mut self, output: &mut VFolder, context: &PluginContext) -> anyhow::Result<()> {
+//!         // ...
+//!         Ok(())
+//!     }
+//! }
+//!
+//! shulkerscript_cli::declare_plugin!(Header::default());
+//! ```
+//!
+//! Dynamic libraries have to be compiled with the same compiler version and the same version of
+//! this crate as the cli loading them, as the trait objects are passed across the library
+//! boundary.
+
+use std::path::Path;
+
+use anyhow::Result;
+use shulkerscript::shulkerbox::virtual_fs::VFolder;
+
+use crate::{
+    config::ProjectConfig,
+    error::Error,
+    terminal_output::{print_error, print_info},
+};
+
+/// The version of the plugin interface, plugins built against a different version are rejected.
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+/// The information about the project passed to plugins.
+#[derive(Debug, Clone, Copy)]
+pub struct PluginContext<'a> {
+    /// The root directory of the project.
+    pub project_root: &'a Path,
+    /// The configuration of the project.
+    pub config: &'a ProjectConfig,
+    /// The options of the plugin given in its `[[plugins]]` entry.
+    pub options: &'a toml::Table,
+}
+
+/// A pass over the output of the build.
+pub trait Plugin {
+    /// The name of the plugin used in messages.
+    fn name(&self) -> &str;
+
+    /// Inspect or modify the output before it is placed.
+    ///
+    /// Returning an error fails the build.
+    fn process(&mut self, output: &mut VFolder, context: &PluginContext) -> Result<()>;
+}
+
+/// Export the constructor of a plugin from a dynamic library.
+#[macro_export]
+macro_rules! declare_plugin {
+    ($constructor:expr) => {
+        #[no_mangle]
+        pub extern "C" fn _shulkerscript_plugin_api_version() -> u32 {
+            $crate::plugin::PLUGIN_API_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn _shulkerscript_plugin_create() -> *mut Box<dyn $crate::plugin::Plugin> {
+            let plugin: Box<dyn $crate::plugin::Plugin> = Box::new($constructor);
+            Box::into_raw(Box::new(plugin))
+        }
+    };
+}
+
+/// The plugins of a build, run in the order they were added.
+#[derive(Default)]
+pub struct PluginHost {
+    // Declared before the libraries, so the plugins are dropped before their code is unloaded.
+    plugins: Vec<(Box<dyn Plugin>, toml::Table)>,
+    #[cfg(feature = "plugins")]
+    libraries: Vec<libloading::Library>,
+}
+
+impl PluginHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the plugins listed in the `[[plugins]]` section of the config.
+    ///
+    /// The paths are relative to the project root. Fails if plugins are configured but not
+    /// `allowed`, as loading them runs their native code.
+    pub fn from_config(config: &ProjectConfig, project_root: &Path, allowed: bool) -> Result<Self> {
+        if !config.plugins.is_empty() && !allowed {
+            print_error("The project uses plugins, which run native code. Build with --allow-plugins to load them, plugins are never loaded for remote repositories.");
+            return Err(Error::PluginsNotAllowedError.into());
+        }
+
+        #[cfg(not(feature = "plugins"))]
+        if !config.plugins.is_empty() {
+            let _ = project_root;
+            print_error("The plugins feature is not enabled. Please install with the `plugins` feature enabled to use the `[[plugins]]` section.");
+            return Err(Error::FeatureNotEnabledError("plugins".to_string()).into());
+        }
+
+        #[cfg_attr(not(feature = "plugins"), allow(unused_mut))]
+        let mut host = Self::new();
+        #[cfg(feature = "plugins")]
+        for plugin in &config.plugins {
+            // SAFETY: the libraries are only loaded after the user allowed it with --allow-plugins,
+            // which is refused for remote repositories.
+            unsafe { host.load(&project_root.join(&plugin.path), plugin.options.clone())? };
+        }
+
+        Ok(host)
+    }
+
+    /// Add a plugin with its options.
+    pub fn register(&mut self, plugin: Box<dyn Plugin>, options: toml::Table) {
+        self.plugins.push((plugin, options));
+    }
+
+    /// Load a plugin from a dynamic library exporting it with [`declare_plugin!`].
+    ///
+    /// # Safety
+    /// Loading a library runs its initialization code, and the library has to be built with the
+    /// same compiler and version of this crate.
+    #[cfg(feature = "plugins")]
+    pub unsafe fn load(&mut self, path: &Path, options: toml::Table) -> Result<()> {
+        let invalid = |reason: String| {
+            print_error(format!(
+                "Failed to load the plugin at {}: {reason}",
+                path.display()
+            ));
+            Error::InvalidPluginError(path.to_path_buf())
+        };
+
+        let library = libloading::Library::new(path).map_err(|err| invalid(err.to_string()))?;
+        let api_version = library
+            .get::<extern "C" fn() -> u32>(b"_shulkerscript_plugin_api_version")
+            .map_err(|_| invalid("the library does not export a plugin".to_string()))?(
+        );
+        if api_version != PLUGIN_API_VERSION {
+            return Err(invalid(format!(
+                "the plugin was built for version {api_version} of the plugin interface, but version {PLUGIN_API_VERSION} is required"
+            ))
+            .into());
+        }
+        let create = library
+            .get::<extern "C" fn() -> *mut Box<dyn Plugin>>(b"_shulkerscript_plugin_create")
+            .map_err(|_| invalid("the library does not export a plugin".to_string()))?;
+        let plugin = *Box::from_raw(create());
+
+        self.plugins.push((plugin, options));
+        self.libraries.push(library);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    /// Run all plugins on the output, stopping at the first failing plugin.
+    pub fn run(
+        &mut self,
+        output: &mut VFolder,
+        project_root: &Path,
+        config: &ProjectConfig,
+    ) -> Result<()> {
+        for (plugin, options) in &mut self.plugins {
+            print_info(format!("Running plugin {}...", plugin.name()));
+            let context = PluginContext {
+                project_root,
+                config,
+                options,
+            };
+            if let Err(err) = plugin.process(output, &context) {
+                print_error(format!("The plugin {} failed: {err}", plugin.name()));
+                return Err(Error::PluginError(plugin.name().to_string()).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shulkerscript::shulkerbox::virtual_fs::VFile;
+
+    use super::*;
+
+    struct Stamp;
+
+    impl Plugin for Stamp {
+        fn name(&self) -> &str {
+            "stamp"
+        }
+
+        fn process(&mut self, output: &mut VFolder, context: &PluginContext) -> Result<()> {
+            let text = context
+                .options
+                .get("text")
+                .and_then(|text| text.as_str())
+                .unwrap_or_default();
+            output.add_file("data/test/stamp.txt", VFile::Text(text.to_string()));
+            Ok(())
+        }
+    }
+
+    struct Reject;
+
+    impl Plugin for Reject {
+        fn name(&self) -> &str {
+            "reject"
+        }
+
+        fn process(&mut self, output: &mut VFolder, _context: &PluginContext) -> Result<()> {
+            if output.get_file("data/test/stamp.txt").is_some() {
+                anyhow::bail!("stamps are not allowed");
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_plugin_host() {
+        let config = toml::from_str::<ProjectConfig>(
+            "[pack]\nname = \"test\"\ndescription = \"\"\npack_format = 48\nversion = \"0.1.0\"",
+        )
+        .unwrap();
+        let mut options = toml::Table::new();
+        options.insert("text".to_string(), "hello".into());
+
+        let mut host = PluginHost::new();
+        host.register(Box::new(Stamp), options);
+        let mut output = VFolder::new();
+        host.run(&mut output, Path::new("."), &config).unwrap();
+        assert_eq!(
+            output
+                .get_file("data/test/stamp.txt")
+                .and_then(|file| file.as_text()),
+            Some("hello")
+        );
+
+        host.register(Box::new(Reject), toml::Table::new());
+        let err = host.run(&mut output, Path::new("."), &config).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::PluginError(name)) if name == "reject"
+        ));
+    }
+}
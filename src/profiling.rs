@@ -0,0 +1,197 @@
+//! Scoreboard-based instrumentation of the compiled datapack to measure the cost of functions
+//! in-game.
+//!
+//! Every function counts its calls and the commands it contains in two scoreboard objectives.
+//! The generated `<namespace>:profile` function prints the collected numbers together with the
+//! number of ticks since the last reset, `<namespace>:profile/reset` starts a new measurement.
+
+use std::collections::BTreeMap;
+
+use serde_json::json;
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+use crate::{
+    layout,
+    optimize::{function_id, text},
+};
+
+/// Add profiling instrumentation to all functions of the compiled datapack.
+///
+/// Returns the instrumented datapack and the number of instrumented functions.
+pub fn instrument(folder: &VFolder, namespace: &str, pack_format: u8) -> (VFolder, usize) {
+    let function_directory = layout::directory("function", pack_format);
+    let calls = format!("{namespace}.prof.calls");
+    let commands = format!("{namespace}.prof.cmds");
+    let profile_prefix = format!("{namespace}:profile");
+
+    let mut files = folder
+        .flatten()
+        .into_iter()
+        .map(|(path, file)| (path, file.clone()))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut instrumented = Vec::new();
+    for (path, file) in files.iter_mut() {
+        let Some(id) = function_id(path) else {
+            continue;
+        };
+        if id == profile_prefix || id.starts_with(&format!("{profile_prefix}/")) {
+            continue;
+        }
+        let Some(content) = text(file) else {
+            continue;
+        };
+
+        let command_count = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .count();
+        let holder = format!("#{}", instrumented.len());
+        *file = VFile::Text(format!(
+            "scoreboard players add {holder} {calls} 1\nscoreboard players add {holder} {commands} {command_count}\n{content}"
+        ));
+        instrumented.push(id);
+    }
+
+    let function_path =
+        |name: &str| format!("data/{namespace}/{function_directory}/{name}.mcfunction");
+
+    files.insert(
+        function_path("profile/setup"),
+        VFile::Text(format!(
+            "scoreboard objectives add {calls} dummy\nscoreboard objectives add {commands} dummy"
+        )),
+    );
+    files.insert(
+        function_path("profile/tick"),
+        VFile::Text(format!("scoreboard players add #ticks {calls} 1")),
+    );
+    files.insert(
+        function_path("profile/reset"),
+        VFile::Text(format!(
+            "scoreboard players reset * {calls}\nscoreboard players reset * {commands}"
+        )),
+    );
+
+    let mut report = vec![format!(
+        "tellraw @s {}",
+        json!([
+            {"text": "Profile after ", "color": "gold"},
+            {"score": {"name": "#ticks", "objective": calls}},
+            {"text": " ticks (calls / commands):"}
+        ])
+    )];
+    for (index, id) in instrumented.iter().enumerate() {
+        report.push(format!(
+            "execute if score #{index} {calls} matches 1.. run tellraw @s {}",
+            json!([
+                {"text": format!("{id}: "), "color": "gray"},
+                {"score": {"name": format!("#{index}"), "objective": calls}, "color": "white"},
+                {"text": " / "},
+                {"score": {"name": format!("#{index}"), "objective": commands}, "color": "white"}
+            ])
+        ));
+    }
+    files.insert(function_path("profile"), VFile::Text(report.join("\n")));
+
+    add_to_tag(
+        &mut files,
+        &format!("data/minecraft/tags/{function_directory}/load.json"),
+        &format!("{profile_prefix}/setup"),
+    );
+    add_to_tag(
+        &mut files,
+        &format!("data/minecraft/tags/{function_directory}/tick.json"),
+        &format!("{profile_prefix}/tick"),
+    );
+
+    let mut folder = VFolder::new();
+    for (path, file) in files {
+        folder.add_file(&path, file);
+    }
+
+    (folder, instrumented.len())
+}
+
+/// Add a function to a function tag, creating the tag if it does not exist.
+fn add_to_tag(files: &mut BTreeMap<String, VFile>, path: &str, function: &str) {
+    let mut tag = files
+        .get(path)
+        .and_then(|file| serde_json::from_slice::<serde_json::Value>(file.as_bytes()).ok())
+        .unwrap_or_else(|| json!({ "values": [] }));
+    if let Some(values) = tag
+        .as_object_mut()
+        .map(|tag| tag.entry("values").or_insert_with(|| json!([])))
+        .and_then(serde_json::Value::as_array_mut)
+    {
+        values.push(json!(function));
+    }
+    files.insert(path.to_string(), VFile::Text(tag.to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instrument() {
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/test/function/main.mcfunction",
+            VFile::Text("# comment\nsay a\nsay b".to_string()),
+        );
+        folder.add_file(
+            "data/minecraft/tags/function/tick.json",
+            VFile::Text(r#"{"values":["test:main"]}"#.to_string()),
+        );
+
+        let (instrumented, count) = instrument(&folder, "test", 48);
+
+        assert_eq!(count, 1);
+        assert_eq!(
+            instrumented
+                .get_file("data/test/function/main.mcfunction")
+                .and_then(VFile::as_text),
+            Some("scoreboard players add #0 test.prof.calls 1\nscoreboard players add #0 test.prof.cmds 2\n# comment\nsay a\nsay b")
+        );
+        assert_eq!(
+            instrumented
+                .get_file("data/minecraft/tags/function/tick.json")
+                .and_then(VFile::as_text),
+            Some(r#"{"values":["test:main","test:profile/tick"]}"#)
+        );
+        assert_eq!(
+            instrumented
+                .get_file("data/minecraft/tags/function/load.json")
+                .and_then(VFile::as_text),
+            Some(r#"{"values":["test:profile/setup"]}"#)
+        );
+        assert!(instrumented
+            .get_file("data/test/function/profile.mcfunction")
+            .is_some());
+    }
+
+    #[test]
+    fn test_instrument_plural_directories() {
+        let pack_format = layout::SINGULAR_FORMAT - 1;
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/test/functions/main.mcfunction",
+            VFile::Text("say a".to_string()),
+        );
+
+        let (instrumented, count) = instrument(&folder, "test", pack_format);
+
+        assert_eq!(count, 1);
+        assert!(instrumented
+            .get_file("data/test/functions/profile.mcfunction")
+            .is_some());
+        assert!(instrumented
+            .get_file("data/minecraft/tags/functions/load.json")
+            .is_some());
+        assert!(instrumented
+            .get_file("data/test/function/profile.mcfunction")
+            .is_none());
+    }
+}
@@ -0,0 +1,113 @@
+//! Prompts for user input that never wait for input that cannot be given.
+//!
+//! The prompts wrap the ones of [`inquire`]. When the input is not a terminal, e.g. in CI
+//! pipelines, or messages are printed as JSON, they are answered with their default instead of
+//! waiting for input, and fail with [`InquireError::NotTTY`] if they have none.
+
+use std::{
+    fmt::Display,
+    io::{self, IsTerminal},
+};
+
+use inquire::{error::InquireResult, Confirm, InquireError, MultiSelect, Select, Text};
+
+use crate::terminal_output::{self, print_error, print_info};
+
+/// Whether the user can answer prompts.
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && !terminal_output::is_json()
+}
+
+/// Ask a yes/no question, answered with the default if the input is not a terminal.
+///
+/// # Errors
+/// - If the prompt is cancelled.
+/// - If the input is not a terminal and the prompt has no default.
+pub fn confirm(prompt: Confirm) -> InquireResult<bool> {
+    if is_interactive() {
+        return prompt.prompt();
+    }
+    let answer = answer_default(
+        prompt.message,
+        prompt
+            .default
+            .map(|default| if default { "yes" } else { "no" }),
+    )?;
+    Ok(answer == "yes")
+}
+
+/// Ask for text, answered with the default if the input is not a terminal.
+///
+/// # Errors
+/// - If the prompt is cancelled.
+/// - If the input is not a terminal and the prompt has no default.
+pub fn text(prompt: Text) -> InquireResult<String> {
+    if is_interactive() {
+        return prompt.prompt();
+    }
+    answer_default(prompt.message, prompt.default).map(str::to_string)
+}
+
+/// Ask to select an option, answered with the option at the starting cursor if the input is not
+/// a terminal.
+///
+/// # Errors
+/// - If the prompt is cancelled.
+/// - If the input is not a terminal and the prompt has no options.
+pub fn select<T: Display>(mut prompt: Select<T>) -> InquireResult<T> {
+    if is_interactive() {
+        return prompt.prompt();
+    }
+    if prompt.starting_cursor >= prompt.options.len() {
+        answer_default::<&str>(prompt.message, None)?;
+    }
+    let option = prompt.options.swap_remove(prompt.starting_cursor);
+    answer_default(prompt.message, Some(&option))?;
+    Ok(option)
+}
+
+/// Ask to select any number of options, answered with the default options if the input is not a
+/// terminal.
+///
+/// # Errors
+/// - If the prompt is cancelled.
+pub fn multi_select<T: Display>(prompt: MultiSelect<T>) -> InquireResult<Vec<T>> {
+    if is_interactive() {
+        return prompt.prompt();
+    }
+    let default = prompt.default.unwrap_or_default();
+    let selected = prompt
+        .options
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, option)| default.contains(&index).then_some(option))
+        .collect::<Vec<_>>();
+    let answer = selected
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    answer_default(
+        prompt.message,
+        Some(if answer.is_empty() { "none" } else { &answer }),
+    )?;
+    Ok(selected)
+}
+
+/// Report the default answer of a prompt that cannot be asked, or that it has none.
+fn answer_default<T: Display>(message: &str, default: Option<T>) -> InquireResult<T> {
+    match default {
+        Some(default) => {
+            print_info(format!(
+                "{message} {default} (the input is not a terminal, using the default)"
+            ));
+            Ok(default)
+        }
+        None => {
+            print_error(format!(
+                "Cannot ask \"{message}\" because the input is not a terminal. Pass the answer as option instead."
+            ));
+            Err(InquireError::NotTTY)
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! A minimal client of the RCON protocol of Minecraft servers.
+//!
+//! Only single-packet responses are supported, which covers the `/datapack` and `/reload`
+//! commands.
+
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+const TYPE_RESPONSE: i32 = 0;
+const TYPE_COMMAND: i32 = 2;
+const TYPE_LOGIN: i32 = 3;
+
+/// The maximum length of a packet sent by the server.
+const MAX_PACKET_LENGTH: usize = 4096 + 10;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An authenticated connection to a server.
+#[derive(Debug)]
+pub struct Rcon {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl Rcon {
+    /// Connect to the server and log in with the password.
+    ///
+    /// # Errors
+    /// - If the server cannot be reached.
+    /// - If the password is wrong, with [`io::ErrorKind::PermissionDenied`].
+    pub fn connect(address: impl ToSocketAddrs, password: &str) -> io::Result<Self> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "address not found"))?;
+        let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let mut rcon = Self { stream, next_id: 1 };
+        let id = rcon.send(TYPE_LOGIN, password)?;
+        // the login response has the id -1 if the password is wrong
+        let (response_id, _, _) = rcon.receive()?;
+        if response_id != id {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "wrong RCON password",
+            ));
+        }
+        Ok(rcon)
+    }
+
+    /// Run the command and return its output.
+    ///
+    /// # Errors
+    /// - If the connection fails.
+    pub fn command(&mut self, command: &str) -> io::Result<String> {
+        let id = self.send(TYPE_COMMAND, command)?;
+        loop {
+            let (response_id, kind, payload) = self.receive()?;
+            if response_id == id && kind == TYPE_RESPONSE {
+                return Ok(payload);
+            }
+        }
+    }
+
+    fn send(&mut self, kind: i32, payload: &str) -> io::Result<i32> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let length = (payload.len() + 10) as i32;
+        let mut packet = Vec::with_capacity(payload.len() + 14);
+        packet.extend(length.to_le_bytes());
+        packet.extend(id.to_le_bytes());
+        packet.extend(kind.to_le_bytes());
+        packet.extend(payload.as_bytes());
+        packet.extend([0, 0]);
+        self.stream.write_all(&packet)?;
+        Ok(id)
+    }
+
+    fn receive(&mut self) -> io::Result<(i32, i32, String)> {
+        let mut length = [0; 4];
+        self.stream.read_exact(&mut length)?;
+        let length = i32::from_le_bytes(length);
+        if !(10..=MAX_PACKET_LENGTH as i32).contains(&length) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid RCON packet length {length}"),
+            ));
+        }
+
+        let mut packet = vec![0; length as usize];
+        self.stream.read_exact(&mut packet)?;
+        let id = i32::from_le_bytes([packet[0], packet[1], packet[2], packet[3]]);
+        let kind = i32::from_le_bytes([packet[4], packet[5], packet[6], packet[7]]);
+        let payload = String::from_utf8_lossy(&packet[8..packet.len() - 2]).into_owned();
+        Ok((id, kind, payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use super::*;
+
+    fn read_packet(stream: &mut TcpStream) -> (i32, i32, String) {
+        let mut length = [0; 4];
+        stream.read_exact(&mut length).unwrap();
+        let mut packet = vec![0; i32::from_le_bytes(length) as usize];
+        stream.read_exact(&mut packet).unwrap();
+        (
+            i32::from_le_bytes(packet[0..4].try_into().unwrap()),
+            i32::from_le_bytes(packet[4..8].try_into().unwrap()),
+            String::from_utf8(packet[8..packet.len() - 2].to_vec()).unwrap(),
+        )
+    }
+
+    fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, payload: &str) {
+        let mut packet = ((payload.len() + 10) as i32).to_le_bytes().to_vec();
+        packet.extend(id.to_le_bytes());
+        packet.extend(kind.to_le_bytes());
+        packet.extend(payload.as_bytes());
+        packet.extend([0, 0]);
+        stream.write_all(&packet).unwrap();
+    }
+
+    #[test]
+    fn test_login_and_command() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let (id, kind, password) = read_packet(&mut stream);
+            assert_eq!((kind, password.as_str()), (TYPE_LOGIN, "secret"));
+            write_packet(&mut stream, id, TYPE_COMMAND, "");
+            let (id, kind, command) = read_packet(&mut stream);
+            assert_eq!(kind, TYPE_COMMAND);
+            write_packet(&mut stream, id, TYPE_RESPONSE, &format!("ran {command}"));
+        });
+
+        let mut rcon = Rcon::connect(address, "secret").unwrap();
+        assert_eq!(rcon.command("reload").unwrap(), "ran reload");
+        server.join().unwrap();
+    }
+}
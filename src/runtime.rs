@@ -0,0 +1,115 @@
+//! The async runtime shared by the long running subsystems, e.g. the watcher, its HTTP trigger
+//! endpoint and RCON connections.
+//!
+//! The runtime is started on first use and lives until the process exits. Synchronous code enters
+//! it with [`block_on`], and blocking operations are raced against the shutdown signal with
+//! [`until_cancelled`], so they can be abandoned when Ctrl-C is pressed or SIGTERM is received.
+
+use std::{future::Future, panic, sync::OnceLock, thread};
+
+use tokio::{
+    runtime::{Builder, Handle, Runtime, RuntimeFlavor},
+    task,
+};
+
+use crate::{cancel::CancellationToken, error::Error};
+
+/// The shared multi-threaded runtime.
+pub fn handle() -> Handle {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME
+        .get_or_init(|| {
+            Builder::new_multi_thread()
+                .thread_name("shulkerscript-runtime")
+                .enable_all()
+                .build()
+                .expect("Failed to start the async runtime")
+        })
+        .handle()
+        .clone()
+}
+
+/// Run the future to completion, blocking the current thread.
+///
+/// May also be called from tasks and blocking threads of the runtime, e.g. by commands run from
+/// the watcher, and from within other runtimes, e.g. of frontends embedding the crate.
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            task::block_in_place(|| handle.block_on(future))
+        }
+        // blocking in place is not possible on a current-thread runtime, the future runs on
+        // another thread instead
+        Ok(_) => thread::scope(|scope| {
+            scope
+                .spawn(|| handle().block_on(future))
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload))
+        }),
+        Err(_) => handle().block_on(future),
+    }
+}
+
+/// Run the blocking function on a thread of the runtime and wait for it, unless the token is
+/// cancelled before it returns.
+///
+/// The function keeps running in the background after the cancellation, so it must not hold
+/// resources that need to be released before the process exits.
+///
+/// # Errors
+/// - [`Error::CancelledError`] if the token is cancelled first.
+pub fn until_cancelled<T, F>(cancel: &CancellationToken, function: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    cancel.check()?;
+    block_on(async {
+        tokio::select! {
+            result = task::spawn_blocking(function) => {
+                result.map_err(|err| match err.try_into_panic() {
+                    Ok(panic) => std::panic::resume_unwind(panic),
+                    Err(_) => Error::CancelledError,
+                })
+            }
+            () = cancel.cancelled() => Err(Error::CancelledError),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn test_until_cancelled() {
+        let cancel = CancellationToken::new();
+        assert_eq!(until_cancelled(&cancel, || 42).unwrap(), 42);
+
+        let canceller = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+        });
+        let result = until_cancelled(&cancel, || thread::sleep(Duration::from_secs(10)));
+        assert!(matches!(result, Err(Error::CancelledError)));
+    }
+
+    #[test]
+    fn test_block_on_in_runtime() {
+        let current_thread = Builder::new_current_thread().enable_all().build().unwrap();
+        assert_eq!(
+            current_thread.block_on(async { block_on(async { 42 }) }),
+            42
+        );
+
+        let multi_thread = Builder::new_multi_thread().enable_all().build().unwrap();
+        assert_eq!(multi_thread.block_on(async { block_on(async { 42 }) }), 42);
+    }
+}
@@ -0,0 +1,256 @@
+//! Build statistics recorded locally in the project.
+//!
+//! Recording is opt-in with the `record_stats` field of the `[compiler]` section. Every build
+//! appends a line to `.shulkerscript/stats.jsonl` in the project root, nothing ever leaves the
+//! machine. The `.shulkerscript` directory ignores itself, so the statistics are not committed.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    history::{self, BuildRecord},
+    terminal_output::{print_warning, Transcript},
+};
+
+/// The directory of local, uncommitted state in the project root.
+pub const STATE_DIR: &str = ".shulkerscript";
+/// The file the statistics are appended to, relative to the project root.
+pub const STATS_FILE: &str = ".shulkerscript/stats.jsonl";
+
+/// The statistics of a single build.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildStats {
+    /// The end of the build in seconds since the unix epoch.
+    pub timestamp: u64,
+    /// The duration of the build in milliseconds.
+    pub duration_ms: u64,
+    /// Whether the build succeeded.
+    pub success: bool,
+    /// The number of compiled scripts, including the ones of dependencies.
+    #[serde(default)]
+    pub scripts: usize,
+    /// The number of files in the output.
+    #[serde(default)]
+    pub output_files: usize,
+    /// The total size of the files in the output in bytes.
+    #[serde(default)]
+    pub output_bytes: usize,
+    /// The number of git dependencies read from the cache.
+    #[serde(default)]
+    pub cache_hits: usize,
+    /// The number of git dependencies fetched because they were not cached.
+    #[serde(default)]
+    pub cache_misses: usize,
+}
+
+impl BuildStats {
+    /// The duration of the build.
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+/// Collects the statistics of a build and records them if enabled.
+#[derive(Debug)]
+pub struct Recorder {
+    start: Instant,
+    project_root: Option<PathBuf>,
+    /// The project root and the number of builds kept in its history.
+    history: Option<(PathBuf, usize)>,
+    /// The statistics collected so far.
+    pub stats: BuildStats,
+}
+
+impl Recorder {
+    /// Start measuring a build, without recording it until enabled.
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            project_root: None,
+            history: None,
+            stats: BuildStats::default(),
+        }
+    }
+
+    /// Record the build to the statistics of the project.
+    pub fn enable(&mut self, project_root: &Path) {
+        self.project_root = Some(project_root.to_path_buf());
+    }
+
+    /// Keep the build in the history of the project, with at most `limit` builds.
+    pub fn keep_history(&mut self, project_root: &Path, limit: usize) {
+        self.history = Some((project_root.to_path_buf(), limit));
+    }
+
+    /// Finish measuring and record the build with its artifact or error and the printed
+    /// messages if enabled.
+    ///
+    /// Failing to record is only reported as warning, as it must not fail the build.
+    pub fn finish(mut self, result: Result<&Path, String>, transcript: Transcript) {
+        self.stats.success = result.is_ok();
+        self.stats.duration_ms = self.start.elapsed().as_millis() as u64;
+        self.stats.timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+
+        if let Some(project_root) = &self.project_root {
+            if let Err(err) = append(project_root, &self.stats) {
+                print_warning(format!("Could not record the build statistics: {err}"));
+            }
+        }
+        if let Some((project_root, limit)) = &self.history {
+            let record = BuildRecord::new(self.stats, result, transcript);
+            if let Err(err) = history::save(project_root, &record, *limit) {
+                print_warning(format!("Could not save the build to the history: {err}"));
+            }
+        }
+    }
+}
+
+/// Append the statistics of a build to the statistics file of the project.
+///
+/// # Errors
+/// - If the statistics file cannot be written.
+pub fn append(project_root: &Path, stats: &BuildStats) -> io::Result<()> {
+    create_state_dir(project_root)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(project_root.join(STATS_FILE))?;
+    writeln!(file, "{}", serde_json::to_string(stats)?)
+}
+
+/// Create the state directory in the project root if it does not exist, ignoring itself in git.
+///
+/// # Errors
+/// - If the directory cannot be created.
+pub fn create_state_dir(project_root: &Path) -> io::Result<()> {
+    let state_dir = project_root.join(STATE_DIR);
+    if !state_dir.is_dir() {
+        fs::create_dir_all(&state_dir)?;
+        fs::write(state_dir.join(".gitignore"), "*\n")?;
+    }
+    Ok(())
+}
+
+/// Load the recorded statistics of the project, oldest first.
+///
+/// Lines that cannot be parsed, e.g. of an interrupted write, are skipped with a warning.
+///
+/// # Errors
+/// - If the statistics file exists but cannot be read.
+pub fn load(project_root: &Path) -> io::Result<Vec<BuildStats>> {
+    let path = project_root.join(STATS_FILE);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| {
+            serde_json::from_str(line)
+                .inspect_err(|err| {
+                    print_warning(format!(
+                        "Skipping line {} of {}: {err}",
+                        index + 1,
+                        path.display()
+                    ));
+                })
+                .ok()
+        })
+        .collect())
+}
+
+/// Summary statistics of durations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics {
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub median: Duration,
+    pub std_dev: Duration,
+}
+
+impl Statistics {
+    /// Summarize the durations, returns `None` if there are none.
+    pub fn new(durations: impl IntoIterator<Item = Duration>) -> Option<Self> {
+        let mut sorted = durations.into_iter().collect::<Vec<_>>();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort();
+
+        let count = sorted.len() as f64;
+        let mean = sorted.iter().map(Duration::as_secs_f64).sum::<f64>() / count;
+        let variance = sorted
+            .iter()
+            .map(|duration| (duration.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+
+        Some(Self {
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean: Duration::from_secs_f64(mean),
+            median,
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_statistics() {
+        let durations = [6, 2, 1, 3].map(Duration::from_millis);
+        let statistics = Statistics::new(durations).unwrap();
+        assert_eq!(statistics.min, Duration::from_millis(1));
+        assert_eq!(statistics.max, Duration::from_millis(6));
+        assert_eq!(statistics.median, Duration::from_micros(2500));
+        assert_eq!(statistics.mean.as_millis(), 3);
+        assert!((statistics.std_dev.as_secs_f64() - 0.001_870_8).abs() < 1e-6);
+        assert!(Statistics::new([]).is_none());
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let temp = TestProject::empty("stats");
+        let dir = temp.path();
+
+        let stats = BuildStats {
+            timestamp: 1,
+            duration_ms: 20,
+            success: true,
+            scripts: 3,
+            ..Default::default()
+        };
+        append(dir, &stats).unwrap();
+        fs::write(
+            dir.join(STATS_FILE),
+            fs::read_to_string(dir.join(STATS_FILE)).unwrap() + "{\"timestamp\":\n",
+        )
+        .unwrap();
+        append(dir, &stats).unwrap();
+
+        assert_eq!(load(dir).unwrap(), [stats.clone(), stats]);
+        assert!(dir.join(STATE_DIR).join(".gitignore").is_file());
+    }
+}
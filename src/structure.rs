@@ -0,0 +1,149 @@
+//! Structure files of the `structures` source directory of a project.
+//!
+//! `.nbt` files are validated and `.snbt` files are converted to compressed NBT, before both are
+//! placed in the structure directory of the namespace of the pack.
+
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+use crate::{
+    layout,
+    nbt::{self, Tag},
+};
+
+/// The largest size of a structure that can be saved and loaded with structure blocks.
+pub const MAX_STRUCTURE_BLOCK_SIZE: i64 = 48;
+
+/// Convert the files of the structures directory into the structure files of the datapack.
+///
+/// Returns the datapack files and warnings about corrupt, invalid or oversized structures.
+/// Corrupt files are skipped.
+pub fn convert(structures: &VFolder, namespace: &str, pack_format: u8) -> (VFolder, Vec<String>) {
+    let directory = layout::directory("structure", pack_format);
+
+    let mut output = VFolder::new();
+    let mut warnings = Vec::new();
+    let mut files = structures.flatten();
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (path, file) in files {
+        let (name, tag) = if let Some(name) = path.strip_suffix(".snbt") {
+            let parsed = std::str::from_utf8(file.as_bytes())
+                .map_err(|err| err.to_string())
+                .and_then(nbt::parse_snbt);
+            match parsed {
+                Ok(tag) => (name, tag),
+                Err(err) => {
+                    warnings.push(format!("Skipping corrupt structure {path}: {err}"));
+                    continue;
+                }
+            }
+        } else if let Some(name) = path.strip_suffix(".nbt") {
+            match nbt::read(file.as_bytes()) {
+                Ok((_, tag)) => (name, tag),
+                Err(err) => {
+                    warnings.push(format!("Skipping corrupt structure {path}: {err}"));
+                    continue;
+                }
+            }
+        } else {
+            warnings.push(format!(
+                "Ignoring {path} in the structures directory, only .nbt and .snbt files are supported"
+            ));
+            continue;
+        };
+
+        warnings.extend(
+            validate(&tag)
+                .into_iter()
+                .map(|issue| format!("Structure {path} {issue}")),
+        );
+
+        let file = if path.ends_with(".snbt") {
+            match nbt::write_compressed("", &tag) {
+                Ok(data) => VFile::Binary(data),
+                Err(err) => {
+                    warnings.push(format!("Skipping structure {path}: {err}"));
+                    continue;
+                }
+            }
+        } else {
+            file.clone()
+        };
+        output.add_file(&format!("data/{namespace}/{directory}/{name}.nbt"), file);
+    }
+
+    (output, warnings)
+}
+
+/// Check the structure for missing fields and a size exceeding the limit of structure blocks.
+fn validate(tag: &Tag) -> Vec<String> {
+    let mut issues = Vec::new();
+    if !matches!(tag, Tag::Compound(_)) {
+        issues.push("is not a compound".to_string());
+        return issues;
+    }
+
+    match tag
+        .get("size")
+        .and_then(Tag::as_list)
+        .map(|size| size.iter().map(Tag::as_i64).collect::<Option<Vec<_>>>())
+    {
+        Some(Some(size)) if size.len() == 3 => {
+            if size
+                .iter()
+                .any(|dimension| *dimension > MAX_STRUCTURE_BLOCK_SIZE)
+            {
+                issues.push(format!(
+                    "has a size of {}x{}x{}, which exceeds the limit of {MAX_STRUCTURE_BLOCK_SIZE} blocks of structure blocks",
+                    size[0], size[1], size[2]
+                ));
+            }
+        }
+        _ => issues.push("has no valid `size` of three integers".to_string()),
+    }
+
+    if tag.get("blocks").and_then(Tag::as_list).is_none() {
+        issues.push("has no `blocks` list".to_string());
+    }
+    if tag.get("palette").or_else(|| tag.get("palettes")).is_none() {
+        issues.push("has no `palette` or `palettes` list".to_string());
+    }
+    if tag.get("DataVersion").and_then(Tag::as_i64).is_none() {
+        issues.push(
+            "has no `DataVersion`, so Minecraft treats it as data of a very old version"
+                .to_string(),
+        );
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let mut structures = VFolder::new();
+        structures.add_file(
+            "house.snbt",
+            VFile::Text(
+                "{DataVersion:3953,size:[64,2,1],blocks:[],palette:[],entities:[]}".to_string(),
+            ),
+        );
+        structures.add_file("broken.nbt", VFile::Binary(vec![1, 2, 3]));
+
+        let (output, warnings) = convert(&structures, "test", 48);
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("Skipping corrupt structure broken.nbt"));
+        assert!(warnings[1].contains("64x2x1"));
+        let house = output
+            .get_file("data/test/structure/house.nbt")
+            .map(|file| nbt::read(file.as_bytes()).unwrap().1);
+        assert_eq!(
+            house.as_ref().and_then(|tag| tag.get("DataVersion")),
+            Some(&Tag::Int(3953))
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use std::{env, fs, path::PathBuf, time::Instant};
+
+use anyhow::Result;
+
+use crate::{
+    cancel,
+    cli::GlobalOptions,
+    stats::Statistics,
+    terminal_output::{self, print_error, print_info, print_success, Level},
+    units::format_duration,
+    util,
+};
+
+use super::BuildArgs;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct BenchProjectArgs {
+    /// The path of the project to benchmark.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// The number of measured builds.
+    #[arg(short, long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    pub runs: u32,
+    /// The number of builds before the measured ones, e.g. to warm up the file system cache.
+    #[arg(short, long, default_value_t = 1)]
+    pub warmup: u32,
+}
+
+pub fn bench_project(args: &BenchProjectArgs, options: &GlobalOptions) -> Result<()> {
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+    let output = env::temp_dir().join(format!("shulkerscript-bench-{}", std::process::id()));
+    let build_args = BuildArgs {
+        path,
+        output: Some(output.clone()),
+        ..Default::default()
+    };
+
+    cancel::install_shutdown_handler();
+    let result = (0..args.warmup + args.runs)
+        .map(|run| {
+            cancel::global().check()?;
+            let start = Instant::now();
+            let (result, messages) = terminal_output::capture(|| {
+                super::build::build_artifact(&build_args, options, cancel::global())
+            });
+            let duration = start.elapsed();
+            if let Err(err) = result {
+                for message in messages.iter().filter(|msg| msg.level == Level::Error) {
+                    print_error(&message.text);
+                }
+                return Err(err);
+            }
+            if run >= args.warmup {
+                print_info(format!(
+                    "Run {}/{}: {}",
+                    run - args.warmup + 1,
+                    args.runs,
+                    format_duration(duration)
+                ));
+            }
+            Ok(duration)
+        })
+        .collect::<Result<Vec<_>>>();
+    let _ = fs::remove_dir_all(&output);
+    let durations = result?.split_off(args.warmup as usize);
+
+    let statistics = Statistics::new(durations).expect("at least one run is measured");
+    print_success(format!(
+        "Built the project {} times: mean {} ± {}, median {}, min {}, max {}",
+        args.runs,
+        format_duration(statistics.mean),
+        format_duration(statistics.std_dev),
+        format_duration(statistics.median),
+        format_duration(statistics.min),
+        format_duration(statistics.max),
+    ));
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use anyhow::Result;
+use colored::Colorize;
 use path_absolutize::Absolutize;
+use serde::Serialize;
 use shulkerscript::{
-    base::{FsProvider, PrintHandler},
+    base::FileProvider,
     shulkerbox::{
         util::compile::CompileOptions,
         virtual_fs::{VFile, VFolder},
@@ -9,18 +11,49 @@ use shulkerscript::{
 };
 
 use crate::{
-    config::ProjectConfig,
+    analysis::{self, FunctionTagIssue},
+    cancel::{self, CancellationToken},
+    cli::GlobalOptions,
+    config::{CompilerConfig, LangConfig, PackConfig, ProjectConfig, UnstableFormat},
+    data_files,
+    dependency::{self, PackScripts, ResolvedDependency, ScriptProvider},
+    deploy::{self, DeployTarget},
+    diagnostics::{CollectingHandler, DiagnosticsCache, OutputHandler},
     error::Error,
-    terminal_output::{print_error, print_info, print_success, print_warning},
-    util,
+    fetch, generate,
+    lang::Translations,
+    layout,
+    lints::{self, Lint, Lints},
+    lock::PathLock,
+    mcdata,
+    obfuscation::{self, MappingEntry},
+    optimize::{self, OptimizeOptions},
+    overlay,
+    plugin::PluginHost,
+    profiling, prompt,
+    stats::Recorder,
+    structure, sync,
+    terminal_output::{
+        self, print_error, print_file_diagnostics, print_info, print_plain, print_success,
+        print_warning, Progress,
+    },
+    units, upgrade, util,
+    validation::{self, validate_resource_locations, Origin, Origins, ScriptIndex},
+    vcs::{self, GitInfo},
+    versions,
+    walk::{self, StreamedFiles, SymlinkPolicy, WalkOptions},
+    workspace::{MemberOutcome, Workspace},
+    worldgen,
 };
 use std::{
     borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
     fs,
     path::{Path, PathBuf},
+    time::{Instant, SystemTime},
 };
 
-#[derive(Debug, clap::Args, Clone)]
+#[derive(Debug, clap::Args, Clone, Default)]
 pub struct BuildArgs {
     /// The path of the project to build.
     #[arg(default_value = ".")]
@@ -39,26 +72,358 @@ pub struct BuildArgs {
     /// Package the project to a zip file.
     #[arg(short, long)]
     pub zip: bool,
-    /// Skip validating the project for pack format compatibility.
+    /// Skip validating the project for pack format compatibility and valid resource locations.
     #[arg(long)]
     pub no_validate: bool,
+    /// Build a release artifact.
+    ///
+    /// Warns if the project is built from a git working tree with uncommitted changes
+    /// or from a commit that is not tagged.
+    #[arg(long)]
+    pub release: bool,
+    /// Refuse to use dependencies whose content is not pinned.
+    ///
+    /// Enabled automatically if the `CI` environment variable is set.
+    #[arg(long)]
+    pub locked: bool,
     /// Check if the project can be built without actually building it.
+    ///
+    /// Reports the errors of all scripts, reusing the cached diagnostics of unchanged scripts.
     #[arg(long, conflicts_with_all = ["output", "zip"])]
     pub check: bool,
+    /// Only check the scripts added or modified since the git revision, including uncommitted
+    /// and untracked ones, e.g. in pre-commit hooks.
+    ///
+    /// Defaults to `HEAD` if no revision is given.
+    #[arg(
+        long,
+        value_name = "REF",
+        num_args = 0..=1,
+        default_missing_value = "HEAD",
+        requires = "check",
+        conflicts_with = "workspace"
+    )]
+    pub changed: Option<String>,
+    /// Parse all scripts first and report the syntax errors of every script and a summary
+    /// instead of stopping at the first script with syntax errors.
+    ///
+    /// Semantic errors are reported by the compilation afterwards, which stops at the first one.
+    #[arg(long)]
+    pub all_syntax_errors: bool,
+    /// Build all members of the workspace containing the path.
+    #[arg(long)]
+    pub workspace: bool,
+    /// The number of workspace members to build in parallel.
+    ///
+    /// Defaults to the number of available CPU cores.
+    #[arg(short, long, requires = "workspace")]
+    pub jobs: Option<usize>,
+    /// Build the project of a git repository instead of a local one.
+    ///
+    /// Accepts git URLs and the GitHub shorthands `gh:owner/repo` and `owner/repo`.
+    /// The path is interpreted relative to the root of the repository and the output is placed in
+    /// the `dist` folder of the current directory unless specified otherwise.
+    #[arg(long, value_name = "SOURCE")]
+    pub remote: Option<String>,
+    /// The branch, tag or commit of the remote repository to build.
+    #[arg(long = "ref", value_name = "REF", requires = "remote")]
+    pub reference: Option<String>,
+    /// Write a `<artifact>.pack-info.json` file with metadata about the build next to the artifact.
+    ///
+    /// Contains the name, version, pack format, supported Minecraft versions, SHA-256 hash,
+    /// build time and git hash of the build.
+    #[arg(long, conflicts_with = "check")]
+    pub pack_info: bool,
+    /// Strip comments and empty lines from the emitted `.mcfunction` files.
+    ///
+    /// Can also be enabled with the `strip_comments` field in the `compiler` section of the
+    /// pack.toml file.
+    #[arg(long)]
+    pub strip_comments: bool,
+    /// Merge trivially chained generated functions and collapse generated functions with
+    /// identical bodies.
+    ///
+    /// Can also be enabled with the `optimize` field in the `compiler` section of the pack.toml
+    /// file.
+    #[arg(long)]
+    pub optimize: bool,
+    /// Give generated functions readable names (`shu/<script>/<function>`) instead of hashes.
+    ///
+    /// Useful for debug builds. Can also be enabled with the `deobfuscate_all` field in the
+    /// `compiler` section of the pack.toml file.
+    #[arg(long)]
+    pub deobfuscate_all: bool,
+    /// Write a `<artifact>.mapping.json` file linking the generated function names to the
+    /// functions in the scripts next to the artifact.
+    ///
+    /// Can also be enabled with the `mapping` field in the `compiler` section of the pack.toml
+    /// file.
+    #[arg(long, conflicts_with = "check")]
+    pub mapping: bool,
+    /// Add scoreboard-based instrumentation counting the calls and commands of every function.
+    ///
+    /// Generates a `<namespace>:profile` function printing the collected numbers and a
+    /// `<namespace>:profile/reset` function starting a new measurement. Build again without this
+    /// option for releases.
+    #[arg(long, conflicts_with = "release")]
+    pub profile_instrumentation: bool,
+    /// Run the whole build in memory and list the files that would be written with their sizes,
+    /// grouped by namespace, without writing anything.
+    #[arg(long, conflicts_with = "check")]
+    pub dry_run: bool,
+    /// Build a folder of scripts without a pack.toml file.
+    ///
+    /// The scripts are read from the folder itself and the pack name is derived from the name of
+    /// the folder.
+    #[arg(long, conflicts_with_all = ["workspace", "remote"])]
+    pub no_manifest: bool,
+    /// The pack format of a build with `--no-manifest`.
+    ///
+    /// Defaults to the newest pack format.
+    #[arg(long, value_name = "FORMAT", requires = "no_manifest")]
+    pub format: Option<u8>,
+    /// Build for the pack format of a snapshot, e.g. `61.1`, overriding the `unstable_format`
+    /// field of the pack.toml file.
+    ///
+    /// Experimental: the major format is used for the validation.
+    #[arg(long, value_name = "FORMAT", conflicts_with = "format")]
+    pub unstable_format: Option<UnstableFormat>,
+    /// Fail on symlinks in the source, assets and structures folders instead of following them.
+    #[arg(long)]
+    pub refuse_symlinks: bool,
+    /// Replace the invalid characters of file names that are not valid UTF-8 with `_` instead of
+    /// failing.
+    #[arg(long)]
+    pub lossy_file_names: bool,
+    /// Offer to raise the pack format in the pack.toml file to the minimum format compatible with
+    /// the output if it is incompatible, and build again.
+    #[arg(long, conflicts_with_all = ["no_validate", "no_manifest"])]
+    pub auto_format: bool,
+    /// Only write the files that changed since the previous build and delete the removed ones,
+    /// instead of replacing the output folder.
+    ///
+    /// Reduces the writes and file watcher events when the output folder is inside a world.
+    #[arg(long, conflicts_with = "zip")]
+    pub sync: bool,
+    /// Load the plugins of the `[[plugins]]` section of the pack.toml file.
+    ///
+    /// Plugins are native code running with the permissions of the cli, so they are only loaded
+    /// when allowed explicitly and never for remote repositories.
+    #[arg(long, conflicts_with = "remote")]
+    pub allow_plugins: bool,
+}
+
+/// Metadata about a built artifact, written with `--pack-info` or embedded in zip files.
+#[derive(Debug, Serialize)]
+struct PackInfo<'a> {
+    name: &'a str,
+    version: String,
+    pack_format: u8,
+    minecraft_versions: Option<&'static str>,
+    /// The hash of the artifact, unknown for the metadata embedded in it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    built_at: String,
+    git_hash: Option<&'a str>,
+}
+
+impl<'a> PackInfo<'a> {
+    fn new(config: &'a ProjectConfig, git: Option<&'a GitInfo>, sha256: Option<String>) -> Self {
+        Self {
+            name: &config.pack.name,
+            version: stamped_version(config, git),
+            pack_format: config.pack.pack_format,
+            minecraft_versions: versions::pack_format(config.pack.pack_format)
+                .map(|entry| entry.versions),
+            sha256,
+            built_at: util::format_timestamp(SystemTime::now()),
+            git_hash: git.map(|git| git.hash.as_str()),
+        }
+    }
+}
+
+pub fn build(args: &BuildArgs, options: &GlobalOptions) -> Result<()> {
+    cancel::install_shutdown_handler();
+
+    let result = if let Some(remote) = &args.remote {
+        build_remote(remote, args, options)
+    } else if args.workspace {
+        build_workspace(args, options)
+    } else {
+        build_artifact(args, options, cancel::global()).map(|_| ())
+    };
+
+    if result
+        .as_ref()
+        .is_err_and(|err| matches!(err.downcast_ref(), Some(Error::CancelledError)))
+    {
+        print_warning("The build was cancelled, the previous output was kept.");
+    }
+    result
+}
+
+/// Fetch the remote repository into the cache and build the project in it.
+fn build_remote(remote: &str, args: &BuildArgs, options: &GlobalOptions) -> Result<()> {
+    let url = fetch::resolve_git_url(remote);
+    let checkout = fetch::git_cache_path(&url, args.reference.as_deref());
+
+    print_info(format!("Fetching {url}"));
+    fetch::fetch_git(&url, args.reference.as_deref(), &checkout)
+        .inspect_err(|err| print_error(format!("Could not fetch {url}: {err}")))?;
+
+    let output = match &args.output {
+        Some(output) => output.absolutize()?.into_owned(),
+        None => Path::new("dist").absolutize()?.into_owned(),
+    };
+    let remote_args = BuildArgs {
+        path: checkout.join(&args.path),
+        output: Some(output),
+        remote: None,
+        reference: None,
+        allow_plugins: false,
+        ..args.clone()
+    };
+
+    if args.workspace {
+        build_workspace(&remote_args, options)
+    } else {
+        build_artifact(&remote_args, options, cancel::global()).map(|_| ())
+    }
+}
+
+/// Build all members of the workspace in parallel and print a summary.
+fn build_workspace(args: &BuildArgs, options: &GlobalOptions) -> Result<()> {
+    let workspace = Workspace::discover(&args.path, options)?;
+
+    print_info(format!(
+        "Building workspace at {} with {} members",
+        workspace.root.display(),
+        workspace.members.len()
+    ));
+
+    build_members(&workspace, args, options, cancel::global())
+}
+
+/// Build the members of the workspace in parallel and print a summary with the status of each member.
+pub(super) fn build_members(
+    workspace: &Workspace,
+    args: &BuildArgs,
+    options: &GlobalOptions,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let jobs = args.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1)
+    });
+
+    let start = Instant::now();
+    let outcomes = workspace.run_parallel(jobs, |member| {
+        build_artifact(
+            &BuildArgs {
+                path: member.path.clone(),
+                workspace: false,
+                jobs: None,
+                ..args.clone()
+            },
+            options,
+            cancel,
+        )
+    });
+
+    print_info("Workspace summary:");
+    let mut failed = 0;
+    for (member, outcome) in workspace.members.iter().zip(&outcomes) {
+        match outcome {
+            MemberOutcome::Finished(artifact, duration) => print_success(format!(
+                "{} built in {} to {}",
+                member.name(),
+                units::format_duration(*duration),
+                artifact.display()
+            )),
+            MemberOutcome::Failed(_, duration) => {
+                failed += 1;
+                print_error(format!(
+                    "{} failed after {}",
+                    member.name(),
+                    units::format_duration(*duration)
+                ));
+            }
+            MemberOutcome::Skipped(dependency) => {
+                failed += 1;
+                print_warning(format!(
+                    "{} skipped because its dependency {dependency} was not built",
+                    member.name()
+                ));
+            }
+        }
+    }
+
+    if failed > 0 {
+        print_error(format!(
+            "{failed} of {} workspace members were not built.",
+            workspace.members.len()
+        ));
+        return Err(anyhow::anyhow!("Building the workspace failed."));
+    }
+
+    print_success(format!(
+        "Finished building {} workspace members in {}",
+        workspace.members.len(),
+        units::format_duration(start.elapsed())
+    ));
+
+    Ok(())
+}
+
+/// Build the project and return the path of the output folder/zip file.
+///
+/// The token is checked between the stages of the build. The output is written next to the
+/// previous artifact and only replaces it once complete, so a cancelled build keeps the previous
+/// artifact.
+pub(crate) fn build_artifact(
+    args: &BuildArgs,
+    options: &GlobalOptions,
+    cancel: &CancellationToken,
+) -> Result<PathBuf> {
+    let mut recorder = Recorder::start();
+    let progress = Progress::begin(format!("Building {}", args.path.display()));
+    let (result, transcript) =
+        terminal_output::tee(|| build_pack(args, options, cancel, &mut recorder, &progress));
+    recorder.finish(
+        result.as_deref().map_err(|err| format!("{err:#}")),
+        transcript,
+    );
+    if let Ok(artifact) = &result {
+        progress.end(format!("Built {}", artifact.display()));
+    }
+    result
 }
 
-pub fn build(args: &BuildArgs) -> Result<()> {
+/// Build the project, collecting the statistics of the build in the recorder.
+fn build_pack(
+    args: &BuildArgs,
+    options: &GlobalOptions,
+    cancel: &CancellationToken,
+    recorder: &mut Recorder,
+    progress: &Progress,
+) -> Result<PathBuf> {
     if args.zip && !cfg!(feature = "zip") {
         print_error("The zip feature is not enabled. Please install with the `zip` feature enabled to use the `--zip` option.");
         return Err(Error::FeatureNotEnabledError("zip".to_string()).into());
     }
 
-    let path = util::get_project_path(&args.path).unwrap_or(args.path.clone());
-    let dist_path = args
-        .output
-        .as_ref()
-        .map(Cow::Borrowed)
-        .unwrap_or_else(|| Cow::Owned(path.join("dist")));
+    let path = if args.no_manifest {
+        args.path.clone()
+    } else {
+        util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone())
+    };
+    let deploy_target = args.output.as_deref().and_then(DeployTarget::detect);
+    let dist_path = match (&deploy_target, &args.output) {
+        (Some(target), _) => Cow::Owned(target.datapacks_dir()),
+        (None, Some(output)) => Cow::Borrowed(output),
+        (None, None) => Cow::Owned(path.join("dist")),
+    };
 
     let and_package_msg = if args.zip { " and packaging" } else { "" };
 
@@ -71,32 +436,196 @@ pub fn build(args: &BuildArgs) -> Result<()> {
         "Building{and_package_msg} project at {path_display}"
     ));
 
-    let (project_config, toml_path) = get_pack_config(&path)?;
+    let (mut project_config, toml_path) = if args.no_manifest {
+        manifestless_config(&path, args.format)?
+    } else {
+        get_pack_config(&path, options.strict_config)?
+    };
+    if let Some(format) = args.unstable_format {
+        project_config.pack.set_unstable_format(format);
+    }
+    if let Some(format) = project_config.pack.unstable_format {
+        print_warning(format!(
+            "Building for the unstable pack format {format}, the output is only validated against the pack format {}.",
+            format.major
+        ));
+    }
 
-    let script_paths = get_script_paths(
-        &toml_path
-            .parent()
-            .ok_or(Error::InvalidPackPathError(path.to_path_buf()))?
-            .join("src"),
+    let namespace = project_config.pack.namespace();
+    if !util::is_valid_namespace(&namespace) {
+        print_error(format!(
+            "The namespace \"{namespace}\" of the pack is invalid. Set a valid `namespace` in the pack.toml file."
+        ));
+        return Err(Error::InvalidNamespaceError(namespace.into_owned()).into());
+    }
+
+    check_required_features(&project_config.pack, args.no_validate)?;
+
+    if let Some(target) = &deploy_target {
+        print_info(format!("Deploying to {target}"));
+        check_deploy_target(target, project_config.pack.pack_format, args.no_validate)?;
+    }
+
+    let git_info = GitInfo::discover(&path);
+    if args.release {
+        match &git_info {
+            Some(git) => {
+                if git.dirty {
+                    print_warning("Building a release artifact from a git working tree with uncommitted changes.");
+                }
+                if git.tag.is_none() {
+                    print_warning(format!(
+                        "Building a release artifact from the untagged commit {}.",
+                        git.short_hash()
+                    ));
+                }
+            }
+            None => print_warning("Building a release artifact outside of a git repository."),
+        }
+    }
+
+    let project_root = toml_path
+        .parent()
+        .ok_or(Error::InvalidPackPathError(path.to_path_buf()))?;
+    if !args.check && !args.dry_run {
+        if project_config
+            .compiler
+            .as_ref()
+            .is_some_and(|c| c.record_stats)
+        {
+            recorder.enable(project_root);
+        }
+        recorder.keep_history(
+            project_root,
+            project_config
+                .compiler
+                .as_ref()
+                .and_then(|c| c.history)
+                .unwrap_or(CompilerConfig::DEFAULT_HISTORY),
+        );
+    }
+
+    let source_path = if args.no_manifest {
+        project_root.to_path_buf()
+    } else {
+        project_root.join("src")
+    };
+    let walk_options = WalkOptions {
+        symlinks: if args.refuse_symlinks
+            || project_config
+                .compiler
+                .as_ref()
+                .is_some_and(|c| c.refuse_symlinks)
+        {
+            SymlinkPolicy::Refuse
+        } else {
+            SymlinkPolicy::Follow
+        },
+        lossy_names: args.lossy_file_names,
+    };
+    let mut script_paths = get_script_paths(&source_path, walk_options)?;
+
+    progress.report("Resolving dependencies", 10);
+    let dependencies = dependency::resolve_dependencies(
+        &project_config,
+        project_root,
+        fetch::is_locked(args.locked),
+        options.strict_config,
     )?;
+    let file_provider = merge_dependencies(&project_config, &mut script_paths, &dependencies)?;
+    let lints = Lints::new(project_config.lints.as_ref(), project_root, &script_paths);
+    recorder.stats.scripts = script_paths.len();
+    for fetched in dependencies
+        .iter()
+        .filter_map(|dependency| dependency.fetched)
+    {
+        if fetched {
+            recorder.stats.cache_misses += 1;
+        } else {
+            recorder.stats.cache_hits += 1;
+        }
+    }
+    cancel.check()?;
+
+    if let Some(revision) = &args.changed {
+        let changed = changed_scripts(&script_paths, project_root, revision)?;
+        if changed.is_empty() {
+            print_success(format!("No scripts changed since {revision}."));
+        } else {
+            progress.report(format!("Checking {} changed scripts", changed.len()), 20);
+            let cache_root = (!args.no_manifest).then_some(project_root);
+            check_scripts(&file_provider, &changed, cache_root)?;
+            print_success(format!(
+                "{} scripts changed since {revision} can be compiled.",
+                changed.len()
+            ));
+        }
+        return Ok(dist_path.into_owned());
+    }
 
+    progress.report(format!("Compiling {} scripts", script_paths.len()), 20);
+    if args.all_syntax_errors || args.check {
+        let cache_root = (!args.no_manifest).then_some(project_root);
+        check_scripts(&file_provider, &script_paths, cache_root)?;
+    }
     let datapack = shulkerscript::transpile(
-        &PrintHandler::new(),
-        &FsProvider::default(),
+        &OutputHandler::default(),
+        &file_provider,
         project_config.pack.pack_format,
         &script_paths,
     )?;
 
     if !args.no_validate && !datapack.validate() {
+        let pack_format = project_config.pack.pack_format;
         print_warning(format!(
-            "The datapack is not compatible with the specified pack format: {}",
-            project_config.pack.pack_format
+            "The datapack is not compatible with the specified pack format: {pack_format}"
         ));
+        let min_format =
+            report_format_requirements(&datapack.compile(&CompileOptions::default()), pack_format);
+        if let Some(min_format) = min_format.filter(|_| args.auto_format) {
+            if raise_pack_format(&toml_path, pack_format, min_format)? {
+                return build_pack(args, options, cancel, recorder, progress);
+            }
+        }
         return Err(Error::IncompatiblePackVersionError.into());
     }
 
+    cancel.check()?;
     let mut compiled = datapack.compile(&CompileOptions::default());
+    if let Some(format) = project_config.pack.unstable_format {
+        edit_pack_mcmeta(&mut compiled, |mcmeta| {
+            let format = serde_json::json!([format.major, format.minor]);
+            mcmeta["pack"]["min_format"] = format.clone();
+            mcmeta["pack"]["max_format"] = format;
+        })?;
+    }
+    let required_features = &project_config.pack.required_features;
+    if !required_features.is_empty() {
+        edit_pack_mcmeta(&mut compiled, |mcmeta| {
+            mcmeta["features"] = serde_json::json!({ "enabled": required_features });
+        })?;
+    }
+
+    let compiler_config = project_config.compiler.as_ref();
+    let deobfuscate_all =
+        args.deobfuscate_all || compiler_config.is_some_and(|c| c.deobfuscate_all);
+    let write_mapping = args.mapping || compiler_config.is_some_and(|c| c.mapping);
+    let mut function_mapping = BTreeMap::new();
+    if deobfuscate_all || write_mapping {
+        let functions = obfuscation::hashed_functions(&script_paths, &file_provider)?;
+        let function_ids = optimize::function_ids(&compiled);
+        function_mapping = obfuscation::mapping(&function_ids, &functions);
+        if deobfuscate_all {
+            let names = obfuscation::readable_names(&function_ids, &functions);
+            compiled = optimize::rename_functions(&compiled, &names);
+            function_mapping = function_mapping
+                .into_iter()
+                .map(|(id, entry)| (names.get(&id).cloned().unwrap_or(id), entry))
+                .collect();
+        }
+    }
 
+    progress.report("Merging assets and data files", 50);
     let icon_path = toml_path.parent().unwrap().join("pack.png");
 
     if icon_path.is_file() {
@@ -110,22 +639,32 @@ pub fn build(args: &BuildArgs) -> Result<()> {
         .as_ref()
         .and_then(|c| c.assets.as_ref().map(|p| path.join(p))));
 
-    let output = if let Some(assets_path) = assets_path {
-        let assets = VFolder::try_from(assets_path.as_path());
+    // large assets are copied from disk when placing the output
+    let mut streamed = StreamedFiles::default();
+    // the files without a recorded origin are compiled from the scripts
+    let mut origins = Origins::default();
+    let mut output = if let Some(assets_path) = &assets_path {
+        let assets = walk::read_folder_streamed(assets_path, walk_options);
         if assets.is_err() {
             print_error(format!(
                 "The specified assets path does not exist: {}",
                 assets_path.display()
             ));
         }
-        let mut assets = assets?;
+        let (mut assets, streamed_assets) = assets?;
+        streamed = streamed_assets;
+        origins.record(&assets, |path| Origin::Asset(assets_path.join(path)));
         let replaced = assets.merge(compiled);
+        streamed.replaced(&replaced);
+        origins.forget(&replaced);
 
         for replaced in replaced {
-            print_warning(format!(
-                "Template file {} was replaced by a file in the compiled datapack",
-                replaced
-            ));
+            let replaced = replaced.to_string();
+            lints.warn(
+                Lint::ReplacedFile,
+                Some(&replaced),
+                format!("Template file {replaced} was replaced by a file in the compiled datapack"),
+            );
         }
 
         assets
@@ -133,28 +672,366 @@ pub fn build(args: &BuildArgs) -> Result<()> {
         compiled
     };
 
+    let src_folder = walk::read_folder(&source_path, walk_options)?;
+    let (data_files, problems) =
+        data_files::convert(&src_folder, &namespace, project_config.pack.pack_format);
+    if !problems.is_empty() {
+        for problem in &problems {
+            print_error(problem);
+        }
+        return Err(Error::InvalidDataFileError.into());
+    }
+    origins.record(&data_files, |_| Origin::Asset(source_path.clone()));
+    let replaced = output.merge(data_files);
+    streamed.replaced(&replaced);
+    for replaced in replaced {
+        let replaced = replaced.to_string();
+        lints.warn(
+            Lint::ReplacedFile,
+            Some(&replaced),
+            format!(
+                "File {replaced} was replaced by a recipe or advancement of the source directory"
+            ),
+        );
+    }
+
+    let structures_path = project_root.join("structures");
+    if structures_path.is_dir() {
+        let structures = walk::read_folder(&structures_path, walk_options)?;
+        let (structures, warnings) =
+            structure::convert(&structures, &namespace, project_config.pack.pack_format);
+        for warning in warnings {
+            print_warning(warning);
+        }
+        origins.record(&structures, |_| Origin::Asset(structures_path.clone()));
+        let replaced = output.merge(structures);
+        streamed.replaced(&replaced);
+        for replaced in replaced {
+            let replaced = replaced.to_string();
+            lints.warn(
+                Lint::ReplacedFile,
+                Some(&replaced),
+                format!("File {replaced} was replaced by a structure of the structures directory"),
+            );
+        }
+    }
+
+    if !project_config.generate.is_empty() {
+        let (generated, problems) = generate::generate(
+            &project_config.generate,
+            project_root,
+            &template_values(&project_config, git_info.as_ref()),
+        );
+        if !problems.is_empty() {
+            for problem in &problems {
+                print_error(problem);
+            }
+            return Err(Error::InvalidGeneratorError.into());
+        }
+        origins.record(&generated, |_| Origin::Stage("the [[generate]] section"));
+        let replaced = output.merge(generated);
+        streamed.replaced(&replaced);
+        for replaced in replaced {
+            let replaced = replaced.to_string();
+            lints.warn(
+                Lint::ReplacedFile,
+                Some(&replaced),
+                format!("File {replaced} was replaced by a file of the [[generate]] section"),
+            );
+        }
+    }
+
+    if !project_config.overlays.is_empty() {
+        let (overlays, problems, warnings) =
+            overlay::read(project_root, &project_config.overlays, walk_options)?;
+        for warning in warnings {
+            print_warning(warning);
+        }
+        if !problems.is_empty() {
+            for problem in &problems {
+                print_error(problem);
+            }
+            return Err(Error::InvalidOverlayError.into());
+        }
+        origins.record(&overlays, |_| {
+            Origin::Asset(project_root.join(overlay::SOURCE_DIR))
+        });
+        let replaced = output.merge(overlays);
+        streamed.replaced(&replaced);
+        for replaced in replaced {
+            let replaced = replaced.to_string();
+            lints.warn(
+                Lint::ReplacedFile,
+                Some(&replaced),
+                format!(
+                    "File {replaced} was replaced by a file of the {} directory",
+                    overlay::SOURCE_DIR
+                ),
+            );
+        }
+        edit_pack_mcmeta(&mut output, |mcmeta| {
+            mcmeta["overlays"] = overlay::mcmeta_entries(&project_config.overlays);
+        })?;
+    }
+
+    run_build_script(
+        &mut output,
+        &mut streamed,
+        &mut origins,
+        &project_config,
+        project_root,
+        cancel,
+    )?;
+    let (laid_out, moved) = layout::apply(
+        &output,
+        project_config.pack.pack_format,
+        &project_config.overlays,
+    );
+    if !moved.is_empty() {
+        output = laid_out;
+        streamed.follow_moves(&moved);
+        origins.follow_moves(&moved);
+        print_info(format!(
+            "Placed {} files in the directories named for the targeted pack formats (e.g. `functions` before pack format {})",
+            moved.len(),
+            layout::SINGULAR_FORMAT
+        ));
+    }
+    if !args.no_validate {
+        check_worldgen(&output, project_config.pack.pack_format, &lints)?;
+    }
+
+    progress.report("Processing the output", 70);
+    let mut resource_pack = None;
+    if let Some(lang_config) = &project_config.lang {
+        let (translated, lang_resource_pack) =
+            translate(&output, lang_config, project_root, &namespace, &lints)?;
+        output = translated;
+        resource_pack = lang_resource_pack;
+    }
+
+    cancel.check()?;
+    let optimize_options = OptimizeOptions {
+        strip_comments: args.strip_comments
+            || project_config
+                .compiler
+                .as_ref()
+                .is_some_and(|c| c.strip_comments),
+        merge_chains: args.optimize || project_config.compiler.as_ref().is_some_and(|c| c.optimize),
+        collapse_duplicates: args.optimize
+            || project_config.compiler.as_ref().is_some_and(|c| c.optimize),
+    };
+    if optimize_options.is_enabled() {
+        let (optimized, stats) = optimize::optimize(&output, &optimize_options);
+        output = optimized;
+        print_info(format!(
+            "Optimized output: stripped {} comment lines, merged {} chained and {} duplicate functions, {} bytes saved",
+            stats.stripped_lines,
+            stats.merged_chains,
+            stats.collapsed_duplicates,
+            stats.bytes_before.saturating_sub(stats.bytes_after)
+        ));
+    }
+
+    let mut plugins = PluginHost::from_config(&project_config, project_root, args.allow_plugins)?;
+    if !plugins.is_empty() {
+        cancel.check()?;
+        let before = output
+            .flatten()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<BTreeSet<_>>();
+        plugins.run(&mut output, project_root, &project_config)?;
+        // plugins do not report the files they write
+        streamed.replaced(streamed.overwritten(&output));
+        origins.record_paths(
+            output
+                .flatten()
+                .into_iter()
+                .map(|(path, _)| path)
+                .filter(|path| !before.contains(path)),
+            Origin::Stage("a plugin"),
+        );
+    }
+
+    if let Some(lib) = &project_config.lib {
+        check_exported_namespaces(&output, &lib.exported_namespaces)?;
+    }
+    check_command_budgets(
+        &output,
+        compiler_config,
+        project_config.pack.pack_format,
+        &lints,
+    );
+    audit_resources(&output, &project_config.pack.namespace(), &lints);
+    let script_index = (!args.no_validate).then(|| ScriptIndex::new(&script_paths));
+    if let Some(script_index) = &script_index {
+        check_registry_ids(&output, &project_config, &lints);
+        check_function_tags(&output, assets_path.as_deref(), script_index, &lints);
+    }
+
+    if args.profile_instrumentation {
+        let namespace = project_config.pack.namespace();
+        let (instrumented, count) =
+            profiling::instrument(&output, &namespace, project_config.pack.pack_format);
+        output = instrumented;
+        print_info(format!(
+            "Instrumented {count} functions, run `/function {namespace}:profile` in-game to print the results"
+        ));
+    }
+
+    // validated last, as every stage of the build may add files
+    if let Some(script_index) = &script_index {
+        let issues = validate_resource_locations(&output, |path, resource_location| {
+            origins
+                .get(path)
+                .cloned()
+                .unwrap_or_else(|| script_index.origin_of(path, resource_location))
+        });
+        if !issues.is_empty() {
+            for issue in &issues {
+                print_error(issue);
+            }
+            print_error("Resource locations may only contain the characters a-z, 0-9, _, - and . (and / in paths).");
+            return Err(Error::InvalidResourceLocationError.into());
+        }
+    }
+
+    lints.print_summary();
+
     let dist_extension = if args.zip { ".zip" } else { "" };
 
-    let dist_path = dist_path.join(project_config.pack.name + dist_extension);
+    let dist_path =
+        dist_path.join(artifact_name(&project_config, git_info.as_ref()) + dist_extension);
 
     if args.check {
         print_success("Project is valid and can be built.");
+    } else if args.dry_run {
+        print_planned_writes(&dist_path, &output, &streamed, args.zip);
+        if let Some(resource_pack) = &resource_pack {
+            let resource_pack_path = dist_path.with_file_name(format!(
+                "{}_resources{dist_extension}",
+                artifact_name(&project_config, git_info.as_ref())
+            ));
+            print_planned_writes(
+                &resource_pack_path,
+                resource_pack,
+                &StreamedFiles::default(),
+                args.zip,
+            );
+        }
+        let artifact_name = artifact_name(&project_config, git_info.as_ref());
+        if write_mapping {
+            print_info(format!(
+                "Would write the function mapping to {}",
+                dist_path
+                    .with_file_name(format!("{artifact_name}.mapping.json"))
+                    .display()
+            ));
+        }
+        if args.pack_info {
+            print_info(format!(
+                "Would write the pack info to {}",
+                dist_path
+                    .with_file_name(format!("{artifact_name}.pack-info.json"))
+                    .display()
+            ));
+        }
+        print_success("Dry run finished, nothing was written.");
     } else {
-        #[cfg(feature = "zip")]
-        if args.zip {
-            output.zip_with_comment(
+        progress.report(format!("Writing {}", dist_path.display()), 90);
+        let files = output.flatten();
+        recorder.stats.output_files = files.len();
+        recorder.stats.output_bytes = files
+            .iter()
+            .map(|(path, file)| streamed.size_of(&output, path, file))
+            .sum();
+
+        let dist_dir = dist_path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dist_dir)?;
+        // held until all files of the build are written
+        let _lock = PathLock::acquire(dist_dir, cancel)?;
+
+        cancel.check()?;
+        if args.sync {
+            sync_output(&output, &streamed, &dist_path)?;
+        } else {
+            place_atomically(&dist_path, cancel, |temp_path| {
+                #[cfg(feature = "zip")]
+                if args.zip {
+                    if compiler_config.is_some_and(|c| c.embed_pack_info) {
+                        let info = PackInfo::new(&project_config, git_info.as_ref(), None);
+                        output.add_file(
+                            "META-INF/pack-info.json",
+                            VFile::Text(serde_json::to_string_pretty(&info)? + "\n"),
+                        );
+                    }
+                    // an empty comment is omitted
+                    crate::archive::write_folder(
+                        &output,
+                        &streamed,
+                        temp_path,
+                        &zip_comment(&project_config, git_info.as_ref()),
+                    )?;
+                    return Ok(());
+                }
+
+                output.place(temp_path)?;
+                streamed.copy_into(&output, temp_path)?;
+                Ok(())
+            })?;
+        }
+
+        if let Some(resource_pack) = &resource_pack {
+            let resource_pack_path = dist_path.with_file_name(format!(
+                "{}_resources{dist_extension}",
+                artifact_name(&project_config, git_info.as_ref())
+            ));
+
+            if args.sync {
+                sync_output(
+                    resource_pack,
+                    &StreamedFiles::default(),
+                    &resource_pack_path,
+                )?;
+            } else {
+                place_atomically(&resource_pack_path, cancel, |temp_path| {
+                    #[cfg(feature = "zip")]
+                    if args.zip {
+                        crate::archive::write_folder(
+                            resource_pack,
+                            &StreamedFiles::default(),
+                            temp_path,
+                            &zip_comment(&project_config, git_info.as_ref()),
+                        )?;
+                        return Ok(());
+                    }
+
+                    resource_pack.place(temp_path)?;
+                    Ok(())
+                })?;
+            }
+
+            print_info(format!(
+                "Placed the resource pack with the lang files at {}",
+                resource_pack_path.display()
+            ));
+        }
+
+        if write_mapping {
+            write_function_mapping(
                 &dist_path,
-                format!(
-                    "{} - v{}",
-                    &project_config.pack.description, &project_config.pack.version
-                ),
+                &project_config,
+                git_info.as_ref(),
+                &output,
+                function_mapping,
+                project_root,
             )?;
-        } else {
-            output.place(&dist_path)?;
         }
 
-        #[cfg(not(feature = "zip"))]
-        output.place(&dist_path)?;
+        if args.pack_info {
+            write_pack_info(&dist_path, &project_config, git_info.as_ref())?;
+        }
 
         print_success(format!(
             "Finished building{and_package_msg} project to {}",
@@ -162,49 +1039,928 @@ pub fn build(args: &BuildArgs) -> Result<()> {
         ));
     }
 
+    Ok(dist_path)
+}
+
+/// Run the build script of the project on the output, if it exists.
+fn run_build_script(
+    output: &mut VFolder,
+    streamed: &mut StreamedFiles,
+    origins: &mut Origins,
+    project_config: &ProjectConfig,
+    project_root: &Path,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let configured = project_config
+        .compiler
+        .as_ref()
+        .and_then(|c| c.build_script.as_deref());
+    let script = project_root
+        .join(configured.unwrap_or_else(|| Path::new(CompilerConfig::DEFAULT_BUILD_SCRIPT)));
+    if !script.is_file() {
+        if configured.is_some() {
+            print_error(format!(
+                "The build script {} does not exist.",
+                script.display()
+            ));
+            return Err(Error::PathNotFoundError(script).into());
+        }
+        return Ok(());
+    }
+
+    cancel.check()?;
+    #[cfg(feature = "build-script")]
+    {
+        print_info(format!("Running build script {}...", script.display()));
+        let written =
+            crate::build_script::run(&script, output, project_root, project_config, cancel)?;
+        print_info(format!("The build script wrote {} files", written.len()));
+        streamed.replaced(&written);
+        origins.record_paths(&written, Origin::Stage("the build script"));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "build-script"))]
+    {
+        let _ = (output, streamed, origins);
+        print_error("The build-script feature is not enabled. Please install with the `build-script` feature enabled to run build scripts.");
+        Err(Error::FeatureNotEnabledError("build-script".to_string()).into())
+    }
+}
+
+/// Print the files of the output that would be written to the artifact with their sizes, grouped
+/// by the namespace they belong to.
+fn print_planned_writes(artifact: &Path, output: &VFolder, streamed: &StreamedFiles, zip: bool) {
+    let mut groups: BTreeMap<String, Vec<(String, usize)>> = BTreeMap::new();
+    for (path, file) in output.flatten() {
+        let mut parts = path.splitn(3, '/');
+        let group = match (parts.next(), parts.next(), parts.next()) {
+            (Some("data" | "assets"), Some(namespace), Some(_)) => namespace.to_string(),
+            _ => String::new(),
+        };
+        let size = streamed.size_of(output, &path, file);
+        groups.entry(group).or_default().push((path, size));
+    }
+
+    let kind = if zip {
+        "the zip file (uncompressed sizes)"
+    } else {
+        "the folder"
+    };
+    print_info(format!("Would write {kind} {}:", artifact.display()));
+    for (group, mut files) in groups {
+        files.sort();
+        let size = files.iter().map(|(_, size)| size).sum::<usize>();
+        let group = if group.is_empty() {
+            "pack root".to_string()
+        } else {
+            format!("namespace {group}")
+        };
+        print_plain(format!(
+            "  {} ({} files, {})",
+            group.bold(),
+            units::format_count(files.len()),
+            units::format_size(size as u64)
+        ));
+        for (path, size) in files {
+            print_plain(format!("    {path} ({})", units::format_size(size as u64)));
+        }
+    }
+
+    let (count, size) = output
+        .flatten()
+        .iter()
+        .fold((0, 0), |(count, size), (path, file)| {
+            (count + 1, size + streamed.size_of(output, path, file))
+        });
+    print_info(format!(
+        "{} files with {} in total",
+        units::format_count(count),
+        units::format_size(size as u64)
+    ));
+}
+
+/// Write the changed files of the output to the destination folder and delete the removed ones.
+fn sync_output(output: &VFolder, streamed: &StreamedFiles, destination: &Path) -> Result<()> {
+    if destination.is_file() {
+        remove_artifact(destination)?;
+    }
+    let stats = sync::sync(output, streamed, destination)?;
+    print_info(format!(
+        "Synced {}: {} files written, {} unchanged, {} removed",
+        destination.display(),
+        stats.written,
+        stats.unchanged,
+        stats.removed
+    ));
     Ok(())
 }
 
-/// Recursively get all script paths in a directory.
-pub(super) fn get_script_paths(path: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
-    _get_script_paths(path, "")
-}
-
-fn _get_script_paths(path: &Path, prefix: &str) -> std::io::Result<Vec<(String, PathBuf)>> {
-    if path.exists() && path.is_dir() {
-        let contents = path.read_dir()?;
-
-        let mut paths = Vec::new();
-
-        for entry in contents {
-            let path = entry?.path();
-            if path.is_dir() {
-                let prefix = path
-                    .absolutize()?
-                    .file_name()
-                    .unwrap()
-                    .to_str()
-                    .expect("Invalid folder name")
-                    .to_string()
-                    + "/";
-                paths.extend(_get_script_paths(&path, &prefix)?);
-            } else if path.extension().unwrap_or_default() == "shu" {
-                paths.push((
-                    prefix.to_string()
-                        + path
-                            .file_stem()
-                            .expect("Shulkerscript files are not allowed to have empty names")
-                            .to_str()
-                            .expect("Invalid characters in filename"),
-                    path,
-                ));
+/// Write an artifact to a temporary path next to the destination with `write` and move it into
+/// place afterwards, so an interrupted or cancelled build keeps the previous artifact.
+pub(crate) fn place_atomically(
+    destination: &Path,
+    cancel: &CancellationToken,
+    write: impl FnOnce(&Path) -> Result<()>,
+) -> Result<()> {
+    let file_name = destination
+        .file_name()
+        .ok_or_else(|| Error::InvalidPackPathError(destination.to_path_buf()))?;
+    let temp_path = destination.with_file_name(format!(".{}.partial", file_name.to_string_lossy()));
+    remove_artifact(&temp_path)?;
+
+    if let Err(err) = write(&temp_path).and_then(|()| Ok(cancel.check()?)) {
+        let _ = remove_artifact(&temp_path);
+        return Err(err);
+    }
+
+    remove_artifact(destination)?;
+    fs::rename(&temp_path, destination)?;
+    Ok(())
+}
+
+/// Remove the artifact folder or zip file at the path, if it exists.
+fn remove_artifact(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        Ok(())
+    }
+}
+
+/// Replace the translation placeholders in the functions with text components and build the
+/// resource pack with the lang files if one is configured.
+fn translate(
+    output: &VFolder,
+    lang_config: &LangConfig,
+    project_root: &Path,
+    namespace: &str,
+    lints: &Lints,
+) -> Result<(VFolder, Option<VFolder>)> {
+    let source = project_root.join(
+        lang_config
+            .source
+            .as_deref()
+            .unwrap_or(Path::new(LangConfig::DEFAULT_SOURCE)),
+    );
+    let default = lang_config
+        .default
+        .as_deref()
+        .unwrap_or(LangConfig::DEFAULT_LANGUAGE);
+
+    let translations = Translations::load(&source).inspect_err(|err| {
+        print_error(format!("Failed to load the translations: {err:#}"));
+    })?;
+    if !translations.languages.contains_key(default) {
+        print_warning(format!(
+            "No translations found for the default language {default} in {}.",
+            source.display()
+        ));
+    }
+    for (code, key) in translations.missing_keys(default) {
+        lints.warn(
+            Lint::MissingTranslation,
+            None,
+            format!("The translation key {key} is missing in {code}."),
+        );
+    }
+
+    let resource_pack = match &lang_config.resource_pack {
+        Some(resource_pack_path) => {
+            let resource_pack_path = project_root.join(resource_pack_path);
+            let mut resource_pack =
+                VFolder::try_from(resource_pack_path.as_path()).inspect_err(|_| {
+                    print_error(format!(
+                        "The specified resource pack path does not exist: {}",
+                        resource_pack_path.display()
+                    ));
+                })?;
+            for (code, content) in translations.lang_files() {
+                resource_pack.add_file(
+                    &format!("assets/{namespace}/lang/{code}.json"),
+                    VFile::Text(content),
+                );
+            }
+            Some(resource_pack)
+        }
+        None => None,
+    };
+
+    match translations.replace_placeholders(output, default, resource_pack.is_some()) {
+        Ok((translated, count)) => {
+            if count > 0 {
+                print_info(format!("Replaced {count} translation placeholders"));
+            }
+            Ok((translated, resource_pack))
+        }
+        Err(unknown) => {
+            let unknown = unknown.into_iter().collect::<Vec<_>>().join(", ");
+            print_error(format!(
+                "The translation keys {unknown} are not defined for the default language {default}."
+            ));
+            Err(Error::UnknownTranslationKeyError(unknown).into())
+        }
+    }
+}
+
+/// Get the scripts of the project that were added or modified since the git revision.
+fn changed_scripts(
+    script_paths: &[(String, PathBuf)],
+    project_root: &Path,
+    revision: &str,
+) -> Result<Vec<(String, PathBuf)>> {
+    let changed = vcs::changed_files(project_root, revision).inspect_err(|err| {
+        print_error(format!(
+            "Could not get the files changed since {revision}: {err}"
+        ));
+    })?;
+    let changed = changed
+        .iter()
+        .map(PathBuf::as_path)
+        .collect::<BTreeSet<_>>();
+
+    let mut scripts = Vec::new();
+    for (identifier, path) in script_paths {
+        if changed.contains(path.absolutize()?.as_ref()) {
+            scripts.push((identifier.clone(), path.clone()));
+        }
+    }
+    Ok(scripts)
+}
+
+/// Parse every script on its own, so the errors of all scripts are reported instead of only the
+/// ones of the first failing script, and fail with a summary if any script failed.
+///
+/// The diagnostics are cached in the project root if given, so unchanged scripts are not parsed
+/// again by the next check.
+fn check_scripts(
+    file_provider: &ScriptProvider,
+    script_paths: &[(String, PathBuf)],
+    cache_root: Option<&Path>,
+) -> Result<()> {
+    let mut cache = cache_root.map(DiagnosticsCache::load);
+    let mut reused = 0;
+    let mut keys = Vec::new();
+    let mut failed = Vec::new();
+    for (identifier, path) in script_paths {
+        let key = cache_root
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        // the identifier appears in the diagnostics
+        let content = file_provider
+            .read_bytes(path)
+            .ok()
+            .map(|content| [identifier.as_bytes(), &[0], &content].concat());
+
+        let cached = cache
+            .as_ref()
+            .zip(content.as_ref())
+            .and_then(|(cache, content)| cache.get(&key, content))
+            .map(<[String]>::to_vec);
+        let diagnostics = if let Some(diagnostics) = cached {
+            reused += 1;
+            diagnostics
+        } else {
+            let handler = CollectingHandler::default();
+            let result = shulkerscript::parse(&handler, file_provider, path, identifier.clone());
+            let mut diagnostics = handler.into_diagnostics();
+            if let Err(err) = result {
+                if diagnostics.is_empty() {
+                    diagnostics.push(err.to_string());
+                }
+            }
+            if let Some((cache, content)) = cache.as_mut().zip(content.as_ref()) {
+                cache.insert(key.clone(), content, diagnostics.clone());
+            }
+            diagnostics
+        };
+
+        print_file_diagnostics(path, &diagnostics);
+        if !diagnostics.is_empty() {
+            failed.push(path);
+        }
+        keys.push(key);
+    }
+
+    if let Some((mut cache, root)) = cache.zip(cache_root) {
+        cache.retain(keys.iter().map(String::as_str));
+        if let Err(err) = cache.save(root) {
+            print_warning(format!("Could not cache the diagnostics: {err}"));
+        }
+    }
+    if reused > 0 {
+        print_info(format!(
+            "Reused the cached diagnostics of {reused} unchanged scripts"
+        ));
+    }
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    for path in &failed {
+        print_error(format!("Syntax errors in {}", path.display()));
+    }
+    print_error(format!(
+        "{} of {} scripts contain syntax errors.",
+        failed.len(),
+        script_paths.len()
+    ));
+    Err(Error::ScriptSyntaxError(failed.len()).into())
+}
+
+/// Print the features of the output that require a newer pack format than the one of the pack.
+///
+/// Returns the minimum pack format compatible with the output, `None` if no feature requiring a
+/// newer format is known.
+fn report_format_requirements(output: &VFolder, pack_format: u8) -> Option<u8> {
+    let requirements = validation::format_requirements(output);
+    let min_format = requirements
+        .iter()
+        .map(|requirement| requirement.min_format)
+        .max()
+        .filter(|&min_format| min_format > pack_format);
+    let Some(min_format) = min_format else {
+        print_error(format!(
+            "No features requiring a newer pack format than {pack_format} were found, the pack format may not be supported by the compiler."
+        ));
+        return None;
+    };
+
+    print_error("The output uses features of newer pack formats:");
+    for requirement in requirements
+        .iter()
+        .filter(|requirement| requirement.min_format > pack_format)
+    {
+        print_error(format!("  {requirement}"));
+    }
+    let versions = versions::pack_format(min_format)
+        .map(|format| format!(" (Minecraft {})", format.versions))
+        .unwrap_or_default();
+    print_info(format!(
+        "The minimum compatible pack format is {min_format}{versions}. Set it in the pack.toml file or build with --auto-format."
+    ));
+    Some(min_format)
+}
+
+/// Set the pack format in the pack.toml file after confirmation, returns whether it was changed.
+fn raise_pack_format(toml_path: &Path, pack_format: u8, min_format: u8) -> Result<bool> {
+    let confirmed = prompt::confirm(
+        inquire::Confirm::new(&format!(
+            "Raise the pack format from {pack_format} to {min_format} in {}?",
+            toml_path.display()
+        ))
+        .with_default(true),
+    )
+    .unwrap_or(false);
+    if !confirmed {
+        return Ok(false);
+    }
+
+    let mut document = fs::read_to_string(toml_path)?.parse::<toml_edit::DocumentMut>()?;
+    if upgrade::set_pack_format(&mut document, min_format).is_none() {
+        return Ok(false);
+    }
+    fs::write(toml_path, document.to_string())?;
+    print_success(format!(
+        "Raised the pack format to {min_format}, building again..."
+    ));
+    Ok(true)
+}
+
+/// Check that the required features are available in the pack format of the pack.
+///
+/// Unknown features, e.g. of mods, are only warned about. Unavailable features are only a warning
+/// with `--no-validate`.
+fn check_required_features(pack: &PackConfig, no_validate: bool) -> Result<()> {
+    for feature in &pack.required_features {
+        match versions::feature_flag(feature) {
+            None => print_warning(format!(
+                "The required feature {feature} is unknown, its availability is not checked."
+            )),
+            Some(flag) if !flag.formats.contains(&pack.pack_format) => {
+                let message = format!(
+                    "The required feature {} is not available in the pack format {}.",
+                    flag.name, pack.pack_format
+                );
+                if no_validate {
+                    print_warning(message);
+                } else {
+                    print_error(message);
+                    return Err(Error::UnavailableFeatureError(flag.name.to_string()).into());
+                }
             }
+            Some(_) => {}
         }
+    }
+    Ok(())
+}
+
+/// Edit the pack.mcmeta file of the output, if it exists.
+fn edit_pack_mcmeta(output: &mut VFolder, edit: impl FnOnce(&mut serde_json::Value)) -> Result<()> {
+    let Some(file) = output.get_file_mut("pack.mcmeta") else {
+        return Ok(());
+    };
+    let mut mcmeta: serde_json::Value = serde_json::from_slice(file.as_bytes())?;
+    edit(&mut mcmeta);
+    *file = VFile::Text(serde_json::to_string(&mcmeta)?);
+    Ok(())
+}
+
+/// Check that the Minecraft version of the world or server uses the pack format of the pack.
+///
+/// A mismatch is only a warning with `--no-validate`.
+pub(crate) fn check_deploy_target(
+    target: &DeployTarget,
+    pack_format: u8,
+    no_validate: bool,
+) -> Result<()> {
+    let Some(version) = target.minecraft_version() else {
+        print_warning(format!(
+            "Could not detect the Minecraft version of {target}, the pack format is not checked."
+        ));
+        return Ok(());
+    };
+
+    match deploy::check_compatibility(&version, pack_format) {
+        Ok(()) => Ok(()),
+        Err(mismatch) if no_validate => {
+            print_warning(mismatch);
+            Ok(())
+        }
+        Err(mismatch) => {
+            print_error(mismatch);
+            print_info("Set the `format` field in the [pack] section of the pack.toml file to the format of the version, or use --no-validate to deploy anyway.");
+            Err(Error::IncompatibleTargetError(version).into())
+        }
+    }
+}
+
+/// Report the worldgen files of the output that do not match the pack format, failing on files
+/// that would not load.
+fn check_worldgen(output: &VFolder, pack_format: u8, lints: &Lints) -> Result<()> {
+    let issues = worldgen::validate(output, pack_format);
+    let (errors, warnings) = issues
+        .iter()
+        .partition::<Vec<_>, _>(|issue| issue.is_error());
+    for warning in &warnings {
+        lints.warn(
+            Lint::RemovedWorldgenField,
+            Some(&warning.output_path),
+            warning,
+        );
+    }
+    for error in &errors {
+        print_error(error);
+    }
+    if !issues.is_empty() {
+        print_info(format!(
+            "Worldgen files were checked against the pack format {pack_format}, use --no-validate to skip the check."
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(Error::InvalidWorldgenError(errors.len()).into());
+    }
+    Ok(())
+}
 
-        Ok(paths)
+/// Fail if the output contains namespaces that are not exported by the library.
+fn check_exported_namespaces(output: &VFolder, exported: &[String]) -> Result<()> {
+    let leaks = validation::leaked_namespaces(output, exported);
+    if leaks.is_empty() {
+        return Ok(());
+    }
+
+    for leak in &leaks {
+        print_error(leak);
+    }
+    print_info("Add the namespaces to `exported_namespaces` in the [lib] section of the pack.toml file or move their files to an exported namespace.");
+    Err(Error::NamespaceLeakError.into())
+}
+
+/// Estimate the commands run per tick and on load and warn if they exceed the configured budgets.
+fn check_command_budgets(
+    output: &VFolder,
+    compiler_config: Option<&CompilerConfig>,
+    pack_format: u8,
+    lints: &Lints,
+) {
+    let budgets = [
+        (
+            "minecraft:tick",
+            "per tick",
+            compiler_config
+                .and_then(|c| c.tick_budget)
+                .unwrap_or(CompilerConfig::DEFAULT_TICK_BUDGET),
+        ),
+        (
+            "minecraft:load",
+            "on load",
+            compiler_config
+                .and_then(|c| c.load_budget)
+                .unwrap_or(CompilerConfig::DEFAULT_LOAD_BUDGET),
+        ),
+    ];
+
+    for (tag, when, budget) in budgets {
+        let Some(estimate) = analysis::estimate_commands(output, tag) else {
+            continue;
+        };
+        let bound = if estimate.recursive { "at least " } else { "" };
+        print_info(format!(
+            "Estimated commands run {when}: {bound}{}",
+            estimate.total
+        ));
+        // suppressed if the most expensive function is
+        let most_expensive = estimate
+            .functions
+            .first()
+            .map(|(function, _)| lints::function_path(function, pack_format));
+        if estimate.total > budget
+            && lints.warn(
+                Lint::CommandBudget,
+                most_expensive.as_deref(),
+                format!("The estimated commands run {when} exceed the budget of {budget}. The most expensive functions are:"),
+            )
+        {
+            for (function, count) in estimate.functions.iter().take(5) {
+                print_warning(format!("  {function}: {count}"));
+            }
+        }
+    }
+}
+
+/// Warn about unknown block, item and entity ids if the registries of the Minecraft version of
+/// the pack are cached.
+fn check_registry_ids(output: &VFolder, project_config: &ProjectConfig, lints: &Lints) {
+    let Some(registries) = mcdata::minecraft_version(&project_config.pack)
+        .and_then(|version| mcdata::Registries::load_cached(&version))
+    else {
+        return;
+    };
+    for (function, registry, id) in mcdata::unknown_ids(output, &registries) {
+        lints.warn(
+            Lint::UnknownId,
+            Some(&lints::function_path(
+                &function,
+                project_config.pack.pack_format,
+            )),
+            format!("Unknown {registry} id {id} in function {function}"),
+        );
+    }
+}
+
+/// Print the functions run by the `tick` and `load` function tags and warn about references to
+/// missing functions and function tags and about `tick` and `load` functions that are never run.
+fn check_function_tags(
+    output: &VFolder,
+    assets_path: Option<&Path>,
+    script_index: &ScriptIndex,
+    lints: &Lints,
+) {
+    for tag in ["minecraft:tick", "minecraft:load"] {
+        if let Some(functions) = analysis::tag_functions(output, tag) {
+            print_info(format!("Function tag #{tag}: {}", functions.join(", ")));
+        }
+    }
+
+    // hand-merged tags are usually copied from the assets
+    let origin_of = |output_path: &str| match assets_path {
+        Some(assets_path) if assets_path.join(output_path).is_file() => {
+            Origin::Asset(assets_path.join(output_path))
+        }
+        _ => script_index.origin_of(output_path, output_path),
+    };
+    for issue in analysis::function_tag_issues(output) {
+        let (output_path, message) = match &issue {
+            FunctionTagIssue::MissingFunction {
+                output_path,
+                function,
+            } => (
+                output_path,
+                format!(
+                    "{output_path} ({}) references the missing function {function}",
+                    origin_of(output_path)
+                ),
+            ),
+            FunctionTagIssue::MissingTag { output_path, tag } => (
+                output_path,
+                format!(
+                    "{output_path} ({}) references the missing function tag #{tag}",
+                    origin_of(output_path)
+                ),
+            ),
+            FunctionTagIssue::NotRun {
+                output_path,
+                function,
+                tag,
+            } => (
+                output_path,
+                format!(
+                    "The function {function} ({}) is never run, add it to the function tag #{tag}",
+                    origin_of(output_path)
+                ),
+            ),
+        };
+        lints.warn(Lint::FunctionTag, Some(output_path), message);
+    }
+}
+
+/// Report the used scoreboard objectives and data storages and warn about the ones that are not
+/// prefixed with a namespace of the datapack.
+fn audit_resources(output: &VFolder, namespace: &str, lints: &Lints) {
+    let usage = analysis::used_resources(output);
+    if !usage.objectives.is_empty() {
+        print_info(format!(
+            "Scoreboard objectives: {}",
+            usage
+                .objectives
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if !usage.storages.is_empty() {
+        print_info(format!(
+            "Data storages: {}",
+            usage
+                .storages
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    // namespaces of dependencies are accepted as well
+    let namespaces = output
+        .flatten()
+        .into_iter()
+        .filter_map(|(path, _)| {
+            path.strip_prefix("data/")
+                .and_then(|path| path.split_once('/'))
+                .map(|(namespace, _)| namespace.to_string())
+        })
+        .filter(|data_namespace| data_namespace != "minecraft")
+        .chain(std::iter::once(namespace.to_string()))
+        .collect::<BTreeSet<_>>();
+    let namespaces = namespaces.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let (objectives, storages) = usage.unprefixed(&namespaces);
+    if !objectives.is_empty() {
+        lints.warn(
+            Lint::UnprefixedResource,
+            None,
+            format!(
+                "Scoreboard objectives not prefixed with the namespace `{namespace}` may collide with other packs: {}",
+                objectives.join(", ")
+            ),
+        );
+    }
+    if !storages.is_empty() {
+        lints.warn(
+            Lint::UnprefixedResource,
+            None,
+            format!(
+                "Data storages outside of the namespace `{namespace}` may collide with other packs: {}",
+                storages.join(", ")
+            ),
+        );
+    }
+}
+
+/// Write the `<artifact>.mapping.json` file with the functions remaining in the output next to the
+/// artifact.
+fn write_function_mapping(
+    artifact: &Path,
+    config: &ProjectConfig,
+    git: Option<&GitInfo>,
+    output: &VFolder,
+    mut mapping: BTreeMap<String, MappingEntry>,
+    project_root: &Path,
+) -> Result<()> {
+    let remaining = optimize::function_ids(output)
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    mapping.retain(|id, _| remaining.contains(id));
+    for entry in mapping.values_mut() {
+        if let Some(relative) = pathdiff::diff_paths(&entry.source.file, project_root) {
+            entry.source.file = relative;
+        }
+    }
+
+    let mapping_path =
+        artifact.with_file_name(format!("{}.mapping.json", artifact_name(config, git)));
+    fs::write(
+        &mapping_path,
+        serde_json::to_string_pretty(&mapping)? + "\n",
+    )?;
+
+    print_info(format!(
+        "Wrote function mapping to {}",
+        mapping_path.display()
+    ));
+
+    Ok(())
+}
+
+/// Write the `<artifact>.pack-info.json` file next to the artifact.
+fn write_pack_info(artifact: &Path, config: &ProjectConfig, git: Option<&GitInfo>) -> Result<()> {
+    let sha256 = if artifact.is_dir() {
+        fetch::content_hash(artifact)?
     } else {
-        Ok(Vec::new())
+        fetch::file_hash(artifact)?
+    };
+    let info = PackInfo::new(config, git, Some(sha256.to_string()));
+
+    let file_name = format!("{}.pack-info.json", artifact_name(config, git));
+    let info_path = artifact.with_file_name(file_name);
+    fs::write(&info_path, serde_json::to_string_pretty(&info)? + "\n")?;
+
+    print_info(format!("Wrote build metadata to {}", info_path.display()));
+
+    Ok(())
+}
+
+/// Add the scripts of the dependencies to the script paths and check that the packs can be merged.
+///
+/// Returns the file provider to use for transpiling, which applies the configured namespace renames.
+fn merge_dependencies(
+    project_config: &ProjectConfig,
+    script_paths: &mut Vec<(String, PathBuf)>,
+    dependencies: &[ResolvedDependency],
+) -> Result<ScriptProvider> {
+    let mut file_provider = ScriptProvider::default();
+    if dependencies.is_empty() {
+        return Ok(file_provider);
+    }
+
+    let mut packs = vec![PackScripts {
+        origin: format!("project {}", project_config.pack.name),
+        scripts: script_paths.clone(),
+    }];
+
+    for dependency in dependencies {
+        let foreign_imports = dependency.foreign_imports()?;
+        if !foreign_imports.is_empty() {
+            for foreign_import in &foreign_imports {
+                print_error(foreign_import);
+            }
+            Err(Error::ForeignImportError(dependency.name.clone()))?;
+        }
+
+        dependency.register_renamed_scripts(&mut file_provider)?;
+
+        let scripts = dependency.script_paths()?;
+        script_paths.extend(scripts.iter().cloned());
+        packs.push(PackScripts {
+            origin: match &dependency.required_by {
+                Some(required_by) => format!("dependency {} of {required_by}", dependency.name),
+                None => format!("dependency {}", dependency.name),
+            },
+            scripts,
+        });
+    }
+
+    let collisions = dependency::find_collisions(&packs, &file_provider)?;
+    if !collisions.is_empty() {
+        for collision in &collisions {
+            print_error(collision);
+        }
+        print_info("Use `namespace_prefix` or `rename_namespaces` on a dependency in the pack.toml file to move it to another namespace.");
+        Err(Error::PackCollisionError)?;
+    }
+
+    Ok(file_provider)
+}
+
+/// Values of the placeholders available in the `version` and `output_name` fields of the pack config.
+fn template_values(config: &ProjectConfig, git: Option<&GitInfo>) -> Vec<(&'static str, String)> {
+    vec![
+        ("name", config.pack.name.clone()),
+        ("namespace", config.pack.namespace().into_owned()),
+        (
+            "git_hash",
+            git.map_or_else(|| "unknown".to_string(), |git| git.short_hash().to_string()),
+        ),
+        (
+            "git_tag",
+            git.and_then(|git| git.tag.clone())
+                .unwrap_or_else(|| "untagged".to_string()),
+        ),
+    ]
+}
+
+/// Get the version of the pack with all placeholders replaced.
+pub(super) fn stamped_version(config: &ProjectConfig, git: Option<&GitInfo>) -> String {
+    util::expand_placeholders(&config.pack.version, &template_values(config, git))
+}
+
+/// Get the name of the output folder/zip file (without extension) with all placeholders replaced.
+pub(super) fn artifact_name(config: &ProjectConfig, git: Option<&GitInfo>) -> String {
+    let template = config
+        .compiler
+        .as_ref()
+        .and_then(|c| c.output_name.as_deref())
+        .unwrap_or("{name}");
+
+    expand_artifact_name(template, config, git)
+}
+
+/// Replace the placeholders of an artifact name template.
+pub(super) fn expand_artifact_name(
+    template: &str,
+    config: &ProjectConfig,
+    git: Option<&GitInfo>,
+) -> String {
+    let mut values = template_values(config, git);
+    values.push(("version", stamped_version(config, git)));
+
+    util::expand_placeholders(template, &values)
+}
+
+/// Get the comment of built zip files with all placeholders replaced, empty if it is disabled.
+#[cfg_attr(not(feature = "zip"), allow(dead_code))]
+fn zip_comment(config: &ProjectConfig, git: Option<&GitInfo>) -> String {
+    let template = config
+        .compiler
+        .as_ref()
+        .and_then(|c| c.zip_comment.as_deref())
+        .unwrap_or(CompilerConfig::DEFAULT_ZIP_COMMENT);
+
+    let mut values = template_values(config, git);
+    values.push(("version", stamped_version(config, git)));
+    values.push(("description", config.pack.description.clone()));
+    util::expand_placeholders(template, &values)
+}
+
+/// Recursively get all script paths in a directory.
+///
+/// The identifier of a script is its file stem, prefixed with the name of the folder containing
+/// it if it is in a subfolder.
+pub(crate) fn get_script_paths(
+    path: &Path,
+    options: WalkOptions,
+) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let mut paths = Vec::new();
+    for file in walk::files(path, options)? {
+        if file.path.extension().unwrap_or_default() != "shu" {
+            continue;
+        }
+
+        let components = file.str_components(options.lossy_names)?;
+        let [folders @ .., name] = components.as_slice() else {
+            continue;
+        };
+        let Some(stem) = name.strip_suffix(".shu").filter(|stem| !stem.is_empty()) else {
+            continue;
+        };
+        let identifier = match folders.last() {
+            Some(folder) => format!("{folder}/{stem}"),
+            None => stem.to_string(),
+        };
+        paths.push((identifier, file.path));
     }
+
+    Ok(paths)
+}
+
+/// Get the pack config of a folder of scripts without a pack.toml file.
+///
+/// The returned config path is where the pack.toml file would be.
+pub(crate) fn manifestless_config(
+    path: &Path,
+    format: Option<u8>,
+) -> Result<(ProjectConfig, PathBuf)> {
+    let path = path.absolutize()?;
+    if !path.is_dir() {
+        print_error("The specified path is not a directory.");
+        return Err(Error::NotDirectoryError(path.to_path_buf()).into());
+    }
+
+    let toml_path = path.join("pack.toml");
+    if toml_path.exists() {
+        print_warning("Ignoring the pack.toml file of the directory, as `--no-manifest` is used.");
+    }
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| PackConfig::DEFAULT_NAME.to_string());
+    let config = ProjectConfig {
+        pack: PackConfig {
+            name,
+            pack_format: format.unwrap_or(PackConfig::DEFAULT_PACK_FORMAT),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    Ok((config, toml_path))
 }
 
 /// Get the pack config and config path from a project path.
@@ -212,7 +1968,8 @@ fn _get_script_paths(path: &Path, prefix: &str) -> std::io::Result<Vec<(String,
 /// # Errors
 /// - If the specified path does not exist.
 /// - If the specified directory does not contain a pack.toml file.
-pub(super) fn get_pack_config(path: &Path) -> Result<(ProjectConfig, PathBuf)> {
+/// - If the pack.toml file contains unknown keys and `strict` is set.
+pub(crate) fn get_pack_config(path: &Path, strict: bool) -> Result<(ProjectConfig, PathBuf)> {
     let path = path.absolutize()?;
     let toml_path = if !path.exists() {
         print_error("The specified path does not exist.");
@@ -236,8 +1993,17 @@ pub(super) fn get_pack_config(path: &Path) -> Result<(ProjectConfig, PathBuf)> {
         return Err(Error::InvalidPackPathError(path.to_path_buf()))?;
     };
 
-    let toml_content = fs::read_to_string(&toml_path)?;
-    let project_config = toml::from_str::<ProjectConfig>(&toml_content)?;
+    let (project_config, unknown_keys) = ProjectConfig::load(&toml_path)?;
+    for key in &unknown_keys {
+        if strict {
+            print_error(format!("{}: {key}", toml_path.display()));
+        } else {
+            print_warning(format!("{}: {key}", toml_path.display()));
+        }
+    }
+    if strict && !unknown_keys.is_empty() {
+        return Err(Error::UnknownConfigKeyError(toml_path.clone()).into());
+    }
 
     Ok((project_config, toml_path))
 }
@@ -1,11 +1,23 @@
-use std::{borrow::Cow, path::PathBuf};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Result;
 use path_absolutize::Absolutize as _;
 
 use crate::{
-    terminal_output::{print_error, print_info, print_success},
+    cancel,
+    cli::GlobalOptions,
+    config::GlobalConfig,
+    deploy,
+    error::Error,
+    lock::PathLock,
+    plan::{Operation, Plan},
+    prompt,
+    terminal_output::{print_error, print_info, print_success, print_warning},
     util,
+    vcs::GitInfo,
 };
 
 #[derive(Debug, clap::Args, Clone)]
@@ -25,30 +37,92 @@ pub struct CleanArgs {
     /// Enable verbose output.
     #[arg(short, long)]
     pub verbose: bool,
+    /// List the files and folders that would be deleted without deleting anything.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Print the deletions as one line of JSON before performing them.
+    ///
+    /// With `--dry-run`, the deletions are printed instead of being performed.
+    #[arg(long)]
+    pub plan_json: bool,
 }
 
-pub fn clean(args: &CleanArgs) -> Result<()> {
+pub fn clean(args: &CleanArgs, options: &GlobalOptions) -> Result<()> {
     let verbose = args.verbose;
-    let path = util::get_project_path(&args.path).unwrap_or(args.path.clone());
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
     let dist_path = args
         .output
-        .as_ref()
-        .map(Cow::Borrowed)
-        .unwrap_or_else(|| Cow::Owned(path.join("dist")));
+        .as_deref()
+        .map_or_else(|| path.join("dist"), deploy::resolve_output_dir);
 
     let mut delete_paths = Vec::new();
 
-    let (project_config, _) = super::build::get_pack_config(&path)?;
+    let (project_config, _) = super::build::get_pack_config(&path, options.strict_config)?;
+
+    if args.all && dist_path.exists() {
+        let allowed = GlobalConfig::load()
+            .map(|config| config.clean.allowed_outputs)
+            .unwrap_or_default();
+        match output_location(&dist_path, &path, &allowed) {
+            OutputLocation::Safe => {}
+            OutputLocation::Outside => {
+                print_warning(format!(
+                    "The output folder {} is outside of the project. Add it to `allowed_outputs` in the [clean] section of the global config to skip this confirmation.",
+                    dist_path.display()
+                ));
+                if args.dry_run {
+                    return plan_clean(args, &dist_path, std::slice::from_ref(&dist_path));
+                }
+                let name = dist_path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let answer = prompt::text(inquire::Text::new(&format!(
+                    "Type the name of the folder ({name}) to delete it:"
+                )));
+                match answer {
+                    Ok(answer) if answer == name => {}
+                    Ok(_) => {
+                        print_error("The name does not match. Not cleaning the output folder.");
+                        return Err(Error::UnsafeCleanError(dist_path).into());
+                    }
+                    Err(_) => {
+                        print_error("Not cleaning an output folder outside of the project without confirmation.");
+                        return Err(Error::UnsafeCleanError(dist_path).into());
+                    }
+                }
+            }
+            OutputLocation::Dangerous => {
+                print_error(format!(
+                    "Refusing to delete {}, as it is a root, home or project folder. Check the --output option and the DATAPACK_DIR environment variable.",
+                    dist_path.display()
+                ));
+                return Err(Error::UnsafeCleanError(dist_path).into());
+            }
+        }
+    }
 
     if args.all {
-        if args.force {
-            delete_paths.push(dist_path.clone().into_owned());
+        let confirmed = args.force
+            || args.dry_run
+            || prompt::confirm(
+                inquire::Confirm::new(&format!(
+                    "Do you want to delete the whole output folder {}?",
+                    dist_path.display()
+                ))
+                .with_default(false),
+            )
+            .unwrap_or_default();
+        if confirmed {
+            delete_paths.push(dist_path.clone());
         } else {
             print_error("You must use the --force flag to clean the whole output folder.")
         }
     } else {
-        delete_paths.push(dist_path.join(&project_config.pack.name));
-        delete_paths.push(dist_path.join(project_config.pack.name + ".zip"));
+        let artifact_name =
+            super::build::artifact_name(&project_config, GitInfo::discover(&path).as_ref());
+        delete_paths.push(dist_path.join(&artifact_name));
+        delete_paths.push(dist_path.join(artifact_name + ".zip"));
     }
 
     print_info(format!(
@@ -56,10 +130,24 @@ pub fn clean(args: &CleanArgs) -> Result<()> {
         path.absolutize_from(&path)?.display()
     ));
 
+    if args.dry_run || args.plan_json {
+        plan_clean(args, &dist_path, &delete_paths)?;
+        if args.dry_run {
+            return Ok(());
+        }
+    }
+
+    // do not remove artifacts while they are written
+    let _lock = if dist_path.is_dir() {
+        Some(PathLock::acquire(&dist_path, cancel::global())?)
+    } else {
+        None
+    };
+
     for delete_path in delete_paths {
         if delete_path.exists() {
             if verbose {
-                print_info(&format!("Deleting {:?}", delete_path));
+                print_info(format!("Deleting {:?}", delete_path));
             }
             if delete_path.is_file() {
                 std::fs::remove_file(&delete_path)?;
@@ -76,10 +164,115 @@ pub fn clean(args: &CleanArgs) -> Result<()> {
         if verbose {
             print_info(format!("Deleting {:?}, as it is empty", dist_path));
         }
-        std::fs::remove_dir(dist_path.as_ref())?;
+        std::fs::remove_dir(&dist_path)?;
     }
 
     print_success("Project cleaned successfully.");
 
     Ok(())
 }
+
+/// Print the deletions of the paths as JSON with `--plan-json`, otherwise list them with
+/// `--dry-run`.
+fn plan_clean(args: &CleanArgs, dist_path: &Path, delete_paths: &[PathBuf]) -> Result<()> {
+    let mut plan = Plan::new("clean", args.dry_run);
+    for path in delete_paths.iter().filter(|path| path.exists()) {
+        plan.push(Operation::Delete { path: path.clone() });
+    }
+    // the output folder is removed if nothing else is left in it
+    let emptied = dist_path.is_dir()
+        && dist_path.file_name().is_some_and(|s| s != "datapacks")
+        && !delete_paths.iter().any(|path| path == dist_path)
+        && dist_path
+            .read_dir()?
+            .all(|entry| entry.is_ok_and(|entry| delete_paths.contains(&entry.path())));
+    if emptied {
+        plan.push(Operation::Delete {
+            path: dist_path.to_path_buf(),
+        });
+    }
+
+    if args.plan_json {
+        plan.print()?;
+    } else {
+        for operation in &plan.operations {
+            if let Operation::Delete { path } = operation {
+                print_info(format!("Would delete {}", path.display()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Where the output folder is in relation to the project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputLocation {
+    /// Inside the project or an allowed directory.
+    Safe,
+    /// Outside of the project, only deleted after typing its name.
+    Outside,
+    /// A root, home or project directory or one of their parents, never deleted.
+    Dangerous,
+}
+
+fn output_location(output: &Path, project_root: &Path, allowed: &[PathBuf]) -> OutputLocation {
+    let resolve = |path: &Path| {
+        path.canonicalize()
+            .or_else(|_| path.absolutize().map(|path| path.to_path_buf()))
+            .unwrap_or_else(|_| path.to_path_buf())
+    };
+    let output = resolve(output);
+    let project_root = resolve(project_root);
+    let home = env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })
+        .map(|home| resolve(Path::new(&home)));
+
+    if output.parent().is_none()
+        || home.is_some_and(|home| home.starts_with(&output))
+        || project_root.starts_with(&output)
+    {
+        OutputLocation::Dangerous
+    } else if output.starts_with(&project_root)
+        || allowed
+            .iter()
+            .any(|allowed| output.starts_with(resolve(allowed)))
+    {
+        OutputLocation::Safe
+    } else {
+        OutputLocation::Outside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_output_location() {
+        let temp = TestProject::empty("clean");
+        let dir = temp.path();
+        let project = dir.join("project");
+        let other = dir.join("other");
+
+        assert_eq!(
+            output_location(&project.join("dist"), &project, &[]),
+            OutputLocation::Safe
+        );
+        assert_eq!(
+            output_location(&other.join("dist"), &project, &[]),
+            OutputLocation::Outside
+        );
+        assert_eq!(
+            output_location(&other.join("dist"), &project, std::slice::from_ref(&other)),
+            OutputLocation::Safe
+        );
+        assert_eq!(
+            output_location(dir, &project, &[dir.to_path_buf()]),
+            OutputLocation::Dangerous
+        );
+        assert_eq!(
+            output_location(Path::new("/"), &project, &[]),
+            OutputLocation::Dangerous
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use git2::{build::RepoBuilder, FetchOptions as GitFetchOptions};
+use path_absolutize::Absolutize as _;
+
+use crate::{
+    cli::GlobalOptions,
+    error::Error,
+    fetch,
+    terminal_output::{print_error, print_info, print_plain, print_success},
+    util,
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct CloneArgs {
+    /// The project to clone.
+    ///
+    /// Can be a git URL or a GitHub shorthand (`owner/repo` or `gh:owner/repo`).
+    pub source: String,
+    /// The path of the folder to clone into.
+    ///
+    /// Defaults to the name of the repository.
+    pub path: Option<PathBuf>,
+    /// The branch of the repository to check out.
+    #[arg(short, long)]
+    pub branch: Option<String>,
+    /// Strip the git history and start a new repository with a single commit.
+    #[arg(long)]
+    pub no_history: bool,
+    /// Enable verbose output.
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+pub fn clone(args: &CloneArgs, options: &GlobalOptions) -> Result<()> {
+    let url = fetch::resolve_git_url(&args.source);
+    let path = args
+        .path
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(fetch::repo_name(&url)));
+
+    if path.exists() && path.read_dir()?.next().is_some() {
+        print_error(format!(
+            "The destination {} already exists and is not empty.",
+            path.display()
+        ));
+        Err(Error::NonEmptyDirectoryError(path.clone()))?;
+    }
+
+    print_info(format!("Cloning {url} into {}", path.display()));
+
+    let mut builder = RepoBuilder::new();
+    if let Some(branch) = &args.branch {
+        builder.branch(branch);
+    }
+    // the local transport does not support shallow fetches
+    if args.no_history && (url.contains("://") && !url.starts_with("file://")) {
+        let mut fetch_options = GitFetchOptions::new();
+        fetch_options.depth(1);
+        builder.fetch_options(fetch_options);
+    }
+    builder
+        .clone(&url, &path)
+        .inspect_err(|err| print_error(format!("Could not clone {url}: {err}")))?;
+
+    let project_config = match super::build::get_pack_config(&path, options.strict_config) {
+        Ok((config, _)) => config,
+        Err(err) => {
+            print_error("The cloned repository is not a valid Shulkerscript project, removing it.");
+            fs::remove_dir_all(&path)?;
+            return Err(err);
+        }
+    };
+
+    let namespace = project_config.pack.namespace();
+    if !util::is_valid_namespace(&namespace) {
+        print_error(format!(
+            "The namespace \"{namespace}\" of the cloned project is invalid. Set a valid `namespace` in the pack.toml file before building."
+        ));
+    }
+
+    if args.no_history {
+        fs::remove_dir_all(path.join(".git"))?;
+        if args.verbose {
+            print_info("Removed the git history of the cloned repository.");
+        }
+        super::init::initialize_git_squashed(&path, args.verbose)?;
+    }
+
+    print_success(format!(
+        "Cloned {} v{} into {}.",
+        project_config.pack.name,
+        project_config.pack.version,
+        path.absolutize()?.display()
+    ));
+
+    print_info("Next steps:");
+    print_plain(format!("    cd {}", path.display()));
+    print_plain("    shulkerscript build");
+    if cfg!(feature = "watch") {
+        print_plain("    shulkerscript watch");
+    }
+    if !project_config.scripts.is_empty() {
+        print_plain("    shulkerscript run-script    (lists the scripts of the project)");
+    }
+
+    Ok(())
+}
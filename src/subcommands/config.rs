@@ -0,0 +1,79 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+
+use crate::{
+    cli::GlobalOptions,
+    error::Error,
+    terminal_output::{print_diff, print_error, print_success},
+    toml_format, util,
+};
+
+use super::upgrade::edit_pack_toml;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Debug, clap::Subcommand, Clone)]
+pub enum ConfigCommand {
+    /// Normalize the order of the keys and tables and the formatting of the pack.toml file,
+    /// keeping comments.
+    Fmt(ConfigFmtArgs),
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ConfigFmtArgs {
+    /// The path of the project to format the pack.toml file of.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Fail with a diff of the changes if the file is not formatted, without writing it.
+    #[arg(long)]
+    pub check: bool,
+    /// Print the changes as a diff without writing them.
+    #[arg(long, conflicts_with = "check")]
+    pub dry_run: bool,
+}
+
+pub fn config(args: &ConfigArgs, options: &GlobalOptions) -> Result<()> {
+    match &args.command {
+        ConfigCommand::Fmt(args) if args.check => check(args, options.discover()),
+        ConfigCommand::Fmt(args) => {
+            edit_pack_toml(&args.path, options.discover(), args.dry_run, |document| {
+                let before = document.to_string();
+                toml_format::normalize(document);
+                if document.to_string() == before {
+                    Vec::new()
+                } else {
+                    vec!["Normalized the order and formatting of the keys and tables".to_string()]
+                }
+            })
+        }
+    }
+}
+
+fn check(args: &ConfigFmtArgs, discover: bool) -> Result<()> {
+    let path = util::get_project_path(&args.path, discover).unwrap_or(args.path.clone());
+    let toml_path = path.join("pack.toml");
+    if !toml_path.is_file() {
+        print_error("The specified directory does not contain a pack.toml file.");
+        return Err(Error::InvalidPackPathError(path).into());
+    }
+
+    let content = fs::read_to_string(&toml_path)?;
+    let mut document = content.parse::<toml_edit::DocumentMut>()?;
+    toml_format::normalize(&mut document);
+    let formatted = document.to_string();
+    if formatted == content {
+        print_success("The pack.toml file is formatted.");
+        return Ok(());
+    }
+
+    print_diff(&util::line_diff(&content, &formatted), 2);
+    print_error(
+        "The pack.toml file is not formatted, run `shulkerscript config fmt` to format it.",
+    );
+    Err(Error::UnformattedConfigError(toml_path).into())
+}
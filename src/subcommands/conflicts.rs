@@ -0,0 +1,148 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use shulkerscript::shulkerbox::virtual_fs::VFolder;
+
+use crate::{
+    analysis::{self, PackConflict},
+    cancel,
+    cli::GlobalOptions,
+    deploy::{self, DeployTarget},
+    error::Error,
+    terminal_output::{print_error, print_info, print_success, print_warning},
+    units::format_count,
+    walk::{self, WalkOptions},
+};
+
+use super::BuildArgs;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ConflictsArgs {
+    /// The path of the project to check.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// The worlds or server directories with the installed datapacks to check against.
+    ///
+    /// Can be given multiple times.
+    #[arg(long = "world", value_name = "PATH", required = true)]
+    pub worlds: Vec<PathBuf>,
+}
+
+pub fn conflicts(args: &ConflictsArgs, options: &GlobalOptions) -> Result<()> {
+    let targets = args
+        .worlds
+        .iter()
+        .map(|path| {
+            DeployTarget::detect(path).ok_or_else(|| {
+                print_error(format!(
+                    "The path {} is neither a world nor a server directory.",
+                    path.display()
+                ));
+                Error::NoDeployTargetError(path.clone())
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    cancel::install_shutdown_handler();
+    let staging = env::temp_dir().join(format!("shulkerscript-conflicts-{}", std::process::id()));
+    let build_args = BuildArgs {
+        path: args.path.clone(),
+        output: Some(staging.clone()),
+        ..BuildArgs::default()
+    };
+    let result = super::build::build_artifact(&build_args, options, cancel::global())
+        .and_then(|artifact| Ok((read_pack(&artifact)?, pack_name(&artifact))));
+    let _ = fs::remove_dir_all(&staging);
+    let (pack, name) = result?;
+
+    let mut count = 0;
+    for target in &targets {
+        print_info(format!("Checking the datapacks of {target}"));
+        let datapacks = target.datapacks_dir();
+        let mut installed = match fs::read_dir(&datapacks) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        installed.sort();
+        let disabled = deploy::read_world_datapacks(target.world())
+            .map(|state| state.disabled)
+            .unwrap_or_default();
+
+        for path in installed {
+            let other_name = pack_name(&path);
+            let id = deploy::datapack_id(
+                target.world(),
+                &path.file_name().unwrap_or_default().to_string_lossy(),
+            );
+            // earlier deploys of the project are replaced and disabled datapacks are not loaded
+            if other_name == name || disabled.contains(&id) {
+                continue;
+            }
+            let other = match read_pack(&path) {
+                Ok(other) => other,
+                Err(err) => {
+                    print_warning(format!(
+                        "Could not read the datapack {}: {err}",
+                        path.display()
+                    ));
+                    continue;
+                }
+            };
+            for conflict in analysis::pack_conflicts(&pack, &other) {
+                print_warning(describe(&conflict, &other_name));
+                count += 1;
+            }
+        }
+    }
+
+    if count > 0 {
+        print_error(format!(
+            "Found {} conflicts with the installed datapacks.",
+            format_count(count)
+        ));
+        return Err(Error::PackConflictError(count).into());
+    }
+    print_success("No conflicts with the installed datapacks found.");
+    Ok(())
+}
+
+/// Read a datapack folder or zip file.
+fn read_pack(path: &Path) -> Result<VFolder> {
+    if path.is_dir() {
+        return Ok(walk::read_folder(path, WalkOptions::default())?);
+    }
+    #[cfg(feature = "zip")]
+    if path.extension().is_some_and(|extension| extension == "zip") {
+        return Ok(crate::archive::read_folder(path)?);
+    }
+    Err(Error::InvalidPackPathError(path.to_path_buf()).into())
+}
+
+/// The name of a datapack folder or zip file without the extension.
+fn pack_name(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.strip_suffix(".zip").unwrap_or(&name).to_string()
+}
+
+/// The message of a conflict with the datapack.
+fn describe(conflict: &PackConflict, pack: &str) -> String {
+    match conflict {
+        PackConflict::Namespace(namespace) => {
+            format!("The namespace {namespace} is also used by the datapack {pack}.")
+        }
+        PackConflict::FunctionTag { tag, replace: true } => format!(
+            "The function tag #{tag} is also defined by the datapack {pack} and one of them replaces the other."
+        ),
+        PackConflict::FunctionTag { tag, replace: false } => {
+            format!("The function tag #{tag} is also defined by the datapack {pack}.")
+        }
+        PackConflict::Objective(objective) => {
+            format!("The scoreboard objective {objective} is also used by the datapack {pack}.")
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use crate::{
+    cancel,
+    deploy::{self, DeployTarget},
+    error::Error,
+    rcon::Rcon,
+    runtime,
+    terminal_output::{print_error, print_info, print_plain, print_success, print_warning},
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct DatapackArgs {
+    #[command(subcommand)]
+    pub command: DatapackCommand,
+}
+
+#[derive(Debug, clap::Subcommand, Clone)]
+pub enum DatapackCommand {
+    /// Enable a datapack of a world or server.
+    Enable(DatapackStateArgs),
+    /// Disable a datapack of a world or server.
+    Disable(DatapackStateArgs),
+    /// List the enabled and disabled datapacks of a world or server.
+    List(DatapackTargetArgs),
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct DatapackStateArgs {
+    /// The name of the folder or zip file in the `datapacks` folder, or the id of the datapack,
+    /// e.g. `file/pack.zip` or `bundle`.
+    pub name: String,
+    #[command(flatten)]
+    pub target: DatapackTargetArgs,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct DatapackTargetArgs {
+    /// The world or server directory.
+    ///
+    /// Running servers with RCON enabled in their server.properties file are changed with the
+    /// `/datapack` command, other worlds by editing their level.dat file.
+    #[arg(long, value_name = "PATH")]
+    pub target: PathBuf,
+    /// The host of the server to connect to via RCON.
+    #[arg(long, default_value = "localhost")]
+    pub rcon_host: String,
+    /// Edit the level.dat file even if RCON is enabled for the server, e.g. while it is stopped.
+    #[arg(long)]
+    pub offline: bool,
+}
+
+pub fn datapack(args: &DatapackArgs) -> Result<()> {
+    match &args.command {
+        DatapackCommand::Enable(args) => set_enabled(args, true),
+        DatapackCommand::Disable(args) => set_enabled(args, false),
+        DatapackCommand::List(args) => list(args),
+    }
+}
+
+fn set_enabled(args: &DatapackStateArgs, enabled: bool) -> Result<()> {
+    let target = detect_target(&args.target.target)?;
+    let id = deploy::datapack_id(target.world(), &args.name);
+    let action = if enabled { "enabled" } else { "disabled" };
+
+    if let Some((port, password)) = rcon(&args.target, &target) {
+        let command_id = id.clone();
+        let listed = run_rcon(&args.target.rcon_host, port, password, move |rcon| {
+            deploy::set_enabled_on_server(rcon, &command_id, enabled)
+        })?;
+        if !listed {
+            print_error(format!(
+                "The datapack {id} is not listed as {action} by the server, check the name and the server log for errors."
+            ));
+            return Err(Error::DatapackStateError(id).into());
+        }
+        print_success(format!("The datapack {id} is {action} on {target}"));
+        return Ok(());
+    }
+
+    let missing = id
+        .strip_prefix("file/")
+        .is_some_and(|name| !target.datapacks_dir().join(name).exists());
+    if enabled && missing {
+        print_warning(format!(
+            "The datapack {id} does not exist in {}.",
+            target.datapacks_dir().display()
+        ));
+    }
+    let changed = deploy::set_enabled_in_world(target.world(), &id, enabled).map_err(|err| {
+        print_error(format!(
+            "Could not change the level.dat file of {target}: {err}"
+        ));
+        Error::DatapackStateError(id.clone())
+    })?;
+    if changed {
+        print_success(format!("The datapack {id} is {action} in {target}"));
+        if matches!(target, DeployTarget::Server { .. }) {
+            print_info("A running server overwrites the change when saving the world, so it has to be stopped first.");
+        }
+    } else {
+        print_info(format!("The datapack {id} is already {action} in {target}"));
+    }
+    Ok(())
+}
+
+fn list(args: &DatapackTargetArgs) -> Result<()> {
+    let target = detect_target(&args.target)?;
+
+    if let Some((port, password)) = rcon(args, &target) {
+        let output = run_rcon(&args.rcon_host, port, password, |rcon| {
+            rcon.command("datapack list")
+        })?;
+        for line in output.lines() {
+            print_plain(line);
+        }
+        return Ok(());
+    }
+
+    let state = deploy::read_world_datapacks(target.world()).inspect_err(|err| {
+        print_error(format!(
+            "Could not read the level.dat file of {target}: {err}"
+        ));
+    })?;
+    print_info(format!("Datapacks of {target}, by ascending priority:"));
+    for id in &state.enabled {
+        print_plain(format!("  [x] {id}"));
+    }
+    for id in &state.disabled {
+        print_plain(format!("  [ ] {id}"));
+    }
+    Ok(())
+}
+
+fn detect_target(path: &Path) -> Result<DeployTarget> {
+    DeployTarget::detect(path).ok_or_else(|| {
+        print_error(format!(
+            "The path {} is neither a world nor a server directory.",
+            path.display()
+        ));
+        Error::NoDeployTargetError(path.to_path_buf()).into()
+    })
+}
+
+/// The RCON port and password of the target, unless it is changed offline.
+fn rcon(args: &DatapackTargetArgs, target: &DeployTarget) -> Option<(u16, String)> {
+    target.rcon().filter(|_| !args.offline)
+}
+
+fn run_rcon<T: Send + 'static>(
+    host: &str,
+    port: u16,
+    password: String,
+    command: impl FnOnce(&mut Rcon) -> io::Result<T> + Send + 'static,
+) -> Result<T> {
+    cancel::install_shutdown_handler();
+    let address = (host.to_string(), port);
+    runtime::until_cancelled(cancel::global(), move || {
+        Rcon::connect(address, &password).and_then(|mut rcon| command(&mut rcon))
+    })?
+    .map_err(|err| {
+        print_error(format!(
+            "Could not connect to the server via RCON on {host}:{port}: {err}. Use --offline to edit the level.dat file of a stopped server."
+        ));
+        Error::RconError(format!("{host}:{port}")).into()
+    })
+}
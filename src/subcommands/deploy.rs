@@ -0,0 +1,421 @@
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+
+use crate::{
+    cancel,
+    cli::GlobalOptions,
+    deploy::{self, BackupScope, DeployTarget},
+    error::Error,
+    launcher::{self, Instance},
+    lock::PathLock,
+    plan::{Operation, Plan},
+    prompt,
+    rcon::Rcon,
+    runtime,
+    terminal_output::{print_error, print_info, print_success, print_warning},
+    util,
+};
+
+use super::BuildArgs;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct DeployArgs {
+    #[command(flatten)]
+    pub build: BuildArgs,
+    /// The worlds or server directories to deploy to.
+    ///
+    /// Can be given multiple times, e.g. for several worlds of a server. Defaults to the output
+    /// directory.
+    #[arg(long = "world", value_name = "PATH")]
+    pub worlds: Vec<PathBuf>,
+    /// Enable the datapack on the running servers via RCON and verify that it is loaded.
+    ///
+    /// The RCON port and password are read from the server.properties file of the server.
+    #[arg(long)]
+    pub enable: bool,
+    /// The host of the servers to connect to via RCON.
+    #[arg(long, default_value = "localhost", requires = "enable")]
+    pub rcon_host: String,
+    /// Start Minecraft with the first singleplayer world after deploying.
+    ///
+    /// Worlds of Prism Launcher instances are opened directly, MultiMC instances are started
+    /// without selecting the world.
+    #[arg(long)]
+    pub launch: bool,
+    /// The executable of the launcher to start with `--launch`.
+    ///
+    /// Defaults to the launcher of the instance containing the world. Worlds outside of launcher
+    /// instances, e.g. of the official launcher, require this option.
+    #[arg(long, value_name = "PATH", requires = "launch")]
+    pub launcher: Option<PathBuf>,
+    /// Back up the replaced datapack, or the whole `datapacks` folder with `all`, before
+    /// deploying.
+    ///
+    /// The backups are kept in the `shulkerscript-backups` folder of the world.
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "pack",
+        value_name = "SCOPE"
+    )]
+    pub backup: Option<BackupScope>,
+    /// The number of backups kept per world.
+    #[arg(long, value_name = "N", default_value = "5", requires = "backup")]
+    pub keep_backups: NonZeroUsize,
+    /// Restore the datapacks backed up by the last deploy to the worlds instead of deploying.
+    #[arg(long, conflicts_with_all = ["backup", "enable", "launch"])]
+    pub rollback: bool,
+    /// Print the operations as one line of JSON before performing them.
+    ///
+    /// With `--dry-run`, the operations are printed instead of being performed.
+    #[arg(long, conflicts_with = "rollback")]
+    pub plan_json: bool,
+}
+
+pub fn deploy(args: &DeployArgs, options: &GlobalOptions) -> Result<()> {
+    for (used, option) in [
+        (args.build.workspace, "--workspace"),
+        (args.build.remote.is_some(), "--remote"),
+        (args.build.check, "--check"),
+        (
+            args.build.dry_run && !args.plan_json,
+            "--dry-run without --plan-json",
+        ),
+    ] {
+        if used {
+            print_error(format!(
+                "The option {option} cannot be used when deploying."
+            ));
+            return Err(Error::UnsupportedOptionError(option.to_string()).into());
+        }
+    }
+
+    let mut paths = if args.worlds.is_empty() {
+        args.build.output.iter().cloned().collect()
+    } else {
+        args.worlds.clone()
+    };
+    if paths.is_empty() {
+        // never select a world without asking, e.g. in CI pipelines
+        let worlds = deploy::minecraft_saves_dir()
+            .filter(|_| prompt::is_interactive())
+            .and_then(|saves| deploy::saved_worlds(&saves).ok())
+            .unwrap_or_default();
+        let answer = if worlds.is_empty() {
+            prompt::text(
+                inquire::Text::new("Enter the path of the world or server to deploy to:")
+                    .with_autocomplete(util::PathAutocomplete::new()),
+            )
+        } else {
+            let options = worlds
+                .iter()
+                .map(|world| world.display().to_string())
+                .collect();
+            prompt::select(inquire::Select::new(
+                "Select the world to deploy to:",
+                options,
+            ))
+        };
+        match answer {
+            Ok(path) => paths.push(PathBuf::from(path)),
+            Err(_) => {
+                print_error("No world to deploy to. Use --world or set the output directory.");
+                return Err(Error::DeployError(0).into());
+            }
+        }
+    }
+    let targets = paths
+        .iter()
+        .map(|path| {
+            DeployTarget::detect(path).ok_or_else(|| {
+                print_error(format!(
+                    "The path {} is neither a world nor a server directory.",
+                    path.display()
+                ));
+                Error::NoDeployTargetError(path.clone())
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if args.rollback {
+        return rollback(&targets);
+    }
+
+    cancel::install_shutdown_handler();
+    let staging = env::temp_dir().join(format!("shulkerscript-deploy-{}", std::process::id()));
+    let build_args = BuildArgs {
+        output: Some(staging.clone()),
+        dry_run: false,
+        ..args.build.clone()
+    };
+    let result =
+        super::build::build_artifact(&build_args, options, cancel::global()).and_then(|artifact| {
+            if args.plan_json {
+                plan(args, &artifact, &targets).print()?;
+            }
+            if args.build.dry_run {
+                return Ok(0);
+            }
+            deploy_to_targets(args, options, &artifact, &targets)
+        });
+    let _ = fs::remove_dir_all(&staging);
+
+    let failed = result?;
+    if failed > 0 {
+        return Err(Error::DeployError(failed).into());
+    }
+    if args.launch && !args.build.dry_run {
+        launch(args.launcher.as_deref(), &targets)?;
+    }
+    Ok(())
+}
+
+/// The operations of deploying the artifact to the targets.
+fn plan(args: &DeployArgs, artifact: &Path, targets: &[DeployTarget]) -> Plan {
+    let name = artifact
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let mut plan = Plan::new("deploy", args.build.dry_run);
+    let mut servers = BTreeMap::new();
+    for target in targets {
+        if let Some(scope) = args.backup {
+            plan.push(Operation::Backup {
+                world: target.world().to_path_buf(),
+                datapack: name.clone(),
+                scope,
+                keep: args.keep_backups.get(),
+            });
+        }
+        let destination = target.datapacks_dir().join(&name);
+        plan.push(Operation::Copy {
+            from: artifact.to_path_buf(),
+            replace: destination.exists(),
+            to: destination,
+        });
+        if let DeployTarget::Server { root, .. } = target {
+            servers.entry(root.clone()).or_insert(target);
+        }
+    }
+
+    if args.enable {
+        for (root, target) in servers {
+            if let Some((port, _)) = target.rcon() {
+                plan.push(Operation::Enable {
+                    server: root,
+                    address: format!("{}:{port}", args.rcon_host),
+                    datapack: format!("file/{name}"),
+                });
+            }
+        }
+    }
+    plan
+}
+
+/// Restore the backups of the last deploy to the targets.
+fn rollback(targets: &[DeployTarget]) -> Result<()> {
+    let mut failed = 0;
+    for target in targets {
+        let result = PathLock::acquire(&target.datapacks_dir(), cancel::global())
+            .and_then(|_lock| Ok(deploy::rollback(target)?));
+        match result {
+            Ok(Some(backup)) => {
+                let time =
+                    util::format_timestamp(UNIX_EPOCH + Duration::from_secs(backup.timestamp));
+                let restored = match backup.scope {
+                    BackupScope::Pack => backup.datapack,
+                    BackupScope::All => "the datapacks folder".to_string(),
+                };
+                print_success(format!(
+                    "Restored {restored} of {target} as backed up at {time}"
+                ));
+            }
+            Ok(None) => print_warning(format!("There is no backup of {target} to restore.")),
+            Err(err) => {
+                print_error(format!("Could not roll back {target}: {err}"));
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(Error::DeployError(failed).into());
+    }
+    Ok(())
+}
+
+/// Start Minecraft with the first singleplayer world of the targets.
+fn launch(launcher: Option<&Path>, targets: &[DeployTarget]) -> Result<()> {
+    let mut worlds = targets
+        .iter()
+        .filter(|target| matches!(target, DeployTarget::World { .. }));
+    let Some(target) = worlds.next() else {
+        print_warning("No singleplayer world was deployed to, not launching Minecraft.");
+        return Ok(());
+    };
+    if worlds.next().is_some() {
+        print_info(format!("Launching only {target}."));
+    }
+
+    let (executable, result) = match Instance::of_world(target.world()) {
+        Some(instance) => {
+            let executable = launcher.map_or_else(|| instance.executable(), Path::to_path_buf);
+            print_info(format!(
+                "Launching the {} instance {}...",
+                instance.launcher, instance.id
+            ));
+            let result = instance.launch(&executable);
+            (executable, result)
+        }
+        None => {
+            let Some(executable) = launcher else {
+                print_warning(format!(
+                    "{} is not in a Prism Launcher or MultiMC instance, use --launcher to start another launcher.",
+                    target.world().display()
+                ));
+                return Ok(());
+            };
+            print_info(format!("Starting {}...", executable.display()));
+            (executable.to_path_buf(), launcher::start(executable))
+        }
+    };
+
+    result.map_err(|err| {
+        print_error(format!(
+            "Could not start the launcher {}: {err}",
+            executable.display()
+        ));
+        Error::LaunchError(executable).into()
+    })
+}
+
+/// Copy the artifact to the targets and enable it on their servers, returns the number of targets
+/// that failed.
+fn deploy_to_targets(
+    args: &DeployArgs,
+    options: &GlobalOptions,
+    artifact: &Path,
+    targets: &[DeployTarget],
+) -> Result<usize> {
+    let name = artifact
+        .file_name()
+        .ok_or_else(|| Error::InvalidPackPathError(artifact.to_path_buf()))?;
+    let (mut project_config, _) = if args.build.no_manifest {
+        super::build::manifestless_config(&args.build.path, args.build.format)?
+    } else {
+        let path = util::get_project_path(&args.build.path, options.discover())
+            .unwrap_or(args.build.path.clone());
+        super::build::get_pack_config(&path, options.strict_config)?
+    };
+    if let Some(format) = args.build.unstable_format {
+        project_config.pack.set_unstable_format(format);
+    }
+
+    let mut failed = 0;
+    // the targets deployed to, grouped by server
+    let mut servers = BTreeMap::new();
+    for target in targets {
+        cancel::global().check()?;
+        print_info(format!("Deploying to {target}"));
+        let result = super::build::check_deploy_target(
+            target,
+            project_config.pack.pack_format,
+            args.build.no_validate,
+        )
+        .and_then(|()| {
+            let datapacks = target.datapacks_dir();
+            fs::create_dir_all(&datapacks)?;
+            let _lock = PathLock::acquire(&datapacks, cancel::global())?;
+            if let Some(scope) = args.backup {
+                let backup = deploy::backup(
+                    target,
+                    &name.to_string_lossy(),
+                    scope,
+                    args.keep_backups.get(),
+                )?;
+                print_info(format!("Backed up the datapacks to {}", backup.display()));
+            }
+            super::build::place_atomically(&datapacks.join(name), cancel::global(), |temp| {
+                Ok(deploy::copy_artifact(artifact, temp)?)
+            })
+        });
+
+        match result {
+            Ok(()) => {
+                print_success(format!(
+                    "Deployed to {}",
+                    target.datapacks_dir().join(name).display()
+                ));
+                if let DeployTarget::Server { root, .. } = target {
+                    servers.entry(root.clone()).or_insert(target);
+                }
+            }
+            Err(err) => {
+                print_error(format!("Could not deploy to {target}: {err}"));
+                failed += 1;
+            }
+        }
+    }
+
+    if args.enable {
+        let name = name.to_string_lossy();
+        for (root, target) in servers {
+            if !enable_on_server(&args.rcon_host, &root, target, &name) {
+                failed += 1;
+            }
+        }
+    }
+
+    Ok(failed)
+}
+
+/// Enable the datapack on the server via RCON, returns whether it is enabled afterwards.
+///
+/// The datapacks of a server are enabled for all of its worlds at once.
+fn enable_on_server(host: &str, root: &Path, target: &DeployTarget, name: &str) -> bool {
+    let Some((port, password)) = target.rcon() else {
+        print_warning(format!(
+            "RCON is not enabled in the server.properties file of the server at {}, enable the datapack with `/datapack enable \"file/{name}\"`.",
+            root.display()
+        ));
+        return true;
+    };
+
+    let (address, datapack) = ((host.to_string(), port), name.to_string());
+    let result = runtime::until_cancelled(cancel::global(), move || {
+        Rcon::connect(address, &password)
+            .and_then(|mut rcon| deploy::enable_datapack(&mut rcon, &datapack))
+    })
+    .unwrap_or_else(|err| Err(io::Error::new(io::ErrorKind::Interrupted, err)));
+    match result {
+        Ok(true) => {
+            print_success(format!(
+                "Enabled the datapack on the server at {}",
+                root.display()
+            ));
+            true
+        }
+        Ok(false) => {
+            print_error(format!(
+                "The datapack is not listed as enabled by the server at {}, check the server log for errors.",
+                root.display()
+            ));
+            false
+        }
+        Err(err) => {
+            print_error(format!(
+                "Could not enable the datapack on the server at {} via RCON on {host}:{port}: {err}",
+                root.display()
+            ));
+            false
+        }
+    }
+}
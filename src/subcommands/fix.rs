@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::{cli::GlobalOptions, upgrade};
+
+use super::upgrade::edit_pack_toml;
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct FixArgs {
+    /// The path of the project to fix.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Print the changes as a diff without writing them.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn fix(args: &FixArgs, options: &GlobalOptions) -> Result<()> {
+    edit_pack_toml(
+        &args.path,
+        options.discover(),
+        args.dry_run,
+        upgrade::fix_deprecations,
+    )
+}
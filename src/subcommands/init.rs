@@ -1,25 +1,34 @@
 use std::{
     borrow::Cow,
     fmt::Display,
-    fs,
+    fs, iter,
     path::{Path, PathBuf},
 };
 
 use anyhow::Result;
 use clap::ValueEnum;
+use colored::Colorize;
 use git2::{
-    IndexAddOption as GitIndexAddOption, Repository as GitRepository, Signature as GitSignature,
+    IndexAddOption as GitIndexAddOption, Repository as GitRepository,
+    RepositoryInitOptions as GitRepositoryInitOptions, Signature as GitSignature,
 };
-use inquire::validator::Validation;
+use inquire::{error::InquireResult, validator::Validation};
 use path_absolutize::Absolutize;
 
 use crate::{
-    config::{PackConfig, ProjectConfig},
+    cli::GlobalOptions,
+    config::{GlobalConfig, InitDefaults, PackConfig, ProjectConfig},
     error::Error,
-    terminal_output::{print_error, print_info, print_success},
+    fetch::{self, Sha256Pin},
+    prompt,
+    template::{self, Template},
+    terminal_output::{print_error, print_info, print_plain, print_success, print_warning},
+    util::{self, name_to_namespace},
+    versions::{self, PackFormat},
+    workspace::Workspace,
 };
 
-#[derive(Debug, clap::Args, Clone)]
+#[derive(Debug, clap::Args, Clone, Default)]
 pub struct InitArgs {
     /// The path of the folder to initialize in.
     #[arg(default_value = ".")]
@@ -27,21 +36,65 @@ pub struct InitArgs {
     /// The name of the project.
     #[arg(short, long)]
     pub name: Option<String>,
+    /// The namespace of the datapack.
+    ///
+    /// Defaults to the namespace derived from the project name.
+    #[arg(long)]
+    pub namespace: Option<String>,
     /// The description of the project.
     #[arg(short, long)]
     pub description: Option<String>,
     /// The pack format version.
-    #[arg(short, long, value_name = "FORMAT", visible_alias = "format")]
+    #[arg(short, long = "format", value_name = "FORMAT", alias = "pack-format")]
     pub pack_format: Option<u8>,
     /// The path of the icon file.
     #[arg(short, long = "icon", value_name = "PATH")]
     pub icon_path: Option<PathBuf>,
+    /// Do not create a pack.png file.
+    #[arg(long, conflicts_with = "icon_path")]
+    pub no_icon: bool,
     /// Force initialization even if the directory is not empty.
     #[arg(short, long)]
     pub force: bool,
+    /// The template to create the project from instead of the default main.shu file.
+    ///
+    /// A built-in template (`library`, `minigame` or `worldgen`), the path of a folder or a git
+    /// URL. The placeholders `{name}`, `{namespace}` and `{description}` in its files are replaced.
+    #[arg(short, long, value_name = "NAME|GIT_URL|PATH")]
+    pub template: Option<String>,
+    /// The expected SHA-256 pin of the content of a git template.
+    #[arg(long, value_name = "PIN", requires = "template")]
+    pub template_sha256: Option<Sha256Pin>,
+    /// Refuse to use unpinned git templates.
+    ///
+    /// Enabled automatically if the `CI` environment variable is set.
+    #[arg(long)]
+    pub locked: bool,
+    /// Generate a README.md file with build instructions.
+    #[arg(long)]
+    pub readme: bool,
+    /// Generate an .editorconfig file and VS Code settings recommending the Shulkerscript extension.
+    #[arg(long)]
+    pub editor_config: bool,
     /// The version control system to initialize. [default: git]
     #[arg(long)]
     pub vcs: Option<VersionControlSystem>,
+    /// The name of the author of the initial commits.
+    ///
+    /// Defaults to the global config, then the Git config.
+    #[arg(long, value_name = "NAME")]
+    pub author_name: Option<String>,
+    /// The email of the author of the initial commits.
+    ///
+    /// Defaults to the global config, then the Git config.
+    #[arg(long, value_name = "EMAIL")]
+    pub author_email: Option<String>,
+    /// The name of the initial branch of the repository.
+    #[arg(long, value_name = "BRANCH")]
+    pub initial_branch: Option<String>,
+    /// Create a single initial commit instead of an empty commit followed by the template files.
+    #[arg(long)]
+    pub squash: bool,
     /// Enable verbose output.
     #[arg(short, long)]
     pub verbose: bool,
@@ -51,12 +104,59 @@ pub struct InitArgs {
     /// will use the default values instead if possible or fail.
     #[arg(long)]
     pub batch: bool,
+    /// Create a member pack of the workspace at the path instead of a standalone project.
+    ///
+    /// The member is created in `packs/<NAME>`, registered in the `[workspace]` section and extends
+    /// the shared `base-pack.toml` file of the workspace. It does not prompt for input.
+    #[arg(long, value_name = "NAME", conflicts_with = "name")]
+    pub member: Option<String>,
+}
+
+/// The directory new workspace members are created in, relative to the workspace root.
+const MEMBERS_DIR: &str = "packs";
+/// The config file extended by the workspace members, relative to the workspace root.
+const SHARED_CONFIG: &str = "base-pack.toml";
+
+const INVALID_NAME_HELP: &str = "Names must not be empty, end with a dot or space, contain any of the characters < > : \" / \\ | ? * or be reserved on Windows (e.g. CON, NUL, COM1)";
+
+/// Warn about characters of the name that are dropped when deriving the namespace.
+fn warn_dropped_namespace_chars(name: &str) {
+    let dropped = util::dropped_namespace_chars(name);
+    if !dropped.is_empty() {
+        print_warning(format!(
+            "The characters {} of the project name are not allowed in namespaces and are dropped from the derived namespace \"{}\".",
+            dropped
+                .iter()
+                .map(|c| format!("'{c}'"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            name_to_namespace(name)
+        ));
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+/// An option of the pack format prompt.
+#[derive(Debug, Clone, Copy)]
+enum PackFormatOption {
+    Known(&'static PackFormat),
+    Manual,
+}
+
+impl Display for PackFormatOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Known(entry) => entry.fmt(f),
+            Self::Manual => write!(f, "Other (enter manually)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
 pub enum VersionControlSystem {
     #[default]
     Git,
+    /// Initialize a Git repository without creating any commits.
+    GitNoCommit,
     None,
 }
 
@@ -64,20 +164,165 @@ impl Display for VersionControlSystem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             VersionControlSystem::Git => write!(f, "git"),
+            VersionControlSystem::GitNoCommit => write!(f, "git-no-commit"),
             VersionControlSystem::None => write!(f, "none"),
         }
     }
 }
 
-pub fn init(args: &InitArgs) -> Result<()> {
-    if args.batch {
-        initialize_batch(args)
+/// The options of the initialized Git repository.
+#[derive(Debug, Clone, Default)]
+struct GitOptions {
+    author_name: Option<String>,
+    author_email: Option<String>,
+    initial_branch: Option<String>,
+    squash: bool,
+    /// Whether the initial branch or squashing was requested on the command line, which is
+    /// ignored in existing repositories.
+    explicit: bool,
+}
+
+impl GitOptions {
+    /// Get the options given by the arguments, falling back to the defaults of the global config.
+    fn new(args: &InitArgs, defaults: &InitDefaults) -> Self {
+        Self {
+            author_name: args
+                .author_name
+                .clone()
+                .or_else(|| defaults.author_name.clone()),
+            author_email: args
+                .author_email
+                .clone()
+                .or_else(|| defaults.author_email.clone()),
+            initial_branch: args
+                .initial_branch
+                .clone()
+                .or_else(|| defaults.initial_branch.clone()),
+            squash: args.squash || defaults.squash.unwrap_or_default(),
+            explicit: args.initial_branch.is_some() || args.squash,
+        }
+    }
+
+    /// Get the signature of the initial commits.
+    ///
+    /// Missing parts are taken from the Git config of the repository, falling back to a generic
+    /// identity.
+    fn signature(&self, repo: &GitRepository) -> Result<GitSignature<'static>> {
+        let configured = repo.signature().ok();
+        let name = self
+            .author_name
+            .as_deref()
+            .or_else(|| configured.as_ref().and_then(|s| s.name()))
+            .unwrap_or("Shulkerscript CLI");
+        let email = self
+            .author_email
+            .as_deref()
+            .or_else(|| configured.as_ref().and_then(|s| s.email()))
+            .unwrap_or("cli@shulkerscript");
+
+        Ok(GitSignature::now(name, email)?)
+    }
+}
+
+/// The icon of the new project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Icon<'a> {
+    /// The bundled default icon.
+    Default,
+    /// No pack.png file.
+    None,
+    /// A copy of the given png file.
+    Custom(Cow<'a, Path>),
+}
+
+impl<'a> Icon<'a> {
+    /// Get the icon selected by the arguments, falling back to the default of the global config.
+    fn from_args(args: &'a InitArgs, defaults: &InitDefaults) -> Option<Self> {
+        if args.no_icon {
+            Some(Self::None)
+        } else if let Some(icon_path) = &args.icon_path {
+            Some(Self::Custom(Cow::Borrowed(icon_path)))
+        } else {
+            defaults
+                .icon
+                .map(|icon| if icon { Self::Default } else { Self::None })
+        }
+    }
+}
+
+/// An option of the icon prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconOption {
+    Default,
+    None,
+    Custom,
+}
+
+impl Display for IconOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "Default icon"),
+            Self::None => write!(f, "No icon"),
+            Self::Custom => write!(f, "Custom png file"),
+        }
+    }
+}
+
+pub fn init(args: &InitArgs, options: &GlobalOptions) -> Result<()> {
+    let defaults = match GlobalConfig::load() {
+        Ok(config) => config.init,
+        Err(err) => {
+            print_warning(format!("Could not read the global config: {err}"));
+            InitDefaults::default()
+        }
+    };
+
+    // resolved first, so unknown templates fail before anything is written
+    let locked = fetch::is_locked(args.locked);
+    let template = match args
+        .template
+        .as_deref()
+        .map(|source| Template::resolve(source, args.template_sha256.as_ref(), locked))
+        .transpose()
+    {
+        Ok(template) => template,
+        Err(err) => {
+            if let Some(Error::UnknownTemplateError(name)) = err.downcast_ref::<Error>() {
+                print_error(format!(
+                    "The template \"{name}\" does not exist. The built-in templates are {}, other templates are given by their path or git URL.",
+                    template::BUILTIN
+                        .iter()
+                        .map(|template| template.name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            } else {
+                print_error(format!("Could not fetch the template: {err}"));
+            }
+            return Err(err);
+        }
+    };
+    let template = template.as_ref();
+
+    if let Some(member) = &args.member {
+        initialize_member(member, args, options, &defaults, template)
+    } else if args.batch {
+        initialize_batch(args, &defaults, template)
+    } else if !prompt::is_interactive() {
+        print_warning(
+            "The input is not a terminal, initializing in batch mode. Use --batch to hide this warning.",
+        );
+        initialize_batch(args, &defaults, template)
     } else {
-        initialize_interactive(args)
+        initialize_interactive(args, &defaults, template)
     }
 }
 
-fn initialize_batch(args: &InitArgs) -> Result<()> {
+fn initialize_batch(
+    args: &InitArgs,
+    defaults: &InitDefaults,
+    template: Option<&Template>,
+) -> Result<()> {
     let verbose = args.verbose;
     let force = args.force;
     let path = args.path.as_path();
@@ -105,52 +350,303 @@ fn initialize_batch(args: &InitArgs) -> Result<()> {
         .as_deref()
         .or_else(|| path.file_name().and_then(|os| os.to_str()));
 
+    if let Some(name) = name {
+        if !util::is_valid_file_name(name) {
+            print_error(format!(
+                "The project name \"{name}\" cannot be used as file name. {INVALID_NAME_HELP}"
+            ));
+            return Err(Error::InvalidProjectNameError(name.to_string()).into());
+        }
+        if args.namespace.is_none() {
+            warn_dropped_namespace_chars(name);
+        }
+    }
+
+    let namespace = args
+        .namespace
+        .clone()
+        .unwrap_or_else(|| name_to_namespace(name.unwrap_or(PackConfig::DEFAULT_NAME)));
+    if namespace.is_empty() && args.namespace.is_none() {
+        print_error("The project name contains no characters usable in a namespace. Set the namespace with --namespace.");
+        return Err(Error::InvalidNamespaceError(namespace).into());
+    } else if !util::is_valid_namespace(&namespace) {
+        print_error(format!(
+            "The namespace \"{namespace}\" is invalid. Namespaces may only contain the characters a-z, 0-9, _, - and . and must not be empty."
+        ));
+        return Err(Error::InvalidNamespaceError(namespace).into());
+    }
+
     print_info("Initializing a new Shulkerscript project in batch mode...");
 
     // Create the pack.toml file
-    create_pack_config(verbose, path, name, description, pack_format)?;
+    create_pack_config(verbose, path, name, &namespace, description, pack_format)?;
 
     // Create the pack.png file
-    create_pack_png(path, args.icon_path.as_deref(), verbose)?;
+    create_pack_png(
+        path,
+        &Icon::from_args(args, defaults).unwrap_or(Icon::Default),
+        verbose,
+    )?;
 
     // Create the src directory
     let src_path = path.join("src");
     create_dir(&src_path, verbose)?;
 
-    // Create the main.shu file
-    create_main_file(
+    // Create the main.shu file or the files of the template
+    create_source_files(
+        path,
+        template,
+        name.unwrap_or(PackConfig::DEFAULT_NAME),
+        &namespace,
+        description.unwrap_or(PackConfig::DEFAULT_DESCRIPTION),
+        verbose,
+    )?;
+
+    // Create the optional starter files
+    create_extra_files(
         path,
-        &name_to_namespace(name.unwrap_or(PackConfig::DEFAULT_NAME)),
+        &ExtraFiles {
+            readme: args.readme,
+            editor_config: args.editor_config,
+        },
+        name.unwrap_or(PackConfig::DEFAULT_NAME),
+        description.unwrap_or(PackConfig::DEFAULT_DESCRIPTION),
         verbose,
     )?;
 
     // Initialize the version control system
-    initalize_vcs(path, vcs, verbose)?;
+    initalize_vcs(path, vcs, &GitOptions::new(args, defaults), verbose)?;
 
     print_success("Project initialized successfully.");
 
     Ok(())
 }
 
-fn initialize_interactive(args: &InitArgs) -> Result<()> {
+fn initialize_member(
+    member: &str,
+    args: &InitArgs,
+    options: &GlobalOptions,
+    defaults: &InitDefaults,
+    template: Option<&Template>,
+) -> Result<()> {
+    let verbose = args.verbose;
+
+    let Some(root) = Workspace::find_root(&args.path, options.discover()) else {
+        print_error(format!(
+            "No workspace found at {}. Add a [workspace] section to the pack.toml file of the workspace root first.",
+            args.path.display()
+        ));
+        return Err(Error::NoWorkspaceError(args.path.clone()).into());
+    };
+
+    if !util::is_valid_file_name(member) {
+        print_error(format!(
+            "The member name \"{member}\" cannot be used as file name. {INVALID_NAME_HELP}"
+        ));
+        return Err(Error::InvalidProjectNameError(member.to_string()).into());
+    }
+    let path = root.join(MEMBERS_DIR).join(member);
+    if !args.force && path.is_dir() && path.read_dir()?.next().is_some() {
+        print_error(format!(
+            "The directory {} of the member is not empty.",
+            path.display()
+        ));
+        return Err(Error::NonEmptyDirectoryError(path).into());
+    }
+
+    if args.namespace.is_none() {
+        warn_dropped_namespace_chars(member);
+    }
+    let namespace = args
+        .namespace
+        .clone()
+        .unwrap_or_else(|| name_to_namespace(member));
+    if !util::is_valid_namespace(&namespace) {
+        print_error(format!(
+            "The namespace \"{namespace}\" is invalid. Namespaces may only contain the characters a-z, 0-9, _, - and . and must not be empty. Set the namespace with --namespace."
+        ));
+        return Err(Error::InvalidNamespaceError(namespace).into());
+    }
+
+    print_info(format!(
+        "Creating the workspace member {member} in {}...",
+        path.display()
+    ));
+    fs::create_dir_all(&path)?;
+
+    // Create the shared config, the pack format of an existing one is overridden by the member
+    let shared_created = create_shared_config(&root, args.pack_format)?;
+    create_member_config(
+        &path,
+        member,
+        &namespace,
+        args.description.as_deref(),
+        args.pack_format.filter(|_| !shared_created),
+        verbose,
+    )?;
+
+    create_pack_png(
+        &path,
+        &Icon::from_args(args, defaults).unwrap_or(Icon::Default),
+        verbose,
+    )?;
+    create_dir(&path.join("src"), verbose)?;
+    create_source_files(
+        &path,
+        template,
+        member,
+        &namespace,
+        args.description
+            .as_deref()
+            .unwrap_or(PackConfig::DEFAULT_DESCRIPTION),
+        verbose,
+    )?;
+    create_extra_files(
+        &path,
+        &ExtraFiles {
+            readme: args.readme,
+            editor_config: args.editor_config,
+        },
+        member,
+        args.description
+            .as_deref()
+            .unwrap_or(PackConfig::DEFAULT_DESCRIPTION),
+        verbose,
+    )?;
+
+    register_member(&root, &format!("{MEMBERS_DIR}/{member}"), verbose)?;
+
+    // the workspace usually is a repository already, so nothing is committed unless requested
+    initalize_vcs(
+        &path,
+        args.vcs.unwrap_or(VersionControlSystem::None),
+        &GitOptions::new(args, defaults),
+        verbose,
+    )?;
+
+    print_success(format!("Workspace member {member} created successfully."));
+
+    Ok(())
+}
+
+/// Create the config file shared by the workspace members, if it does not exist.
+///
+/// Returns whether the file was created.
+fn create_shared_config(root: &Path, pack_format: Option<u8>) -> Result<bool> {
+    let path = root.join(SHARED_CONFIG);
+    if path.exists() {
+        return Ok(false);
+    }
+
+    let defaults = PackConfig::default();
+    let mut document = toml_edit::DocumentMut::new();
+    document["pack"] = toml_edit::table();
+    document["pack"]["format"] =
+        toml_edit::value(i64::from(pack_format.unwrap_or(defaults.pack_format)));
+    document["pack"]["version"] = toml_edit::value(defaults.version);
+
+    fs::write(
+        &path,
+        format!(
+            "# Settings shared by the members of the workspace, extended by their pack.toml files\n{document}"
+        ),
+    )?;
+    print_info(format!(
+        "Created the shared config {} of the workspace members.",
+        path.display()
+    ));
+    Ok(true)
+}
+
+/// Create the pack.toml file of a workspace member extending the shared config.
+fn create_member_config(
+    path: &Path,
+    name: &str,
+    namespace: &str,
+    description: Option<&str>,
+    pack_format: Option<u8>,
+    verbose: bool,
+) -> Result<()> {
+    let mut document = toml_edit::DocumentMut::new();
+    document["extends"] = toml_edit::value(format!("../../{SHARED_CONFIG}"));
+    document["pack"] = toml_edit::table();
+    document["pack"]["name"] = toml_edit::value(name);
+    document["pack"]["namespace"] = toml_edit::value(namespace);
+    document["pack"]["description"] =
+        toml_edit::value(description.unwrap_or(PackConfig::DEFAULT_DESCRIPTION));
+    if let Some(pack_format) = pack_format {
+        document["pack"]["format"] = toml_edit::value(i64::from(pack_format));
+    }
+
+    let toml_path = path.join("pack.toml");
+    fs::write(&toml_path, document.to_string())?;
+    if verbose {
+        print_info(format!(
+            "Created pack.toml file at {}.",
+            toml_path.absolutize()?.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Add the member to the `members` of the `[workspace]` section, unless a pattern includes it.
+fn register_member(root: &Path, member: &str, verbose: bool) -> Result<()> {
+    let toml_path = root.join("pack.toml");
+    let mut document = fs::read_to_string(&toml_path)?.parse::<toml_edit::DocumentMut>()?;
+
+    let members = document["workspace"]["members"]
+        .or_insert(toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()
+        .ok_or_else(|| Error::InvalidConfigError(toml_path.clone()))?;
+    let registered = members
+        .iter()
+        .filter_map(|entry| entry.as_str())
+        .any(|entry| {
+            let entry = entry.trim_end_matches('/');
+            entry == member
+                || entry
+                    .strip_suffix('*')
+                    .is_some_and(|parent| parent.trim_end_matches('/') == MEMBERS_DIR)
+        });
+
+    if registered {
+        if verbose {
+            print_info(format!(
+                "The member {member} is already included in the members of the workspace."
+            ));
+        }
+    } else {
+        members.push(member);
+        fs::write(&toml_path, document.to_string())?;
+        print_info(format!(
+            "Added {member} to the members of the workspace in {}.",
+            toml_path.display()
+        ));
+    }
+    Ok(())
+}
+
+fn initialize_interactive(
+    args: &InitArgs,
+    defaults: &InitDefaults,
+    template: Option<&Template>,
+) -> Result<()> {
     const ABORT_MSG: &str = "Project initialization interrupted. Aborting...";
 
     let verbose = args.verbose;
     let force = args.force;
     let path = args.path.as_path();
-    let description = args.description.as_deref();
-    let pack_format = args.pack_format;
 
     if !path.exists() {
         if force {
             fs::create_dir_all(path)?;
         } else {
-            match inquire::Confirm::new(
-                "The specified path does not exist. Do you want to create it?",
-            )
-            .with_default(true)
-            .prompt()
-            {
+            match prompt::confirm(
+                inquire::Confirm::new(
+                    "The specified path does not exist. Do you want to create it?",
+                )
+                .with_default(true),
+            ) {
                 Ok(true) => fs::create_dir_all(path)?,
                 Ok(false) | Err(_) => {
                     print_info(ABORT_MSG);
@@ -162,13 +658,11 @@ fn initialize_interactive(args: &InitArgs) -> Result<()> {
         print_error("The specified path is not a directory.");
         Err(Error::NotDirectoryError(path.to_path_buf()))?
     } else if !force && path.read_dir()?.next().is_some() {
-        match inquire::Confirm::new(
-            "The specified directory is not empty. Do you want to continue?",
-        )
-        .with_default(false)
-        .with_help_message("This may overwrite existing files in the directory.")
-        .prompt()
-        {
+        match prompt::confirm(
+            inquire::Confirm::new("The specified directory is not empty. Do you want to continue?")
+                .with_default(false)
+                .with_help_message("This may overwrite existing files in the directory."),
+        ) {
             Ok(false) | Err(_) => {
                 print_info(ABORT_MSG);
                 return Err(inquire::InquireError::OperationCanceled.into());
@@ -177,134 +671,52 @@ fn initialize_interactive(args: &InitArgs) -> Result<()> {
         }
     }
 
-    let mut interrupted = false;
-
-    let name = args.name.as_deref().map(Cow::Borrowed).or_else(|| {
-        let default = path
-            .file_name()
-            .and_then(|os| os.to_str())
-            .unwrap_or(PackConfig::DEFAULT_NAME);
-
-        match inquire::Text::new("Enter the name of the project:")
-            .with_help_message("This will be the name of your datapack folder/zip file")
-            .with_default(default)
-            .prompt()
-        {
-            Ok(res) => Some(Cow::Owned(res)),
-            Err(_) => {
-                interrupted = true;
-                None
-            }
+    if let Some(name) = &args.name {
+        if !util::is_valid_file_name(name) {
+            print_error(format!(
+                "The project name \"{name}\" cannot be used as file name. {INVALID_NAME_HELP}"
+            ));
+            return Err(Error::InvalidProjectNameError(name.to_string()).into());
         }
-        .or_else(|| {
-            path.file_name()
-                .and_then(|os| os.to_str().map(Cow::Borrowed))
-        })
-    });
-
-    if interrupted {
-        print_info(ABORT_MSG);
-        return Err(inquire::InquireError::OperationCanceled.into());
-    }
-
-    let description = description.map(Cow::Borrowed).or_else(||  {
-        match inquire::Text::new("Enter the description of the project:")
-            .with_help_message("This will be the description of your datapack, visible in the datapack selection screen")
-            .with_default(PackConfig::DEFAULT_DESCRIPTION)
-            .prompt() {
-                Ok(res) => Some(Cow::Owned(res)),
-                Err(_) => {
-                    interrupted = true;
-                    None
-                }
-            }
-    });
-
-    if interrupted {
-        print_info(ABORT_MSG);
-        return Err(inquire::InquireError::OperationCanceled.into());
-    }
-
-    let pack_format = pack_format.or_else(|| {
-        match inquire::Text::new("Enter the pack format:")
-            .with_help_message("This will determine the Minecraft version compatible with your pack, find more on the Minecraft wiki")
-            .with_default(PackConfig::DEFAULT_PACK_FORMAT.to_string().as_str())
-            .with_validator(|v: &str| Ok(
-                v.parse::<u8>()
-                .map(|_| Validation::Valid)
-                .unwrap_or(Validation::Invalid(
-                    inquire::validator::ErrorMessage::Custom("Invalid pack format".to_string())))))
-            .prompt() {
-                Ok(res) => res.parse().ok(),
-                Err(_) => {
-                    interrupted = true;
-                    None
-                }
-            }
-    });
-
-    if interrupted {
-        print_info(ABORT_MSG);
-        return Err(inquire::InquireError::OperationCanceled.into());
     }
-
-    let vcs = args.vcs.unwrap_or_else(|| {
-        match inquire::Select::new(
-            "Select the version control system:",
-            vec![VersionControlSystem::Git, VersionControlSystem::None],
-        )
-        .with_help_message("This will initialize a version control system")
-        .prompt()
-        {
-            Ok(res) => res,
-            Err(_) => {
-                interrupted = true;
-                VersionControlSystem::Git
-            }
+    if let Some(namespace) = &args.namespace {
+        if !util::is_valid_namespace(namespace) {
+            print_error(format!(
+                "The namespace \"{namespace}\" is invalid. Namespaces may only contain the characters a-z, 0-9, _, - and . and must not be empty."
+            ));
+            return Err(Error::InvalidNamespaceError(namespace.clone()).into());
         }
-    });
+    }
 
-    if interrupted {
+    let Ok(mut answers) = Answers::prompt(args, defaults, path) else {
         print_info(ABORT_MSG);
         return Err(inquire::InquireError::OperationCanceled.into());
-    }
+    };
 
-    let icon_path = args.icon_path.as_deref().map(Cow::Borrowed).or_else(|| {
-        let autocompleter = crate::util::PathAutocomplete::new();
-        match inquire::Text::new("Enter the path of the icon file:")
-            .with_help_message(
-                "This will be the icon of your datapack, visible in the datapack selection screen [use \"-\" for default]",
-            )
-            .with_autocomplete(autocompleter)
-            .with_validator(|s: &str| {
-                if s == "-" {
-                    Ok(Validation::Valid)
-                } else {
-                    let path = Path::new(s);
-                    if path.exists() && path.is_file() && path.extension().is_some_and(|ext| ext == "png") {
-                        Ok(Validation::Valid)
-                    } else {
-                        Ok(Validation::Invalid(
-                            inquire::validator::ErrorMessage::Custom("Invalid file path. Path must exist and point to a png".to_string()),
-                        ))
-                    }
+    loop {
+        answers.print_summary(path, template);
+        let options = iter::once(SummaryOption::Create)
+            .chain(Setting::ALL.into_iter().map(SummaryOption::Edit))
+            .chain(iter::once(SummaryOption::Abort))
+            .collect::<Vec<_>>();
+        match prompt::select(
+            inquire::Select::new("Create the project with these settings?", options)
+                .with_help_message("Select a setting to change it before creating the project"),
+        ) {
+            Ok(SummaryOption::Create) => break,
+            Ok(SummaryOption::Edit(setting)) => match answers.edit(setting) {
+                // going back from a prompt keeps the previous answer
+                Ok(()) | Err(inquire::InquireError::OperationCanceled) => {}
+                Err(_) => {
+                    print_info(ABORT_MSG);
+                    return Err(inquire::InquireError::OperationCanceled.into());
                 }
-            })
-            .with_default("-")
-            .prompt()
-        {
-            Ok(res) if &res == "-" => None,
-            Ok(res) => Some(Cow::Owned(PathBuf::from(res))),
-            Err(_) => {
-                interrupted = true;
-                None
+            },
+            Ok(SummaryOption::Abort) | Err(_) => {
+                print_info(ABORT_MSG);
+                return Err(inquire::InquireError::OperationCanceled.into());
             }
         }
-    });
-
-    if interrupted {
-        print_info(ABORT_MSG);
-        return Err(inquire::InquireError::OperationCanceled.into());
     }
 
     print_info("Initializing a new Shulkerscript project...");
@@ -313,37 +725,424 @@ fn initialize_interactive(args: &InitArgs) -> Result<()> {
     create_pack_config(
         verbose,
         path,
-        name.as_deref(),
-        description.as_deref(),
-        pack_format,
+        Some(&answers.name),
+        &answers.namespace,
+        Some(&answers.description),
+        Some(answers.pack_format),
     )?;
 
     // Create the pack.png file
-    create_pack_png(path, icon_path.as_deref(), verbose)?;
+    create_pack_png(path, &answers.icon, verbose)?;
 
     // Create the src directory
     let src_path = path.join("src");
     create_dir(&src_path, verbose)?;
 
-    // Create the main.shu file
-    create_main_file(
+    // Create the main.shu file or the files of the template
+    create_source_files(
+        path,
+        template,
+        &answers.name,
+        &answers.namespace,
+        &answers.description,
+        verbose,
+    )?;
+
+    // Create the optional starter files
+    create_extra_files(
         path,
-        &name_to_namespace(&name.unwrap_or(Cow::Borrowed("shulkerscript-pack"))),
+        &answers.extra_files,
+        &answers.name,
+        &answers.description,
         verbose,
     )?;
 
     // Initialize the version control system
-    initalize_vcs(path, vcs, verbose)?;
+    initalize_vcs(path, answers.vcs, &GitOptions::new(args, defaults), verbose)?;
 
     print_success("Project initialized successfully.");
 
     Ok(())
 }
 
+/// The settings of an interactively initialized project, reviewed before creating it.
+#[derive(Debug, Clone)]
+struct Answers<'a> {
+    name: String,
+    namespace: String,
+    description: String,
+    pack_format: u8,
+    icon: Icon<'a>,
+    vcs: VersionControlSystem,
+    extra_files: ExtraFiles,
+}
+
+impl<'a> Answers<'a> {
+    /// Prompt for the settings not given by the arguments.
+    fn prompt(args: &'a InitArgs, defaults: &InitDefaults, path: &Path) -> InquireResult<Self> {
+        let name = match &args.name {
+            Some(name) => name.clone(),
+            None => prompt_name(
+                path.file_name()
+                    .and_then(|os| os.to_str())
+                    .filter(|name| util::is_valid_file_name(name))
+                    .unwrap_or(PackConfig::DEFAULT_NAME),
+            )?,
+        };
+        let namespace = match &args.namespace {
+            Some(namespace) => namespace.clone(),
+            None => prompt_namespace(&name, None)?,
+        };
+        let description = match &args.description {
+            Some(description) => description.clone(),
+            None => prompt_description(PackConfig::DEFAULT_DESCRIPTION)?,
+        };
+        let pack_format = match args.pack_format {
+            Some(pack_format) => pack_format,
+            None => prompt_pack_format(PackConfig::DEFAULT_PACK_FORMAT)?,
+        };
+        let vcs = match args.vcs {
+            Some(vcs) => vcs,
+            None => prompt_vcs(VersionControlSystem::Git)?,
+        };
+        let icon = match Icon::from_args(args, defaults) {
+            Some(icon) => icon,
+            None => prompt_icon(&Icon::Default)?,
+        };
+        let extra_files = if args.readme || args.editor_config {
+            ExtraFiles {
+                readme: args.readme,
+                editor_config: args.editor_config,
+            }
+        } else {
+            prompt_extra_files(ExtraFiles::default())?
+        };
+
+        Ok(Self {
+            name,
+            namespace,
+            description,
+            pack_format,
+            icon,
+            vcs,
+            extra_files,
+        })
+    }
+
+    /// Prompt for the setting again, defaulting to the current answer.
+    fn edit(&mut self, setting: Setting) -> InquireResult<()> {
+        match setting {
+            Setting::Name => {
+                let derived = self.namespace == name_to_namespace(&self.name);
+                self.name = prompt_name(&self.name)?;
+                let namespace = name_to_namespace(&self.name);
+                if derived && util::is_valid_namespace(&namespace) {
+                    self.namespace = namespace;
+                }
+            }
+            Setting::Namespace => {
+                self.namespace = prompt_namespace(&self.name, Some(&self.namespace))?;
+            }
+            Setting::Description => self.description = prompt_description(&self.description)?,
+            Setting::PackFormat => self.pack_format = prompt_pack_format(self.pack_format)?,
+            Setting::Icon => self.icon = prompt_icon(&self.icon)?,
+            Setting::Vcs => self.vcs = prompt_vcs(self.vcs)?,
+            Setting::ExtraFiles => self.extra_files = prompt_extra_files(self.extra_files)?,
+        }
+        Ok(())
+    }
+
+    fn print_summary(&self, path: &Path, template: Option<&Template>) {
+        let path = path
+            .absolutize()
+            .map_or_else(|_| path.to_path_buf(), |path| path.to_path_buf());
+        let versions = versions::pack_format(self.pack_format).map_or_else(
+            || "unknown Minecraft versions".to_string(),
+            |entry| format!("Minecraft {}", entry.versions),
+        );
+        let icon = match &self.icon {
+            Icon::Default => "default icon".to_string(),
+            Icon::None => "none".to_string(),
+            Icon::Custom(path) => path.display().to_string(),
+        };
+        let extra_files = [
+            (self.extra_files.readme, "README.md"),
+            (self.extra_files.editor_config, ".editorconfig, .vscode"),
+        ]
+        .into_iter()
+        .filter_map(|(selected, files)| selected.then_some(files))
+        .collect::<Vec<_>>();
+
+        print_info("The project will be created with the following settings:");
+        for (label, value) in [
+            ("Path", path.display().to_string()),
+            ("Name", self.name.clone()),
+            ("Namespace", self.namespace.clone()),
+            ("Description", self.description.clone()),
+            ("Pack format", format!("{} ({versions})", self.pack_format)),
+            ("Icon", icon),
+            ("VCS", self.vcs.to_string()),
+            (
+                "Template",
+                template.map_or_else(|| "none".to_string(), Template::to_string),
+            ),
+            (
+                "Extra files",
+                if extra_files.is_empty() {
+                    "none".to_string()
+                } else {
+                    extra_files.join(", ")
+                },
+            ),
+        ] {
+            print_plain(format!("  {:<13}{value}", format!("{label}:").bold()));
+        }
+    }
+}
+
+/// A setting that can be changed in the summary of the interactive initialization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    Name,
+    Namespace,
+    Description,
+    PackFormat,
+    Icon,
+    Vcs,
+    ExtraFiles,
+}
+
+impl Setting {
+    const ALL: [Self; 7] = [
+        Self::Name,
+        Self::Namespace,
+        Self::Description,
+        Self::PackFormat,
+        Self::Icon,
+        Self::Vcs,
+        Self::ExtraFiles,
+    ];
+}
+
+/// An option of the summary prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SummaryOption {
+    Create,
+    Edit(Setting),
+    Abort,
+}
+
+impl Display for SummaryOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Create => write!(f, "Create the project"),
+            Self::Edit(setting) => {
+                let setting = match setting {
+                    Setting::Name => "name",
+                    Setting::Namespace => "namespace",
+                    Setting::Description => "description",
+                    Setting::PackFormat => "pack format",
+                    Setting::Icon => "icon",
+                    Setting::Vcs => "version control system",
+                    Setting::ExtraFiles => "additional files",
+                };
+                write!(f, "Change the {setting}")
+            }
+            Self::Abort => write!(f, "Abort"),
+        }
+    }
+}
+
+fn prompt_name(default: &str) -> InquireResult<String> {
+    prompt::text(
+        inquire::Text::new("Enter the name of the project:")
+            .with_help_message("This will be the name of your datapack folder/zip file")
+            .with_default(default)
+            .with_validator(|v: &str| {
+                if util::is_valid_file_name(v) {
+                    Ok(Validation::Valid)
+                } else {
+                    Ok(Validation::Invalid(
+                        inquire::validator::ErrorMessage::Custom(INVALID_NAME_HELP.to_string()),
+                    ))
+                }
+            }),
+    )
+}
+
+/// Prompt for the namespace, defaulting to the current one or the one derived from the name.
+fn prompt_namespace(name: &str, current: Option<&str>) -> InquireResult<String> {
+    let derived = name_to_namespace(name);
+    if current.is_none() {
+        warn_dropped_namespace_chars(name);
+    }
+
+    let help_message = if current.is_some() {
+        "This will be the namespace used for your functions, e.g. in the generated main.shu"
+            .to_string()
+    } else if derived.is_empty() {
+        "The project name contains no characters usable in a namespace, please enter one"
+            .to_string()
+    } else if derived != name {
+        format!("Derived from the project name \"{name}\", used for your functions, e.g. in the generated main.shu")
+    } else {
+        "This will be the namespace used for your functions, e.g. in the generated main.shu"
+            .to_string()
+    };
+
+    let default = current.unwrap_or(&derived);
+    let mut namespace_prompt =
+        inquire::Text::new("Enter the namespace of the datapack:").with_help_message(&help_message);
+    if !default.is_empty() {
+        namespace_prompt = namespace_prompt.with_default(default);
+    }
+
+    prompt::text(namespace_prompt
+        .with_validator(|v: &str| {
+            if util::is_valid_namespace(v) {
+                Ok(Validation::Valid)
+            } else {
+                Ok(Validation::Invalid(inquire::validator::ErrorMessage::Custom(
+                    "Namespaces may only contain the characters a-z, 0-9, _, - and . and must not be empty".to_string(),
+                )))
+            }
+        })
+    )
+}
+
+fn prompt_description(default: &str) -> InquireResult<String> {
+    prompt::text(inquire::Text::new("Enter the description of the project:")
+        .with_help_message(
+            "This will be the description of your datapack, visible in the datapack selection screen",
+        )
+        .with_default(default)
+        )
+}
+
+fn prompt_pack_format(default: u8) -> InquireResult<u8> {
+    let options = versions::PACK_FORMATS
+        .iter()
+        .map(PackFormatOption::Known)
+        .chain(iter::once(PackFormatOption::Manual))
+        .collect::<Vec<_>>();
+    let default_index = versions::PACK_FORMATS
+        .iter()
+        .position(|entry| entry.format == default)
+        .unwrap_or(versions::PACK_FORMATS.len());
+
+    match prompt::select(
+        inquire::Select::new("Select the pack format:", options)
+            .with_help_message(
+                "This will determine the Minecraft versions compatible with your pack",
+            )
+            .with_starting_cursor(default_index),
+    )? {
+        PackFormatOption::Known(entry) => Ok(entry.format),
+        PackFormatOption::Manual => {
+            prompt::text(
+                inquire::Text::new("Enter the pack format:")
+                    .with_help_message(
+                        "Find the pack format of your Minecraft version on the Minecraft wiki",
+                    )
+                    .with_default(default.to_string().as_str())
+                    .with_validator(|v: &str| {
+                        Ok(v.parse::<u8>().map(|_| Validation::Valid).unwrap_or(
+                            Validation::Invalid(inquire::validator::ErrorMessage::Custom(
+                                "Invalid pack format".to_string(),
+                            )),
+                        ))
+                    }),
+            )
+            .map(|res| res.parse().unwrap_or(default))
+        }
+    }
+}
+
+fn prompt_vcs(default: VersionControlSystem) -> InquireResult<VersionControlSystem> {
+    let options = vec![
+        VersionControlSystem::Git,
+        VersionControlSystem::GitNoCommit,
+        VersionControlSystem::None,
+    ];
+    let default_index = options
+        .iter()
+        .position(|vcs| *vcs == default)
+        .unwrap_or_default();
+    prompt::select(
+        inquire::Select::new("Select the version control system:", options)
+            .with_help_message("This will initialize a version control system")
+            .with_starting_cursor(default_index),
+    )
+}
+
+fn prompt_icon<'a>(current: &Icon<'a>) -> InquireResult<Icon<'a>> {
+    let options = vec![IconOption::Default, IconOption::None, IconOption::Custom];
+    let default_index = match current {
+        Icon::Default => 0,
+        Icon::None => 1,
+        Icon::Custom(_) => 2,
+    };
+    match prompt::select(
+        inquire::Select::new("Select the icon of the datapack:", options)
+            .with_help_message(
+                "This will be the icon of your datapack, visible in the datapack selection screen",
+            )
+            .with_starting_cursor(default_index),
+    )? {
+        IconOption::Default => Ok(Icon::Default),
+        IconOption::None => Ok(Icon::None),
+        IconOption::Custom => {
+            let default = match current {
+                Icon::Custom(path) => path.display().to_string(),
+                _ => String::new(),
+            };
+            let autocompleter = crate::util::PathAutocomplete::new();
+            prompt::text(
+                inquire::Text::new("Enter the path of the icon file:")
+                    .with_autocomplete(autocompleter)
+                    .with_initial_value(&default)
+                    .with_validator(|s: &str| {
+                        let path = Path::new(s);
+                        if path.is_file() && path.extension().is_some_and(|ext| ext == "png") {
+                            Ok(Validation::Valid)
+                        } else {
+                            Ok(Validation::Invalid(
+                                inquire::validator::ErrorMessage::Custom(
+                                    "Invalid file path. Path must exist and point to a png"
+                                        .to_string(),
+                                ),
+                            ))
+                        }
+                    }),
+            )
+            .map(|res| Icon::Custom(Cow::Owned(PathBuf::from(res))))
+        }
+    }
+}
+
+fn prompt_extra_files(current: ExtraFiles) -> InquireResult<ExtraFiles> {
+    let options = vec![ExtraFile::Readme, ExtraFile::EditorConfig];
+    let selected = [current.readme, current.editor_config]
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, selected)| selected.then_some(index))
+        .collect::<Vec<_>>();
+    let selected = prompt::multi_select(
+        inquire::MultiSelect::new("Select additional files to generate:", options)
+            .with_help_message("Use space to toggle, enter to confirm")
+            .with_default(&selected),
+    )?;
+    Ok(ExtraFiles {
+        readme: selected.contains(&ExtraFile::Readme),
+        editor_config: selected.contains(&ExtraFile::EditorConfig),
+    })
+}
+
 fn create_pack_config(
     verbose: bool,
     base_path: &Path,
     name: Option<&str>,
+    namespace: &str,
     description: Option<&str>,
     pack_format: Option<u8>,
 ) -> Result<()> {
@@ -355,6 +1154,7 @@ fn create_pack_config(
     if let Some(name) = name {
         content.pack.name = name.to_string();
     }
+    content.pack.namespace = Some(namespace.to_string());
     if let Some(description) = description {
         content.pack.description = description.to_string();
     }
@@ -387,37 +1187,86 @@ fn create_dir(path: &Path, verbose: bool) -> std::io::Result<()> {
 
 fn create_gitignore(path: &Path, verbose: bool) -> std::io::Result<()> {
     let gitignore = path.join(".gitignore");
-    fs::write(&gitignore, "/dist\n")?;
-    if verbose {
-        print_info(format!(
-            "Created .gitignore file at {}.",
-            gitignore.absolutize()?.display()
-        ));
+    if gitignore.is_file() {
+        let mut content = fs::read_to_string(&gitignore)?;
+        if !content.lines().any(|line| line.trim() == "/dist") {
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str("/dist\n");
+            fs::write(&gitignore, content)?;
+            if verbose {
+                print_info(format!(
+                    "Added /dist to the .gitignore file at {}.",
+                    gitignore.absolutize()?.display()
+                ));
+            }
+        }
+    } else {
+        fs::write(&gitignore, "/dist\n")?;
+        if verbose {
+            print_info(format!(
+                "Created .gitignore file at {}.",
+                gitignore.absolutize()?.display()
+            ));
+        }
     }
     Ok(())
 }
 
-fn create_pack_png(
-    project_path: &Path,
-    icon_path: Option<&Path>,
-    verbose: bool,
-) -> std::io::Result<()> {
+fn create_pack_png(project_path: &Path, icon: &Icon, verbose: bool) -> std::io::Result<()> {
     let pack_png = project_path.join("pack.png");
-    if let Some(icon_path) = icon_path {
-        fs::copy(icon_path, &pack_png)?;
-        if verbose {
-            print_info(format!(
-                "Copied pack.png file from {} to {}.",
-                icon_path.absolutize()?.display(),
-                pack_png.absolutize()?.display()
-            ));
+    match icon {
+        Icon::Custom(icon_path) => {
+            fs::copy(icon_path, &pack_png)?;
+            if verbose {
+                print_info(format!(
+                    "Copied pack.png file from {} to {}.",
+                    icon_path.absolutize()?.display(),
+                    pack_png.absolutize()?.display()
+                ));
+            }
         }
-    } else {
-        fs::write(&pack_png, include_bytes!("../../assets/default-icon.png"))?;
+        Icon::Default => {
+            fs::write(&pack_png, include_bytes!("../../assets/default-icon.png"))?;
+            if verbose {
+                print_info(format!(
+                    "Created pack.png file at {}.",
+                    pack_png.absolutize()?.display()
+                ));
+            }
+        }
+        Icon::None => {
+            if verbose {
+                print_info("Skipped creating the pack.png file.");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Create the main.shu file, or the files of the template if one is given.
+fn create_source_files(
+    path: &Path,
+    template: Option<&Template>,
+    name: &str,
+    namespace: &str,
+    description: &str,
+    verbose: bool,
+) -> Result<()> {
+    let Some(template) = template else {
+        return Ok(create_main_file(path, namespace, verbose)?);
+    };
+    let values = [
+        ("name", name),
+        ("namespace", namespace),
+        ("description", description),
+    ];
+    for file in template.apply(path, &values)? {
         if verbose {
             print_info(format!(
-                "Created pack.png file at {}.",
-                pack_png.absolutize()?.display()
+                "Created {} from the template {template}.",
+                file.absolutize()?.display()
             ));
         }
     }
@@ -442,50 +1291,169 @@ fn create_main_file(path: &Path, namespace: &str, verbose: bool) -> std::io::Res
     Ok(())
 }
 
-fn initalize_vcs(path: &Path, vcs: VersionControlSystem, verbose: bool) -> Result<()> {
+/// The optional starter files to generate.
+#[derive(Debug, Clone, Copy, Default)]
+struct ExtraFiles {
+    readme: bool,
+    editor_config: bool,
+}
+
+/// An option of the additional files prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExtraFile {
+    Readme,
+    EditorConfig,
+}
+
+impl Display for ExtraFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Readme => write!(f, "README.md with build instructions"),
+            Self::EditorConfig => write!(f, ".editorconfig and VS Code settings"),
+        }
+    }
+}
+
+fn create_extra_files(
+    path: &Path,
+    extra_files: &ExtraFiles,
+    name: &str,
+    description: &str,
+    verbose: bool,
+) -> std::io::Result<()> {
+    let mut files = Vec::<(PathBuf, Cow<str>)>::new();
+    if extra_files.readme {
+        files.push((
+            path.join("README.md"),
+            Cow::Owned(format!(
+                include_str!("../../assets/default-readme.md"),
+                name = name,
+                description = description
+            )),
+        ));
+    }
+    if extra_files.editor_config {
+        files.push((
+            path.join(".editorconfig"),
+            Cow::Borrowed(include_str!("../../assets/default-editorconfig")),
+        ));
+        files.push((
+            path.join(".vscode").join("settings.json"),
+            Cow::Borrowed(include_str!("../../assets/vscode/settings.json")),
+        ));
+        files.push((
+            path.join(".vscode").join("extensions.json"),
+            Cow::Borrowed(include_str!("../../assets/vscode/extensions.json")),
+        ));
+    }
+
+    for (file_path, content) in files {
+        if let Some(parent) = file_path.parent() {
+            create_dir(parent, verbose)?;
+        }
+        fs::write(&file_path, content.as_ref())?;
+        if verbose {
+            print_info(format!(
+                "Created {} file at {}.",
+                file_path.file_name().unwrap_or_default().to_string_lossy(),
+                file_path.absolutize()?.display()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Initialize Git in the project like `init`, creating a single commit of all files.
+///
+/// The commit author and initial branch are taken from the global config.
+pub(super) fn initialize_git_squashed(path: &Path, verbose: bool) -> Result<()> {
+    let defaults = GlobalConfig::load()
+        .map(|config| config.init)
+        .unwrap_or_default();
+    let options = GitOptions {
+        author_name: defaults.author_name,
+        author_email: defaults.author_email,
+        initial_branch: defaults.initial_branch,
+        squash: true,
+        explicit: false,
+    };
+
+    initalize_vcs(path, VersionControlSystem::Git, &options, verbose)
+}
+
+fn initalize_vcs(
+    path: &Path,
+    vcs: VersionControlSystem,
+    options: &GitOptions,
+    verbose: bool,
+) -> Result<()> {
     match vcs {
         VersionControlSystem::None => Ok(()),
-        VersionControlSystem::Git => {
+        VersionControlSystem::Git | VersionControlSystem::GitNoCommit => {
+            if let Some(repo) = GitRepository::discover(path)
+                .ok()
+                .filter(|repo| !repo.is_bare())
+            {
+                return add_to_existing_repo(&repo, path, vcs, options, verbose);
+            }
+
             if verbose {
                 print_info("Initializing a new Git repository...");
             }
             // Initalize the Git repository
-            let repo = GitRepository::init(path)?;
+            let mut init_options = GitRepositoryInitOptions::new();
+            if let Some(branch) = &options.initial_branch {
+                init_options.initial_head(branch);
+            }
+            let repo = GitRepository::init_opts(path, &init_options)?;
             repo.add_ignore_rule("/dist")?;
 
             // Create the .gitignore file
             create_gitignore(path, verbose)?;
 
+            if matches!(vcs, VersionControlSystem::GitNoCommit) {
+                print_info("Initialized a new Git repository.");
+                return Ok(());
+            }
+
+            let signature = options.signature(&repo)?;
+            let mut parents = Vec::new();
+
             // Create the initial commit
-            let mut index = repo.index()?;
-            let oid = index.write_tree()?;
-            let tree = repo.find_tree(oid)?;
-            let signature = repo
-                .signature()
-                .unwrap_or(GitSignature::now("Shulkerscript CLI", "cli@shulkerscript")?);
-            repo.commit(
-                Some("HEAD"),
-                &signature,
-                &signature,
-                "Inital commit",
-                &tree,
-                &[],
-            )?;
+            if !options.squash {
+                let mut index = repo.index()?;
+                let oid = index.write_tree()?;
+                let tree = repo.find_tree(oid)?;
+                repo.commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    "Inital commit",
+                    &tree,
+                    &[],
+                )?;
+                parents.push(repo.head()?.peel_to_commit()?);
+            }
 
-            // Create the second commit with the template files
+            // Create the commit with the template files
             let mut index = repo.index()?;
             index.add_all(["."].iter(), GitIndexAddOption::DEFAULT, None)?;
             index.write()?;
             let oid = index.write_tree()?;
             let tree = repo.find_tree(oid)?;
-            let parent = repo.head()?.peel_to_commit()?;
+            let message = if options.squash {
+                "Initial commit"
+            } else {
+                "Add template files"
+            };
             repo.commit(
                 Some("HEAD"),
                 &signature,
                 &signature,
-                "Add template files",
+                message,
                 &tree,
-                &[&parent],
+                &parents.iter().collect::<Vec<_>>(),
             )?;
 
             print_info("Initialized a new Git repository.");
@@ -495,23 +1463,230 @@ fn initalize_vcs(path: &Path, vcs: VersionControlSystem, verbose: bool) -> Resul
     }
 }
 
-fn name_to_namespace(name: &str) -> String {
-    const VALID_CHARS: &str = "0123456789abcdefghijklmnopqrstuvwxyz_-.";
+/// Add the project to the Git repository containing it instead of initializing a nested one.
+///
+/// The template files are staged and committed on the current branch, unless committing is
+/// disabled or other changes are already staged.
+fn add_to_existing_repo(
+    repo: &GitRepository,
+    path: &Path,
+    vcs: VersionControlSystem,
+    options: &GitOptions,
+    verbose: bool,
+) -> Result<()> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("Git repository has no working directory"))?;
+    print_info(format!(
+        "Adding the project to the existing Git repository at {}.",
+        workdir.display()
+    ));
+    if options.explicit {
+        print_warning(
+            "The initial branch and squash options are ignored in existing repositories.",
+        );
+    }
 
-    name.to_lowercase()
-        .chars()
-        .filter_map(|c| {
-            if VALID_CHARS.contains(c) {
-                Some(c)
-            } else if c.is_ascii_uppercase() {
-                Some(c.to_ascii_lowercase())
-            } else if c.is_ascii_punctuation() {
-                Some('-')
-            } else if c.is_ascii_whitespace() {
-                Some('_')
-            } else {
-                None
-            }
-        })
-        .collect()
+    // Create the .gitignore file
+    create_gitignore(path, verbose)?;
+
+    let relative = fs::canonicalize(path)?
+        .strip_prefix(fs::canonicalize(workdir)?)
+        .map(Path::to_path_buf)?;
+    let pathspec = if relative.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        relative.to_string_lossy().replace('\\', "/")
+    };
+
+    let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let mut index = repo.index()?;
+    let already_staged = repo
+        .diff_tree_to_index(
+            head.as_ref()
+                .map(|commit| commit.tree())
+                .transpose()?
+                .as_ref(),
+            Some(&index),
+            None,
+        )?
+        .deltas()
+        .len()
+        > 0;
+
+    // Stage the template files
+    index.add_all([pathspec.as_str()].iter(), GitIndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    if verbose {
+        print_info(format!("Staged the template files in {pathspec}."));
+    }
+
+    if matches!(vcs, VersionControlSystem::GitNoCommit) {
+        print_info("Staged the template files without committing.");
+        return Ok(());
+    }
+    if already_staged {
+        print_warning(
+            "Other changes were already staged, skipping the commit. Commit the template files manually.",
+        );
+        return Ok(());
+    }
+
+    // Commit the template files on the current branch
+    let oid = index.write_tree()?;
+    let tree = repo.find_tree(oid)?;
+    let signature = options.signature(repo)?;
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Add Shulkerscript project",
+        &tree,
+        &head.iter().collect::<Vec<_>>(),
+    )?;
+
+    print_info("Committed the template files on the current branch.");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        terminal_output::{self, Level},
+        testing::TestProject,
+    };
+
+    fn options() -> GitOptions {
+        GitOptions {
+            author_name: Some("Steve".to_string()),
+            author_email: Some("steve@example.com".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn commit_count(repo: &GitRepository) -> usize {
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        revwalk.count()
+    }
+
+    #[test]
+    fn test_initialize_git() {
+        let dir = TestProject::empty("git");
+        fs::write(dir.path().join("pack.toml"), "").unwrap();
+        // the author is taken from the global config unless given on the command line
+        let defaults = InitDefaults {
+            author_name: Some("Alex".to_string()),
+            author_email: Some("steve@example.com".to_string()),
+            ..Default::default()
+        };
+        let args = InitArgs {
+            author_name: Some("Steve".to_string()),
+            ..Default::default()
+        };
+        let options = GitOptions::new(&args, &defaults);
+        initalize_vcs(dir.path(), VersionControlSystem::Git, &options, false).unwrap();
+
+        let repo = GitRepository::open(dir.path()).unwrap();
+        assert_eq!(commit_count(&repo), 2);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("Add template files"));
+        assert_eq!(head.author().name(), Some("Steve"));
+        assert_eq!(head.author().email(), Some("steve@example.com"));
+        assert!(head.tree().unwrap().get_name("pack.toml").is_some());
+    }
+
+    #[test]
+    fn test_initialize_git_squashed_with_branch() {
+        let dir = TestProject::empty("git-squashed");
+        fs::write(dir.path().join("pack.toml"), "").unwrap();
+        let options = GitOptions {
+            initial_branch: Some("trunk".to_string()),
+            squash: true,
+            ..options()
+        };
+        initalize_vcs(dir.path(), VersionControlSystem::Git, &options, false).unwrap();
+
+        let repo = GitRepository::open(dir.path()).unwrap();
+        assert_eq!(commit_count(&repo), 1);
+        assert_eq!(repo.head().unwrap().shorthand(), Some("trunk"));
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("Initial commit"));
+    }
+
+    #[test]
+    fn test_add_to_existing_repo() {
+        let dir = TestProject::empty("git-existing");
+        let repo = GitRepository::init(dir.path()).unwrap();
+
+        // only staged without committing
+        let staged = dir.path().join("staged");
+        fs::create_dir_all(&staged).unwrap();
+        fs::write(staged.join("pack.toml"), "").unwrap();
+        initalize_vcs(
+            &staged,
+            VersionControlSystem::GitNoCommit,
+            &options(),
+            false,
+        )
+        .unwrap();
+        assert!(!staged.join(".git").exists());
+        assert!(repo.head().is_err());
+        assert!(repo
+            .index()
+            .unwrap()
+            .get_path(Path::new("staged/pack.toml"), 0)
+            .is_some());
+
+        // the commit is skipped, as other changes are already staged
+        let skipped = dir.path().join("skipped");
+        fs::create_dir_all(&skipped).unwrap();
+        fs::write(skipped.join("pack.toml"), "").unwrap();
+        let (result, messages) = terminal_output::capture(|| {
+            initalize_vcs(&skipped, VersionControlSystem::Git, &options(), false)
+        });
+        result.unwrap();
+        assert!(repo.head().is_err());
+        assert!(messages
+            .iter()
+            .any(|message| message.level == Level::Warning
+                && message.text.contains("already staged")));
+
+        // committed on the current branch
+        let mut index = repo.index().unwrap();
+        index.read(true).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = GitSignature::now("Alex", "alex@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Base", &tree, &[])
+            .unwrap();
+        let committed = dir.path().join("committed");
+        fs::create_dir_all(&committed).unwrap();
+        fs::write(committed.join("pack.toml"), "").unwrap();
+        initalize_vcs(&committed, VersionControlSystem::Git, &options(), false).unwrap();
+        assert!(!committed.join(".git").exists());
+        assert_eq!(commit_count(&repo), 2);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("Add Shulkerscript project"));
+        assert_eq!(head.author().name(), Some("Steve"));
+        let tree = head.tree().unwrap();
+        assert!(tree.get_path(Path::new("committed/pack.toml")).is_ok());
+    }
+
+    #[test]
+    fn test_squashed_in_existing_repo() {
+        let dir = TestProject::empty("squashed");
+        GitRepository::init(dir.path()).unwrap();
+        let project = dir.path().join("cloned");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("pack.toml"), "").unwrap();
+
+        let (result, messages) =
+            terminal_output::capture(|| initialize_git_squashed(&project, false));
+        result.unwrap();
+        assert!(!messages
+            .iter()
+            .any(|message| message.level == Level::Warning));
+    }
 }
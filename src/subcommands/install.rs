@@ -0,0 +1,151 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use path_absolutize::Absolutize as _;
+
+use crate::{
+    cli::GlobalOptions,
+    config::DependencyConfig,
+    dependency,
+    fetch::{self, Sha256Pin},
+    terminal_output::{print_error, print_info, print_plain, print_success},
+    util,
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct InstallArgs {
+    /// The library to install.
+    ///
+    /// Can be a git URL, a GitHub shorthand (`owner/repo` or `gh:owner/repo`) or a local path.
+    pub source: String,
+    /// The path of the project to install the library into.
+    #[arg(short, long, default_value = ".")]
+    pub path: PathBuf,
+    /// The name of the dependency, defaults to the name of the library.
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// The branch, tag or commit of the git repository to install.
+    #[arg(long)]
+    pub rev: Option<String>,
+    /// The expected SHA-256 pin of the content of the library.
+    #[arg(long, value_name = "PIN")]
+    pub sha256: Option<Sha256Pin>,
+    /// Refuse to install unpinned content.
+    ///
+    /// Enabled automatically if the `CI` environment variable is set.
+    #[arg(long)]
+    pub locked: bool,
+}
+
+pub fn install(args: &InstallArgs, options: &GlobalOptions) -> Result<()> {
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+    let (_, toml_path) = super::build::get_pack_config(&path, options.strict_config)?;
+    let project_root = toml_path.parent().unwrap_or(&path).to_path_buf();
+
+    let local_path = PathBuf::from(&args.source);
+    let mut dependency = if local_path.join("pack.toml").is_file() {
+        let absolute = local_path.absolutize()?;
+        let relative = pathdiff::diff_paths(&absolute, project_root.absolutize()?)
+            .unwrap_or_else(|| absolute.to_path_buf());
+        DependencyConfig {
+            path: Some(relative),
+            ..Default::default()
+        }
+    } else if args.source.contains(['/', ':', '\\']) {
+        DependencyConfig {
+            git: Some(args.source.clone()),
+            rev: args.rev.clone(),
+            sha256: args.sha256.clone(),
+            ..Default::default()
+        }
+    } else {
+        print_error(format!(
+            "Could not resolve \"{}\": there is no package registry available yet. Use a git URL, a GitHub shorthand (owner/repo) or a local path.",
+            args.source
+        ));
+        return Err(anyhow::anyhow!("Could not resolve library {}", args.source));
+    };
+
+    let fallback_name = args
+        .name
+        .clone()
+        .unwrap_or_else(|| fetch::repo_name(&fetch::resolve_git_url(&args.source)).to_string());
+
+    let resolved = dependency::resolve_dependency(
+        &fallback_name,
+        &dependency,
+        &project_root,
+        fetch::is_locked(args.locked),
+        true,
+        options.strict_config,
+    )?;
+
+    let name = args
+        .name
+        .clone()
+        .unwrap_or_else(|| util::name_to_namespace(&resolved.config.pack.name));
+
+    if dependency.git.is_some() && dependency.sha256.is_none() {
+        dependency.sha256 = Some(fetch::content_hash(&resolved.path)?);
+    }
+
+    add_dependency(&toml_path, &name, &dependency)?;
+
+    print_success(format!(
+        "Installed {} v{} as dependency {name}.",
+        resolved.config.pack.name, resolved.config.pack.version
+    ));
+
+    let resolved = dependency::ResolvedDependency { name, ..resolved };
+    let exported = resolved.exported_functions()?;
+    if exported.values().all(Vec::is_empty) {
+        print_info("The library does not export any public functions.");
+    } else {
+        print_info("Exported functions:");
+        for (namespace, functions) in exported {
+            for function in functions {
+                print_plain(format!("    {namespace}: {function}"));
+            }
+        }
+        print_info(format!(
+            "Import them with `from \"/{}/<script>\" import <function>;`",
+            resolved.name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Add the dependency to the pack.toml file, preserving its formatting.
+fn add_dependency(
+    toml_path: &std::path::Path,
+    name: &str,
+    dependency: &DependencyConfig,
+) -> Result<()> {
+    let mut document = fs::read_to_string(toml_path)?.parse::<toml_edit::DocumentMut>()?;
+
+    let dependencies = document
+        .entry("dependencies")
+        .or_insert_with(toml_edit::table)
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("`dependencies` in pack.toml is not a table"))?;
+
+    let mut entry = toml_edit::InlineTable::new();
+    if let Some(git) = &dependency.git {
+        entry.insert("git", git.as_str().into());
+    }
+    if let Some(rev) = &dependency.rev {
+        entry.insert("rev", rev.as_str().into());
+    }
+    if let Some(path) = &dependency.path {
+        entry.insert("path", path.to_string_lossy().replace('\\', "/").into());
+    }
+    if let Some(sha256) = &dependency.sha256 {
+        entry.insert("sha256", sha256.to_string().into());
+    }
+    dependencies.insert(name, toml_edit::value(entry));
+
+    fs::write(toml_path, document.to_string())?;
+
+    Ok(())
+}
@@ -1,14 +1,26 @@
 use clap::ValueEnum;
 
 use anyhow::Result;
-use shulkerscript::base::{FsProvider, PrintHandler};
-use std::path::PathBuf;
+use shulkerscript::{base::PrintHandler, shulkerbox::util::compile::CompileOptions};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
-use crate::{config::PackConfig, util};
+use crate::{
+    cli::GlobalOptions,
+    config::PackConfig,
+    dependency::ScriptProvider,
+    terminal_output::{print_diff, print_error, print_info, print_plain},
+    util, vcs,
+    walk::WalkOptions,
+};
 
 #[derive(Debug, clap::Args, Clone)]
 pub struct LangDebugArgs {
-    /// The path of the project to compile.
+    /// The path of the script or project to compile.
+    ///
+    /// The tokens and the abstract syntax tree of a project are the ones of its `src/main.shu`.
     #[arg(default_value = ".")]
     pub path: PathBuf,
     /// The state to dump.
@@ -19,6 +31,12 @@ pub struct LangDebugArgs {
     /// Pretty-print the output.
     #[arg(short, long)]
     pub pretty: bool,
+    /// Print the difference of the state to the one of a git revision or another path.
+    ///
+    /// Accepts anything git understands, e.g. `HEAD~1` or a branch name, or the path of another
+    /// script or project. The datapack state is compared by the files of the compiled datapack.
+    #[arg(long, value_name = "REVISION|PATH")]
+    pub diff: Option<String>,
 }
 
 #[derive(ValueEnum, Debug, Clone, Copy, Default)]
@@ -29,57 +47,150 @@ pub enum DumpState {
     Datapack,
 }
 
-pub fn lang_debug(args: &LangDebugArgs) -> Result<()> {
-    let file_provider = FsProvider::default();
-    match args.dump {
+pub fn lang_debug(args: &LangDebugArgs, options: &GlobalOptions) -> Result<()> {
+    let discover = options.discover();
+    let Some(diff) = &args.diff else {
+        print_plain(dump(&args.path, discover, args.dump, args.pretty)?);
+        return Ok(());
+    };
+
+    let (old, new) = if Path::new(diff).exists() {
+        (
+            dump_files(Path::new(diff), discover, args.dump)?,
+            dump_files(&args.path, discover, args.dump)?,
+        )
+    } else {
+        let export =
+            env::temp_dir().join(format!("shulkerscript-lang-debug-{}", std::process::id()));
+        let result = vcs::export_revision(&args.path, diff, &export)
+            .inspect_err(|err| {
+                print_error(format!(
+                    "{diff} is neither an existing path nor a revision of the git repository: {err}"
+                ));
+            })
+            .and_then(|(workdir, old_path)| {
+                let new_path = workdir.join(old_path.strip_prefix(&export)?);
+                // the dumps contain the paths of the scripts
+                let old = dump_files(&old_path, discover, args.dump)?.replace(
+                    &export.display().to_string(),
+                    &workdir.display().to_string(),
+                );
+                Ok((old, dump_files(&new_path, discover, args.dump)?))
+            });
+        let _ = fs::remove_dir_all(&export);
+        result?
+    };
+
+    if old == new {
+        print_info(format!("No differences to {diff}."));
+    } else {
+        print_diff(&util::line_diff(&old, &new), 3);
+    }
+    Ok(())
+}
+
+/// Dump the state of the script or project at the path.
+fn dump(path: &Path, discover: bool, state: DumpState, pretty: bool) -> Result<String> {
+    let file_provider = ScriptProvider::default();
+    let project_path = util::get_project_path(path, discover).unwrap_or(path.to_path_buf());
+    let script_path = if path.is_dir() {
+        project_path.join("src").join("main.shu")
+    } else {
+        path.to_path_buf()
+    };
+    let script_name = script_path
+        .file_stem()
+        .map_or(String::from("main"), |s| s.to_string_lossy().into_owned());
+
+    let dumped = match state {
         DumpState::Tokens => {
             let tokens = shulkerscript::tokenize(
                 &PrintHandler::new(),
                 &file_provider,
-                &args.path,
-                args.path.file_stem().map_or(String::from("main"), |s| {
-                    s.to_string_lossy().into_owned().to_string()
-                }),
+                &script_path,
+                script_name,
             )?;
-            if args.pretty {
-                println!("{:#?}", tokens);
+            if pretty {
+                format!("{:#?}", tokens)
             } else {
-                println!("{:?}", tokens);
+                format!("{:?}", tokens)
             }
         }
         DumpState::Ast => {
             let ast = shulkerscript::parse(
                 &PrintHandler::new(),
                 &file_provider,
-                &args.path,
-                args.path.file_stem().map_or(String::from("main"), |s| {
-                    s.to_string_lossy().into_owned().to_string()
-                }),
+                &script_path,
+                script_name,
             )?;
-            if args.pretty {
-                println!("{:#?}", ast);
+            if pretty {
+                format!("{:#?}", ast)
             } else {
-                println!("{:?}", ast);
+                format!("{:?}", ast)
             }
         }
         DumpState::Datapack => {
-            let program_paths = super::build::get_script_paths(
-                &util::get_project_path(&args.path)
-                    .unwrap_or(args.path.clone())
-                    .join("src"),
-            )?;
-            let datapack = shulkerscript::transpile(
-                &PrintHandler::new(),
-                &file_provider,
-                PackConfig::DEFAULT_PACK_FORMAT,
-                &program_paths,
-            )?;
-            if args.pretty {
-                println!("{:#?}", datapack);
+            let datapack = transpile(&project_path)?;
+            if pretty {
+                format!("{:#?}", datapack)
             } else {
-                println!("{:?}", datapack);
+                format!("{:?}", datapack)
             }
         }
+    };
+    Ok(dumped)
+}
+
+/// Dump the state of the script or project at the path in a line-based form for diffing.
+///
+/// The datapack state is dumped as the compiled files sorted by path, as the datapack itself
+/// contains unordered maps.
+fn dump_files(path: &Path, discover: bool, state: DumpState) -> Result<String> {
+    let DumpState::Datapack = state else {
+        return dump(path, discover, state, true);
+    };
+
+    let project_path = util::get_project_path(path, discover).unwrap_or(path.to_path_buf());
+    let compiled = transpile(&project_path)?.compile(&CompileOptions::default());
+    let mut files = compiled.flatten();
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(files
+        .into_iter()
+        .map(|(path, file)| match file.as_text() {
+            Some(text) => format!("=== {path}\n{text}"),
+            None => format!("=== {path} ({} bytes)", file.as_bytes().len()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn transpile(project_path: &Path) -> Result<shulkerscript::shulkerbox::datapack::Datapack> {
+    let program_paths =
+        super::build::get_script_paths(&project_path.join("src"), WalkOptions::default())?;
+    Ok(shulkerscript::transpile(
+        &PrintHandler::new(),
+        &ScriptProvider::default(),
+        PackConfig::DEFAULT_PACK_FORMAT,
+        &program_paths,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_dump_with_allow_annotations() {
+        let project = TestProject::init("annotated");
+        let main = project.read("src/main.shu").unwrap();
+        project.write(
+            "src/main.shu",
+            main.replacen("fn ", "#[allow(all)]\nfn ", 1),
+        );
+
+        for state in [DumpState::Tokens, DumpState::Ast, DumpState::Datapack] {
+            assert!(dump(project.path(), false, state, false).is_ok());
+        }
     }
-    Ok(())
 }
@@ -0,0 +1,98 @@
+use std::{
+    num::NonZeroUsize,
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+
+use crate::{
+    cli::GlobalOptions,
+    error::Error,
+    history::{self, BuildRecord},
+    terminal_output::{
+        print_diagnostic, print_error, print_info, print_message, print_success, Level,
+    },
+    units::{format_count, format_duration, format_size},
+    util,
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct LastArgs {
+    /// The path of the project to print the last build of.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Only print the diagnostics of the compiler and the warnings and errors of the build.
+    #[arg(long, conflicts_with = "stats")]
+    pub diagnostics: bool,
+    /// Only print the statistics and the artifact of the build.
+    #[arg(long)]
+    pub stats: bool,
+    /// Print the N-th most recent build instead of the last one.
+    #[arg(short, long, value_name = "N", default_value = "1")]
+    pub nth: NonZeroUsize,
+}
+
+pub fn last(args: &LastArgs, options: &GlobalOptions) -> Result<()> {
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+    if !path.join("pack.toml").is_file() {
+        print_error("The specified directory does not contain a pack.toml file.");
+        return Err(Error::InvalidPackPathError(path).into());
+    }
+
+    let builds = history::load(&path)?;
+    let Some(build) = builds.get(args.nth.get() - 1) else {
+        print_info(format!(
+            "{} builds are kept in {}. Builds are added to the history unless `history = 0` is set in the `[compiler]` section of the pack.toml file.",
+            builds.len(),
+            path.join(history::HISTORY_DIR).display()
+        ));
+        return Ok(());
+    };
+
+    if args.stats {
+        print_summary(build);
+        return Ok(());
+    }
+
+    for diagnostic in &build.diagnostics {
+        print_diagnostic(diagnostic);
+    }
+    for message in build.messages.iter().filter(|message| {
+        !args.diagnostics || matches!(message.level, Level::Warning | Level::Error)
+    }) {
+        print_message(message);
+    }
+    if let Some(error) = &build.error {
+        print_error(error);
+    }
+    if !args.diagnostics {
+        print_summary(build);
+    }
+
+    Ok(())
+}
+
+fn print_summary(build: &BuildRecord) {
+    let stats = &build.stats;
+    let finished = util::format_timestamp(UNIX_EPOCH + Duration::from_secs(stats.timestamp));
+    match &build.artifact {
+        Some(artifact) => print_success(format!(
+            "Built {} at {finished} in {}",
+            artifact.display(),
+            format_duration(stats.duration())
+        )),
+        None => print_error(format!(
+            "Build failed at {finished} after {}",
+            format_duration(stats.duration())
+        )),
+    }
+    if stats.success {
+        print_info(format!(
+            "{} scripts, {} output files with {}",
+            format_count(stats.scripts),
+            format_count(stats.output_files),
+            format_size(stats.output_bytes as u64)
+        ));
+    }
+}
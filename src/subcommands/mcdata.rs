@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::{
+    cli::GlobalOptions,
+    mcdata::{self, Registries},
+    terminal_output::{print_error, print_info, print_plain},
+    util, versions,
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct McdataArgs {
+    /// The registry to query, e.g. `item`, `block` or `entity_type`.
+    ///
+    /// Lists the available registries if omitted.
+    pub registry: Option<String>,
+    /// Only list the entries containing this text.
+    pub filter: Option<String>,
+    /// The path of the project, used to determine the Minecraft version.
+    #[arg(short, long, default_value = ".")]
+    pub path: PathBuf,
+    /// The Minecraft version, defaults to the version of the project.
+    #[arg(long = "mc-version", value_name = "VERSION")]
+    pub minecraft_version: Option<String>,
+    /// Fetch the registries even if they are cached.
+    #[arg(long)]
+    pub refresh: bool,
+    /// Print the result as JSON, e.g. for editor integrations.
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn mcdata(args: &McdataArgs, options: &GlobalOptions) -> Result<()> {
+    let version = args
+        .minecraft_version
+        .clone()
+        .or_else(|| {
+            let path =
+                util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+            super::get_pack_config(&path, options.strict_config)
+                .ok()
+                .and_then(|(config, _)| mcdata::minecraft_version(&config.pack))
+        })
+        .or_else(|| {
+            versions::PACK_FORMATS
+                .first()
+                .map(|format| format.newest_version().to_string())
+        })
+        .unwrap_or_default();
+
+    let cached = if args.refresh {
+        None
+    } else {
+        Registries::load_cached(&version)
+    };
+    let registries = match cached {
+        Some(registries) => registries,
+        None => {
+            print_info(format!("Fetching the registries of Minecraft {version}"));
+            Registries::fetch(&version).inspect_err(|err| {
+                print_error(format!(
+                    "Failed to fetch the registries of Minecraft {version}: {err}"
+                ));
+            })?
+        }
+    };
+
+    let Some(registry) = &args.registry else {
+        let names = registries.0.keys().collect::<Vec<_>>();
+        if args.json {
+            print_plain(serde_json::to_string(&names)?);
+        } else {
+            names.into_iter().for_each(print_plain);
+        }
+        return Ok(());
+    };
+
+    let registry = registry.strip_prefix("minecraft:").unwrap_or(registry);
+    let Some(entries) = registries.0.get(registry) else {
+        print_error(format!(
+            "The registry {registry} does not exist in Minecraft {version}. Run without arguments to list the registries."
+        ));
+        return Err(anyhow::anyhow!("Unknown registry {registry}"));
+    };
+
+    let entries = entries
+        .iter()
+        .filter(|entry| {
+            args.filter
+                .as_deref()
+                .is_none_or(|filter| entry.contains(filter))
+        })
+        .map(|entry| format!("minecraft:{entry}"))
+        .collect::<Vec<_>>();
+    if args.json {
+        print_plain(serde_json::to_string(&entries)?);
+    } else {
+        entries.iter().for_each(print_plain);
+    }
+
+    Ok(())
+}
@@ -3,6 +3,8 @@ use path_absolutize::Absolutize as _;
 use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
 use std::{
     borrow::Cow,
+    env,
+    fmt::Display,
     fs::{self, File},
     io::BufReader,
     path::{Path, PathBuf},
@@ -10,24 +12,58 @@ use std::{
 use walkdir::WalkDir;
 
 use crate::{
-    terminal_output::{print_error, print_info, print_success},
+    error::Error,
+    layout, prompt,
+    terminal_output::{print_error, print_info, print_plain, print_success, print_warning},
+    units::format_count,
     util::Relativize as _,
 };
 
 #[derive(Debug, clap::Args, Clone)]
 #[command(allow_missing_positional = true)]
 pub struct MigrateArgs {
-    /// The path of the project to migrate.
+    /// The path of the project to migrate, or of the folder of the datapacks with `--all`.
     #[arg(default_value = ".")]
     pub path: PathBuf,
-    /// The path of the folder to create the Shulkerscript project.
+    /// The path of the folder to create the Shulkerscript project, or of the parent folder of
+    /// the projects with `--all`.
     pub target: PathBuf,
     /// Force migration even if some features will be lost.
     #[arg(short, long)]
     pub force: bool,
+    /// Migrate every datapack folder and zip file in the folder into a project of the same name.
+    #[arg(long)]
+    pub all: bool,
+    /// Make the target folder a workspace with the migrated projects as members.
+    #[arg(long, requires = "all")]
+    pub workspace: bool,
+}
+
+/// The numbers of files created by migrating a datapack.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct MigrationReport {
+    functions: usize,
+    tags: usize,
+    assets: usize,
+}
+
+impl Display for MigrationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} functions, {} tags, {} asset files",
+            format_count(self.functions),
+            format_count(self.tags),
+            format_count(self.assets)
+        )
+    }
 }
 
 pub fn migrate(args: &MigrateArgs) -> Result<()> {
+    if args.all {
+        return migrate_all(args);
+    }
+
     let base_path = args.path.as_path();
     let base_path = if base_path.is_absolute() {
         Cow::Borrowed(base_path)
@@ -43,60 +79,195 @@ pub fn migrate(args: &MigrateArgs) -> Result<()> {
             "Migrating from {:?} to {:?}",
             base_path, args.target
         ));
+        migrate_pack(&base_path, &args.target, args.force)?;
+        print_success("Migration successful.");
+        Ok(())
+    } else {
+        let msg = format!(
+            "Could not find a valid datapack to migrate at {}.",
+            args.path.display()
+        );
+        print_error(&msg);
+        Err(anyhow::anyhow!("{}", &msg))
+    }
+}
 
-        let mcmeta_path = base_path.join("pack.mcmeta");
-        let mcmeta: serde_json::Value =
-            serde_json::from_reader(BufReader::new(fs::File::open(&mcmeta_path)?))?;
+/// Migrate every datapack in the folder into a project in the target folder and print a combined
+/// report.
+fn migrate_all(args: &MigrateArgs) -> Result<()> {
+    let mut datapacks = fs::read_dir(&args.path)
+        .inspect_err(|err| {
+            print_error(format!("Could not read {}: {err}", args.path.display()));
+        })?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.join("pack.mcmeta").is_file()
+                || path.extension().is_some_and(|extension| extension == "zip")
+        })
+        .collect::<Vec<_>>();
+    datapacks.sort();
+    if datapacks.is_empty() {
+        let msg = format!("Could not find any datapacks in {}.", args.path.display());
+        print_error(&msg);
+        return Err(anyhow::anyhow!("{}", &msg));
+    }
 
-        if !args.force && !is_mcmeta_compatible(&mcmeta) {
-            print_error("Your datapack uses features in the pack.mcmeta file that are not yet supported by Shulkerscript.");
-            print_error(
-                r#""features", "filter", "overlays" and "language" will get lost if you continue."#,
-            );
-            print_error("Use the force flag to continue anyway.");
+    let staging = env::temp_dir().join(format!("shulkerscript-migrate-{}", std::process::id()));
+    let mut results = Vec::new();
+    for path in &datapacks {
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        print_info(format!("Migrating {}", path.display()));
+        let result = extract(path, &staging.join(&name))
+            .and_then(|source| migrate_pack(&source, &args.target.join(&name), args.force));
+        if let Err(err) = &result {
+            print_error(format!("Could not migrate {}: {err}", path.display()));
+        }
+        results.push((name, result));
+    }
+    let _ = fs::remove_dir_all(&staging);
+
+    let width = results
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, result) in &results {
+        match result {
+            Ok(report) => print_plain(format!("  {name:<width$}  {report}")),
+            Err(_) => print_plain(format!("  {name:<width$}  failed")),
+        }
+    }
+    let migrated = results
+        .iter()
+        .filter(|(_, result)| result.is_ok())
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+
+    if args.workspace && !migrated.is_empty() {
+        let manifest = args.target.join("pack.toml");
+        if manifest.exists() {
+            print_warning(format!(
+                "{} already exists, add the migrated projects to its [workspace] section manually.",
+                manifest.display()
+            ));
+        } else {
+            let workspace = toml::toml! {
+                [workspace]
+                members = (migrated.clone())
+            };
+            fs::write(&manifest, toml::to_string_pretty(&workspace)?)?;
+            print_info(format!(
+                "Created a workspace with the migrated projects at {}",
+                args.target.display()
+            ));
+        }
+    }
+
+    let failed = results.len() - migrated.len();
+    if failed > 0 {
+        print_error(format!(
+            "Migrated {} of {} datapacks.",
+            format_count(migrated.len()),
+            format_count(results.len())
+        ));
+        return Err(Error::MigrationError(failed).into());
+    }
+    print_success(format!(
+        "Migrated {} datapacks.",
+        format_count(migrated.len())
+    ));
+    Ok(())
+}
 
+/// Get the folder of a datapack, extracting zip files to the staging folder.
+fn extract(path: &Path, staging: &Path) -> Result<PathBuf> {
+    if path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    #[cfg(feature = "zip")]
+    {
+        crate::archive::read_folder(path)?.place(staging)?;
+        Ok(staging.to_path_buf())
+    }
+    #[cfg(not(feature = "zip"))]
+    {
+        let _ = staging;
+        Err(Error::FeatureNotEnabledError("zip".to_string()).into())
+    }
+}
+
+/// Migrate the datapack at the base path into a project at the target.
+fn migrate_pack(base_path: &Path, target: &Path, force: bool) -> Result<MigrationReport> {
+    let mcmeta_path = base_path.join("pack.mcmeta");
+    let mcmeta: serde_json::Value =
+        serde_json::from_reader(BufReader::new(fs::File::open(&mcmeta_path)?))?;
+
+    if !force && !is_mcmeta_compatible(&mcmeta) {
+        print_warning("Your datapack uses features in the pack.mcmeta file that are not yet supported by Shulkerscript.");
+        print_warning(r#""filter", "overlays" and "language" will get lost if you continue."#);
+
+        let confirmed = prompt::confirm(
+            inquire::Confirm::new("Do you want to continue anyway?").with_default(false),
+        )
+        .unwrap_or_default();
+        if !confirmed {
+            print_error("Use the force flag to continue anyway.");
             return Err(anyhow::anyhow!("Incompatible mcmeta."));
         }
+    }
 
-        let mcmeta = serde_json::from_value::<McMeta>(mcmeta)?;
+    let mcmeta = serde_json::from_value::<McMeta>(mcmeta)?;
 
-        let mut root = VFolder::new();
-        root.add_file("pack.toml", generate_pack_toml(&base_path, &mcmeta)?);
+    let mut root = VFolder::new();
+    root.add_file("pack.toml", generate_pack_toml(base_path, &mcmeta)?);
 
-        let data_path = base_path.join("data");
-        if data_path.exists() && data_path.is_dir() {
-            for namespace in data_path.read_dir()? {
-                let namespace = namespace?;
-                if namespace.file_type()?.is_dir() {
-                    handle_namespace(&mut root, &namespace.path())?;
-                }
+    let data_path = base_path.join("data");
+    if data_path.exists() && data_path.is_dir() {
+        for namespace in data_path.read_dir()? {
+            let namespace = namespace?;
+            if namespace.file_type()?.is_dir() {
+                handle_namespace(&mut root, &namespace.path())?;
             }
-        } else {
-            print_error("Could not find a data folder.");
         }
+    } else {
+        print_error("Could not find a data folder.");
+    }
 
-        root.place(&args.target)?;
-
-        let logo_path = base_path.join("pack.png");
-        if logo_path.exists() {
-            fs::copy(logo_path, args.target.join("pack.png"))?;
+    let mut report = MigrationReport::default();
+    for (path, _) in root.flatten() {
+        if path.starts_with("src/functions/") {
+            report.functions += 1;
+        } else if path.starts_with("src/tags/") {
+            report.tags += 1;
+        } else if path.starts_with("assets/") {
+            report.assets += 1;
         }
+    }
 
-        print_success("Migration successful.");
-        Ok(())
-    } else {
-        let msg = format!(
-            "Could not find a valid datapack to migrate at {}.",
-            args.path.display()
-        );
-        print_error(&msg);
-        Err(anyhow::anyhow!("{}", &msg))
+    root.place(target)?;
+
+    let logo_path = base_path.join("pack.png");
+    if logo_path.exists() {
+        fs::copy(logo_path, target.join("pack.png"))?;
     }
+
+    Ok(report)
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
 struct McMeta {
     pack: McMetaPack,
+    #[serde(default)]
+    features: Option<McMetaFeatures>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+struct McMetaFeatures {
+    enabled: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
@@ -106,8 +277,8 @@ struct McMetaPack {
 }
 
 fn is_mcmeta_compatible(mcmeta: &serde_json::Value) -> bool {
-    mcmeta.as_object().map_or(false, |mcmeta| {
-        mcmeta.len() == 1
+    mcmeta.as_object().is_some_and(|mcmeta| {
+        mcmeta.keys().all(|key| key == "pack" || key == "features")
             && mcmeta.contains_key("pack")
             && mcmeta["pack"]
                 .as_object()
@@ -136,9 +307,8 @@ fn generate_pack_toml(base_path: &Path, mcmeta: &McMeta) -> Result<VFile> {
                                 }
                             })
                         })
-                        .map_err(|e| {
+                        .inspect_err(|_| {
                             err = true;
-                            e
                         })
                         .unwrap_or_default()
             } else {
@@ -172,13 +342,26 @@ fn generate_pack_toml(base_path: &Path, mcmeta: &McMeta) -> Result<VFile> {
     let description = mcmeta.pack.description.as_str();
     let pack_format = mcmeta.pack.pack_format;
 
-    let main_fragment = toml::toml! {
+    let mut main_fragment = toml::toml! {
         [pack]
         name = name
         description = description
         format = pack_format
         version = "0.1.0"
     };
+    if let Some(features) = mcmeta
+        .features
+        .as_ref()
+        .filter(|features| !features.enabled.is_empty())
+    {
+        main_fragment["pack"]
+            .as_table_mut()
+            .expect("pack is a table")
+            .insert(
+                "required_features".to_string(),
+                features.enabled.clone().into(),
+            );
+    }
 
     let assets_dir_fragment_text = assets_dir_fragment
         .map(|fragment| toml::to_string_pretty(&fragment))
@@ -219,7 +402,7 @@ fn handle_namespace(root: &mut VFolder, namespace: &Path) -> Result<()> {
                 if entry.file_type().is_file()
                     && entry.path().extension().unwrap_or_default() == "mcfunction"
                 {
-                    handle_function(root, namespace, &namespace_name, entry.path())?;
+                    handle_function(root, &subfolder.path(), &namespace_name, entry.path())?;
                 }
             }
         } else if filename.as_ref() == "tags" {
@@ -239,12 +422,13 @@ fn handle_namespace(root: &mut VFolder, namespace: &Path) -> Result<()> {
 
 fn handle_function(
     root: &mut VFolder,
-    namespace: &Path,
+    function_dir: &Path,
     namespace_name: &str,
     function: &Path,
 ) -> Result<()> {
-    let function_path = pathdiff::diff_paths(function, namespace.join("function"))
-        .expect("function path is always a subpath of namespace/function")
+    // `functions` before pack format 45, `function` after
+    let function_path = pathdiff::diff_paths(function, function_dir)
+        .expect("function path is always a subpath of the function directory")
         .to_string_lossy()
         .replace('\\', "/");
     let function_path = function_path
@@ -268,7 +452,7 @@ fn handle_function(
 
     let function_name = function_path
         .split('/')
-        .last()
+        .next_back()
         .expect("split always returns at least one element")
         .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
 
@@ -299,12 +483,14 @@ fn handle_tag_type_dir(root: &mut VFolder, namespace: &str, tag_type_dir: &Path)
         .file_name()
         .expect("cannot end with ..")
         .to_string_lossy();
+    // the plural tag types before pack format 45 are written with their singular name
+    let tag_type = layout::singular_tag_type(&tag_type);
 
     // loop through all tag files in the tag type directory
     for entry in WalkDir::new(tag_type_dir).min_depth(1) {
         let entry = entry?;
         if entry.file_type().is_file() && entry.path().extension().unwrap_or_default() == "json" {
-            handle_tag(root, namespace, tag_type_dir, &tag_type, entry.path())?;
+            handle_tag(root, namespace, tag_type_dir, tag_type, entry.path())?;
         }
     }
 
@@ -378,3 +564,49 @@ struct Tag {
     replace: bool,
     values: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_migrate_pack() {
+        let temp = TestProject::empty("migrate");
+        let dir = temp.path();
+        let pack = dir.join("datapacks/game");
+        fs::create_dir_all(pack.join("data/game/function")).unwrap();
+        fs::create_dir_all(pack.join("data/minecraft/tags/function")).unwrap();
+        fs::create_dir_all(pack.join("data/game/loot_table")).unwrap();
+        fs::write(
+            pack.join("pack.mcmeta"),
+            r#"{"pack":{"description":"Game","pack_format":48}}"#,
+        )
+        .unwrap();
+        fs::write(pack.join("data/game/function/load.mcfunction"), "say hi").unwrap();
+        fs::write(
+            pack.join("data/minecraft/tags/function/load.json"),
+            r#"{"values":["game:load"]}"#,
+        )
+        .unwrap();
+        fs::write(pack.join("data/game/loot_table/chest.json"), "{}").unwrap();
+
+        let target = dir.join("projects/game");
+        let report = migrate_pack(&pack, &target, false).unwrap();
+        assert_eq!(
+            report,
+            MigrationReport {
+                functions: 1,
+                tags: 1,
+                assets: 1,
+            }
+        );
+        assert!(target.join("src/functions/game/load.shu").is_file());
+        assert!(target
+            .join("src/tags/minecraft/function/load.shu")
+            .is_file());
+        assert!(target
+            .join("assets/data/game/loot_table/chest.json")
+            .is_file());
+    }
+}
@@ -1,22 +1,85 @@
 mod init;
-pub use init::{init, InitArgs};
+pub use init::{init, InitArgs, VersionControlSystem};
 
 mod build;
 pub use build::{build, BuildArgs};
+pub(crate) use build::{build_artifact, get_pack_config, get_script_paths};
+
+mod bench_project;
+pub use bench_project::{bench_project, BenchProjectArgs};
+
+mod clone;
+pub use clone::{clone, CloneArgs};
 
 mod clean;
 pub use clean::{clean, CleanArgs};
 
+mod config;
+pub use config::{config, ConfigArgs, ConfigCommand};
+
+mod conflicts;
+pub use conflicts::{conflicts, ConflictsArgs};
+
+mod datapack;
+pub use datapack::{datapack, DatapackArgs, DatapackCommand};
+
+mod deploy;
+pub use deploy::{deploy, DeployArgs};
+
+mod fix;
+pub use fix::{fix, FixArgs};
+
+mod install;
+pub use install::{install, InstallArgs};
+
+mod last;
+pub use last::{last, LastArgs};
+
+mod nbt;
+pub use nbt::{nbt, NbtArgs, NbtCommand};
+
+mod run_script;
+pub use run_script::{run_script, RunScriptArgs};
+
+mod stats;
+pub use stats::{stats, StatsArgs};
+
+mod todo;
+pub use todo::{todo, TodoArgs};
+
+mod upgrade;
+pub use upgrade::{upgrade, UpgradeArgs};
+
 #[cfg(feature = "lang-debug")]
 mod lang_debug;
 #[cfg(feature = "lang-debug")]
 pub use lang_debug::{lang_debug, LangDebugArgs};
 
+#[cfg(feature = "mcdata")]
+mod mcdata;
+#[cfg(feature = "mcdata")]
+pub use mcdata::{mcdata, McdataArgs};
+
 #[cfg(feature = "migrate")]
 mod migrate;
 #[cfg(feature = "migrate")]
 pub use migrate::{migrate, MigrateArgs};
 
+#[cfg(feature = "zip")]
+mod package;
+#[cfg(feature = "zip")]
+pub use package::{package, PackageArgs};
+
+#[cfg(feature = "zip")]
+mod patch;
+#[cfg(feature = "zip")]
+pub use patch::{apply, patch, ApplyArgs, PatchArgs};
+
+#[cfg(feature = "publish")]
+mod publish;
+#[cfg(feature = "publish")]
+pub use publish::{publish, PublishArgs};
+
 #[cfg(feature = "watch")]
 mod watch;
 #[cfg(feature = "watch")]
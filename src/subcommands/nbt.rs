@@ -0,0 +1,162 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    nbt::{self, Tag},
+    terminal_output::{print_error, print_plain, print_success, print_warning},
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct NbtArgs {
+    #[command(subcommand)]
+    pub command: NbtCommand,
+}
+
+#[derive(Debug, clap::Subcommand, Clone)]
+pub enum NbtCommand {
+    /// Convert a file between SNBT, binary NBT and JSON.
+    Convert(NbtConvertArgs),
+    /// Print the content of a SNBT, binary NBT or JSON file.
+    Print(NbtPrintArgs),
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct NbtConvertArgs {
+    /// The file to convert.
+    pub input: PathBuf,
+    /// The file to write.
+    pub output: PathBuf,
+    /// The format of the input, detected from the extension by default.
+    #[arg(long, value_name = "FORMAT")]
+    pub from: Option<NbtFormat>,
+    /// The format of the output, detected from the extension by default.
+    #[arg(long, value_name = "FORMAT")]
+    pub to: Option<NbtFormat>,
+    /// Write binary NBT without gzip compression.
+    #[arg(long)]
+    pub uncompressed: bool,
+    /// Overwrite the output file if it exists.
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct NbtPrintArgs {
+    /// The file to print.
+    pub input: PathBuf,
+    /// The format of the input, detected from the extension by default.
+    #[arg(long, value_name = "FORMAT")]
+    pub from: Option<NbtFormat>,
+    /// Print as JSON instead of SNBT.
+    #[arg(long)]
+    pub json: bool,
+    /// Print everything on a single line.
+    #[arg(long)]
+    pub compact: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum NbtFormat {
+    /// Stringified NBT, as used in commands.
+    Snbt,
+    /// Binary NBT, as used for structures. Compressed with gzip unless `--uncompressed` is set.
+    Nbt,
+    /// JSON, which does not preserve the numeric types.
+    Json,
+}
+
+impl NbtFormat {
+    /// Detect the format from the extension of a path, defaulting to binary NBT.
+    fn detect(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("snbt") => Self::Snbt,
+            Some("json") => Self::Json,
+            _ => Self::Nbt,
+        }
+    }
+}
+
+pub fn nbt(args: &NbtArgs) -> Result<()> {
+    match &args.command {
+        NbtCommand::Convert(args) => convert(args),
+        NbtCommand::Print(args) => print(args),
+    }
+}
+
+fn convert(args: &NbtConvertArgs) -> Result<()> {
+    if args.output.exists() && !args.force {
+        print_error(format!(
+            "The file {} already exists. Use --force to overwrite it.",
+            args.output.display()
+        ));
+        return Err(anyhow!("Output file already exists"));
+    }
+
+    let from = args.from.unwrap_or_else(|| NbtFormat::detect(&args.input));
+    let to = args.to.unwrap_or_else(|| NbtFormat::detect(&args.output));
+    let (name, tag) = read_tag(&args.input, from)?;
+
+    if to == NbtFormat::Json && from != NbtFormat::Json {
+        print_warning(
+            "JSON does not preserve the numeric types of NBT, converting it back may change them.",
+        );
+    }
+
+    let data = match to {
+        NbtFormat::Snbt => (tag.to_pretty_snbt() + "\n").into_bytes(),
+        NbtFormat::Json => (serde_json::to_string_pretty(&tag.to_json())? + "\n").into_bytes(),
+        NbtFormat::Nbt if args.uncompressed => nbt::write(&name, &tag),
+        NbtFormat::Nbt => nbt::write_compressed(&name, &tag)?,
+    };
+    fs::write(&args.output, data)?;
+
+    print_success(format!(
+        "Converted {} to {}",
+        args.input.display(),
+        args.output.display()
+    ));
+
+    Ok(())
+}
+
+fn print(args: &NbtPrintArgs) -> Result<()> {
+    let from = args.from.unwrap_or_else(|| NbtFormat::detect(&args.input));
+    let (_, tag) = read_tag(&args.input, from)?;
+
+    match (args.json, args.compact) {
+        (false, true) => print_plain(tag),
+        (false, false) => print_plain(tag.to_pretty_snbt()),
+        (true, true) => print_plain(tag.to_json()),
+        (true, false) => print_plain(serde_json::to_string_pretty(&tag.to_json())?),
+    }
+
+    Ok(())
+}
+
+/// Read a tag and the name of the root tag (empty for SNBT and JSON) from a file.
+fn read_tag(path: &Path, format: NbtFormat) -> Result<(String, Tag)> {
+    let data = fs::read(path).inspect_err(|err| {
+        print_error(format!("Failed to read {}: {err}", path.display()));
+    })?;
+
+    let parsed = match format {
+        NbtFormat::Nbt => nbt::read(&data).map_err(|err| err.to_string()),
+        NbtFormat::Snbt => String::from_utf8(data)
+            .map_err(|err| err.to_string())
+            .and_then(|snbt| nbt::parse_snbt(&snbt))
+            .map(|tag| (String::new(), tag)),
+        NbtFormat::Json => serde_json::from_slice(&data)
+            .map_err(|err| err.to_string())
+            .and_then(|json| Tag::from_json(&json))
+            .map(|tag| (String::new(), tag)),
+    };
+
+    parsed.map_err(|err| {
+        print_error(format!("Failed to parse {}: {err}", path.display()));
+        anyhow!("Invalid NBT data")
+    })
+}
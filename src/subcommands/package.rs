@@ -0,0 +1,243 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Write as _},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::Result;
+use path_absolutize::Absolutize as _;
+use sha2::{Digest as _, Sha256};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    archive, cancel,
+    cli::GlobalOptions,
+    error::Error,
+    terminal_output::{print_error, print_info, print_success},
+    util,
+    vcs::GitInfo,
+};
+
+use super::BuildArgs;
+
+/// The directory in the output directory the artifacts are built in before being renamed.
+const STAGING_DIR: &str = ".package";
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct PackageArgs {
+    /// The path of the project to package.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Path of output directory
+    ///
+    /// The path of the directory to place the zip file.
+    #[arg(short, long, env = "DATAPACK_DIR")]
+    pub output: Option<PathBuf>,
+    /// Path of the assets folder
+    ///
+    /// The path of a folder which files and subfolders will be copied to the root of the datapack.
+    /// Overrides the `assets` field in the pack.toml file.
+    #[arg(short, long)]
+    pub assets: Option<PathBuf>,
+    /// The name of the zip file (without extension).
+    ///
+    /// Supports the placeholders `{name}`, `{namespace}`, `{version}`, `{git_hash}` and `{git_tag}`.
+    /// Defaults to the `output_name` field of the `[compiler]` section in the pack.toml file.
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// The compression method of the files in the zip file.
+    #[arg(long, value_enum, default_value_t = Compression::Deflated)]
+    pub compression: Compression,
+    /// The deflate compression level, from 0 (fastest) to 9 (smallest).
+    #[arg(long, value_parser = clap::value_parser!(i64).range(0..=9))]
+    pub compression_level: Option<i64>,
+    /// Write a `<artifact>.sha256` file with the SHA-256 checksum next to the zip file.
+    #[arg(long)]
+    pub checksum: bool,
+    /// Sign the zip file with GnuPG, writing a detached `<artifact>.asc` signature next to it.
+    #[arg(long)]
+    pub sign: bool,
+    /// The GnuPG key to sign with, defaults to the default key of GnuPG.
+    #[arg(long, value_name = "KEY", requires = "sign")]
+    pub sign_key: Option<String>,
+    /// Build a release artifact.
+    ///
+    /// Warns if the git working tree has uncommitted changes or HEAD is not tagged.
+    #[arg(long)]
+    pub release: bool,
+    /// Skip validating the project for pack format compatibility and valid resource locations.
+    #[arg(long)]
+    pub no_validate: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Compression {
+    /// Store the files without compression.
+    Stored,
+    /// Compress the files with deflate.
+    Deflated,
+}
+
+pub fn package(args: &PackageArgs, options: &GlobalOptions) -> Result<()> {
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+    let dist_path = args.output.clone().unwrap_or_else(|| path.join("dist"));
+    let staging_path = dist_path.join(STAGING_DIR);
+
+    cancel::install_shutdown_handler();
+    let result = super::build::build_artifact(
+        &BuildArgs {
+            path: path.clone(),
+            output: Some(staging_path.clone()),
+            assets: args.assets.clone(),
+            zip: true,
+            release: args.release,
+            no_validate: args.no_validate,
+            ..Default::default()
+        },
+        options,
+        cancel::global(),
+    )
+    .and_then(|staged| {
+        let (project_config, _) = super::build::get_pack_config(&path, options.strict_config)?;
+        let git_info = GitInfo::discover(&path);
+        let built_name = super::build::artifact_name(&project_config, git_info.as_ref());
+        let name = match &args.name {
+            Some(template) => {
+                super::build::expand_artifact_name(template, &project_config, git_info.as_ref())
+            }
+            None => built_name.clone(),
+        };
+        if !util::is_valid_file_name(&name) {
+            print_error(format!("The name \"{name}\" cannot be used as file name."));
+            return Err(Error::InvalidProjectNameError(name).into());
+        }
+
+        let mut artifacts = vec![(staged.clone(), dist_path.join(format!("{name}.zip")))];
+        let resource_pack = staged.with_file_name(format!("{built_name}_resources.zip"));
+        if resource_pack.is_file() {
+            artifacts.push((
+                resource_pack,
+                dist_path.join(format!("{name}_resources.zip")),
+            ));
+        }
+
+        for (staged, artifact) in &artifacts {
+            if args.compression != Compression::Deflated || args.compression_level.is_some() {
+                recompress(staged, args.compression, args.compression_level)?;
+            }
+            fs::rename(staged, artifact)?;
+        }
+
+        Ok(artifacts
+            .into_iter()
+            .map(|(_, artifact)| artifact)
+            .collect::<Vec<_>>())
+    });
+    // only the built zip files are moved out of the staging directory
+    let _ = fs::remove_dir_all(&staging_path);
+    let artifacts = result?;
+
+    for artifact in &artifacts {
+        if args.checksum {
+            let checksum = write_checksum(artifact)?;
+            print_info(format!("SHA-256 of {}: {checksum}", file_name(artifact)));
+        }
+        if args.sign {
+            sign(artifact, args.sign_key.as_deref())?;
+            print_info(format!("Signed {}", file_name(artifact)));
+        }
+    }
+
+    print_success(format!(
+        "Packaged project to {}",
+        artifacts[0].absolutize_from(&path)?.display()
+    ));
+
+    Ok(())
+}
+
+/// Rewrite the zip file with the compression method and level, keeping its comment.
+fn recompress(path: &Path, method: Compression, level: Option<i64>) -> Result<()> {
+    let mut source = ZipArchive::new(fs::File::open(path)?)?;
+    let partial = path.with_extension("zip.partial");
+    let mut writer = ZipWriter::new(BufWriter::new(fs::File::create(&partial)?));
+
+    let options = match method {
+        Compression::Stored => SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .compression_level(None),
+        Compression::Deflated => SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .compression_level(level),
+    };
+    for index in 0..source.len() {
+        let mut file = source.by_index(index)?;
+        if file.is_dir() {
+            writer.add_directory(file.name(), options)?;
+        } else {
+            let (name, size) = (file.name().to_string(), file.size());
+            archive::copy_entry(&mut writer, &name, options, size, &mut file)?;
+        }
+    }
+    writer.set_raw_comment(source.comment().into());
+    writer.finish()?.flush()?;
+    fs::rename(&partial, path)?;
+
+    Ok(())
+}
+
+/// Write the SHA-256 checksum of the file to `<file>.sha256` in the format of `sha256sum`.
+fn write_checksum(path: &Path) -> Result<String> {
+    // streamed, as the zip file may not fit into memory
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    let checksum = hex::encode(hasher.finalize());
+    fs::write(
+        append_extension(path, "sha256"),
+        format!("{checksum}  {}\n", file_name(path)),
+    )?;
+    Ok(checksum)
+}
+
+/// Write a detached ASCII-armored signature of the file to `<file>.asc` with GnuPG.
+fn sign(path: &Path, key: Option<&str>) -> Result<()> {
+    let mut command = Command::new("gpg");
+    command.args(["--batch", "--yes", "--armor", "--detach-sign"]);
+    if let Some(key) = key {
+        command.args(["--local-user", key]);
+    }
+    command
+        .arg("--output")
+        .arg(append_extension(path, "asc"))
+        .arg(path);
+
+    match command.status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => {
+            print_error(format!(
+                "GnuPG failed to sign {} ({status}).",
+                path.display()
+            ));
+            Err(Error::SigningError(path.to_path_buf()).into())
+        }
+        Err(err) => {
+            print_error(format!("Could not run GnuPG (`gpg`): {err}"));
+            Err(Error::SigningError(path.to_path_buf()).into())
+        }
+    }
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use zip::ZipWriter;
+
+use crate::{
+    archive,
+    error::Error,
+    fetch::{self, Sha256Pin},
+    terminal_output::{print_error, print_info, print_success},
+    units,
+};
+
+const MANIFEST_NAME: &str = "patch.json";
+const FILES_DIR: &str = "files/";
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct PatchArgs {
+    /// The zip file of the old release.
+    pub old: PathBuf,
+    /// The zip file of the new release.
+    pub new: PathBuf,
+    /// The path of the patch file to create.
+    ///
+    /// Defaults to `<new>.patch.zip` next to the new release.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct ApplyArgs {
+    /// The zip file of the release to update.
+    pub target: PathBuf,
+    /// The patch file created with the `patch` subcommand.
+    pub patch: PathBuf,
+    /// The path of the updated zip file.
+    ///
+    /// Defaults to replacing the target.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Apply the patch even if the target is not the release the patch was created from.
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// The description of the differences between two releases, stored as `patch.json` in the patch
+/// file next to the contents of the added and changed files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PatchManifest {
+    /// The content hash of the release the patch applies to.
+    from: Sha256Pin,
+    /// The content hash of the release the patch produces.
+    to: Sha256Pin,
+    /// The comment of the zip file of the new release.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    comment: String,
+    added: Vec<String>,
+    changed: Vec<String>,
+    removed: Vec<String>,
+}
+
+pub fn patch(args: &PatchArgs) -> Result<()> {
+    let (old, _) = read_zip(&args.old)?;
+    let (new, comment) = read_zip(&args.new)?;
+
+    let mut manifest = PatchManifest {
+        from: entries_hash(&old),
+        to: entries_hash(&new),
+        comment,
+        added: Vec::new(),
+        changed: Vec::new(),
+        removed: Vec::new(),
+    };
+    for (name, content) in &new {
+        match old.get(name) {
+            None => manifest.added.push(name.clone()),
+            Some(old_content) if old_content != content => manifest.changed.push(name.clone()),
+            Some(_) => {}
+        }
+    }
+    manifest.removed = old
+        .keys()
+        .filter(|name| !new.contains_key(*name))
+        .cloned()
+        .collect();
+
+    let output = args.output.clone().unwrap_or_else(|| {
+        let stem = args
+            .new
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "pack".to_string());
+        args.new.with_file_name(format!("{stem}.patch.zip"))
+    });
+
+    let mut writer = ZipWriter::new(BufWriter::new(fs::File::create(&output)?));
+    writer.start_file(MANIFEST_NAME, archive::default_options())?;
+    writer.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    for name in manifest.added.iter().chain(&manifest.changed) {
+        let content = &new[name];
+        writer.start_file(
+            format!("{FILES_DIR}{name}"),
+            archive::entry_options(archive::default_options(), content.len() as u64),
+        )?;
+        writer.write_all(content)?;
+    }
+    archive::finish(writer, "")?;
+
+    print_success(format!(
+        "Created patch {} with {} added, {} changed and {} removed files ({}).",
+        output.display(),
+        units::format_count(manifest.added.len()),
+        units::format_count(manifest.changed.len()),
+        units::format_count(manifest.removed.len()),
+        units::format_size(fs::metadata(&output)?.len())
+    ));
+
+    Ok(())
+}
+
+pub fn apply(args: &ApplyArgs) -> Result<()> {
+    let (mut files, _) = read_zip(&args.target)?;
+    let (patch_files, _) = read_zip(&args.patch)?;
+
+    let Some(manifest) = patch_files.get(MANIFEST_NAME) else {
+        print_error(format!(
+            "The file {} is not a patch created with the patch subcommand.",
+            args.patch.display()
+        ));
+        return Err(Error::PathNotFoundError(args.patch.join(MANIFEST_NAME)).into());
+    };
+    let manifest = serde_json::from_slice::<PatchManifest>(manifest)?;
+
+    let actual = entries_hash(&files);
+    if actual != manifest.from {
+        if args.force {
+            print_info(
+                "The target is not the release the patch was created from, applying anyway.",
+            );
+        } else {
+            print_error(format!(
+                "The patch does not apply to {}, it was created from another release. Use --force to apply it anyway.",
+                args.target.display()
+            ));
+            return Err(Error::ChecksumMismatchError {
+                source_name: args.target.display().to_string(),
+                expected: manifest.from.to_string(),
+                actual: actual.to_string(),
+            }
+            .into());
+        }
+    }
+
+    for name in &manifest.removed {
+        files.remove(name);
+    }
+    for name in manifest.added.iter().chain(&manifest.changed) {
+        let Some(content) = patch_files.get(&format!("{FILES_DIR}{name}")) else {
+            print_error(format!("The patch is missing the content of {name}."));
+            return Err(Error::PathNotFoundError(PathBuf::from(name)).into());
+        };
+        files.insert(name.clone(), content.clone());
+    }
+
+    let result = entries_hash(&files);
+    if result != manifest.to && !args.force {
+        print_error("The patched release does not match the release the patch was created for.");
+        return Err(Error::ChecksumMismatchError {
+            source_name: "the patched release".to_string(),
+            expected: manifest.to.to_string(),
+            actual: result.to_string(),
+        }
+        .into());
+    }
+
+    let output = args.output.as_deref().unwrap_or(&args.target);
+    write_zip(output, &files, &manifest.comment)?;
+
+    print_success(format!(
+        "Applied patch to {} ({} added, {} changed, {} removed).",
+        output.display(),
+        manifest.added.len(),
+        manifest.changed.len(),
+        manifest.removed.len()
+    ));
+
+    Ok(())
+}
+
+/// Read all files of a zip archive and its comment.
+fn read_zip(path: &Path) -> Result<(BTreeMap<String, Vec<u8>>, String)> {
+    Ok(archive::read_files(path).inspect_err(|err| {
+        print_error(format!("Could not read {}: {err}", path.display()));
+    })?)
+}
+
+/// Write the files to a zip archive, replacing the file at the path only once it is complete.
+fn write_zip(path: &Path, files: &BTreeMap<String, Vec<u8>>, comment: &str) -> Result<()> {
+    let partial = path.with_extension("zip.partial");
+    let mut writer = ZipWriter::new(BufWriter::new(fs::File::create(&partial)?));
+    for (name, content) in files {
+        writer.start_file(
+            name.as_str(),
+            archive::entry_options(archive::default_options(), content.len() as u64),
+        )?;
+        writer.write_all(content)?;
+    }
+    archive::finish(writer, comment)?;
+    fs::rename(&partial, path)?;
+
+    Ok(())
+}
+
+fn entries_hash(files: &BTreeMap<String, Vec<u8>>) -> Sha256Pin {
+    fetch::entries_hash(
+        files
+            .iter()
+            .map(|(name, content)| (name.as_str(), content.as_slice())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, Vec<u8>> {
+        entries
+            .iter()
+            .map(|(name, content)| (name.to_string(), content.as_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_patch_and_apply() {
+        let dir = TestProject::empty("patch");
+        let old_files = files(&[("pack.mcmeta", "{}"), ("a.txt", "a"), ("b.txt", "b")]);
+        let new_files = files(&[("pack.mcmeta", "{}"), ("a.txt", "changed"), ("c.txt", "c")]);
+        let old = dir.path().join("old.zip");
+        let new = dir.path().join("new.zip");
+        write_zip(&old, &old_files, "").unwrap();
+        write_zip(&new, &new_files, "release").unwrap();
+
+        patch(&PatchArgs {
+            old: old.clone(),
+            new: new.clone(),
+            output: None,
+        })
+        .unwrap();
+        let patch_path = dir.path().join("new.patch.zip");
+        let output = dir.path().join("patched.zip");
+        apply(&ApplyArgs {
+            target: old.clone(),
+            patch: patch_path.clone(),
+            output: Some(output.clone()),
+            force: false,
+        })
+        .unwrap();
+        assert_eq!(
+            read_zip(&output).unwrap(),
+            (new_files.clone(), "release".to_string())
+        );
+
+        // the patch does not apply to another release without --force
+        let other = dir.path().join("other.zip");
+        write_zip(&other, &files(&[("pack.mcmeta", "{}"), ("b.txt", "b")]), "").unwrap();
+        let args = ApplyArgs {
+            target: other.clone(),
+            patch: patch_path,
+            output: Some(output.clone()),
+            force: false,
+        };
+        assert!(apply(&args).is_err());
+        apply(&ApplyArgs {
+            force: true,
+            ..args
+        })
+        .unwrap();
+        let (forced, _) = read_zip(&output).unwrap();
+        assert_eq!(forced, new_files);
+    }
+
+    #[test]
+    fn test_read_zip_rejects_outside_entries() {
+        let dir = TestProject::empty("patch-outside");
+        let path = dir.path().join("outside.zip");
+        write_zip(&path, &files(&[("../outside.txt", "")]), "").unwrap();
+        assert!(read_zip(&path).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use path_absolutize::Absolutize as _;
+
+use crate::{
+    cancel,
+    cli::GlobalOptions,
+    config::{ModrinthPublishConfig, ProjectConfig},
+    runtime,
+    terminal_output::{print_error, print_info, print_success, print_warning},
+    units, util,
+    vcs::GitInfo,
+};
+
+use super::BuildArgs;
+
+const MODRINTH_API: &str = "https://api.modrinth.com/v2";
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct PublishArgs {
+    /// The path of the project to publish.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Rehearse the release without uploading anything.
+    ///
+    /// Validates and builds the project, renders the release metadata, checks the credentials
+    /// and prints what would be uploaded where.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// The Modrinth API token used for uploading.
+    #[arg(long, env = "MODRINTH_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+}
+
+pub fn publish(args: &PublishArgs, options: &GlobalOptions) -> Result<()> {
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+    let (project_config, toml_path) = super::build::get_pack_config(&path, options.strict_config)?;
+    let project_root = toml_path.parent().unwrap_or(&path);
+
+    let Some(modrinth) = project_config
+        .publish
+        .as_ref()
+        .and_then(|p| p.modrinth.as_ref())
+    else {
+        print_error(
+            "No publish target configured. Add a [publish.modrinth] section to the pack.toml file.",
+        );
+        return Err(anyhow::anyhow!("No publish target configured."));
+    };
+
+    if args.dry_run {
+        print_info("Rehearsing release (dry run), nothing will be uploaded.");
+    }
+
+    let mut problems = 0;
+
+    // validate and build the artifact
+    cancel::install_shutdown_handler();
+    let artifact = super::build::build_artifact(
+        &BuildArgs {
+            path: path.clone(),
+            zip: true,
+            release: true,
+            ..Default::default()
+        },
+        options,
+        cancel::global(),
+    )?;
+
+    // render the release metadata
+    let git_info = GitInfo::discover(&path);
+    let version = super::build::stamped_version(&project_config, git_info.as_ref());
+    let changelog_path = project_root.join(
+        modrinth
+            .changelog
+            .as_deref()
+            .unwrap_or(Path::new("CHANGELOG.md")),
+    );
+    let changelog = match fs::read_to_string(&changelog_path) {
+        Ok(content) => {
+            let section = changelog_section(&content, &version);
+            if section.is_none() {
+                print_warning(format!(
+                    "No section for version {version} found in {}.",
+                    changelog_path.display()
+                ));
+            }
+            section.unwrap_or_default()
+        }
+        Err(_) => {
+            print_warning(format!(
+                "Changelog file {} not found, publishing without changelog.",
+                changelog_path.display()
+            ));
+            String::new()
+        }
+    };
+    let metadata = version_metadata(&project_config, modrinth, &version, &changelog);
+
+    if modrinth.game_versions.is_empty() {
+        print_error("No game versions configured in [publish.modrinth].");
+        problems += 1;
+    }
+
+    // check the credentials
+    match args.token.clone() {
+        Some(token) => match runtime::until_cancelled(cancel::global(), move || {
+            check_token(&token)
+        })? {
+            Ok(username) => print_success(format!("Authenticated with Modrinth as {username}.")),
+            Err(err) => {
+                print_error(format!("Modrinth credentials are invalid: {err}"));
+                problems += 1;
+            }
+        },
+        None => {
+            print_error("No Modrinth token provided. Set the MODRINTH_TOKEN environment variable or use --token.");
+            problems += 1;
+        }
+    }
+
+    let artifact_size = fs::metadata(&artifact)?.len();
+    let target = format!("{MODRINTH_API}/version");
+
+    if args.dry_run {
+        print_info(format!(
+            "Would upload {} ({}) to {target}",
+            artifact.absolutize()?.display(),
+            units::format_size(artifact_size)
+        ));
+        print_info(format!(
+            "Version metadata:\n{}",
+            serde_json::to_string_pretty(&metadata)?
+        ));
+
+        if problems > 0 {
+            print_error(format!(
+                "Release rehearsal found {problems} problem(s) that would prevent publishing."
+            ));
+            return Err(anyhow::anyhow!("Release rehearsal failed."));
+        }
+
+        print_success("Release rehearsal finished, the project is ready to be published.");
+        return Ok(());
+    }
+
+    if problems > 0 {
+        return Err(anyhow::anyhow!("Publishing failed."));
+    }
+
+    let token = args.token.clone().unwrap_or_default();
+    runtime::until_cancelled(cancel::global(), move || {
+        upload(&token, &target, &artifact, &metadata)
+    })??;
+
+    print_success(format!(
+        "Published version {version} of project {} to Modrinth.",
+        modrinth.project
+    ));
+
+    Ok(())
+}
+
+/// Get the section of the changelog for the given version, falling back to the unreleased section.
+fn changelog_section(changelog: &str, version: &str) -> Option<String> {
+    let find_section = |pattern: &str| {
+        let mut lines = changelog.lines().skip_while(|line| {
+            !(line.starts_with("## ") && line.to_lowercase().contains(&pattern.to_lowercase()))
+        });
+        lines.next()?;
+        let section = lines
+            .take_while(|line| {
+                let is_link_definition = line.starts_with('[') && line.contains("]: ");
+                !line.starts_with("## ") && !is_link_definition
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(section.trim().to_string())
+    };
+
+    find_section(&format!("[{version}]"))
+        .or_else(|| find_section(version))
+        .or_else(|| find_section("[unreleased]"))
+}
+
+fn version_metadata(
+    config: &ProjectConfig,
+    modrinth: &ModrinthPublishConfig,
+    version: &str,
+    changelog: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "name": format!("{} {version}", config.pack.name),
+        "version_number": version,
+        "changelog": changelog,
+        "dependencies": [],
+        "game_versions": modrinth.game_versions,
+        "version_type": modrinth.version_type.as_deref().unwrap_or("release"),
+        "loaders": ["datapack"],
+        "featured": false,
+        "project_id": modrinth.project,
+        "file_parts": ["file"],
+        "primary_file": "file",
+    })
+}
+
+/// Check the token and return the name of the authenticated user.
+fn check_token(token: &str) -> Result<String> {
+    let user: serde_json::Value = ureq::get(&format!("{MODRINTH_API}/user"))
+        .set("Authorization", token)
+        .set("User-Agent", util::USER_AGENT)
+        .call()?
+        .into_json()?;
+
+    Ok(user
+        .get("username")
+        .and_then(|name| name.as_str())
+        .unwrap_or("unknown user")
+        .to_string())
+}
+
+fn upload(token: &str, target: &str, artifact: &Path, metadata: &serde_json::Value) -> Result<()> {
+    let boundary = format!(
+        "shulkerscript-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default()
+    );
+    let file_name = artifact
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "datapack.zip".to_string());
+
+    let mut body = Vec::new();
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"data\"\r\nContent-Type: application/json\r\n\r\n{metadata}\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\nContent-Type: application/zip\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(&fs::read(artifact)?);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    ureq::post(target)
+        .set("Authorization", token)
+        .set("User-Agent", util::USER_AGENT)
+        .set(
+            "Content-Type",
+            &format!("multipart/form-data; boundary={boundary}"),
+        )
+        .send_bytes(&body)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changelog_section() {
+        let changelog = "# Changelog\n\n## [Unreleased]\n\n- next\n\n## [1.0.0] - 2024-10-01\n\n- first\n\n[unreleased]: https://example.com\n";
+
+        assert_eq!(
+            changelog_section(changelog, "1.0.0").as_deref(),
+            Some("- first")
+        );
+        assert_eq!(
+            changelog_section(changelog, "2.0.0").as_deref(),
+            Some("- next")
+        );
+    }
+}
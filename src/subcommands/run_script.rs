@@ -0,0 +1,154 @@
+use std::{borrow::Cow, env, path::PathBuf, time::Duration};
+
+use anyhow::Result;
+use path_absolutize::Absolutize as _;
+
+use crate::{
+    cli::GlobalOptions,
+    config::{LimitsConfig, ProjectConfig},
+    terminal_output::{print_error, print_info, print_plain, print_success},
+    units,
+    util::{self, CommandOutcome},
+    vcs::GitInfo,
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct RunScriptArgs {
+    /// The name of the script to run.
+    ///
+    /// Lists the available scripts if omitted.
+    pub name: Option<String>,
+    /// Additional arguments appended to the command of the script, each passed as a single
+    /// argument without being interpreted by the shell.
+    #[arg(last = true)]
+    pub args: Vec<String>,
+    /// The path of the project to run the script in.
+    #[arg(short, long, default_value = ".")]
+    pub path: PathBuf,
+    /// The path of the directory where the compiled datapacks are placed.
+    #[arg(short, long, env = "DATAPACK_DIR")]
+    pub output: Option<PathBuf>,
+    /// Kill the script after the time in seconds, overriding `timeout` of the `[limits]` section.
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+    /// Only print the first bytes of the stdout and stderr of the script, overriding
+    /// `max_output` of the `[limits]` section.
+    #[arg(long, value_name = "BYTES")]
+    pub max_output: Option<usize>,
+}
+
+pub fn run_script(args: &RunScriptArgs, options: &GlobalOptions) -> Result<()> {
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+    let (project_config, toml_path) = super::build::get_pack_config(&path, options.strict_config)?;
+    let project_root = toml_path.parent().unwrap_or(&path).to_path_buf();
+
+    let Some(name) = &args.name else {
+        if project_config.scripts.is_empty() {
+            print_info(
+                "No scripts defined. Add them to the [scripts] section of the pack.toml file.",
+            );
+        } else {
+            print_info("Available scripts:");
+            for (name, command) in &project_config.scripts {
+                print_plain(format!("    {name}: {command}"));
+            }
+        }
+        return Ok(());
+    };
+
+    let Some(script) = project_config.scripts.get(name) else {
+        print_error(format!(
+            "No script named {name} found in the [scripts] section of the pack.toml file."
+        ));
+        return Err(anyhow::anyhow!("Script {name} not found"));
+    };
+
+    let command_line = if args.args.is_empty() {
+        Cow::Borrowed(script)
+    } else {
+        let quoted = args
+            .args
+            .iter()
+            .map(|arg| util::quote_shell_arg(arg))
+            .collect::<Vec<_>>();
+        Cow::Owned(format!("{script} {}", quoted.join(" ")))
+    };
+
+    let dist_path = args
+        .output
+        .as_ref()
+        .map(Cow::Borrowed)
+        .unwrap_or_else(|| Cow::Owned(project_root.join("dist")));
+
+    print_info(format!("Running script {name}: {command_line}"));
+
+    let mut command = util::shell_command(&command_line);
+    command.current_dir(&project_root);
+    for (key, value) in script_env(&project_config, &project_root, &dist_path)? {
+        command.env(key, value);
+    }
+
+    let limits = LimitsConfig::command_limits(
+        project_config.limits.as_ref(),
+        args.timeout.map(Duration::from_secs),
+        args.max_output,
+    );
+    match util::run_limited(&mut command, &limits)? {
+        CommandOutcome::Exited(status) if status.success() => {
+            print_success(format!("Script {name} finished successfully."));
+            Ok(())
+        }
+        CommandOutcome::Exited(status) => {
+            print_error(format!(
+                "Script {name} exited unsuccessfully with status code {}",
+                status.code().unwrap_or(1)
+            ));
+            Err(anyhow::anyhow!("Script {name} failed"))
+        }
+        CommandOutcome::TimedOut(elapsed) => {
+            print_error(format!(
+                "Script {name} timed out after {} and was killed.",
+                units::format_duration(elapsed)
+            ));
+            Err(anyhow::anyhow!("Script {name} timed out"))
+        }
+    }
+}
+
+/// The environment variables describing the project that are passed to scripts.
+fn script_env(
+    config: &ProjectConfig,
+    project_root: &std::path::Path,
+    dist_path: &std::path::Path,
+) -> Result<Vec<(&'static str, String)>> {
+    let git_info = GitInfo::discover(project_root);
+    let dist_path = dist_path.absolutize_from(project_root)?;
+    let artifact = dist_path.join(super::build::artifact_name(config, git_info.as_ref()));
+
+    let mut vars = vec![
+        ("SHULKERSCRIPT_PACK_NAME", config.pack.name.clone()),
+        (
+            "SHULKERSCRIPT_PACK_NAMESPACE",
+            config.pack.namespace().into_owned(),
+        ),
+        (
+            "SHULKERSCRIPT_PACK_VERSION",
+            super::build::stamped_version(config, git_info.as_ref()),
+        ),
+        (
+            "SHULKERSCRIPT_PACK_FORMAT",
+            config.pack.pack_format.to_string(),
+        ),
+        (
+            "SHULKERSCRIPT_PROJECT_DIR",
+            project_root.absolutize()?.display().to_string(),
+        ),
+        ("SHULKERSCRIPT_DIST", dist_path.display().to_string()),
+        ("SHULKERSCRIPT_ARTIFACT", artifact.display().to_string()),
+    ];
+    if let Ok(exe) = env::current_exe() {
+        vars.push(("SHULKERSCRIPT_BIN", exe.display().to_string()));
+    }
+
+    Ok(vars)
+}
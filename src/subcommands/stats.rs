@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::{
+    cli::GlobalOptions,
+    error::Error,
+    stats::{self, BuildStats, Statistics},
+    terminal_output::{print_error, print_info},
+    units::{format_count, format_decimal, format_duration, format_size},
+    util,
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct StatsArgs {
+    /// The path of the project to summarize the build statistics of.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// The number of recent builds compared with the builds before them.
+    #[arg(short, long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    pub last: u32,
+}
+
+pub fn stats(args: &StatsArgs, options: &GlobalOptions) -> Result<()> {
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+    if !path.join("pack.toml").is_file() {
+        print_error("The specified directory does not contain a pack.toml file.");
+        return Err(Error::InvalidPackPathError(path).into());
+    }
+
+    let builds = stats::load(&path)?;
+    if builds.is_empty() {
+        print_info(format!(
+            "No builds are recorded in {}. Set `record_stats = true` in the `[compiler]` section of the pack.toml file to record them.",
+            path.join(stats::STATS_FILE).display()
+        ));
+        return Ok(());
+    }
+
+    let failed = builds.iter().filter(|build| !build.success).count();
+    print_info(format!(
+        "{} builds recorded, {} failed ({}% success rate)",
+        format_count(builds.len()),
+        format_count(failed),
+        format_decimal(percentage(builds.len() - failed, builds.len()), 0)
+    ));
+
+    let successful = builds
+        .iter()
+        .filter(|build| build.success)
+        .collect::<Vec<_>>();
+    let last = (args.last as usize).min(successful.len());
+    let (previous, recent) = successful.split_at(successful.len() - last);
+    let previous = &previous[previous.len().saturating_sub(last)..];
+    if let Some(recent_stats) = duration_statistics(recent) {
+        print_info(format!(
+            "Last {} successful builds: mean {} ± {}, median {}",
+            recent.len(),
+            format_duration(recent_stats.mean),
+            format_duration(recent_stats.std_dev),
+            format_duration(recent_stats.median),
+        ));
+        if let Some(previous_stats) = duration_statistics(previous) {
+            let change =
+                (recent_stats.mean.as_secs_f64() / previous_stats.mean.as_secs_f64() - 1.0) * 100.0;
+            let sign = if change >= 0.0 { "+" } else { "" };
+            print_info(format!(
+                "Previous {} successful builds: mean {} ± {}, median {} ({sign}{}% since)",
+                previous.len(),
+                format_duration(previous_stats.mean),
+                format_duration(previous_stats.std_dev),
+                format_duration(previous_stats.median),
+                format_decimal(change, 1),
+            ));
+        }
+    }
+
+    if let Some(latest) = successful.last() {
+        print_info(format!(
+            "Latest successful build: {} scripts, {} output files with {}",
+            format_count(latest.scripts),
+            format_count(latest.output_files),
+            format_size(latest.output_bytes as u64)
+        ));
+    }
+
+    let hits = builds.iter().map(|build| build.cache_hits).sum::<usize>();
+    let misses = builds.iter().map(|build| build.cache_misses).sum::<usize>();
+    if hits + misses > 0 {
+        print_info(format!(
+            "Dependency cache hit rate: {}% ({} of {} git dependencies)",
+            format_decimal(percentage(hits, hits + misses), 0),
+            format_count(hits),
+            format_count(hits + misses)
+        ));
+    }
+
+    Ok(())
+}
+
+fn duration_statistics(builds: &[&BuildStats]) -> Option<Statistics> {
+    Statistics::new(builds.iter().map(|build| build.duration()))
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    part as f64 / total as f64 * 100.0
+}
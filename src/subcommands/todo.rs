@@ -0,0 +1,185 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    cli::GlobalOptions,
+    error::Error,
+    terminal_output::{print_error, print_info, print_plain, print_success},
+    units::format_count,
+    util,
+    walk::WalkOptions,
+};
+
+/// The start of the banner of the files created by the `migrate` subcommand.
+const MIGRATED_BANNER: &str = "// This file was automatically migrated by Shulkerscript CLI";
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct TodoArgs {
+    /// The path of the project to scan for markers.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Print the markers as JSON, e.g. for issue trackers.
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// The kind of a marker of pending cleanup work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkerKind {
+    Todo,
+    Fixme,
+    /// The banner of a file created by the `migrate` subcommand.
+    Migrated,
+    /// A `#[deobfuscate]` annotation of a migrated file, keeping the name of the original
+    /// function until it is reviewed.
+    Deobfuscate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Marker {
+    pub line: usize,
+    pub kind: MarkerKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct FileMarkers {
+    file: PathBuf,
+    markers: Vec<Marker>,
+}
+
+pub fn todo(args: &TodoArgs, options: &GlobalOptions) -> Result<()> {
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
+    if !path.join("pack.toml").is_file() {
+        print_error("The specified directory does not contain a pack.toml file.");
+        return Err(Error::InvalidPackPathError(path).into());
+    }
+
+    let mut script_paths = super::get_script_paths(&path.join("src"), WalkOptions::default())?
+        .into_iter()
+        .map(|(_, script_path)| script_path)
+        .collect::<Vec<_>>();
+    script_paths.sort();
+
+    let mut files = Vec::new();
+    for script_path in script_paths {
+        let markers = scan(&fs::read_to_string(&script_path)?);
+        if !markers.is_empty() {
+            let file = script_path
+                .strip_prefix(&path)
+                .map_or(script_path.clone(), PathBuf::from);
+            files.push(FileMarkers { file, markers });
+        }
+    }
+
+    if args.json {
+        print_plain(serde_json::to_string(&files)?);
+        return Ok(());
+    }
+
+    if files.is_empty() {
+        print_success("No TODO, FIXME or migration markers found.");
+        return Ok(());
+    }
+
+    for file in &files {
+        print_plain(file.file.display());
+        let width = file
+            .markers
+            .last()
+            .map_or(1, |marker| marker.line.to_string().len());
+        for marker in &file.markers {
+            print_plain(format!("  {:>width$}  {}", marker.line, marker.text));
+        }
+    }
+    let count = files.iter().map(|file| file.markers.len()).sum::<usize>();
+    print_info(format!(
+        "Found {} markers in {} files.",
+        format_count(count),
+        format_count(files.len())
+    ));
+
+    Ok(())
+}
+
+/// Find the markers in the content of a script.
+///
+/// `TODO` and `FIXME` are only matched as whole words in comments, `#[deobfuscate]` annotations
+/// only in migrated files.
+pub fn scan(content: &str) -> Vec<Marker> {
+    let migrated = content.contains(MIGRATED_BANNER);
+    let mut markers = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        let marker = if trimmed.starts_with(MIGRATED_BANNER) {
+            Some((MarkerKind::Migrated, trimmed))
+        } else if migrated && trimmed.starts_with("#[deobfuscate") {
+            Some((MarkerKind::Deobfuscate, trimmed))
+        } else {
+            trimmed.find("//").and_then(|start| {
+                let comment = &trimmed[start..];
+                [("TODO", MarkerKind::Todo), ("FIXME", MarkerKind::Fixme)]
+                    .into_iter()
+                    .filter_map(|(word, kind)| find_word(comment, word).map(|at| (at, kind)))
+                    .min_by_key(|(at, _)| *at)
+                    .map(|(at, kind)| (kind, comment[at..].trim_end()))
+            })
+        };
+        if let Some((kind, text)) = marker {
+            markers.push(Marker {
+                line: index + 1,
+                kind,
+                text: text.to_string(),
+            });
+        }
+    }
+    markers
+}
+
+/// The position of the first occurrence of the word that is not part of a longer word.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).map(|(at, _)| at).find(|&at| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + word.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        let content = r#"// This file was automatically migrated by Shulkerscript CLI v0.1.0 from file "a.mcfunction"
+namespace "test";
+
+#[deobfuscate = "a"]
+fn a() {
+    /// TODO: replace with a loop
+    /say TODOS are not markers
+    /say hello // FIXME wrong greeting, TODO later
+}
+"#;
+        let markers = scan(content);
+        let lines = markers
+            .iter()
+            .map(|marker| (marker.line, marker.kind, marker.text.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                (1, MarkerKind::Migrated, content.lines().next().unwrap()),
+                (4, MarkerKind::Deobfuscate, "#[deobfuscate = \"a\"]"),
+                (6, MarkerKind::Todo, "TODO: replace with a loop"),
+                (8, MarkerKind::Fixme, "FIXME wrong greeting, TODO later"),
+            ]
+        );
+
+        assert!(scan("#[deobfuscate = \"a\"]\nfn a() {}").is_empty());
+    }
+}
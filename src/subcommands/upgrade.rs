@@ -0,0 +1,78 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use crate::{
+    cli::GlobalOptions,
+    error::Error,
+    terminal_output::{print_diff, print_error, print_info, print_success, print_warning},
+    upgrade, util,
+};
+
+#[derive(Debug, clap::Args, Clone)]
+pub struct UpgradeArgs {
+    /// The path of the project to upgrade.
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Print the changes as a diff without writing them.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn upgrade(args: &UpgradeArgs, options: &GlobalOptions) -> Result<()> {
+    edit_pack_toml(&args.path, options.discover(), args.dry_run, |document| {
+        let mut changes = upgrade::fix_deprecations(document);
+        match upgrade::update_pack_format(document) {
+            Ok(change) => changes.extend(change),
+            Err(warning) => print_warning(warning),
+        }
+        print_info(format!(
+            "No automated source fixes are available for Shulkerscript v{}.",
+            shulkerscript::VERSION
+        ));
+        changes
+    })
+}
+
+/// Apply the edits to the pack.toml file of the project and print the changes.
+///
+/// With `dry_run`, the changes are printed as a diff instead of being written.
+pub(super) fn edit_pack_toml(
+    path: &Path,
+    discover: bool,
+    dry_run: bool,
+    edit: impl FnOnce(&mut toml_edit::DocumentMut) -> Vec<String>,
+) -> Result<()> {
+    let path = util::get_project_path(path, discover).unwrap_or(path.to_path_buf());
+    let toml_path = path.join("pack.toml");
+    if !toml_path.is_file() {
+        print_error("The specified directory does not contain a pack.toml file.");
+        return Err(Error::InvalidPackPathError(path).into());
+    }
+
+    let content = fs::read_to_string(&toml_path)?;
+    let mut document = content.parse::<toml_edit::DocumentMut>()?;
+
+    let changes = edit(&mut document);
+    if changes.is_empty() {
+        print_success("The project is up to date.");
+        return Ok(());
+    }
+    for change in &changes {
+        print_info(change);
+    }
+
+    let edited = document.to_string();
+    if dry_run {
+        print_diff(&util::line_diff(&content, &edited), 2);
+        print_success("Dry run finished, nothing was written.");
+    } else {
+        fs::write(&toml_path, edited)?;
+        print_success(format!("Updated {}", toml_path.display()));
+    }
+
+    Ok(())
+}
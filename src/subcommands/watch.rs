@@ -1,22 +1,25 @@
 use std::{
-    env, io, iter,
-    path::PathBuf,
-    process::{self, ExitStatus},
-    thread,
+    collections::BTreeSet, net::SocketAddr, num::NonZeroUsize, path::PathBuf, process,
     time::Duration,
 };
 
-use clap::Parser;
+use anyhow::Result;
 use colored::Colorize;
-use notify_debouncer_mini::{new_debouncer, notify::*, DebounceEventResult};
+use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode, DebounceEventResult};
 
 use crate::{
-    cli::Args,
-    error::Result,
-    terminal_output::{print_error, print_info, print_warning},
+    cancel,
+    cli::GlobalOptions,
+    config::LimitsConfig,
+    runtime,
+    terminal_output::print_info,
     util,
+    watcher::{self, ContentHashes, Watcher},
+    workspace::Workspace,
 };
 
+use super::BuildArgs;
+
 #[derive(Debug, clap::Args, Clone)]
 pub struct WatchArgs {
     /// The path of the project to watch.
@@ -30,10 +33,30 @@ pub struct WatchArgs {
     /// The time to wait in ms before running the command after changes are detected.
     #[arg(short, long, value_name = "TIME_IN_MS", default_value = "2000")]
     pub debounce_time: u64,
+    /// The time to wait in ms after changes of the paths matching a glob pattern, relative to
+    /// the project root, e.g. `assets/**=5000`.
+    ///
+    /// Use multiple times for multiple patterns, the first matching one is used. Takes
+    /// precedence over the `debounce` table of the `[watch]` section.
+    #[arg(
+        long,
+        value_name = "GLOB=TIME_IN_MS",
+        value_parser = parse_debounce_path,
+        conflicts_with = "workspace"
+    )]
+    pub debounce: Vec<(String, u64)>,
+    /// Run the commands on every change event, even if the contents of the files did not
+    /// change.
+    ///
+    /// By default, files touched or saved without changes and temporary files of editors do not
+    /// trigger a run.
+    #[arg(long)]
+    pub no_content_check: bool,
     /// Additional paths to watch for changes.
     ///
-    /// By default, the `src` directory, `pack.png`, and `pack.toml` as well as the defined
-    /// assets directory in the config are watched.
+    /// By default, the `src` and `structures` directories, `pack.png`, and `pack.toml` as well as
+    /// the defined assets directory and the paths of the `[watch]` section in the config are
+    /// watched. Changes of `pack.toml` start watching newly configured paths.
     #[arg(short, long, value_name = "PATH")]
     pub watch: Vec<PathBuf>,
     /// The shulkerscript commands to run in the project directory when changes are detected.
@@ -44,7 +67,9 @@ pub struct WatchArgs {
     ///
     /// Use the `--no-execute` flag to disable running these commands, useful when only wanting to
     /// run shell commands and not default build command.
-    #[arg(short = 'x', long, value_name = "COMMAND", default_value = "build .")]
+    ///
+    /// Defaults to the `execute` field of the `[watch]` section in the config or `build .`.
+    #[arg(short = 'x', long, value_name = "COMMAND")]
     pub execute: Vec<String>,
     /// Do not run the internal shulkerscript commands specified by `--execute` (and the default one).
     #[arg(short = 'X', long)]
@@ -54,167 +79,257 @@ pub struct WatchArgs {
     /// Use multiple times to run multiple commands.
     /// Shell commands will always run after shulkerscript commands and a command will only run
     /// if the previous one exited successfully.
+    ///
+    /// Defaults to the `shell` field of the `[watch]` section in the config.
     #[arg(short, long, value_name = "COMMAND")]
     pub shell: Vec<String>,
+    /// Kill shell commands running longer than the time in seconds, overriding `timeout` of the
+    /// `[limits]` section.
+    #[arg(long, value_name = "SECONDS")]
+    pub shell_timeout: Option<u64>,
+    /// Only print the first bytes of the stdout and stderr of shell commands, overriding
+    /// `max_output` of the `[limits]` section.
+    #[arg(long, value_name = "BYTES")]
+    pub max_output: Option<usize>,
+    /// Shulkerscript commands to run as verification stages after the commands of `--execute`
+    /// succeeded, e.g. `--and-then "lint"`.
+    ///
+    /// Use multiple times to run multiple stages. A stage only runs if the previous one
+    /// succeeded, and the status and duration of each stage is printed.
+    #[arg(long, value_name = "COMMAND")]
+    pub and_then: Vec<String>,
+    /// Listen for HTTP requests triggering a run of the commands on the address, e.g.
+    /// `127.0.0.1:7878`.
+    ///
+    /// Send a `POST` request to `/trigger` to run the commands, e.g. from a webhook.
+    #[arg(long, value_name = "ADDRESS")]
+    pub listen: Option<SocketAddr>,
+    /// The token required in the `Authorization: Bearer <TOKEN>` header of trigger requests.
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "SHULKERSCRIPT_WATCH_TOKEN",
+        hide_env_values = true,
+        requires = "listen"
+    )]
+    pub listen_token: Option<String>,
+    /// Stop watching after the first run following a change or trigger.
+    ///
+    /// Exits with an error if the run failed.
+    #[arg(long)]
+    pub once: bool,
+    /// Stop watching after the first run in which all commands succeeded.
+    ///
+    /// Useful to wait until a project builds, e.g. in scripts. Exits with an error if the watcher
+    /// is stopped before a run succeeded.
+    #[arg(long, conflicts_with = "workspace")]
+    pub until_success: bool,
+    /// Stop watching after the number of runs, including the initial one.
+    ///
+    /// Prints a summary of the runs and exits with an error if the last run failed.
+    #[arg(long, value_name = "N", conflicts_with = "workspace")]
+    pub runs: Option<NonZeroUsize>,
+    /// Watch all members of the workspace containing the path.
+    ///
+    /// Only the members whose files changed and the members depending on them are rebuilt.
+    #[arg(long, conflicts_with_all = ["execute", "no_execute", "watch", "listen"])]
+    pub workspace: bool,
 }
 
-pub fn watch(args: &WatchArgs) -> Result<()> {
-    let path = util::get_project_path(&args.path).unwrap_or(args.path.clone());
+pub fn watch(args: &WatchArgs, options: &GlobalOptions) -> Result<()> {
+    if args.workspace {
+        return watch_workspace(args, options);
+    }
+
+    let path = util::get_project_path(&args.path, options.discover()).unwrap_or(args.path.clone());
     print_info(format!("Watching project at {}", path.display()));
     print_info(format!(
         "Press {} to stop watching",
         "Ctrl-C".underline().blue()
     ));
 
-    let commands = args
-        .execute
-        .iter()
-        .map(|cmd| {
-            let split = cmd.split_whitespace();
-            let prog_name = std::env::args()
-                .next()
-                .unwrap_or(env!("CARGO_PKG_NAME").to_string());
-            Args::parse_from(iter::once(prog_name.as_str()).chain(split.clone()))
-        })
-        .collect::<Vec<_>>();
-
-    let current_dir = if args.no_inital {
-        print_info("Skipping initial commands because of cli flag.");
-        None
-    } else {
-        env::current_dir().ok()
-    };
-
-    if !args.no_inital && (current_dir.is_none() || env::set_current_dir(&path).is_err()) {
-        print_warning("Failed to change working directory to project path. Commands may not work.");
+    let mut builder = Watcher::builder(path)
+        .debounce(Duration::from_millis(args.debounce_time))
+        .compare_contents(!args.no_content_check)
+        .no_execute(args.no_execute)
+        .initial(!args.no_inital)
+        .once(args.once)
+        .until_success(args.until_success)
+        .strict_config(options.strict_config)
+        .cancellation(cancel::global().clone());
+    for (pattern, millis) in &args.debounce {
+        builder = builder.debounce_path(pattern, Duration::from_millis(*millis));
     }
+    for path in &args.watch {
+        builder = builder.watch_path(path);
+    }
+    for command in &args.execute {
+        builder = builder.execute(command);
+    }
+    for stage in &args.and_then {
+        builder = builder.and_then(stage);
+    }
+    for command in &args.shell {
+        builder = builder.shell(command);
+    }
+    if let Some(timeout) = args.shell_timeout {
+        builder = builder.shell_timeout(Duration::from_secs(timeout));
+    }
+    if let Some(max_output) = args.max_output {
+        builder = builder.max_output(max_output);
+    }
+    if let Some(runs) = args.runs {
+        builder = builder.runs(runs.get());
+    }
+    if let Some(address) = args.listen {
+        builder = builder.listen(address, args.listen_token.clone());
+    }
+    let watcher = builder
+        .build()
+        .map_err(|err| match err.downcast::<clap::Error>() {
+            Ok(err) => err.exit(),
+            Err(err) => err,
+        })?;
+
+    cancel::install_shutdown_handler();
+    watcher.run()
+}
+
+fn watch_workspace(args: &WatchArgs, options: &GlobalOptions) -> Result<()> {
+    let workspace = Workspace::discover(&args.path, options)?;
+    print_info(format!(
+        "Watching workspace at {} with {} members",
+        workspace.root.display(),
+        workspace.members.len()
+    ));
+    print_info(format!(
+        "Press {} to stop watching",
+        "Ctrl-C".underline().blue()
+    ));
 
-    #[allow(clippy::collapsible_if)]
-    if !args.no_inital {
-        run_cmds(&commands, args.no_execute, &args.shell, true);
+    let stages =
+        watcher::parse_stages(&args.and_then, &workspace.root).unwrap_or_else(|err| err.exit());
+    let limits = LimitsConfig::command_limits(
+        None,
+        args.shell_timeout.map(Duration::from_secs),
+        args.max_output,
+    );
+    cancel::install_shutdown_handler();
+
+    if args.no_inital {
+        print_info("Skipping initial build because of cli flag.");
+    } else {
+        print_info("Building all members initially...");
+        if super::build::build_members(&workspace, &BuildArgs::default(), options, cancel::global())
+            .is_ok()
+            && watcher::run_stages(&stages)
+        {
+            watcher::run_shell_cmds(&args.shell, &workspace.root, &limits);
+        }
     }
 
-    ctrlc::set_handler(move || {
-        print_info("Stopping watcher...");
-        process::exit(0);
-    })
-    .expect("Error setting Ctrl-C handler");
+    let mut paths = Vec::new();
+    for member in &workspace.members {
+        paths.extend([
+            (member.path.join("src"), RecursiveMode::Recursive),
+            (member.path.join("pack.png"), RecursiveMode::NonRecursive),
+            (member.path.join("pack.toml"), RecursiveMode::NonRecursive),
+        ]);
+        if let Some(assets) = member
+            .config
+            .compiler
+            .as_ref()
+            .and_then(|c| c.assets.as_ref())
+        {
+            paths.push((member.path.join(assets), RecursiveMode::Recursive));
+        }
+    }
+    let mut hashes = (!args.no_content_check).then(|| {
+        let mut hashes = ContentHashes::default();
+        hashes.add_all(paths.iter().map(|(path, _)| path));
+        hashes
+    });
 
+    let watched_workspace = workspace.clone();
     let shell_commands = args.shell.clone();
-    let no_execute = args.no_execute;
+    let shell_dir = workspace.root.clone();
+    let options = *options;
 
     let mut debouncer = new_debouncer(
         Duration::from_millis(args.debounce_time),
-        move |res: DebounceEventResult| {
-            if res.is_ok() {
-                run_cmds(&commands, no_execute, &shell_commands, false)
-            } else {
-                process::exit(1);
+        move |res: DebounceEventResult| match res {
+            Ok(events) => {
+                let changed = events
+                    .iter()
+                    .filter(|event| match &mut hashes {
+                        Some(hashes) => hashes.update(&event.path),
+                        None => true,
+                    })
+                    .filter_map(|event| watched_workspace.member_containing(&event.path))
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                if changed.is_empty() {
+                    return;
+                }
+
+                let affected = watched_workspace.affected_by(&changed);
+                print_info(format!(
+                    "Changes have been detected in {}. Rebuilding {}...",
+                    member_names(&watched_workspace, &changed),
+                    affected
+                        .members
+                        .iter()
+                        .map(|member| member.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+                if super::build::build_members(
+                    &affected,
+                    &BuildArgs::default(),
+                    &options,
+                    cancel::global(),
+                )
+                .is_ok()
+                    && watcher::run_stages(&stages)
+                {
+                    watcher::run_shell_cmds(&shell_commands, &shell_dir, &limits);
+                }
             }
+            Err(_) => process::exit(1),
         },
     )
     .expect("Failed to initialize watcher");
 
-    if let Some(prev_cwd) = current_dir {
-        env::set_current_dir(prev_cwd).expect("Failed to change working directory back");
-    }
-
-    let assets_path = super::build::get_pack_config(&path)
-        .ok()
-        .and_then(|(conf, _)| conf.compiler.and_then(|c| c.assets));
-
     let watcher = debouncer.watcher();
-    watcher
-        .watch(path.join("src").as_path(), RecursiveMode::Recursive)
-        .expect("Failed to watch project src");
-    watcher
-        .watch(path.join("pack.png").as_path(), RecursiveMode::NonRecursive)
-        .expect("Failed to watch project pack.png");
-    watcher
-        .watch(
-            path.join("pack.toml").as_path(),
-            RecursiveMode::NonRecursive,
-        )
-        .expect("Failed to watch project pack.toml");
-    if let Some(assets_path) = assets_path {
-        let full_assets_path = path.join(assets_path);
-        if full_assets_path.exists() {
-            watcher
-                .watch(full_assets_path.as_path(), RecursiveMode::Recursive)
-                .expect("Failed to watch project assets");
-        }
-    }
-
-    // custom watch paths
-    for path in args.watch.iter() {
+    for (path, mode) in paths {
         if path.exists() {
             watcher
-                .watch(path, RecursiveMode::Recursive)
-                .expect("Failed to watch custom path");
-        } else {
-            print_warning(format!(
-                "Path {} does not exist. Skipping...",
-                path.display()
-            ));
+                .watch(&path, mode)
+                .expect("Failed to watch workspace member");
         }
     }
 
-    if env::set_current_dir(path).is_err() {
-        print_warning("Failed to change working directory to project path. Commands may not work.");
-    }
+    runtime::block_on(cancel::global().cancelled());
+    print_info("Stopping watcher...");
 
-    loop {
-        thread::sleep(Duration::from_secs(60));
-    }
+    Ok(())
 }
 
-fn run_cmds(cmds: &[Args], no_execute: bool, shell_cmds: &[String], initial: bool) {
-    if initial {
-        print_info("Running commands initially...");
-    } else {
-        print_info("Changes have been detected. Running commands...");
-    }
-    if !no_execute {
-        for (index, args) in cmds.iter().enumerate() {
-            if args.run().is_err() {
-                print_error(format!("Error running command: {}", index + 1));
-                print_error("Not running further commands.");
-                return;
-            }
-        }
-    }
-    for (index, cmd) in shell_cmds.iter().enumerate() {
-        let status = run_shell_cmd(cmd);
-        match status {
-            Ok(status) if !status.success() => {
-                print_error(format!(
-                    "Shell command {} exited unsuccessfully with status code {}",
-                    index + 1,
-                    status.code().unwrap_or(1)
-                ));
-                print_error("Not running further shell commands.");
-                return;
-            }
-            Ok(_) => {}
-            Err(_) => {
-                print_error(format!("Error running shell command: {}", index + 1));
-                print_error("Not running further shell commands.");
-                return;
-            }
-        }
-    }
+fn parse_debounce_path(value: &str) -> Result<(String, u64), String> {
+    let (pattern, millis) = value
+        .rsplit_once('=')
+        .ok_or_else(|| "expected `GLOB=TIME_IN_MS`".to_string())?;
+    let millis = millis
+        .parse()
+        .map_err(|err| format!("invalid time \"{millis}\": {err}"))?;
+    Ok((pattern.to_string(), millis))
 }
 
-fn run_shell_cmd(cmd: &str) -> io::Result<ExitStatus> {
-    let mut command = if cfg!(target_os = "windows") {
-        let mut command = process::Command::new("cmd");
-        command.arg("/C");
-        command
-    } else {
-        let mut command = process::Command::new(env::var("SHELL").unwrap_or("sh".to_string()));
-        command.arg("-c");
-        command
-    };
-
-    command.arg(cmd).status()
+fn member_names(workspace: &Workspace, indices: &[usize]) -> String {
+    indices
+        .iter()
+        .map(|&index| workspace.members[index].name())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
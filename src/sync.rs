@@ -0,0 +1,195 @@
+//! Placing the output by only writing the changed files, e.g. into the datapacks folder of a
+//! running world.
+//!
+//! The hashes of the written files are kept in a manifest in the output folder. Files whose hash
+//! did not change are skipped and files of the previous build missing in the output are
+//! deleted. Without a manifest, the existing files are compared by their content and all files
+//! missing in the output are deleted, as when replacing the folder.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use shulkerscript::shulkerbox::virtual_fs::VFolder;
+
+use crate::walk::StreamedFiles;
+
+/// The name of the manifest file in the output folder.
+pub const MANIFEST_FILE: &str = ".shulkerscript-sync.json";
+
+/// The files written by the previous sync and their hashes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Manifest {
+    files: BTreeMap<String, String>,
+}
+
+/// The changes made by a sync.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncStats {
+    pub written: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+/// Write the files of the output to the destination folder that changed since the previous sync
+/// and delete the removed ones.
+///
+/// Streamed files are hashed and copied from disk.
+///
+/// # Errors
+/// - If files of the destination cannot be read, written or deleted.
+pub fn sync(
+    output: &VFolder,
+    streamed: &StreamedFiles,
+    destination: &Path,
+) -> io::Result<SyncStats> {
+    let manifest_path = destination.join(MANIFEST_FILE);
+    let previous = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Manifest>(&content).ok());
+
+    let mut stats = SyncStats::default();
+    let mut manifest = Manifest::default();
+    for (path, file) in output.flatten() {
+        let source = streamed.source_of(output, &path);
+        let hash = match source {
+            Some(source) => hash_file(source)?,
+            None => hex::encode(Sha256::digest(file.as_bytes())),
+        };
+        let target = destination.join(&path);
+
+        let unchanged = match &previous {
+            Some(previous) => previous.files.get(&path) == Some(&hash) && target.is_file(),
+            None => target.is_file() && hash_file(&target)? == hash,
+        };
+        if unchanged {
+            stats.unchanged += 1;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            match source {
+                Some(source) => fs::copy(source, &target).map(|_| ())?,
+                None => fs::write(&target, file.as_bytes())?,
+            }
+            stats.written += 1;
+        }
+        manifest.files.insert(path, hash);
+    }
+
+    let stale = match &previous {
+        Some(previous) => previous.files.keys().cloned().collect(),
+        None => existing_files(destination)?,
+    };
+    for path in stale {
+        if manifest.files.contains_key(&path) || path == MANIFEST_FILE {
+            continue;
+        }
+        let target = destination.join(&path);
+        if target.is_file() {
+            fs::remove_file(&target)?;
+            stats.removed += 1;
+            remove_empty_parents(&target, destination);
+        }
+    }
+
+    fs::create_dir_all(destination)?;
+    fs::write(manifest_path, serde_json::to_string(&manifest)?)?;
+    Ok(stats)
+}
+
+/// Hash the file without reading it into memory at once.
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// The paths of the files in the folder relative to it, separated by `/`.
+fn existing_files(folder: &Path) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(folder.join(&dir)) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Remove the parent folders of the removed file up to the root that are empty now.
+fn remove_empty_parents(file: &Path, root: &Path) {
+    for dir in file.ancestors().skip(1) {
+        // fails if the folder is not empty
+        if dir == root || fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shulkerscript::shulkerbox::virtual_fs::VFile;
+
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_sync() {
+        let temp = TestProject::empty("sync");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("data/pack/function/old")).unwrap();
+        fs::write(
+            dir.join("data/pack/function/old/stale.mcfunction"),
+            "say old",
+        )
+        .unwrap();
+        fs::write(dir.join("pack.mcmeta"), "{}").unwrap();
+
+        let mut output = VFolder::new();
+        output.add_file("pack.mcmeta", VFile::Text("{}".to_string()));
+        output.add_file(
+            "data/pack/function/main.mcfunction",
+            VFile::Text("say hi".to_string()),
+        );
+
+        let stats = sync(&output, &StreamedFiles::default(), dir).unwrap();
+        assert_eq!(
+            stats,
+            SyncStats {
+                written: 1,
+                unchanged: 1,
+                removed: 1
+            }
+        );
+        assert!(!dir.join("data/pack/function/old").exists());
+
+        let mut output = VFolder::new();
+        output.add_file("pack.mcmeta", VFile::Text("{}".to_string()));
+        output.add_file(
+            "data/pack/function/main.mcfunction",
+            VFile::Text("say changed".to_string()),
+        );
+        let stats = sync(&output, &StreamedFiles::default(), dir).unwrap();
+        assert_eq!((stats.written, stats.unchanged), (1, 1));
+        assert_eq!(
+            fs::read_to_string(dir.join("data/pack/function/main.mcfunction")).unwrap(),
+            "say changed"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Templates new projects are created from with `init --template`.
+//!
+//! Templates are built in (`library`, `minigame` and `worldgen`), local folders or git
+//! repositories. Their files are copied into the project with the placeholders `{name}`,
+//! `{namespace}` and `{description}` replaced in the paths and in the contents of text files. The
+//! `pack.toml` file of a template is merged into the generated one, keeping the name, namespace,
+//! description and format of the new project.
+
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::DocumentMut;
+
+use crate::{
+    error::Error,
+    fetch::{self, Sha256Pin},
+    terminal_output::print_info,
+    toml_format, util,
+    walk::{self, WalkOptions},
+};
+
+/// A template included in the cli.
+#[derive(Debug)]
+pub struct BuiltinTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The paths and contents of the files.
+    files: &'static [(&'static str, &'static str)],
+}
+
+/// The templates included in the cli.
+pub const BUILTIN: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        name: "library",
+        description: "A library with a function callable by other packs",
+        files: &[
+            (
+                "pack.toml",
+                include_str!("../assets/templates/library/pack.toml"),
+            ),
+            (
+                "src/lib.shu",
+                include_str!("../assets/templates/library/src/lib.shu"),
+            ),
+        ],
+    },
+    BuiltinTemplate {
+        name: "minigame",
+        description: "A minigame with load and tick functions and a game state",
+        files: &[
+            (
+                "src/main.shu",
+                include_str!("../assets/templates/minigame/src/main.shu"),
+            ),
+            (
+                "src/game.shu",
+                include_str!("../assets/templates/minigame/src/game.shu"),
+            ),
+        ],
+    },
+    BuiltinTemplate {
+        name: "worldgen",
+        description: "A custom dimension in the assets folder",
+        files: &[
+            (
+                "pack.toml",
+                include_str!("../assets/templates/worldgen/pack.toml"),
+            ),
+            (
+                "src/main.shu",
+                include_str!("../assets/templates/worldgen/src/main.shu"),
+            ),
+            (
+                "assets/data/{namespace}/dimension/flat.json",
+                include_str!(
+                    "../assets/templates/worldgen/assets/data/{namespace}/dimension/flat.json"
+                ),
+            ),
+        ],
+    },
+];
+
+/// The keys of the `[pack]` section set by `init`, which templates cannot override.
+const PROJECT_KEYS: [&str; 5] = ["name", "namespace", "description", "format", "pack_format"];
+
+/// The template of a new project.
+#[derive(Debug)]
+pub enum Template {
+    Builtin(&'static BuiltinTemplate),
+    /// A local folder or the cached clone of a git repository.
+    Folder(PathBuf),
+}
+
+impl Template {
+    /// Resolve the name of a built-in template, the path of a folder or a git source.
+    ///
+    /// Git sources are URLs or GitHub shorthands (`owner/repo` or `gh:owner/repo`), which are
+    /// fetched into the cache and verified against the pin.
+    ///
+    /// # Errors
+    /// - If the source is a name that is not a built-in template.
+    /// - If the git repository cannot be fetched.
+    /// - If the fetched content does not match the pin, or is unpinned and `locked` is set.
+    pub fn resolve(source: &str, pin: Option<&Sha256Pin>, locked: bool) -> anyhow::Result<Self> {
+        if let Some(builtin) = BUILTIN.iter().find(|template| template.name == source) {
+            return Ok(Self::Builtin(builtin));
+        }
+        let path = Path::new(source);
+        if path.is_dir() {
+            return Ok(Self::Folder(path.to_path_buf()));
+        }
+        if !source.contains(['/', '\\', ':']) {
+            return Err(Error::UnknownTemplateError(source.to_string()).into());
+        }
+
+        let url = fetch::resolve_git_url(source);
+        print_info(format!("Fetching the template {url}"));
+        let destination = fetch::git_cache_path(&url, None);
+        fetch::fetch_git(&url, None, &destination)?;
+        fetch::verify(&destination, pin, locked, &format!("template {url}"))?;
+        Ok(Self::Folder(destination))
+    }
+
+    /// Get the files of the template by their path, without the `.git` folder.
+    ///
+    /// # Errors
+    /// - If the folder of the template cannot be read.
+    pub fn files(&self) -> io::Result<Vec<(String, Vec<u8>)>> {
+        match self {
+            Self::Builtin(template) => Ok(template
+                .files
+                .iter()
+                .map(|(path, content)| (path.to_string(), content.as_bytes().to_vec()))
+                .collect()),
+            Self::Folder(dir) => {
+                let mut files = Vec::new();
+                for file in walk::files(dir, WalkOptions::default())? {
+                    let components = file.str_components(false)?;
+                    if components.first().is_some_and(|first| first == ".git") {
+                        continue;
+                    }
+                    files.push((components.join("/"), fs::read(&file.path)?));
+                }
+                Ok(files)
+            }
+        }
+    }
+
+    /// Copy the files of the template into the project, replacing the placeholders with the
+    /// values, and merge its `pack.toml` file into the one of the project.
+    ///
+    /// Returns the paths of the written files.
+    ///
+    /// # Errors
+    /// - If a file cannot be read or written.
+    /// - If a `pack.toml` file is invalid.
+    pub fn apply(&self, project: &Path, values: &[(&str, &str)]) -> anyhow::Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        for (path, content) in self.files()? {
+            let path = project.join(util::expand_placeholders(&path, values));
+            let content = match String::from_utf8(content) {
+                Ok(text) => util::expand_placeholders(&text, values).into_bytes(),
+                Err(binary) => binary.into_bytes(),
+            };
+            let content = if path == project.join("pack.toml") && path.is_file() {
+                let generated = fs::read_to_string(&path)?;
+                merge_pack_toml(&generated, &String::from_utf8_lossy(&content))?.into_bytes()
+            } else {
+                content
+            };
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Builtin(template) => write!(f, "{}", template.name),
+            Self::Folder(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Merge the `pack.toml` file of a template into the generated one.
+///
+/// The keys of the template replace the generated ones, except for the keys of the `[pack]`
+/// section set by `init`.
+///
+/// # Errors
+/// - If one of the files is not valid TOML.
+pub fn merge_pack_toml(generated: &str, template: &str) -> Result<String, toml_edit::TomlError> {
+    let mut document = generated.parse::<DocumentMut>()?;
+    let template = template.parse::<DocumentMut>()?;
+    for (key, item) in template.iter() {
+        match item.as_table_like().filter(|_| key == "pack") {
+            Some(pack) => {
+                for (key, item) in pack.iter() {
+                    if !PROJECT_KEYS.contains(&key) {
+                        document["pack"][key] = item.clone();
+                    }
+                }
+            }
+            None => document[key] = item.clone(),
+        }
+    }
+    toml_format::normalize(&mut document);
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_merge_pack_toml() {
+        let generated = "[pack]\nname = \"game\"\nnamespace = \"game\"\nformat = 48\n";
+        let template = "[pack]\nname = \"template\"\nversion = \"0.1.0\"\n\n[lib]\nexported_namespaces = [\"game\"]\n";
+        assert_eq!(
+            merge_pack_toml(generated, template).unwrap(),
+            "[pack]\nname = \"game\"\nnamespace = \"game\"\nformat = 48\nversion = \"0.1.0\"\n\n[lib]\nexported_namespaces = [\"game\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_builtin_template() {
+        let temp = TestProject::empty("template");
+        let dir = temp.path();
+        fs::write(dir.join("pack.toml"), "[pack]\nname = \"Caves\"\n").unwrap();
+
+        let template = Template::resolve("worldgen", None, true).unwrap();
+        let values = [
+            ("name", "Caves"),
+            ("namespace", "caves"),
+            ("description", ""),
+        ];
+        let written = template.apply(dir, &values).unwrap();
+        assert_eq!(written.len(), 3);
+        assert!(dir.join("assets/data/caves/dimension/flat.json").is_file());
+        assert!(fs::read_to_string(dir.join("src/main.shu"))
+            .unwrap()
+            .starts_with("namespace \"caves\";"));
+        assert_eq!(
+            fs::read_to_string(dir.join("pack.toml")).unwrap(),
+            "[pack]\nname = \"Caves\"\n\n[compiler]\nassets = \"assets\"\n"
+        );
+        assert!(Template::resolve("unknown", None, false).is_err());
+    }
+}
@@ -1,31 +1,408 @@
-use std::fmt::Display;
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
 
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{Event, EventSender},
+    util::{self, DiffLine},
+};
+
+/// The format of the printed messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum MessageFormat {
+    /// Colored messages for humans.
+    #[default]
+    Human,
+    /// One JSON-RPC notification per line, `window/logMessage` for messages and `$/progress` for
+    /// the progress of builds, as sent by language servers.
+    Json,
+}
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Set the format of the printed messages.
+pub fn set_message_format(format: MessageFormat) {
+    JSON.store(format == MessageFormat::Json, Ordering::Relaxed);
+    if format == MessageFormat::Json {
+        // the messages are read by programs, which do not expect escape codes
+        colored::control::set_override(false);
+    }
+}
+
+/// Whether messages are printed as JSON.
+pub(crate) fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+fn print_notification(method: &str, params: serde_json::Value) {
+    println!(
+        "{}",
+        serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    );
+}
+
+/// The level of a printed message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// A message printed with one of the `print_*` functions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    pub level: Level,
+    pub text: String,
+}
+
+/// The messages and diagnostics printed during a [`tee`] call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript {
+    pub messages: Vec<Message>,
+    pub diagnostics: Vec<String>,
+}
+
+thread_local! {
+    /// The messages of the running [`capture`] call of the thread.
+    static CAPTURED: RefCell<Option<Vec<Message>>> = const { RefCell::new(None) };
+    /// The receiver of the events of the running [`forward`] call of the thread.
+    static FORWARDED: RefCell<Option<EventSender>> = const { RefCell::new(None) };
+    /// The transcript of the running [`tee`] call of the thread.
+    static TEED: RefCell<Option<Transcript>> = const { RefCell::new(None) };
+}
+
+/// Run the function and collect the messages it prints on this thread instead of printing them.
+///
+/// Messages printed by other threads, e.g. of parallel workspace builds, are printed as usual.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<Message>) {
+    let outer = CAPTURED.with(|captured| captured.borrow_mut().replace(Vec::new()));
+    let result = f();
+    let messages = CAPTURED.with(|captured| std::mem::replace(&mut *captured.borrow_mut(), outer));
+    (result, messages.unwrap_or_default())
+}
+
+/// Run the function and collect a copy of the messages and diagnostics it prints on this thread,
+/// which are still printed as usual.
+pub fn tee<T>(f: impl FnOnce() -> T) -> (T, Transcript) {
+    let outer = TEED.with(|teed| teed.borrow_mut().replace(Transcript::default()));
+    let result = f();
+    let transcript = TEED.with(|teed| std::mem::replace(&mut *teed.borrow_mut(), outer));
+    (result, transcript.unwrap_or_default())
+}
+
+fn tee_with(f: impl FnOnce(&mut Transcript)) {
+    TEED.with(|teed| {
+        if let Some(transcript) = teed.borrow_mut().as_mut() {
+            f(transcript);
+        }
+    });
+}
+
+fn is_captured() -> bool {
+    CAPTURED.with(|captured| captured.borrow().is_some())
+}
+
+/// Run the function and send the messages, diagnostics and progress it prints on this thread as
+/// events instead of printing them.
+pub fn forward<T>(sender: impl Into<EventSender>, f: impl FnOnce() -> T) -> T {
+    let outer = FORWARDED.with(|forwarded| forwarded.borrow_mut().replace(sender.into()));
+    let result = f();
+    FORWARDED.with(|forwarded| *forwarded.borrow_mut() = outer);
+    result
+}
+
+/// Send the event if the output of the thread is forwarded, returns whether it was sent.
+fn send(event: impl FnOnce() -> Event) -> bool {
+    FORWARDED.with(|forwarded| match forwarded.borrow().as_ref() {
+        Some(sender) => {
+            sender.send(event());
+            true
+        }
+        None => false,
+    })
+}
+
+fn is_forwarded() -> bool {
+    FORWARDED.with(|forwarded| forwarded.borrow().is_some())
+}
+
+/// Record the message if it is captured or print it as JSON, returns whether it was handled.
+fn record(level: Level, msg: &dyn Display) -> bool {
+    tee_with(|transcript| {
+        transcript.messages.push(Message {
+            level,
+            text: msg.to_string(),
+        });
+    });
+    let captured = CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(messages) => {
+            messages.push(Message {
+                level,
+                text: msg.to_string(),
+            });
+            true
+        }
+        None => false,
+    });
+    if captured
+        || send(|| {
+            Event::Message(Message {
+                level,
+                text: msg.to_string(),
+            })
+        })
+    {
+        return true;
+    }
+    if !is_json() {
+        return false;
+    }
+
+    // the message types of the language server protocol
+    let kind = match level {
+        Level::Error => 1,
+        Level::Warning => 2,
+        Level::Info | Level::Success => 3,
+    };
+    print_notification(
+        "window/logMessage",
+        serde_json::json!({ "type": kind, "message": msg.to_string() }),
+    );
+    true
+}
+
+/// The progress of a long running operation.
+///
+/// Reported as `$/progress` notifications with `begin`, `report` and `end` values with the JSON
+/// message format, as events if the output is forwarded and not at all otherwise, as the
+/// operations print their messages anyway. The progress ends when dropped at the latest.
+#[derive(Debug)]
+pub struct Progress {
+    token: Option<String>,
+}
+
+impl Progress {
+    /// Begin reporting the progress of an operation with the title.
+    pub fn begin(title: impl Display) -> Self {
+        static NEXT_TOKEN: AtomicUsize = AtomicUsize::new(0);
+
+        if is_captured() || !(is_json() || is_forwarded()) {
+            return Self { token: None };
+        }
+        let token = format!(
+            "shulkerscript/{}",
+            NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)
+        );
+        if send(|| Event::ProgressBegin {
+            token: token.clone(),
+            title: title.to_string(),
+        }) {
+            return Self { token: Some(token) };
+        }
+        print_notification(
+            "$/progress",
+            serde_json::json!({
+                "token": token,
+                "value": { "kind": "begin", "title": title.to_string(), "percentage": 0 },
+            }),
+        );
+        Self { token: Some(token) }
+    }
+
+    /// Report the current step and the percentage of the operation that is done.
+    pub fn report(&self, message: impl Display, percentage: u32) {
+        if let Some(token) = &self.token {
+            if send(|| Event::Progress {
+                token: token.clone(),
+                message: message.to_string(),
+                percentage: percentage.min(100),
+            }) {
+                return;
+            }
+            print_notification(
+                "$/progress",
+                serde_json::json!({
+                    "token": token,
+                    "value": { "kind": "report", "message": message.to_string(), "percentage": percentage.min(100) },
+                }),
+            );
+        }
+    }
+
+    /// End the operation with a final message.
+    pub fn end(mut self, message: impl Display) {
+        self.finish(Some(message.to_string()));
+    }
+
+    fn finish(&mut self, message: Option<String>) {
+        if let Some(token) = self.token.take() {
+            if send(|| Event::ProgressEnd {
+                token: token.clone(),
+                message: message.clone(),
+            }) {
+                return;
+            }
+            let mut value = serde_json::json!({ "kind": "end" });
+            if let Some(message) = message {
+                value["message"] = message.into();
+            }
+            print_notification(
+                "$/progress",
+                serde_json::json!({ "token": token, "value": value }),
+            );
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.finish(None);
+    }
+}
+
+/// Print a diagnostic of the compiler, e.g. a syntax error with the source code, to stderr.
+pub fn print_diagnostic<D>(msg: D)
+where
+    D: Display,
+{
+    tee_with(|transcript| transcript.diagnostics.push(msg.to_string()));
+    if send(|| Event::Diagnostic(msg.to_string())) {
+        return;
+    }
+    if is_json() {
+        print_notification(
+            "window/logMessage",
+            serde_json::json!({ "type": 1, "message": msg.to_string() }),
+        );
+    } else {
+        eprintln!("{msg}");
+    }
+}
+
+/// Print the diagnostics of a script.
+///
+/// With the JSON message format, they are printed as one `textDocument/publishDiagnostics`
+/// notification, which is also printed for scripts without diagnostics to report them as fine.
+/// The diagnostics have no position, as the compiler renders it into the message.
+pub fn print_file_diagnostics(path: &Path, diagnostics: &[String]) {
+    if !is_json() || is_forwarded() {
+        diagnostics.iter().for_each(print_diagnostic);
+        return;
+    }
+    for diagnostic in diagnostics {
+        tee_with(|transcript| transcript.diagnostics.push(diagnostic.clone()));
+    }
+    let position = serde_json::json!({ "line": 0, "character": 0 });
+    print_notification(
+        "textDocument/publishDiagnostics",
+        serde_json::json!({
+            "uri": util::file_uri(path),
+            "diagnostics": diagnostics
+                .iter()
+                .map(|message| serde_json::json!({
+                    "range": { "start": position, "end": position },
+                    "severity": 1,
+                    "source": "shulkerscript",
+                    "message": message,
+                }))
+                .collect::<Vec<_>>(),
+        }),
+    );
+}
 
 pub fn print_info<D>(msg: D)
 where
     D: Display,
 {
-    println!("[{}]    {msg}", "INFO".blue())
+    if !record(Level::Info, &msg) {
+        println!("[{}]    {msg}", "INFO".blue())
+    }
 }
 
 pub fn print_success<D>(msg: D)
 where
     D: Display,
 {
-    println!("[{}] {msg}", "SUCCESS".green())
+    if !record(Level::Success, &msg) {
+        println!("[{}] {msg}", "SUCCESS".green())
+    }
 }
 
 pub fn print_warning<D>(msg: D)
 where
     D: Display,
 {
-    println!("[{}] {msg}", "WARNING".yellow())
+    if !record(Level::Warning, &msg) {
+        println!("[{}] {msg}", "WARNING".yellow())
+    }
 }
 
 pub fn print_error<D>(msg: D)
 where
     D: Display,
 {
-    println!("[{}]   {msg}", "ERROR".red())
+    if !record(Level::Error, &msg) {
+        println!("[{}]   {msg}", "ERROR".red())
+    }
+}
+
+/// Print a line without a level, e.g. an entry of a listing or the output of a command.
+///
+/// Recorded, forwarded and printed as JSON like an info message, so it does not interrupt the
+/// JSON notifications.
+pub fn print_plain<D>(msg: D)
+where
+    D: Display,
+{
+    if !record(Level::Info, &msg) {
+        println!("{msg}")
+    }
+}
+
+/// Print a message, e.g. a recorded one, with its level.
+pub fn print_message(message: &Message) {
+    match message.level {
+        Level::Info => print_info(&message.text),
+        Level::Success => print_success(&message.text),
+        Level::Warning => print_warning(&message.text),
+        Level::Error => print_error(&message.text),
+    }
+}
+
+/// Print the changed lines of a diff with the given number of unchanged lines around them.
+pub fn print_diff(diff: &[DiffLine], context: usize) {
+    let changed = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Unchanged(_)))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let mut last_printed = None;
+    for (index, line) in diff.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|&changed| index.abs_diff(changed) <= context);
+        if !near_change {
+            continue;
+        }
+        if last_printed.is_some_and(|last| last + 1 < index) {
+            print_plain("...".dimmed());
+        }
+        match line {
+            DiffLine::Unchanged(line) => print_plain(format!("  {line}")),
+            DiffLine::Removed(line) => print_plain(format!("- {line}").red()),
+            DiffLine::Added(line) => print_plain(format!("+ {line}").green()),
+        }
+        last_printed = Some(index);
+    }
 }
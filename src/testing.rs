@@ -0,0 +1,176 @@
+//! Helpers to run subcommands against temporary projects and inspect their output, e.g. for
+//! integration tests of tools embedding the cli.
+//!
+//! ```ignore
+//! use shulkerscript_cli::testing::TestProject;
+//!
+//! let project = TestProject::init("example");
+//! let output = project.run(["build"]);
+//! assert!(output.success);
+//! assert!(project.path().join("dist/example/pack.mcmeta").is_file());
+//! ```
+//!
+//! The subcommands run on the calling thread and their messages are captured instead of
+//! printed. Interactive prompts are not supported, subcommands have to be run in batch mode.
+
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use clap::Parser as _;
+
+use crate::{
+    cli::Args,
+    terminal_output::{self, Level, Message},
+};
+
+/// The result of running a subcommand.
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// Whether the subcommand succeeded.
+    pub success: bool,
+    /// The messages printed by the subcommand.
+    pub messages: Vec<Message>,
+    /// The error the subcommand failed with, or the usage error of invalid arguments.
+    pub error: Option<String>,
+}
+
+impl Output {
+    /// Get the texts of the messages with the level.
+    pub fn texts(&self, level: Level) -> Vec<&str> {
+        self.messages
+            .iter()
+            .filter(|message| message.level == level)
+            .map(|message| message.text.as_str())
+            .collect()
+    }
+
+    /// Check whether a message with the level contains the text.
+    pub fn contains(&self, level: Level, text: &str) -> bool {
+        self.texts(level)
+            .iter()
+            .any(|message| message.contains(text))
+    }
+}
+
+/// Run the cli with the arguments, without the binary name.
+pub fn run<I, S>(args: I) -> Output
+where
+    I: IntoIterator<Item = S>,
+    S: Into<OsString>,
+{
+    let args = std::iter::once(OsString::from("shulkerscript"))
+        .chain(args.into_iter().map(Into::into))
+        .collect::<Vec<_>>();
+    let args = match Args::try_parse_from(args) {
+        Ok(args) => args,
+        Err(err) => {
+            return Output {
+                success: false,
+                messages: Vec::new(),
+                error: Some(err.to_string()),
+            }
+        }
+    };
+
+    let (result, messages) = terminal_output::capture(|| args.run());
+    Output {
+        success: result.is_ok(),
+        messages,
+        error: result.err().map(|err| err.to_string()),
+    }
+}
+
+/// A temporary directory for a project, removed when dropped.
+#[derive(Debug)]
+pub struct TestProject {
+    path: PathBuf,
+}
+
+impl TestProject {
+    /// Create an empty temporary directory.
+    pub fn empty(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir()
+            .join(format!(
+                "shulkerscript-test-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ))
+            .join(name);
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("Failed to create the test directory");
+        Self { path }
+    }
+
+    /// Create a temporary directory with a project initialized by `init` in batch mode.
+    ///
+    /// # Panics
+    /// - If the project cannot be initialized.
+    pub fn init(name: &str) -> Self {
+        let project = Self::empty(name);
+        let output = run([
+            OsString::from("init"),
+            "--batch".into(),
+            "--force".into(),
+            "--no-icon".into(),
+            "--vcs".into(),
+            "none".into(),
+            "--name".into(),
+            name.into(),
+            project.path.clone().into(),
+        ]);
+        assert!(
+            output.success,
+            "Failed to initialize the test project: {output:?}"
+        );
+        project
+    }
+
+    /// The path of the project directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run the cli with the arguments followed by the path of the project.
+    pub fn run<I, S>(&self, args: I) -> Output
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        run(args
+            .into_iter()
+            .map(Into::into)
+            .chain(std::iter::once(self.path.clone().into_os_string())))
+    }
+
+    /// Write a file of the project, creating its parent directories.
+    ///
+    /// # Panics
+    /// - If the file cannot be written.
+    pub fn write(&self, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> &Self {
+        let path = self.path.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create the parent directory");
+        }
+        fs::write(&path, content).expect("Failed to write the test file");
+        self
+    }
+
+    /// Read a text file of the project.
+    pub fn read(&self, path: impl AsRef<Path>) -> Option<String> {
+        fs::read_to_string(self.path.join(path)).ok()
+    }
+}
+
+impl Drop for TestProject {
+    fn drop(&mut self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::remove_dir_all(parent);
+        }
+    }
+}
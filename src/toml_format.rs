@@ -0,0 +1,216 @@
+//! Normalizing the `pack.toml` file, so its diffs stay minimal regardless of the editors used.
+//!
+//! The tables are ordered like the sections of the config and the keys of a table
+//! alphabetically, except for the `[pack]` section, which is ordered like its fields. Arrays of
+//! tables keep the order of their entries. Whitespace around keys, values and table headers is
+//! normalized and blank lines are only kept between tables. Comments are kept and move with the
+//! key or table they precede.
+
+use toml_edit::{DocumentMut, Item, RawString, Table};
+
+/// The order of the top-level keys and sections.
+const ROOT_ORDER: [&str; 17] = [
+    "extends",
+    "workspace",
+    "pack",
+    "compiler",
+    "publish",
+    "dependencies",
+    "alias",
+    "defaults",
+    "scripts",
+    "lang",
+    "watch",
+    "limits",
+    "plugins",
+    "generate",
+    "lib",
+    "overlays",
+    "lints",
+];
+
+/// The order of the keys of the `[pack]` section.
+const PACK_ORDER: [&str; 9] = [
+    "name",
+    "namespace",
+    "description",
+    "format",
+    "pack_format",
+    "unstable_format",
+    "version",
+    "minecraft_version",
+    "required_features",
+];
+
+/// Normalize the order of the keys and tables and the formatting of the document.
+pub fn normalize(document: &mut DocumentMut) {
+    let has_values = document.as_table().iter().any(|(_, item)| item.is_value());
+    normalize_table(document.as_table_mut(), &[]);
+
+    // the root table is at position 0
+    let mut position = 1;
+    let mut first = !has_values;
+    order_tables(document.as_table_mut(), &[], &mut position, &mut first);
+
+    let trailing = comments(Some(document.trailing()));
+    document.set_trailing(if trailing.is_empty() {
+        trailing
+    } else {
+        format!("\n{trailing}")
+    });
+}
+
+/// Sort the values of the table and normalize their whitespace, recursing into the subtables.
+fn normalize_table(table: &mut Table, path: &[String]) {
+    table.sort_values_by(|a, _, b, _| rank(path, a.get()).cmp(&rank(path, b.get())));
+
+    for (mut key, item) in table.iter_mut() {
+        let mut path = path.to_vec();
+        path.push(key.get().to_string());
+        match item {
+            Item::Value(value) => {
+                let prefix = comments(key.leaf_decor().prefix());
+                key.leaf_decor_mut().set_prefix(prefix);
+                key.leaf_decor_mut().set_suffix(" ");
+                let suffix = trailing_comment(value.decor().suffix());
+                value.decor_mut().set_prefix(" ");
+                value.decor_mut().set_suffix(suffix);
+            }
+            // dotted keys are formatted as part of their line
+            Item::Table(table) if !table.is_dotted() => {
+                key.leaf_decor_mut().clear();
+                normalize_table(table, &path);
+            }
+            Item::ArrayOfTables(tables) => {
+                key.leaf_decor_mut().clear();
+                for table in tables.iter_mut() {
+                    normalize_table(table, &path);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Order the subtables of the table and separate their headers by blank lines.
+fn order_tables(table: &mut Table, path: &[String], position: &mut usize, first: &mut bool) {
+    let mut keys = table
+        .iter()
+        .filter(|(_, item)| item.is_table() || item.is_array_of_tables())
+        .map(|(key, _)| key.to_string())
+        .collect::<Vec<_>>();
+    keys.sort_by(|a, b| rank(path, a).cmp(&rank(path, b)));
+
+    for key in keys {
+        let mut path = path.to_vec();
+        path.push(key.clone());
+        match table.get_mut(&key) {
+            Some(Item::Table(table)) if !table.is_dotted() => {
+                order_table(table, &path, position, first);
+            }
+            Some(Item::ArrayOfTables(tables)) => {
+                for table in tables.iter_mut() {
+                    order_table(table, &path, position, first);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn order_table(table: &mut Table, path: &[String], position: &mut usize, first: &mut bool) {
+    table.set_position(*position);
+    *position += 1;
+    if !table.is_implicit() {
+        let comments = comments(table.decor().prefix());
+        let prefix = if *first {
+            comments
+        } else {
+            format!("\n{comments}")
+        };
+        let suffix = trailing_comment(table.decor().suffix());
+        table.decor_mut().set_prefix(prefix);
+        table.decor_mut().set_suffix(suffix);
+        *first = false;
+    }
+    order_tables(table, path, position, first);
+}
+
+/// The position of the key in the table, by the order of the known keys and then alphabetically.
+fn rank<'a>(path: &[String], key: &'a str) -> (usize, &'a str) {
+    let order: &[&str] = match path {
+        [] => &ROOT_ORDER,
+        [table] if table == "pack" => &PACK_ORDER,
+        _ => &[],
+    };
+    let index = order
+        .iter()
+        .position(|known| *known == key)
+        .unwrap_or(order.len());
+    (index, key)
+}
+
+/// The comment lines of the whitespace, each followed by a newline.
+fn comments(raw: Option<&RawString>) -> String {
+    raw.and_then(RawString::as_str)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// The comment at the end of a line, separated by a space.
+fn trailing_comment(raw: Option<&RawString>) -> String {
+    match raw.and_then(RawString::as_str).unwrap_or_default().trim() {
+        "" => String::new(),
+        comment => format!(" {comment}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(content: &str) -> String {
+        let mut document = content.parse::<DocumentMut>().unwrap();
+        normalize(&mut document);
+        document.to_string()
+    }
+
+    #[test]
+    fn test_normalize() {
+        let content = r#"[compiler]
+assets   =   "assets"  # the assets
+
+# the pack
+[pack]
+version = "1.0.0"
+name="pack"
+    format = 48
+
+[[plugins]]
+path = "b"
+[[plugins]]
+path = "a"
+"#;
+        let expected = r#"# the pack
+[pack]
+name = "pack"
+format = 48
+version = "1.0.0"
+
+[compiler]
+assets = "assets" # the assets
+
+[[plugins]]
+path = "b"
+
+[[plugins]]
+path = "a"
+"#;
+        assert_eq!(format(content), expected);
+        assert_eq!(format(expected), expected);
+    }
+}
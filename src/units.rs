@@ -0,0 +1,160 @@
+//! Formatting of counts, file sizes and durations in messages.
+//!
+//! Numbers are grouped by thousands and use the separators of the locale set by the `LC_ALL`,
+//! `LC_NUMERIC` or `LANG` environment variable, e.g. `1,234.5` in English and `1.234,5` in German.
+//! Machine-readable output, e.g. JSON, is never formatted this way.
+
+use std::{env, sync::OnceLock, time::Duration};
+
+/// The separators of numbers in a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub thousands_separator: char,
+    pub decimal_separator: char,
+}
+
+impl NumberFormat {
+    pub const ENGLISH: Self = Self {
+        thousands_separator: ',',
+        decimal_separator: '.',
+    };
+
+    /// The separators of the locale, e.g. `de_DE.UTF-8`, falling back to the English ones.
+    pub fn of_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default();
+        match language {
+            "da" | "de" | "el" | "es" | "hr" | "id" | "it" | "nl" | "pt" | "ro" | "sl" | "sr"
+            | "tr" => Self {
+                thousands_separator: '.',
+                decimal_separator: ',',
+            },
+            "bg" | "cs" | "et" | "fi" | "fr" | "hu" | "lt" | "lv" | "nb" | "nn" | "no" | "pl"
+            | "ru" | "sk" | "sv" | "uk" => Self {
+                thousands_separator: '\u{a0}',
+                decimal_separator: ',',
+            },
+            _ => Self::ENGLISH,
+        }
+    }
+
+    /// The separators of the locale of the environment.
+    pub fn current() -> Self {
+        static CURRENT: OnceLock<NumberFormat> = OnceLock::new();
+        *CURRENT.get_or_init(|| {
+            ["LC_ALL", "LC_NUMERIC", "LANG"]
+                .into_iter()
+                .find_map(|variable| env::var(variable).ok().filter(|value| !value.is_empty()))
+                .map_or(Self::ENGLISH, |locale| Self::of_locale(&locale))
+        })
+    }
+
+    /// Format the count grouped by thousands, e.g. `12,345`.
+    pub fn count(self, count: u64) -> String {
+        let digits = count.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                grouped.push(self.thousands_separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// Format the number with the number of decimal places, e.g. `1,234.57`.
+    pub fn decimal(self, value: f64, precision: usize) -> String {
+        let formatted = format!("{:.precision$}", value.abs());
+        let (integer, fraction) = formatted
+            .split_once('.')
+            .unwrap_or((formatted.as_str(), ""));
+        // values rounded to zero are printed without sign
+        let negative = value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0');
+        let sign = if negative { "-" } else { "" };
+        let integer = self.count(integer.parse().unwrap_or_default());
+        if fraction.is_empty() {
+            format!("{sign}{integer}")
+        } else {
+            format!("{sign}{integer}{}{fraction}", self.decimal_separator)
+        }
+    }
+
+    /// Format a number of bytes with binary units, e.g. `1.5 KiB`.
+    pub fn size(self, bytes: u64) -> String {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+        if bytes < 1024 {
+            return format!("{bytes} B");
+        }
+        let mut size = bytes as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        format!("{} {}", self.decimal(size, 1), UNITS[unit])
+    }
+
+    /// Format the duration in the unit fitting its length, e.g. `12.34ms`, `2.50s` or `3m 05s`.
+    pub fn duration(self, duration: Duration) -> String {
+        let seconds = duration.as_secs_f64();
+        if seconds < 1.0 {
+            format!("{}ms", self.decimal(seconds * 1000.0, 2))
+        } else if seconds < 60.0 {
+            format!("{}s", self.decimal(seconds, 2))
+        } else {
+            let seconds = duration.as_secs();
+            format!("{}m {:02}s", self.count(seconds / 60), seconds % 60)
+        }
+    }
+}
+
+/// Format the count grouped by thousands in the locale of the environment.
+pub fn format_count(count: usize) -> String {
+    NumberFormat::current().count(count as u64)
+}
+
+/// Format the number with the number of decimal places in the locale of the environment.
+pub fn format_decimal(value: f64, precision: usize) -> String {
+    NumberFormat::current().decimal(value, precision)
+}
+
+/// Format a number of bytes with binary units in the locale of the environment.
+pub fn format_size(bytes: u64) -> String {
+    NumberFormat::current().size(bytes)
+}
+
+/// Format the duration in the unit fitting its length in the locale of the environment.
+pub fn format_duration(duration: Duration) -> String {
+    NumberFormat::current().duration(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_format() {
+        let english = NumberFormat::of_locale("en_US.UTF-8");
+        let german = NumberFormat::of_locale("de_DE.UTF-8");
+        assert_eq!(english, NumberFormat::of_locale("C"));
+
+        assert_eq!(english.count(999), "999");
+        assert_eq!(english.count(1_234_567), "1,234,567");
+        assert_eq!(german.count(1_234_567), "1.234.567");
+        assert_eq!(english.decimal(1234.567, 2), "1,234.57");
+        assert_eq!(german.decimal(-1234.5, 1), "-1.234,5");
+        assert_eq!(english.decimal(-0.001, 1), "0.0");
+
+        assert_eq!(english.size(512), "512 B");
+        assert_eq!(english.size(1536), "1.5 KiB");
+        assert_eq!(english.size(3 * 1024 * 1024), "3.0 MiB");
+        assert_eq!(german.size(1536), "1,5 KiB");
+
+        assert_eq!(english.duration(Duration::from_micros(12_346)), "12.35ms");
+        assert_eq!(english.duration(Duration::from_millis(2500)), "2.50s");
+        assert_eq!(english.duration(Duration::from_secs(185)), "3m 05s");
+    }
+}
@@ -0,0 +1,379 @@
+//! Mechanical migrations of the `pack.toml` file across breaking changes of the cli.
+//!
+//! The migrations edit the document in place, keeping comments and formatting.
+
+use std::{ffi::OsStr, fmt::Display};
+
+use toml_edit::{DocumentMut, Item, Key, Table, Value};
+
+use crate::versions;
+
+/// A key of the `pack.toml` file that was replaced by another key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeprecatedKey {
+    /// The dotted path of the deprecated key.
+    pub old: &'static str,
+    /// The dotted path of the key replacing it, in the same table.
+    pub new: &'static str,
+    /// The version of the cli that deprecated the key.
+    pub since: &'static str,
+}
+
+impl Display for DeprecatedKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The key `{}` is deprecated since v{}, rename it to `{}`. Run `shulkerscript fix` to apply the change.",
+            self.old, self.since, self.new
+        )
+    }
+}
+
+/// The deprecated keys of the `pack.toml` file, which are still read.
+pub const DEPRECATED_KEYS: &[DeprecatedKey] = &[DeprecatedKey {
+    old: "pack.pack_format",
+    new: "pack.format",
+    since: "0.1.0",
+}];
+
+/// An option of a subcommand that was replaced by another option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeprecatedFlag {
+    /// The subcommand the option belongs to.
+    pub subcommand: &'static str,
+    /// The deprecated option.
+    pub old: &'static str,
+    /// The option replacing it.
+    pub new: &'static str,
+    /// The version of the cli that deprecated the option.
+    pub since: &'static str,
+}
+
+impl Display for DeprecatedFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The option `{}` of `{}` is deprecated since v{}, use `{}` instead.",
+            self.old, self.subcommand, self.since, self.new
+        )
+    }
+}
+
+/// The deprecated options of the subcommands, which are still accepted.
+pub const DEPRECATED_FLAGS: &[DeprecatedFlag] = &[DeprecatedFlag {
+    subcommand: "init",
+    old: "--pack-format",
+    new: "--format",
+    since: "0.2.0",
+}];
+
+/// Get the deprecated keys used in the table of a config file.
+pub fn deprecated_keys(table: &toml::Table) -> Vec<&'static DeprecatedKey> {
+    DEPRECATED_KEYS
+        .iter()
+        .filter(|deprecated| {
+            let (parents, key) = split_path(deprecated.old);
+            parents
+                .iter()
+                .try_fold(table, |table, parent| {
+                    table.get(*parent).and_then(|value| value.as_table())
+                })
+                .is_some_and(|table| table.contains_key(key))
+        })
+        .collect()
+}
+
+/// Get the deprecated options used in the arguments of a command line, including the binary name.
+pub fn deprecated_flags<S: AsRef<OsStr>>(args: &[S]) -> Vec<&'static DeprecatedFlag> {
+    let args = args
+        .iter()
+        .skip(1)
+        .map(|arg| arg.as_ref().to_string_lossy())
+        .take_while(|arg| arg != "--")
+        .collect::<Vec<_>>();
+    let Some(position) = args.iter().position(|arg| !arg.starts_with('-')) else {
+        return Vec::new();
+    };
+
+    DEPRECATED_FLAGS
+        .iter()
+        .filter(|deprecated| {
+            args[position] == deprecated.subcommand
+                && args[position + 1..]
+                    .iter()
+                    .any(|arg| is_flag(arg, deprecated.old))
+        })
+        .collect()
+}
+
+/// Apply all mechanical fixes of deprecations to the document.
+///
+/// Returns a description of every change.
+pub fn fix_deprecations(document: &mut DocumentMut) -> Vec<String> {
+    let mut changes = rename_deprecated_keys(document);
+    changes.extend(rename_deprecated_flags(document));
+    changes
+}
+
+/// Rename the deprecated keys of the document, keeping their position.
+///
+/// Returns a description of every change.
+pub fn rename_deprecated_keys(document: &mut DocumentMut) -> Vec<String> {
+    let mut changes = Vec::new();
+    for deprecated in DEPRECATED_KEYS {
+        let (parents, old) = split_path(deprecated.old);
+        let (_, new) = split_path(deprecated.new);
+        let Some(table) = table_mut(document, &parents) else {
+            continue;
+        };
+        if !table.contains_key(old) {
+            continue;
+        }
+
+        if table.contains_key(new) {
+            table.remove(old);
+            changes.push(format!(
+                "Removed `{}`, `{}` is already set",
+                deprecated.old, deprecated.new
+            ));
+        } else {
+            rename_key(table, old, new);
+            changes.push(format!(
+                "Renamed `{}` to `{}`",
+                deprecated.old, deprecated.new
+            ));
+        }
+    }
+    changes
+}
+
+/// Replace the deprecated options in the command lines of the `[alias]` section.
+///
+/// Returns a description of every change.
+pub fn rename_deprecated_flags(document: &mut DocumentMut) -> Vec<String> {
+    let mut changes = Vec::new();
+    let Some(aliases) = table_mut(document, &["alias"]) else {
+        return changes;
+    };
+
+    for (name, item) in aliases.iter_mut() {
+        let lines: Vec<&mut toml_edit::Formatted<String>> = match item {
+            Item::Value(Value::String(line)) => vec![line],
+            Item::Value(Value::Array(lines)) => lines
+                .iter_mut()
+                .filter_map(|line| match line {
+                    Value::String(line) => Some(line),
+                    _ => None,
+                })
+                .collect(),
+            _ => continue,
+        };
+        for line in lines {
+            let mut replaced = line.value().clone();
+            for deprecated in DEPRECATED_FLAGS {
+                if let Some(new) = replace_flag(&replaced, deprecated) {
+                    replaced = new;
+                    changes.push(format!(
+                        "Replaced `{}` with `{}` in the alias `{name}`",
+                        deprecated.old, deprecated.new
+                    ));
+                }
+            }
+            if &replaced != line.value() {
+                let decor = line.decor().clone();
+                *line = toml_edit::Formatted::new(replaced);
+                *line.decor_mut() = decor;
+            }
+        }
+    }
+    changes
+}
+
+/// Set the pack format to the one of the configured `minecraft_version`.
+///
+/// Returns a description of the change, or an error if the Minecraft version is unknown.
+pub fn update_pack_format(document: &mut DocumentMut) -> Result<Option<String>, String> {
+    let Some(pack) = table_mut(document, &["pack"]) else {
+        return Ok(None);
+    };
+    let Some(version) = pack.get("minecraft_version").and_then(|item| item.as_str()) else {
+        return Ok(None);
+    };
+    let Some(format) = versions::pack_format_of_version(version) else {
+        return Err(format!(
+            "The pack format of Minecraft {version} is unknown, keeping the pack format."
+        ));
+    };
+    let version = version.to_string();
+
+    let Some((key, current)) = set_pack_format(document, format.format) else {
+        return Ok(None);
+    };
+    Ok(Some(match current {
+        Some(current) => format!(
+            "Updated `pack.{key}` from {current} to {} to match Minecraft {version}",
+            format.format
+        ),
+        None => format!(
+            "Set `pack.{key}` to {} to match Minecraft {version}",
+            format.format
+        ),
+    }))
+}
+
+/// Set the pack format in the `pack` section, keeping the formatting of the value and the
+/// deprecated `pack_format` key if it is used.
+///
+/// Returns the key and the previous value of the pack format, `None` if it is unchanged.
+pub fn set_pack_format(
+    document: &mut DocumentMut,
+    format: u8,
+) -> Option<(&'static str, Option<i64>)> {
+    let pack = table_mut(document, &["pack"])?;
+    let key = ["format", "pack_format"]
+        .into_iter()
+        .find(|key| pack.contains_key(key))
+        .unwrap_or("format");
+    let current = pack.get(key).and_then(|item| item.as_integer());
+    if current == Some(i64::from(format)) {
+        return None;
+    }
+
+    match pack.get_mut(key).and_then(|item| item.as_value_mut()) {
+        Some(value) => {
+            let decor = value.decor().clone();
+            *value = i64::from(format).into();
+            *value.decor_mut() = decor;
+        }
+        None => {
+            pack.insert(key, toml_edit::value(i64::from(format)));
+        }
+    }
+
+    Some((key, current))
+}
+
+/// Check whether the argument is the option, with or without an attached value.
+fn is_flag(arg: &str, flag: &str) -> bool {
+    arg.strip_prefix(flag)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+}
+
+/// Replace the deprecated option in the commands of the command line using its subcommand.
+///
+/// Returns `None` if the command line does not use the option.
+fn replace_flag(line: &str, deprecated: &DeprecatedFlag) -> Option<String> {
+    let mut changed = false;
+    let commands = line
+        .split("&&")
+        .map(|command| {
+            let mut words = command.split(' ').collect::<Vec<_>>();
+            let uses_subcommand = words
+                .iter()
+                .find(|word| !word.is_empty())
+                .is_some_and(|word| *word == deprecated.subcommand);
+            let mut replaced = Vec::new();
+            for word in &mut words {
+                if uses_subcommand && is_flag(word, deprecated.old) {
+                    replaced.push(format!(
+                        "{}{}",
+                        deprecated.new,
+                        &word[deprecated.old.len()..]
+                    ));
+                } else {
+                    replaced.push(word.to_string());
+                }
+                changed |= replaced.last().map(String::as_str) != Some(*word);
+            }
+            replaced.join(" ")
+        })
+        .collect::<Vec<_>>();
+
+    changed.then(|| commands.join("&&"))
+}
+
+/// Split a dotted path into the keys of the parent tables and the key.
+fn split_path(path: &str) -> (Vec<&str>, &str) {
+    let mut keys = path.split('.').collect::<Vec<_>>();
+    let key = keys.pop().unwrap_or_default();
+    (keys, key)
+}
+
+/// Get the table at the path of keys.
+fn table_mut<'a>(document: &'a mut DocumentMut, keys: &[&str]) -> Option<&'a mut Table> {
+    keys.iter().try_fold(document.as_table_mut(), |table, key| {
+        table.get_mut(key).and_then(|item| item.as_table_mut())
+    })
+}
+
+/// Rename a key of the table, keeping the order of the keys and the formatting of the key.
+fn rename_key(table: &mut Table, old: &str, new: &str) {
+    let keys = table
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .collect::<Vec<_>>();
+    for key in keys {
+        if let Some((key, item)) = table.remove_entry(&key) {
+            let key = if key.get() == old {
+                Key::new(new).with_leaf_decor(key.leaf_decor().clone())
+            } else {
+                key
+            };
+            table.insert_formatted(&key, item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade() {
+        let mut document = "[pack]\nname = \"test\"\npack_format = 48 # the format\nversion = \"0.1.0\"\nminecraft_version = \"1.21.4\"\n"
+            .parse::<DocumentMut>()
+            .unwrap();
+
+        assert_eq!(
+            rename_deprecated_keys(&mut document),
+            ["Renamed `pack.pack_format` to `pack.format`"]
+        );
+        assert_eq!(
+            update_pack_format(&mut document),
+            Ok(Some(
+                "Updated `pack.format` from 48 to 61 to match Minecraft 1.21.4".to_string()
+            ))
+        );
+        assert_eq!(
+            document.to_string(),
+            "[pack]\nname = \"test\"\nformat = 61 # the format\nversion = \"0.1.0\"\nminecraft_version = \"1.21.4\"\n"
+        );
+        assert!(rename_deprecated_keys(&mut document).is_empty());
+        assert_eq!(update_pack_format(&mut document), Ok(None));
+    }
+
+    #[test]
+    fn test_deprecations() {
+        let table = toml::from_str::<toml::Table>("[pack]\npack_format = 48").unwrap();
+        assert_eq!(deprecated_keys(&table), [&DEPRECATED_KEYS[0]]);
+
+        assert_eq!(
+            deprecated_flags(&["shulkerscript", "--trace", "init", "--pack-format=48"]),
+            [&DEPRECATED_FLAGS[0]]
+        );
+        assert!(deprecated_flags(&["shulkerscript", "build", "--pack-format"]).is_empty());
+        assert!(deprecated_flags(&["shulkerscript", "init", "--", "--pack-format"]).is_empty());
+
+        let mut document = "[alias]\nnew = \"init --pack-format 48 && build\" # create\nrelease = [\"build --zip\"]\n"
+            .parse::<DocumentMut>()
+            .unwrap();
+        assert_eq!(
+            fix_deprecations(&mut document),
+            ["Replaced `--pack-format` with `--format` in the alias `new`"]
+        );
+        assert_eq!(
+            document.to_string(),
+            "[alias]\nnew = \"init --format 48 && build\" # create\nrelease = [\"build --zip\"]\n"
+        );
+    }
+}
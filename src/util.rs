@@ -1,14 +1,40 @@
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     env,
-    path::{Path, PathBuf},
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    process::{self, ExitStatus, Stdio},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use inquire::{autocompletion::Replacement, Autocomplete};
 use path_absolutize::Absolutize;
 
-pub fn get_project_path<P>(base_path: P) -> Option<PathBuf>
+/// The user agent sent with HTTP requests.
+#[cfg(any(feature = "publish", feature = "mcdata"))]
+pub const USER_AGENT: &str = concat!(
+    "moritz-hoelting/shulkerscript-cli/",
+    env!("CARGO_PKG_VERSION"),
+    " (shulkerscript.hoelting.dev)"
+);
+
+/// Get the number of ancestors of a path (including itself) to search for a project.
+pub fn discovery_depth(discover: bool) -> usize {
+    if discover {
+        usize::MAX
+    } else {
+        1
+    }
+}
+
+/// Get the path of the project containing the path, i.e. the nearest directory with a
+/// pack.toml file.
+///
+/// Without `discover`, only the path itself is checked.
+pub fn get_project_path<P>(base_path: P, discover: bool) -> Option<PathBuf>
 where
     P: AsRef<Path>,
 {
@@ -19,10 +45,439 @@ where
         base_path.absolutize().ok()?
     }
     .ancestors()
+    .take(discovery_depth(discover))
     .find(|p| p.join("pack.toml").exists())
     .map(|p| p.relativize().unwrap_or_else(|| p.to_path_buf()))
 }
 
+/// Convert a pack name to a valid namespace.
+///
+/// Uppercase letters are lowercased, punctuation is replaced by `-`, whitespace by `_`
+/// and all other invalid characters are dropped.
+pub fn name_to_namespace(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if is_valid_namespace_char(c) {
+                Some(c)
+            } else if c.is_ascii_uppercase() {
+                Some(c.to_ascii_lowercase())
+            } else if c.is_ascii_punctuation() {
+                Some('-')
+            } else if c.is_ascii_whitespace() {
+                Some('_')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Get the characters of a pack name that are dropped when converting it to a namespace.
+pub fn dropped_namespace_chars(name: &str) -> Vec<char> {
+    let mut dropped = name
+        .to_lowercase()
+        .chars()
+        .filter(|c| {
+            !is_valid_namespace_char(*c) && !c.is_ascii_punctuation() && !c.is_ascii_whitespace()
+        })
+        .collect::<Vec<_>>();
+    dropped.dedup();
+    dropped
+}
+
+/// Check whether the name can be used as file name on all platforms.
+///
+/// Refuses empty names, names containing characters that are not allowed in file names,
+/// names ending with a dot or space and the reserved device names of Windows.
+pub fn is_valid_file_name(name: &str) -> bool {
+    const RESERVED: &[&str] = &[
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+        "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+
+    !name.trim().is_empty()
+        && !name.ends_with(['.', ' '])
+        && !name.chars().any(|c| {
+            c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*')
+        })
+        && !RESERVED
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Check whether the path is relative and stays inside of the folder it is relative to.
+///
+/// Only paths consisting of plain names are accepted, so `..`, roots and drive prefixes are
+/// refused.
+pub fn is_enclosed_path(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Get the `file://` URI of the path, as used by the language server protocol.
+pub fn file_uri(path: &Path) -> String {
+    let path = path
+        .absolutize()
+        .map_or_else(|_| path.to_path_buf(), Cow::into_owned);
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = if path.starts_with('/') {
+        String::from("file://")
+    } else {
+        String::from("file:///")
+    };
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/:-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
+/// Check whether a character is allowed in a namespace.
+pub fn is_valid_namespace_char(c: char) -> bool {
+    matches!(c, '0'..='9' | 'a'..='z' | '_' | '-' | '.')
+}
+
+/// Check whether the namespace is non-empty and only consists of valid characters.
+pub fn is_valid_namespace(namespace: &str) -> bool {
+    !namespace.is_empty() && namespace.chars().all(is_valid_namespace_char)
+}
+
+/// Replace all `{key}` placeholders in the template with their values.
+pub fn expand_placeholders<S>(template: &str, values: &[(&str, S)]) -> String
+where
+    S: AsRef<str>,
+{
+    values
+        .iter()
+        .fold(template.to_string(), |acc, (key, value)| {
+            acc.replace(&format!("{{{key}}}"), value.as_ref())
+        })
+}
+
+/// Check whether a path relative to the project root matches a glob pattern.
+///
+/// `*` and `?` match within a path component and `**` matches any number of components.
+/// Patterns without a `/` match any component of the path, and a pattern matching a directory
+/// also matches everything inside of it.
+pub fn matches_glob(pattern: &str, path: &str) -> bool {
+    fn matches_component(pattern: &[char], component: &[char]) -> bool {
+        match pattern.split_first() {
+            None => component.is_empty(),
+            Some(('*', rest)) => {
+                (0..=component.len()).any(|index| matches_component(rest, &component[index..]))
+            }
+            Some((first, rest)) => component.split_first().is_some_and(|(c, component_rest)| {
+                (*first == '?' || first == c) && matches_component(rest, component_rest)
+            }),
+        }
+    }
+
+    fn matches_parts(pattern: &[Vec<char>], path: &[Vec<char>]) -> bool {
+        match pattern.split_first() {
+            None => true,
+            Some((first, rest)) if first[..] == ['*', '*'] => {
+                (0..=path.len()).any(|index| matches_parts(rest, &path[index..]))
+            }
+            Some((first, rest)) => path.split_first().is_some_and(|(component, path_rest)| {
+                matches_component(first, component) && matches_parts(rest, path_rest)
+            }),
+        }
+    }
+
+    let split = |s: &str| {
+        s.split(['/', '\\'])
+            .filter(|part| !part.is_empty() && *part != ".")
+            .map(|part| part.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+    let path = split(path);
+    let pattern_parts = split(pattern);
+
+    if pattern_parts.is_empty() {
+        false
+    } else if pattern.trim_matches('/').contains('/') {
+        matches_parts(&pattern_parts, &path)
+    } else {
+        path.iter()
+            .any(|component| matches_component(&pattern_parts[0], component))
+    }
+}
+
+/// Create a command running the given command line in the shell of the platform.
+///
+/// Uses `cmd /C` on Windows and `$SHELL -c` (falling back to `sh`) on other platforms.
+pub fn shell_command(cmd: &str) -> process::Command {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = process::Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = process::Command::new(env::var("SHELL").unwrap_or("sh".to_string()));
+        command.arg("-c");
+        command
+    };
+
+    command.arg(cmd);
+    command
+}
+
+/// Quote the argument for the command line of [`shell_command`], so it is passed as a single
+/// argument and not interpreted by the shell.
+///
+/// On Windows, `%` and `!` are escaped outside of the quotes, as `cmd` expands variables inside
+/// of them as well.
+pub fn quote_shell_arg(arg: &str) -> String {
+    if cfg!(target_os = "windows") {
+        let mut quoted = String::from("\"");
+        let mut backslashes = 0;
+        for c in arg.chars() {
+            match c {
+                '\\' => backslashes += 1,
+                '"' | '%' | '!' => {
+                    // backslashes before a quote escape it in the arguments of the program
+                    quoted.push_str(&"\\".repeat(backslashes * 2));
+                    backslashes = 0;
+                    match c {
+                        '"' => quoted.push_str("\"\""),
+                        _ => quoted.extend(['"', '^', c, '"']),
+                    }
+                }
+                _ => {
+                    quoted.push_str(&"\\".repeat(backslashes));
+                    backslashes = 0;
+                    quoted.push(c);
+                }
+            }
+        }
+        quoted.push_str(&"\\".repeat(backslashes * 2));
+        quoted.push('"');
+        quoted
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Create a command running the given command line in the shell in its own process group, so the
+/// command and all processes started by it can be terminated together.
+///
+/// The command does not read from the terminal, as only the foreground process group may do so.
+pub fn shell_command_group(cmd: &str) -> process::Command {
+    let mut command = shell_command(cmd);
+    command.stdin(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt as _;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt as _;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+
+    command
+}
+
+/// The ids of the processes currently run by [`run_limited`].
+static RUNNING_COMMANDS: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+/// Terminate the process with the id and all processes started by it.
+///
+/// On Unix, the process group of the process is killed if it was started in its own group (see
+/// [`shell_command_group`]), otherwise only the process. On Windows, the tree of processes is
+/// killed with `taskkill`. Returns whether the processes were killed.
+pub fn kill_process_tree(pid: u32) -> bool {
+    let quiet = |command: &mut process::Command| {
+        command
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    };
+
+    if cfg!(windows) {
+        quiet(process::Command::new("taskkill").args(["/T", "/F", "/PID", &pid.to_string()]))
+    } else {
+        quiet(process::Command::new("kill").args(["-s", "KILL", "--", &format!("-{pid}")]))
+            || quiet(process::Command::new("kill").args(["-s", "KILL", "--", &pid.to_string()]))
+    }
+}
+
+/// Terminate all commands currently run by [`run_limited`] together with the processes started by
+/// them, e.g. when Ctrl-C is pressed.
+pub fn kill_running_commands() {
+    let running = RUNNING_COMMANDS
+        .lock()
+        .map(|running| running.clone())
+        .unwrap_or_default();
+    for pid in running {
+        kill_process_tree(pid);
+    }
+}
+
+/// Limits of commands run in the shell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommandLimits {
+    /// The time after which the command is killed.
+    pub timeout: Option<Duration>,
+    /// The number of bytes of stdout and stderr each that are printed, the rest is omitted.
+    pub max_output: Option<usize>,
+}
+
+/// How a command run with [`run_limited`] finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandOutcome {
+    /// The command exited with the status.
+    Exited(ExitStatus),
+    /// The command was killed because it exceeded the timeout.
+    TimedOut(Duration),
+}
+
+/// Run the command with the limits, killing it after the timeout and truncating its output.
+///
+/// Commands created with [`shell_command_group`] are killed together with the processes started
+/// by them.
+pub fn run_limited(
+    command: &mut process::Command,
+    limits: &CommandLimits,
+) -> io::Result<CommandOutcome> {
+    if limits.max_output.is_some() {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    if let Ok(mut running) = RUNNING_COMMANDS.lock() {
+        running.insert(pid);
+    }
+    let outcome = wait_limited(&mut child, limits);
+    if let Ok(mut running) = RUNNING_COMMANDS.lock() {
+        running.remove(&pid);
+    }
+    outcome
+}
+
+fn wait_limited(child: &mut process::Child, limits: &CommandLimits) -> io::Result<CommandOutcome> {
+    let forwarders = limits
+        .max_output
+        .map(|max_output| {
+            let stdout = child
+                .stdout
+                .take()
+                .map(|stdout| forward_truncated(stdout, io::stdout(), max_output));
+            let stderr = child
+                .stderr
+                .take()
+                .map(|stderr| forward_truncated(stderr, io::stderr(), max_output));
+            [stdout, stderr]
+        })
+        .unwrap_or_default();
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if limits
+            .timeout
+            .is_some_and(|timeout| start.elapsed() >= timeout)
+        {
+            if !kill_process_tree(child.id()) {
+                child.kill()?;
+            }
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    match status {
+        Some(status) => {
+            // processes started by the command may keep the pipes open, so the output is only
+            // waited for if the command exited by itself
+            for forwarder in forwarders.into_iter().flatten() {
+                let _ = forwarder.join();
+            }
+            Ok(CommandOutcome::Exited(status))
+        }
+        None => Ok(CommandOutcome::TimedOut(start.elapsed())),
+    }
+}
+
+/// Copy the first `max_output` bytes of the reader to the writer on a new thread, noting how many
+/// bytes were omitted.
+fn forward_truncated<R, W>(
+    mut reader: R,
+    mut writer: W,
+    max_output: usize,
+) -> thread::JoinHandle<()>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut buffer = [0; 8192];
+        let (mut written, mut omitted) = (0, 0);
+        while let Ok(read) = reader.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            let printed = read.min(max_output - written);
+            if printed > 0 {
+                let _ = writer.write_all(&buffer[..printed]);
+                written += printed;
+            }
+            omitted += read - printed;
+        }
+        if omitted > 0 {
+            let _ = writeln!(writer, "\n[... {omitted} bytes of output omitted]");
+        }
+        let _ = writer.flush();
+    })
+}
+
+/// Format a point in time as RFC 3339 timestamp in UTC, e.g. `2024-10-01T12:00:00Z`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (days, time_of_day) = ((secs / 86400) as i64, secs % 86400);
+
+    // convert the days since the epoch to a civil date in the proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
 pub trait Relativize {
     fn relativize(&self) -> Option<PathBuf>;
 }
@@ -150,3 +605,201 @@ impl Autocomplete for PathAutocomplete {
         }
     }
 }
+
+/// A line of the difference between two texts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Unchanged(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Get the lines removed from and added to the old text to get the new text.
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // lengths of the longest common subsequences of the remaining lines
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push(DiffLine::Unchanged(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_to_namespace() {
+        assert_eq!(name_to_namespace("My Pack"), "my_pack");
+        assert_eq!(name_to_namespace("pack!v2"), "pack-v2");
+        assert_eq!(name_to_namespace("äöü"), "");
+        assert!(is_valid_namespace(&name_to_namespace("Some Name.1")));
+    }
+
+    #[test]
+    fn test_dropped_namespace_chars() {
+        assert_eq!(dropped_namespace_chars("Mein Paket!"), Vec::<char>::new());
+        assert_eq!(dropped_namespace_chars("Grüße"), vec!['ü', 'ß']);
+    }
+
+    #[test]
+    fn test_is_valid_file_name() {
+        assert!(is_valid_file_name("My Pack"));
+        assert!(!is_valid_file_name("con"));
+        assert!(!is_valid_file_name("LPT1.txt"));
+        assert!(!is_valid_file_name("pack."));
+        assert!(!is_valid_file_name("a/b"));
+        assert!(!is_valid_file_name(" "));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(
+            file_uri(Path::new("/home/user/my pack/src/main.shu")),
+            "file:///home/user/my%20pack/src/main.shu"
+        );
+        assert!(file_uri(Path::new("src/main.shu")).starts_with("file:///"));
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        assert_eq!(
+            expand_placeholders(
+                "{name}-{version}{x}",
+                &[("name", "pack"), ("version", "1.0")]
+            ),
+            "pack-1.0{x}"
+        );
+    }
+
+    #[test]
+    fn test_matches_glob() {
+        assert!(matches_glob("*.tmp", "src/a/file.tmp"));
+        assert!(matches_glob("build", "assets/build/x.json"));
+        assert!(matches_glob("src/**/*.bak", "src/a/b/c.bak"));
+        assert!(matches_glob("src/**/*.bak", "src/c.bak"));
+        assert!(matches_glob("assets/gen", "assets/gen/a/b.png"));
+        assert!(matches_glob("file?.txt", "file1.txt"));
+        assert!(!matches_glob("src/*.bak", "src/a/c.bak"));
+        assert!(!matches_glob("*.tmp", "src/file.tmp.shu"));
+        assert!(!matches_glob("", "src"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_limited() {
+        let limits = CommandLimits {
+            timeout: Some(Duration::from_millis(200)),
+            max_output: Some(4),
+        };
+        assert!(matches!(
+            run_limited(&mut shell_command("sleep 5"), &limits),
+            Ok(CommandOutcome::TimedOut(_))
+        ));
+        assert!(matches!(
+            run_limited(&mut shell_command("echo 123456789"), &limits),
+            Ok(CommandOutcome::Exited(status)) if status.success()
+        ));
+
+        // the background process keeps the output pipe open unless the whole group is killed
+        let start = Instant::now();
+        assert!(matches!(
+            run_limited(&mut shell_command_group("sleep 5 & sleep 5"), &limits),
+            Ok(CommandOutcome::TimedOut(_))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_quote_shell_arg() {
+        let args = ["two words", "it's", "$HOME; echo x", ""];
+        let quoted = args.map(quote_shell_arg).join(" ");
+        let output = shell_command(&format!("printf '%s\\n' {quoted}"))
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "two words\nit's\n$HOME; echo x\n\n"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_quote_shell_arg_windows() {
+        assert_eq!(quote_shell_arg("two words"), r#""two words""#);
+        assert_eq!(quote_shell_arg(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(quote_shell_arg("100%"), r#""100"^%"""#);
+        assert_eq!(quote_shell_arg(r"C:\dir\"), r#""C:\dir\\""#);
+
+        let quoted = ["%OS%", "!OS!", "a & b"].map(quote_shell_arg).join(" ");
+        let output = shell_command(&format!("echo {quoted}")).output().unwrap();
+        let output = String::from_utf8(output.stdout).unwrap();
+        assert!(output.contains('%') && output.contains('!') && output.contains("a & b"));
+        assert!(!output.contains("Windows_NT"));
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + std::time::Duration::from_secs(1_727_784_000)),
+            "2024-10-01T12:00:00Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + std::time::Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_is_valid_namespace() {
+        assert!(is_valid_namespace("my_pack.v1-2"));
+        assert!(!is_valid_namespace(""));
+        assert!(!is_valid_namespace("MyPack"));
+        assert!(!is_valid_namespace("my pack"));
+    }
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(
+            line_diff("a\nb\nc\nd", "a\nc\nx\nd"),
+            [
+                DiffLine::Unchanged("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Unchanged("c"),
+                DiffLine::Added("x"),
+                DiffLine::Unchanged("d"),
+            ]
+        );
+        assert!(line_diff("a\nb", "a\nb")
+            .iter()
+            .all(|line| matches!(line, DiffLine::Unchanged(_))));
+    }
+}
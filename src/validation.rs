@@ -0,0 +1,786 @@
+//! Validation of the datapack output before it is written.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    iter,
+    path::{Path, PathBuf},
+};
+
+use shulkerscript::{
+    base::SilentHandler,
+    shulkerbox::virtual_fs::{VFile, VFolder},
+    syntax::syntax_tree::declaration::Declaration,
+};
+
+use crate::dependency::ScriptProvider;
+
+/// The origin of a file in the output datapack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// The file was copied from the assets directory.
+    Asset(PathBuf),
+    /// The file was generated from a script.
+    Script { path: PathBuf, line: Option<usize> },
+    /// The file was written by a stage of the build, e.g. the build script.
+    Stage(&'static str),
+    /// The file was generated, but the source could not be determined.
+    Generated,
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Asset(path) => write!(f, "asset file {}", path.display()),
+            Self::Script {
+                path,
+                line: Some(line),
+            } => write!(f, "script {}:{line}", path.display()),
+            Self::Script { path, line: None } => write!(f, "script {}", path.display()),
+            Self::Stage(stage) => write!(f, "{stage}"),
+            Self::Generated => write!(f, "generated code"),
+        }
+    }
+}
+
+/// A resource location in the output that does not conform to Minecraft's naming rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceLocationIssue {
+    /// The path of the affected file in the output.
+    pub output_path: String,
+    /// The invalid namespace, path or resource location.
+    pub resource_location: String,
+    /// The invalid characters found.
+    pub invalid_chars: String,
+    /// Where the invalid resource location comes from.
+    pub origin: Origin,
+}
+
+impl Display for ResourceLocationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.invalid_chars.is_empty() {
+            write!(
+                f,
+                "Empty resource location in {} (from {})",
+                self.output_path, self.origin
+            )
+        } else {
+            write!(
+                f,
+                "Invalid resource location \"{}\" in {} (invalid characters: \"{}\", from {})",
+                self.resource_location, self.output_path, self.invalid_chars, self.origin
+            )
+        }
+    }
+}
+
+/// The origins of the files merged into the output by the stages of the build, following the
+/// files when they are moved.
+#[derive(Debug, Clone, Default)]
+pub struct Origins {
+    files: BTreeMap<String, Origin>,
+}
+
+impl Origins {
+    /// Record the origin of every file of the folder, replacing the origins recorded before.
+    pub fn record(&mut self, folder: &VFolder, origin_of: impl Fn(&str) -> Origin) {
+        for (path, _) in folder.flatten() {
+            let origin = origin_of(&path);
+            self.files.insert(path, origin);
+        }
+    }
+
+    /// Record the origin of the output paths, replacing the origins recorded before.
+    pub fn record_paths<S: AsRef<str>>(
+        &mut self,
+        paths: impl IntoIterator<Item = S>,
+        origin: Origin,
+    ) {
+        for path in paths {
+            self.files.insert(path.as_ref().to_string(), origin.clone());
+        }
+    }
+
+    /// Forget the origins of the output paths, e.g. when their files were replaced by compiled
+    /// ones.
+    pub fn forget<S: AsRef<str>>(&mut self, paths: impl IntoIterator<Item = S>) {
+        for path in paths {
+            self.files.remove(path.as_ref());
+        }
+    }
+
+    /// Keep the origins of the files moved from the first to the second path.
+    pub fn follow_moves(&mut self, moved: &[(String, String)]) {
+        for (from, to) in moved {
+            if let Some(origin) = self.files.remove(from) {
+                self.files.insert(to.clone(), origin);
+            }
+        }
+    }
+
+    /// The recorded origin of the output path.
+    pub fn get(&self, output_path: &str) -> Option<&Origin> {
+        self.files.get(output_path)
+    }
+}
+
+/// Validate all namespaces, file paths and tag entries in the `data` folders of the output and
+/// of its overlays.
+///
+/// The `origin_of` function is called with the output path and the offending resource location
+/// to determine where the issue comes from.
+pub fn validate_resource_locations<F>(folder: &VFolder, origin_of: F) -> Vec<ResourceLocationIssue>
+where
+    F: Fn(&str, &str) -> Origin,
+{
+    let mut issues = Vec::new();
+
+    let mut overlays = folder
+        .get_folders()
+        .iter()
+        .filter(|(name, _)| !matches!(name.as_str(), "data" | "assets"))
+        .filter_map(|(name, overlay)| Some((format!("{name}/"), overlay.get_folder("data")?)))
+        .collect::<Vec<_>>();
+    overlays.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let roots = folder
+        .get_folder("data")
+        .map(|data| (String::new(), data))
+        .into_iter()
+        .chain(overlays);
+
+    let mut report = |output_path: String, resource_location: &str, invalid_chars: String| {
+        let origin = origin_of(&output_path, resource_location);
+        issues.push(ResourceLocationIssue {
+            output_path,
+            resource_location: resource_location.to_string(),
+            invalid_chars,
+            origin,
+        });
+    };
+
+    for (prefix, data) in roots {
+        let mut namespaces = data.get_folders().iter().collect::<Vec<_>>();
+        namespaces.sort_by_key(|(name, _)| name.as_str());
+
+        for (namespace, namespace_folder) in namespaces {
+            if let Some(invalid_chars) = invalid_namespace_chars(namespace) {
+                report(
+                    format!("{prefix}data/{namespace}"),
+                    namespace,
+                    invalid_chars,
+                );
+            }
+
+            let mut files = namespace_folder.flatten();
+            files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (path, file) in files {
+                let output_path = format!("{prefix}data/{namespace}/{path}");
+                if let Some(invalid_chars) = invalid_path_chars(&path) {
+                    report(output_path.clone(), &path, invalid_chars);
+                }
+
+                if path.starts_with("tags/") && path.ends_with(".json") {
+                    for value in tag_values(file) {
+                        if let Some(invalid_chars) =
+                            invalid_resource_location_chars(value.trim_start_matches('#'))
+                        {
+                            report(output_path.clone(), &value, invalid_chars);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// A namespace in the output of a library that is not listed in its exported namespaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceLeak {
+    /// The leaked namespace.
+    pub namespace: String,
+    /// The path of the first file of the namespace in the output.
+    pub output_path: String,
+    /// The number of files of the namespace in the output.
+    pub files: usize,
+}
+
+impl Display for NamespaceLeak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The namespace \"{}\" is not exported, but the output contains {}",
+            self.namespace, self.output_path
+        )?;
+        if self.files > 1 {
+            write!(f, " and {} more files of it", self.files - 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the namespaces in the `data` and `assets` folders of the output that are neither exported
+/// nor `minecraft`.
+pub fn leaked_namespaces(folder: &VFolder, exported: &[String]) -> Vec<NamespaceLeak> {
+    let mut leaks = Vec::<NamespaceLeak>::new();
+    for root in ["data", "assets"] {
+        let Some(root_folder) = folder.get_folder(root) else {
+            continue;
+        };
+        let mut namespaces = root_folder.get_folders().iter().collect::<Vec<_>>();
+        namespaces.sort_by_key(|(name, _)| name.as_str());
+
+        for (namespace, namespace_folder) in namespaces {
+            if namespace == "minecraft" || exported.contains(namespace) {
+                continue;
+            }
+            let mut files = namespace_folder
+                .flatten()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>();
+            files.sort();
+            let Some(first) = files.first() else {
+                continue;
+            };
+
+            match leaks.iter_mut().find(|leak| &leak.namespace == namespace) {
+                Some(leak) => leak.files += files.len(),
+                None => leaks.push(NamespaceLeak {
+                    namespace: namespace.clone(),
+                    output_path: format!("{root}/{namespace}/{first}"),
+                    files: files.len(),
+                }),
+            }
+        }
+    }
+    leaks
+}
+
+/// Commands and syntax of functions that require a minimum pack format, matched against the
+/// commands of a line including the ones run by `execute`.
+const FORMAT_FEATURES: &[(&str, FormatPattern, u8)] = &[
+    ("`item` command", FormatPattern::Command("item"), 7),
+    ("`place` command", FormatPattern::Command("place"), 10),
+    (
+        "`fillbiome` command",
+        FormatPattern::Command("fillbiome"),
+        10,
+    ),
+    ("`ride` command", FormatPattern::Command("ride"), 12),
+    ("`damage` command", FormatPattern::Command("damage"), 12),
+    ("`execute on`", FormatPattern::Execute("on"), 12),
+    ("`execute summon`", FormatPattern::Execute("summon"), 12),
+    (
+        "`execute if dimension`",
+        FormatPattern::Execute("if dimension"),
+        12,
+    ),
+    (
+        "`execute if loaded`",
+        FormatPattern::Execute("if loaded"),
+        12,
+    ),
+    ("`return` command", FormatPattern::Command("return"), 15),
+    ("macro lines", FormatPattern::Macro, 18),
+    ("`random` command", FormatPattern::Command("random"), 18),
+    ("`return run`", FormatPattern::Command("return run"), 26),
+    (
+        "`execute if function`",
+        FormatPattern::Execute("if function"),
+        26,
+    ),
+    ("`tick` command", FormatPattern::Command("tick"), 26),
+    ("`transfer` command", FormatPattern::Command("transfer"), 41),
+    ("`execute if items`", FormatPattern::Execute("if items"), 41),
+    ("`rotate` command", FormatPattern::Command("rotate"), 57),
+    ("`test` command", FormatPattern::Command("test"), 71),
+    ("`dialog` command", FormatPattern::Command("dialog"), 80),
+    ("`waypoint` command", FormatPattern::Command("waypoint"), 80),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum FormatPattern {
+    /// A command starting with the words, also when run by `execute`.
+    Command(&'static str),
+    /// A subcommand of `execute`, conditions starting with `if` also match `unless`.
+    Execute(&'static str),
+    /// A macro line starting with `$`.
+    Macro,
+}
+
+impl FormatPattern {
+    fn matches(self, line: &str) -> bool {
+        let command = line.trim_start_matches('$');
+        match self {
+            Self::Macro => line.starts_with('$'),
+            Self::Command(name) => iter::once(command)
+                .chain(
+                    command
+                        .match_indices(" run ")
+                        .map(|(i, _)| &command[i + 5..]),
+                )
+                .any(|command| {
+                    command
+                        .strip_prefix(name)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+                }),
+            Self::Execute(subcommand) => {
+                let Some(execute) = command.strip_prefix("execute") else {
+                    return false;
+                };
+                // the subcommands before the executed command
+                let execute = format!("{} ", execute.split(" run ").next().unwrap_or_default());
+                match subcommand.strip_prefix("if ") {
+                    Some(condition) => {
+                        execute.contains(&format!(" if {condition} "))
+                            || execute.contains(&format!(" unless {condition} "))
+                    }
+                    None => execute.contains(&format!(" {subcommand} ")),
+                }
+            }
+        }
+    }
+}
+
+/// A feature used by the output that requires a minimum pack format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatRequirement {
+    /// The description of the feature, e.g. ``"`return run`"``.
+    pub feature: &'static str,
+    /// The first pack format supporting the feature.
+    pub min_format: u8,
+    /// The first use of the feature, as path in the output and line number.
+    pub first_use: (String, usize),
+    /// The number of lines using the feature.
+    pub uses: usize,
+}
+
+impl Display for FormatRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let versions = crate::versions::pack_format(self.min_format)
+            .map(|format| format!(" (Minecraft {})", format.versions))
+            .unwrap_or_default();
+        write!(
+            f,
+            "{} requires pack format {}{versions}, used in {}:{}",
+            self.feature, self.min_format, self.first_use.0, self.first_use.1
+        )?;
+        if self.uses > 1 {
+            write!(f, " and {} more lines", self.uses - 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the features of the functions in the output that require a minimum pack format, sorted
+/// by the format.
+///
+/// The highest format of the requirements is the minimum pack format compatible with the output.
+pub fn format_requirements(folder: &VFolder) -> Vec<FormatRequirement> {
+    let mut requirements = Vec::<FormatRequirement>::new();
+
+    let Some(data) = folder.get_folder("data") else {
+        return requirements;
+    };
+    let mut files = data
+        .flatten()
+        .into_iter()
+        .filter(|(path, _)| path.ends_with(".mcfunction"))
+        .collect::<Vec<_>>();
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (path, file) in files {
+        let content = String::from_utf8_lossy(file.as_bytes());
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            for &(feature, pattern, min_format) in FORMAT_FEATURES {
+                if !pattern.matches(line) {
+                    continue;
+                }
+                match requirements.iter_mut().find(|req| req.feature == feature) {
+                    Some(requirement) => requirement.uses += 1,
+                    None => requirements.push(FormatRequirement {
+                        feature,
+                        min_format,
+                        first_use: (format!("data/{path}"), index + 1),
+                        uses: 1,
+                    }),
+                }
+            }
+        }
+    }
+
+    requirements.sort_by_key(|req| req.min_format);
+    requirements
+}
+
+/// Index of the scripts of a project used to attribute generated files to their source.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptIndex {
+    scripts: Vec<IndexedScript>,
+}
+
+/// A parsed script of the [`ScriptIndex`].
+#[derive(Debug, Clone)]
+struct IndexedScript {
+    path: PathBuf,
+    namespace: String,
+    /// The names of the functions and tags declared in the script with the line of their name.
+    declarations: Vec<(String, usize)>,
+}
+
+impl ScriptIndex {
+    /// Parse all scripts and collect their namespaces and declarations.
+    ///
+    /// Scripts that cannot be parsed are left out.
+    pub fn new<P>(script_paths: &[(String, P)]) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let scripts = script_paths
+            .iter()
+            .filter_map(|(identifier, path)| {
+                let program = shulkerscript::parse(
+                    &SilentHandler::new(),
+                    &ScriptProvider::default(),
+                    path.as_ref(),
+                    identifier.clone(),
+                )
+                .ok()?;
+                let declarations = program
+                    .declarations()
+                    .iter()
+                    .flat_map(declared_names)
+                    .collect();
+                Some(IndexedScript {
+                    path: path.as_ref().to_path_buf(),
+                    namespace: program
+                        .namespace()
+                        .namespace_name()
+                        .str_content()
+                        .to_string(),
+                    declarations,
+                })
+            })
+            .collect();
+
+        Self { scripts }
+    }
+
+    /// Find the script and line declaring the function or tag of the given output file.
+    ///
+    /// If no script of the namespace declares a function or tag with the name of the resource
+    /// location, e.g. for obfuscated function names, only the script is given if it is the only
+    /// one of the namespace.
+    pub fn origin_of(&self, output_path: &str, resource_location: &str) -> Origin {
+        let namespace = output_path
+            .strip_prefix("data/")
+            .and_then(|p| p.split('/').next());
+        let needle = resource_location
+            .rsplit(['/', ':'])
+            .next()
+            .unwrap_or(resource_location)
+            .trim_end_matches(".mcfunction")
+            .trim_end_matches(".json");
+
+        let candidates = self
+            .scripts
+            .iter()
+            .filter(|script| namespace.is_none_or(|ns| script.namespace == ns))
+            .collect::<Vec<_>>();
+
+        candidates
+            .iter()
+            .find_map(|script| {
+                script
+                    .declarations
+                    .iter()
+                    .find(|(name, _)| !needle.is_empty() && name.rsplit('/').next() == Some(needle))
+                    .map(|(_, line)| Origin::Script {
+                        path: script.path.clone(),
+                        line: Some(*line),
+                    })
+            })
+            .or_else(|| match candidates.as_slice() {
+                [script] => Some(Origin::Script {
+                    path: script.path.clone(),
+                    line: None,
+                }),
+                _ => None,
+            })
+            .unwrap_or(Origin::Generated)
+    }
+}
+
+/// Get the names of the functions and tags of a declaration with the line of their name.
+///
+/// Functions with a fixed name (`#[deobfuscate = "name"]`) are also found by that name.
+fn declared_names(declaration: &Declaration) -> Vec<(String, usize)> {
+    match declaration {
+        Declaration::Function(function) => {
+            let identifier = &function.identifier().span;
+            let line = identifier.start_location().line;
+            iter::once(identifier.str().to_string())
+                .chain(function.annotations().iter().filter_map(|annotation| {
+                    (annotation.identifier().span.str() == "deobfuscate")
+                        .then(|| annotation.value().as_ref())
+                        .flatten()
+                        .map(|(_, name)| name.str_content().to_string())
+                }))
+                .map(|name| (name, line))
+                .collect()
+        }
+        Declaration::Tag(tag) => vec![(
+            tag.name().str_content().to_string(),
+            tag.name().span.start_location().line,
+        )],
+        Declaration::Import(_) => Vec::new(),
+    }
+}
+
+/// Get all values of a tag file.
+fn tag_values(file: &VFile) -> Vec<String> {
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(file.as_bytes()) else {
+        return Vec::new();
+    };
+
+    json.get("values")
+        .and_then(|values| values.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| {
+                    value
+                        .as_str()
+                        .or_else(|| value.get("id").and_then(|id| id.as_str()))
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn invalid_chars<F>(s: &str, is_valid: F) -> Option<String>
+where
+    F: Fn(char) -> bool,
+{
+    let mut invalid = s.chars().filter(|c| !is_valid(*c)).collect::<Vec<_>>();
+    invalid.sort_unstable();
+    invalid.dedup();
+
+    if invalid.is_empty() && !s.is_empty() {
+        None
+    } else {
+        Some(invalid.into_iter().collect())
+    }
+}
+
+/// Get the invalid characters of a namespace, or `None` if it is valid.
+pub fn invalid_namespace_chars(namespace: &str) -> Option<String> {
+    invalid_chars(namespace, crate::util::is_valid_namespace_char)
+}
+
+/// Get the invalid characters of a resource path, or `None` if it is valid.
+pub fn invalid_path_chars(path: &str) -> Option<String> {
+    invalid_chars(path, |c| {
+        c == '/' || crate::util::is_valid_namespace_char(c)
+    })
+}
+
+/// Get the invalid characters of a resource location (`namespace:path`), or `None` if it is valid.
+///
+/// A missing namespace defaults to `minecraft`.
+pub fn invalid_resource_location_chars(resource_location: &str) -> Option<String> {
+    match resource_location.split_once(':') {
+        Some((namespace, path)) if !namespace.is_empty() && !path.is_empty() => {
+            let invalid = invalid_namespace_chars(namespace)
+                .into_iter()
+                .chain(invalid_path_chars(path))
+                .collect::<String>();
+            invalid_chars(&invalid, |_| false).filter(|chars| !chars.is_empty())
+        }
+        Some(_) => Some(String::new()),
+        None => invalid_path_chars(resource_location),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_invalid_resource_location_chars() {
+        assert_eq!(invalid_resource_location_chars("my_pack:foo/bar"), None);
+        assert_eq!(invalid_resource_location_chars("foo.bar"), None);
+        assert_eq!(
+            invalid_resource_location_chars("My:foo/Bar baz").as_deref(),
+            Some(" BM")
+        );
+        assert_eq!(invalid_resource_location_chars("").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_validate_resource_locations() {
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/ok/function/main.mcfunction",
+            VFile::Text(String::new()),
+        );
+        folder.add_file(
+            "data/ok/function/Main.mcfunction",
+            VFile::Text(String::new()),
+        );
+        folder.add_file(
+            "data/minecraft/tags/function/tick.json",
+            VFile::Text(r#"{"values":["ok:main","ok:Main"]}"#.to_string()),
+        );
+        folder.add_file("data/Bad/function/a.mcfunction", VFile::Text(String::new()));
+        folder.add_file(
+            "overlay_1/data/ok/function/Overlay.mcfunction",
+            VFile::Text(String::new()),
+        );
+
+        let mut origins = Origins::default();
+        origins.record_paths(
+            ["data/ok/function/Main.mcfunction"],
+            Origin::Stage("a plugin"),
+        );
+        let issues = validate_resource_locations(&folder, |path, _| {
+            origins.get(path).cloned().unwrap_or(Origin::Generated)
+        });
+        let locations = issues
+            .iter()
+            .map(|i| i.resource_location.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            locations,
+            [
+                "Bad",
+                "ok:Main",
+                "function/Main.mcfunction",
+                "function/Overlay.mcfunction"
+            ]
+        );
+        assert_eq!(issues[0].origin, Origin::Generated);
+        assert_eq!(issues[2].origin, Origin::Stage("a plugin"));
+        assert_eq!(
+            issues[3].output_path,
+            "overlay_1/data/ok/function/Overlay.mcfunction"
+        );
+    }
+
+    #[test]
+    fn test_script_index() {
+        let temp = TestProject::empty("script-index");
+        let dir = temp.path();
+        let main = dir.join("main.shu");
+        fs::write(
+            &main,
+            "// namespace \"other\"\nnamespace \"pack\";\n\n// calls Setup\nfn main() {\n    /say main\n}\n\n#[deobfuscate = \"Setup\"]\nfn setup() {\n    /say setup\n}\n",
+        )
+        .unwrap();
+        let other = dir.join("other.shu");
+        fs::write(
+            &other,
+            "namespace \"other\";\n\nfn helper() {\n    /say helper\n}\n",
+        )
+        .unwrap();
+
+        let index = ScriptIndex::new(&[
+            ("main".to_string(), main.clone()),
+            ("other".to_string(), other.clone()),
+        ]);
+        assert_eq!(
+            index.origin_of("data/pack/function/Setup.mcfunction", "Setup"),
+            Origin::Script {
+                path: main.clone(),
+                line: Some(10)
+            }
+        );
+        assert_eq!(
+            index.origin_of("data/pack/function/abc123.mcfunction", "abc123"),
+            Origin::Script {
+                path: main,
+                line: None
+            }
+        );
+        assert_eq!(
+            index.origin_of("data/other/function/helper.mcfunction", "helper"),
+            Origin::Script {
+                path: other,
+                line: Some(3)
+            }
+        );
+        assert_eq!(
+            index.origin_of("data/missing/function/main.mcfunction", "main"),
+            Origin::Generated
+        );
+    }
+
+    #[test]
+    fn test_leaked_namespaces() {
+        let mut folder = VFolder::new();
+        for path in [
+            "data/lib/function/api.mcfunction",
+            "data/minecraft/tags/function/load.json",
+            "data/lib_internal/function/b.mcfunction",
+            "data/lib_internal/function/a.mcfunction",
+            "assets/lib_internal/lang/en_us.json",
+        ] {
+            folder.add_file(path, VFile::Text(String::new()));
+        }
+
+        let leaks = leaked_namespaces(&folder, &["lib".to_string()]);
+        assert_eq!(
+            leaks,
+            [NamespaceLeak {
+                namespace: "lib_internal".to_string(),
+                output_path: "data/lib_internal/function/a.mcfunction".to_string(),
+                files: 3,
+            }]
+        );
+        assert_eq!(
+            leaks[0].to_string(),
+            "The namespace \"lib_internal\" is not exported, but the output contains data/lib_internal/function/a.mcfunction and 2 more files of it"
+        );
+    }
+
+    #[test]
+    fn test_format_requirements() {
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/pack/function/main.mcfunction",
+            VFile::Text(
+                "# return run in a comment\nsay hi\nexecute as @a run return run say hi\n$say $(name)\nreturn 1\n"
+                    .to_string(),
+            ),
+        );
+
+        let requirements = format_requirements(&folder);
+        let features = requirements
+            .iter()
+            .map(|req| (req.feature, req.min_format, req.uses))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            features,
+            [
+                ("`return` command", 15, 2),
+                ("macro lines", 18, 1),
+                ("`return run`", 26, 1)
+            ]
+        );
+        assert_eq!(
+            requirements[2].first_use,
+            ("data/pack/function/main.mcfunction".to_string(), 3)
+        );
+    }
+}
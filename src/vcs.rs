@@ -0,0 +1,198 @@
+//! Information about the version control state of a project.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use git2::{
+    Delta as GitDelta, DiffOptions as GitDiffOptions, ObjectType as GitObjectType,
+    Repository as GitRepository, StatusOptions as GitStatusOptions,
+    TreeWalkMode as GitTreeWalkMode, TreeWalkResult as GitTreeWalkResult,
+};
+use path_absolutize::Absolutize as _;
+
+/// State of the git repository containing a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitInfo {
+    /// The full hash of the current commit.
+    pub hash: String,
+    /// The tag pointing at the current commit, if any.
+    pub tag: Option<String>,
+    /// Whether the working tree has uncommitted changes.
+    pub dirty: bool,
+}
+
+impl GitInfo {
+    /// Read the state of the repository containing the given path.
+    ///
+    /// Returns `None` if the path is not inside a git repository or the repository has no commits.
+    pub fn discover<P>(path: P) -> Option<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let repo = GitRepository::discover(path.as_ref().absolutize().ok()?).ok()?;
+        let head = repo.head().ok()?.peel_to_commit().ok()?;
+
+        let tag = repo.tag_names(None).ok().and_then(|names| {
+            names
+                .iter()
+                .flatten()
+                .filter(|name| {
+                    repo.revparse_single(&format!("refs/tags/{name}"))
+                        .and_then(|obj| obj.peel_to_commit())
+                        .is_ok_and(|commit| commit.id() == head.id())
+                })
+                .max()
+                .map(str::to_string)
+        });
+
+        let dirty = repo
+            .statuses(Some(
+                GitStatusOptions::new()
+                    .include_untracked(true)
+                    .include_ignored(false),
+            ))
+            .is_ok_and(|statuses| !statuses.is_empty());
+
+        Some(Self {
+            hash: head.id().to_string(),
+            tag,
+            dirty,
+        })
+    }
+
+    /// The abbreviated hash of the current commit.
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+
+/// Write the files of a revision of the repository containing the path to the destination.
+///
+/// The revision can be anything git understands, e.g. a branch, tag or `HEAD~1`. Returns the
+/// root of the working tree and the path corresponding to the given one in the destination.
+///
+/// # Errors
+/// - If the path is not inside a git repository with a working tree.
+/// - If the revision cannot be resolved.
+/// - If the files cannot be written.
+pub fn export_revision(
+    path: &Path,
+    revision: &str,
+    destination: &Path,
+) -> anyhow::Result<(PathBuf, PathBuf)> {
+    let path = path.absolutize()?;
+    let repo = GitRepository::discover(&path)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("The repository has no working tree."))?
+        .absolutize()?
+        .into_owned();
+    let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+
+    let mut result = Ok(());
+    tree.walk(GitTreeWalkMode::PreOrder, |parent, entry| {
+        if entry.kind() != Some(GitObjectType::Blob) {
+            return GitTreeWalkResult::Ok;
+        }
+        let file_path = destination
+            .join(parent)
+            .join(entry.name().unwrap_or_default());
+        result = entry
+            .to_object(&repo)
+            .map_err(anyhow::Error::from)
+            .and_then(|object| {
+                let blob = object.peel_to_blob()?;
+                if let Some(parent) = file_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                Ok(fs::write(&file_path, blob.content())?)
+            });
+        if result.is_ok() {
+            GitTreeWalkResult::Ok
+        } else {
+            GitTreeWalkResult::Abort
+        }
+    })?;
+    result?;
+
+    let relative = path.strip_prefix(&workdir).unwrap_or(Path::new(""));
+    Ok((workdir.clone(), destination.join(relative)))
+}
+
+/// Get the files of the repository containing the path that were added or modified since the
+/// revision, including staged, unstaged and untracked files.
+///
+/// The paths are absolute, deleted files are not included.
+///
+/// # Errors
+/// - If the path is not inside a git repository with a working tree.
+/// - If the revision cannot be resolved.
+pub fn changed_files(path: &Path, revision: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let repo = GitRepository::discover(path.absolutize()?)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("The repository has no working tree."))?
+        .absolutize()?
+        .into_owned();
+    let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+
+    let diff = repo.diff_tree_to_workdir_with_index(
+        Some(&tree),
+        Some(
+            GitDiffOptions::new()
+                .include_untracked(true)
+                .recurse_untracked_dirs(true),
+        ),
+    )?;
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() != GitDelta::Deleted)
+        .filter_map(|delta| delta.new_file().path().map(|path| workdir.join(path)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_export_revision() {
+        let temp = TestProject::empty("vcs");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("repo/pack/src")).unwrap();
+        fs::write(dir.join("repo/pack/src/main.shu"), "old").unwrap();
+
+        let repo = GitRepository::init(dir.join("repo")).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("pack/src/main.shu")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        fs::write(dir.join("repo/pack/src/main.shu"), "new").unwrap();
+
+        let (workdir, exported) =
+            export_revision(&dir.join("repo/pack"), "HEAD", &dir.join("export")).unwrap();
+        assert_eq!(workdir, dir.join("repo"));
+        assert_eq!(exported, dir.join("export/pack"));
+        assert_eq!(
+            fs::read_to_string(exported.join("src/main.shu")).unwrap(),
+            "old"
+        );
+
+        fs::write(dir.join("repo/pack/src/new.shu"), "").unwrap();
+        let mut changed = changed_files(&dir.join("repo/pack"), "HEAD").unwrap();
+        changed.sort();
+        assert_eq!(
+            changed,
+            [
+                dir.join("repo/pack/src/main.shu"),
+                dir.join("repo/pack/src/new.shu")
+            ]
+        );
+    }
+}
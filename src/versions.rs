@@ -0,0 +1,216 @@
+//! Mapping between data pack formats and Minecraft versions.
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+/// A data pack format and the Minecraft versions using it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackFormat {
+    /// The data pack format number.
+    pub format: u8,
+    /// The Minecraft versions using the format.
+    pub versions: &'static str,
+}
+
+impl PackFormat {
+    /// Get the newest Minecraft version using the format.
+    pub fn newest_version(&self) -> &'static str {
+        self.versions
+            .rsplit('–')
+            .next()
+            .unwrap_or(self.versions)
+            .trim()
+    }
+}
+
+impl Display for PackFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} — {}", self.format, self.versions)
+    }
+}
+
+/// The known data pack formats, newest first.
+pub const PACK_FORMATS: &[PackFormat] = &[
+    PackFormat {
+        format: 81,
+        versions: "1.21.7 – 1.21.8",
+    },
+    PackFormat {
+        format: 80,
+        versions: "1.21.6",
+    },
+    PackFormat {
+        format: 71,
+        versions: "1.21.5",
+    },
+    PackFormat {
+        format: 61,
+        versions: "1.21.4",
+    },
+    PackFormat {
+        format: 57,
+        versions: "1.21.2 – 1.21.3",
+    },
+    PackFormat {
+        format: 48,
+        versions: "1.21 – 1.21.1",
+    },
+    PackFormat {
+        format: 41,
+        versions: "1.20.5 – 1.20.6",
+    },
+    PackFormat {
+        format: 26,
+        versions: "1.20.3 – 1.20.4",
+    },
+    PackFormat {
+        format: 18,
+        versions: "1.20.2",
+    },
+    PackFormat {
+        format: 15,
+        versions: "1.20 – 1.20.1",
+    },
+    PackFormat {
+        format: 12,
+        versions: "1.19.4",
+    },
+    PackFormat {
+        format: 10,
+        versions: "1.19 – 1.19.3",
+    },
+    PackFormat {
+        format: 9,
+        versions: "1.18.2",
+    },
+    PackFormat {
+        format: 8,
+        versions: "1.18 – 1.18.1",
+    },
+    PackFormat {
+        format: 7,
+        versions: "1.17 – 1.17.1",
+    },
+    PackFormat {
+        format: 6,
+        versions: "1.16.2 – 1.16.5",
+    },
+    PackFormat {
+        format: 5,
+        versions: "1.15 – 1.16.1",
+    },
+    PackFormat {
+        format: 4,
+        versions: "1.13 – 1.14.4",
+    },
+];
+
+/// An experimental vanilla feature that data packs can require in the `features` block of their
+/// pack.mcmeta file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureFlag {
+    /// The resource location of the feature.
+    pub name: &'static str,
+    /// The data pack formats of the versions that know the feature.
+    pub formats: RangeInclusive<u8>,
+}
+
+/// The known vanilla feature flags, introduced with Minecraft 1.19.3.
+pub const FEATURE_FLAGS: &[FeatureFlag] = &[
+    FeatureFlag {
+        name: "minecraft:vanilla",
+        formats: 10..=u8::MAX,
+    },
+    FeatureFlag {
+        name: "minecraft:bundle",
+        formats: 10..=48,
+    },
+    FeatureFlag {
+        name: "minecraft:update_1_20",
+        formats: 10..=12,
+    },
+    FeatureFlag {
+        name: "minecraft:trade_rebalance",
+        formats: 18..=u8::MAX,
+    },
+    FeatureFlag {
+        name: "minecraft:update_1_21",
+        formats: 26..=41,
+    },
+    FeatureFlag {
+        name: "minecraft:winter_drop",
+        formats: 57..=57,
+    },
+    FeatureFlag {
+        name: "minecraft:redstone_experiments",
+        formats: 57..=u8::MAX,
+    },
+    FeatureFlag {
+        name: "minecraft:minecart_improvements",
+        formats: 57..=u8::MAX,
+    },
+];
+
+/// Get the known feature flag with the given name, the namespace defaults to `minecraft`.
+pub fn feature_flag(name: &str) -> Option<&'static FeatureFlag> {
+    let name = if name.contains(':') {
+        name.to_string()
+    } else {
+        format!("minecraft:{name}")
+    };
+    FEATURE_FLAGS.iter().find(|flag| flag.name == name)
+}
+
+/// Get the known pack format entry of the given format number.
+pub fn pack_format(format: u8) -> Option<&'static PackFormat> {
+    PACK_FORMATS.iter().find(|entry| entry.format == format)
+}
+
+/// Get the known pack format used by the given Minecraft version, e.g. `1.21.4`.
+pub fn pack_format_of_version(version: &str) -> Option<&'static PackFormat> {
+    let version = parse_version(version)?;
+    PACK_FORMATS.iter().find(|entry| {
+        let (first, last) = entry
+            .versions
+            .split_once('–')
+            .unwrap_or((entry.versions, entry.versions));
+        parse_version(first).is_some_and(|first| first <= version)
+            && parse_version(last).is_some_and(|last| version <= last)
+    })
+}
+
+/// Parse a release version into its major, minor and patch number.
+fn parse_version(version: &str) -> Option<[u32; 3]> {
+    let mut parts = [0; 3];
+    for (index, part) in version.trim().split('.').enumerate() {
+        *parts.get_mut(index)? = part.parse().ok()?;
+    }
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_format_of_version() {
+        assert_eq!(pack_format_of_version("1.21.8").map(|f| f.format), Some(81));
+        assert_eq!(pack_format_of_version("1.21").map(|f| f.format), Some(48));
+        assert_eq!(pack_format_of_version("1.21.1").map(|f| f.format), Some(48));
+        assert_eq!(pack_format_of_version("1.16.3").map(|f| f.format), Some(6));
+        assert_eq!(pack_format_of_version("1.16.1").map(|f| f.format), Some(5));
+        assert_eq!(pack_format_of_version("1.12"), None);
+        assert_eq!(pack_format_of_version("24w14a"), None);
+    }
+
+    #[test]
+    fn test_feature_flag() {
+        let flag = feature_flag("trade_rebalance").unwrap();
+        assert_eq!(flag.name, "minecraft:trade_rebalance");
+        assert!(flag.formats.contains(&48));
+        assert!(!feature_flag("minecraft:update_1_21")
+            .unwrap()
+            .formats
+            .contains(&48));
+        assert_eq!(feature_flag("mymod:feature"), None);
+    }
+}
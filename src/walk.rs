@@ -0,0 +1,411 @@
+//! Walking the directories of a project, following or refusing symlinks.
+//!
+//! Symlinked directories are followed by default, e.g. a shared `lib/` folder linked into `src/`.
+//! Links pointing to a directory containing them are reported as cycles instead of being
+//! followed endlessly.
+//!
+//! File names that are not valid UTF-8 are reported with a suggested name, unless lossy names are
+//! enabled, which replace the invalid characters with `_`.
+
+use std::{
+    collections::BTreeMap,
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+use crate::terminal_output::{print_error, print_warning};
+
+/// The options of walking a directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// How symlinks are treated.
+    pub symlinks: SymlinkPolicy,
+    /// Replace the invalid characters of file names that are not valid UTF-8 instead of failing.
+    pub lossy_names: bool,
+}
+
+/// How symlinks in the directories of a project are treated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Read the files and directories the symlinks point to.
+    #[default]
+    Follow,
+    /// Fail on the first symlink.
+    Refuse,
+}
+
+/// A file found while walking a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkedFile {
+    /// The names of the directories from the walked directory to the file and the file name.
+    pub components: Vec<OsString>,
+    /// The path of the file, through the symlinks followed to reach it.
+    pub path: PathBuf,
+}
+
+impl WalkedFile {
+    /// Get the components as strings.
+    ///
+    /// # Errors
+    /// - If a component is not valid UTF-8 and lossy names are disabled.
+    pub fn str_components(&self, lossy_names: bool) -> io::Result<Vec<String>> {
+        self.components
+            .iter()
+            .map(|component| name_to_str(component, &self.path, lossy_names))
+            .collect()
+    }
+}
+
+/// Convert a file or folder name of the path to a string.
+///
+/// With `lossy`, the invalid characters are replaced with `_` and a warning is printed.
+///
+/// # Errors
+/// - If the name is not valid UTF-8 and `lossy` is disabled.
+pub fn name_to_str(name: &OsStr, path: &Path, lossy: bool) -> io::Result<String> {
+    if let Some(name) = name.to_str() {
+        return Ok(name.to_string());
+    }
+
+    let replaced = name.to_string_lossy().replace('\u{FFFD}', "_");
+    if lossy {
+        print_warning(format!(
+            "The name {} in {} is not valid UTF-8, it is read as {replaced}",
+            name.to_string_lossy(),
+            path.display()
+        ));
+        Ok(replaced)
+    } else {
+        let message = format!(
+            "The name {} in {} is not valid UTF-8, rename it to e.g. {replaced} or build with `--lossy-file-names`",
+            name.to_string_lossy(),
+            path.display()
+        );
+        print_error(&message);
+        Err(io::Error::new(io::ErrorKind::InvalidData, message))
+    }
+}
+
+/// Get all files in the directory and its subdirectories, sorted by path.
+///
+/// Returns an empty list if the directory does not exist.
+///
+/// # Errors
+/// - If a directory cannot be read.
+/// - If a symlink is refused, broken or part of a cycle.
+pub fn files(dir: &Path, options: WalkOptions) -> io::Result<Vec<WalkedFile>> {
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        let mut ancestors = vec![dir.canonicalize()?];
+        walk(
+            dir,
+            &mut Vec::new(),
+            &mut ancestors,
+            options.symlinks,
+            &mut files,
+        )?;
+    }
+    Ok(files)
+}
+
+/// Read the directory into a virtual folder.
+///
+/// # Errors
+/// - If the directory does not exist or cannot be read.
+/// - If a file name is not valid UTF-8 and lossy names are disabled.
+/// - If a symlink is refused, broken or part of a cycle.
+pub fn read_folder(dir: &Path, options: WalkOptions) -> io::Result<VFolder> {
+    if !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("The directory {} does not exist", dir.display()),
+        ));
+    }
+
+    let mut folder = VFolder::new();
+    for file in files(dir, options)? {
+        let path = file.str_components(options.lossy_names)?.join("/");
+        folder.add_file(&path, VFile::try_from(file.path.as_path())?);
+    }
+    Ok(folder)
+}
+
+/// The size from which files are streamed from disk instead of being read into memory.
+pub const STREAM_THRESHOLD: u64 = 8 * 1024 * 1024;
+
+/// The extensions of files that are always read, as the build reads or modifies them.
+const READ_EXTENSIONS: &[&str] = &["json", "mcfunction", "mcmeta", "shu", "toml"];
+
+/// Large files of a walked directory that are copied from disk when placing the output instead of
+/// being held in memory.
+///
+/// The output contains an empty placeholder file at their path, so they are merged and validated
+/// like other files. Stages replacing files of the output report the replaced paths with
+/// [`StreamedFiles::replaced`], so they are not streamed anymore.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamedFiles {
+    files: BTreeMap<String, PathBuf>,
+}
+
+impl StreamedFiles {
+    /// The file on disk of the output path, if it is streamed and its placeholder is still in the
+    /// output.
+    pub fn source_of(&self, output: &VFolder, path: &str) -> Option<&Path> {
+        self.files
+            .get(path)
+            .map(PathBuf::as_path)
+            .filter(|_| output.get_file(path).is_some())
+    }
+
+    /// Stop streaming the output paths, as their placeholders were replaced by other files.
+    pub fn replaced<S: AsRef<str>>(&mut self, paths: impl IntoIterator<Item = S>) {
+        for path in paths {
+            self.files.remove(path.as_ref());
+        }
+    }
+
+    /// The streamed paths whose placeholder in the output was overwritten with content.
+    pub fn overwritten(&self, output: &VFolder) -> Vec<String> {
+        self.files
+            .keys()
+            .filter(|path| {
+                output
+                    .get_file(path)
+                    .is_some_and(|file| !file.as_bytes().is_empty())
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The size of the file at the output path, read from disk for streamed files.
+    pub fn size_of(&self, output: &VFolder, path: &str, file: &VFile) -> usize {
+        match self.source_of(output, path) {
+            Some(source) => fs::metadata(source).map_or(0, |metadata| metadata.len() as usize),
+            None => file.as_bytes().len(),
+        }
+    }
+
+    /// Stream the files moved or copied in the output from the sources of the original paths.
+    pub fn follow_moves(&mut self, moved: &[(String, String)]) {
+        for (from, to) in moved {
+            if let Some(source) = self.files.get(from).cloned() {
+                self.files.insert(to.clone(), source);
+            }
+        }
+    }
+
+    /// Copy the streamed files whose placeholder is still in the output into the placed output
+    /// folder.
+    ///
+    /// # Errors
+    /// - If a file cannot be copied.
+    pub fn copy_into(&self, output: &VFolder, dir: &Path) -> io::Result<()> {
+        for path in self.files.keys() {
+            if let Some(source) = self.source_of(output, path) {
+                fs::copy(source, dir.join(path))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the directory into a virtual folder, streaming large files that the build does not read
+/// from disk when placing the output.
+///
+/// # Errors
+/// - If the directory does not exist or cannot be read.
+/// - If a file name is not valid UTF-8 and lossy names are disabled.
+/// - If a symlink is refused, broken or part of a cycle.
+pub fn read_folder_streamed(
+    dir: &Path,
+    options: WalkOptions,
+) -> io::Result<(VFolder, StreamedFiles)> {
+    if !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("The directory {} does not exist", dir.display()),
+        ));
+    }
+
+    let mut folder = VFolder::new();
+    let mut streamed = StreamedFiles::default();
+    for file in files(dir, options)? {
+        let path = file.str_components(options.lossy_names)?.join("/");
+        let is_read = file
+            .path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|extension| READ_EXTENSIONS.contains(&extension));
+        if !is_read && fs::metadata(&file.path)?.len() >= STREAM_THRESHOLD {
+            folder.add_file(&path, VFile::Binary(Vec::new()));
+            streamed.files.insert(path, file.path);
+        } else {
+            folder.add_file(&path, VFile::try_from(file.path.as_path())?);
+        }
+    }
+    Ok((folder, streamed))
+}
+
+fn walk(
+    dir: &Path,
+    components: &mut Vec<OsString>,
+    ancestors: &mut Vec<PathBuf>,
+    symlinks: SymlinkPolicy,
+    files: &mut Vec<WalkedFile>,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let is_symlink = entry.file_type()?.is_symlink();
+        if is_symlink && symlinks == SymlinkPolicy::Refuse {
+            return Err(symlink_error(
+                &path,
+                "symlinks are refused by `--refuse-symlinks` or the `refuse_symlinks` field",
+            ));
+        }
+
+        // follows symlinks
+        let metadata = match fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(err) if is_symlink && err.kind() == io::ErrorKind::NotFound => {
+                let target = fs::read_link(&path).unwrap_or_default();
+                return Err(symlink_error(
+                    &path,
+                    &format!("it points to {}, which does not exist", target.display()),
+                ));
+            }
+            Err(err) => return Err(err),
+        };
+
+        components.push(entry.file_name());
+        if metadata.is_dir() {
+            let canonical = path.canonicalize()?;
+            if ancestors.contains(&canonical) {
+                return Err(symlink_error(
+                    &path,
+                    &format!(
+                        "it points to {}, which contains the symlink itself",
+                        canonical.display()
+                    ),
+                ));
+            }
+            ancestors.push(canonical);
+            walk(&path, components, ancestors, symlinks, files)?;
+            ancestors.pop();
+        } else {
+            files.push(WalkedFile {
+                components: components.clone(),
+                path,
+            });
+        }
+        components.pop();
+    }
+
+    Ok(())
+}
+
+fn symlink_error(path: &Path, reason: &str) -> io::Error {
+    let message = format!("Cannot read the symlink {}: {reason}", path.display());
+    print_error(&message);
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use super::*;
+    use crate::testing::TestProject;
+
+    #[test]
+    fn test_symlinks() {
+        let temp = TestProject::empty("walk");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::write(dir.join("lib/util.shu"), "").unwrap();
+        fs::write(dir.join("src/main.shu"), "").unwrap();
+        symlink(dir.join("lib"), dir.join("src/lib")).unwrap();
+
+        let follow = WalkOptions::default();
+        let refuse = WalkOptions {
+            symlinks: SymlinkPolicy::Refuse,
+            ..Default::default()
+        };
+
+        let files = files(&dir.join("src"), follow).unwrap();
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| file.components.join(OsString::from("/").as_os_str()))
+                .collect::<Vec<_>>(),
+            ["lib/util.shu", "main.shu"]
+        );
+        assert_eq!(files[0].path, dir.join("src/lib/util.shu"));
+
+        assert!(super::files(&dir.join("src"), refuse).is_err());
+
+        symlink(dir.join("src"), dir.join("src/nested/cycle")).unwrap();
+        assert!(super::files(&dir.join("src"), follow).is_err());
+    }
+
+    #[test]
+    fn test_read_folder_streamed() {
+        let temp = TestProject::empty("walk-streamed");
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("assets/data/pack/structure")).unwrap();
+        let large = dir.join("assets/data/pack/structure/large.nbt");
+        fs::File::create(&large)
+            .unwrap()
+            .set_len(STREAM_THRESHOLD)
+            .unwrap();
+        fs::write(dir.join("assets/pack.mcmeta"), "{}").unwrap();
+
+        let (mut output, mut streamed) =
+            read_folder_streamed(&dir.join("assets"), WalkOptions::default()).unwrap();
+        let path = "data/pack/structure/large.nbt";
+        assert_eq!(streamed.source_of(&output, path), Some(large.as_path()));
+        assert_eq!(streamed.source_of(&output, "pack.mcmeta"), None);
+        let placeholder = output.get_file(path).unwrap();
+        assert_eq!(
+            streamed.size_of(&output, path, placeholder),
+            STREAM_THRESHOLD as usize
+        );
+
+        output.place(dir.join("out")).unwrap();
+        streamed.copy_into(&output, &dir.join("out")).unwrap();
+        assert_eq!(
+            fs::metadata(dir.join("out").join(path)).unwrap().len(),
+            STREAM_THRESHOLD
+        );
+
+        output.add_file(path, VFile::Binary(vec![1]));
+        assert_eq!(streamed.overwritten(&output), [path]);
+
+        // a file deliberately emptied by a later stage is not copied from disk
+        output.add_file(path, VFile::Binary(Vec::new()));
+        streamed.replaced([path]);
+        assert_eq!(streamed.source_of(&output, path), None);
+    }
+
+    #[test]
+    fn test_name_to_str() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b"caf\xe9.shu");
+        assert!(name_to_str(name, Path::new("src"), false).is_err());
+        assert_eq!(
+            name_to_str(name, Path::new("src"), true).unwrap(),
+            "caf_.shu"
+        );
+        assert_eq!(
+            name_to_str(OsStr::new("main.shu"), Path::new("src"), false).unwrap(),
+            "main.shu"
+        );
+    }
+}
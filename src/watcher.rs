@@ -0,0 +1,1210 @@
+//! The watch loop of the `watch` subcommand as a library type.
+//!
+//! The loop can run on the current thread, be spawned on a background thread, returning a
+//! handle to trigger runs and stop the watcher programmatically, or be awaited as task of an async
+//! runtime next to other long running tasks with [`Watcher::run_async`].
+//!
+//! ```no_run
+//! use shulkerscript_cli::watcher::Watcher;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let handle = Watcher::builder("path/to/project")
+//!     .execute("build .")
+//!     .and_then("lint")
+//!     .initial(false)
+//!     .spawn()?;
+//!
+//! handle.trigger();
+//! handle.stop();
+//! handle.join()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    ffi::OsString,
+    fs, io, iter,
+    net::{SocketAddr, TcpListener as StdTcpListener},
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecursiveMode, Watcher as NotifyWatcher},
+    DebounceEventResult,
+};
+use path_absolutize::Absolutize;
+use sha2::{Digest as _, Sha256};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task, time,
+};
+
+use crate::{
+    alias,
+    cancel::CancellationToken,
+    cli::Args,
+    config::{LimitsConfig, ProjectConfig, WatchConfig},
+    defaults,
+    error::Error,
+    runtime,
+    subcommands::get_pack_config,
+    terminal_output::{print_error, print_info, print_plain, print_success, print_warning},
+    units,
+    util::{self, CommandLimits, CommandOutcome},
+};
+
+/// Builder for a [`Watcher`], created with [`Watcher::builder`].
+#[derive(Debug, Clone)]
+pub struct WatcherBuilder {
+    root: PathBuf,
+    debounce: Duration,
+    debounce_paths: Vec<(String, Duration)>,
+    compare_contents: bool,
+    paths: Vec<PathBuf>,
+    execute: Vec<String>,
+    no_execute: bool,
+    stages: Vec<String>,
+    shell: Vec<String>,
+    shell_timeout: Option<Duration>,
+    max_output: Option<usize>,
+    initial: bool,
+    once: bool,
+    until_success: bool,
+    runs: Option<usize>,
+    listen: Option<(SocketAddr, Option<String>)>,
+    cancel: CancellationToken,
+    strict_config: bool,
+}
+
+impl WatcherBuilder {
+    pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(2000);
+
+    /// Set the time to wait after the last change before running the commands.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Wait for the time after the last change of the paths matching the glob pattern, relative
+    /// to the project root, instead of the default debounce time.
+    ///
+    /// Takes precedence over the `debounce` table of the `[watch]` section, the first matching
+    /// pattern is used.
+    pub fn debounce_path(mut self, pattern: impl Into<String>, debounce: Duration) -> Self {
+        self.debounce_paths.push((pattern.into(), debounce));
+        self
+    }
+
+    /// Set whether changes are only reported if the contents of the files changed, enabled by
+    /// default.
+    ///
+    /// Skips the events of files that are touched or saved without changes and of temporary
+    /// files that no longer exist, e.g. of editors saving atomically.
+    pub fn compare_contents(mut self, compare_contents: bool) -> Self {
+        self.compare_contents = compare_contents;
+        self
+    }
+
+    /// Watch a path in addition to the default and configured ones.
+    pub fn watch_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Add a shulkerscript command run when changes are detected, replacing the `execute` field
+    /// of the `[watch]` section and the default `build .`.
+    pub fn execute(mut self, command: impl Into<String>) -> Self {
+        self.execute.push(command.into());
+        self
+    }
+
+    /// Do not run the shulkerscript commands.
+    pub fn no_execute(mut self, no_execute: bool) -> Self {
+        self.no_execute = no_execute;
+        self
+    }
+
+    /// Add a shulkerscript command run as stage after the commands succeeded.
+    pub fn and_then(mut self, command: impl Into<String>) -> Self {
+        self.stages.push(command.into());
+        self
+    }
+
+    /// Add a shell command run after the stages succeeded, replacing the `shell` field of the
+    /// `[watch]` section.
+    pub fn shell(mut self, command: impl Into<String>) -> Self {
+        self.shell.push(command.into());
+        self
+    }
+
+    /// Kill shell commands running longer than the timeout, overriding `timeout` of the
+    /// `[limits]` section.
+    pub fn shell_timeout(mut self, timeout: Duration) -> Self {
+        self.shell_timeout = Some(timeout);
+        self
+    }
+
+    /// Only print the first bytes of the stdout and stderr of shell commands, overriding
+    /// `max_output` of the `[limits]` section.
+    pub fn max_output(mut self, max_output: usize) -> Self {
+        self.max_output = Some(max_output);
+        self
+    }
+
+    /// Set whether the commands are run once when the watcher starts, enabled by default.
+    pub fn initial(mut self, initial: bool) -> Self {
+        self.initial = initial;
+        self
+    }
+
+    /// Set whether the watcher stops after the first run following a change or trigger.
+    pub fn once(mut self, once: bool) -> Self {
+        self.once = once;
+        self
+    }
+
+    /// Set whether the watcher stops after the first run in which all commands succeeded.
+    pub fn until_success(mut self, until_success: bool) -> Self {
+        self.until_success = until_success;
+        self
+    }
+
+    /// Stop the watcher after the number of runs, including the initial one.
+    pub fn runs(mut self, runs: usize) -> Self {
+        self.runs = Some(runs);
+        self
+    }
+
+    /// Whether the watcher stops on its own, reporting a summary and the outcome of the runs.
+    fn is_bounded(&self) -> bool {
+        self.once || self.until_success || self.runs.is_some()
+    }
+
+    /// Listen for `POST /trigger` HTTP requests on the address, requiring the token in the
+    /// `Authorization: Bearer <TOKEN>` header if given.
+    pub fn listen(mut self, address: SocketAddr, token: Option<String>) -> Self {
+        self.listen = Some((address, token));
+        self
+    }
+
+    /// Stop the watcher when the token is cancelled.
+    pub fn cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Fail instead of warning when the pack.toml file contains unknown keys.
+    pub fn strict_config(mut self, strict_config: bool) -> Self {
+        self.strict_config = strict_config;
+        self
+    }
+
+    /// Read the project config, parse the commands and bind the listener.
+    pub fn build(self) -> Result<Watcher> {
+        // event paths are absolute and the commands run relative to the root
+        let root = self.root.absolutize()?.to_path_buf();
+        let custom_paths = self
+            .paths
+            .iter()
+            .map(|path| path.absolutize().map(|path| path.to_path_buf()))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let config = get_pack_config(&root, self.strict_config)
+            .ok()
+            .map(|(conf, _)| conf);
+        let commands = WatchCommands::new(&self, &root, config.as_ref())?;
+
+        let listener = self
+            .listen
+            .clone()
+            .map(|(address, token)| {
+                StdTcpListener::bind(address)
+                    .and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+                    .inspect_err(|err| {
+                        print_error(format!("Failed to listen on {address}: {err}"));
+                    })
+                    .map(|listener| (listener, token))
+            })
+            .transpose()?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        Ok(Watcher {
+            root,
+            custom_paths,
+            config,
+            commands,
+            listener,
+            options: self,
+            tx,
+            rx,
+        })
+    }
+
+    /// Build the watcher and run it on a background thread.
+    pub fn spawn(self) -> Result<WatchHandle> {
+        self.build()?.spawn()
+    }
+}
+
+/// Watches a project for changes and runs commands when changes are detected.
+#[derive(Debug)]
+pub struct Watcher {
+    root: PathBuf,
+    custom_paths: Vec<PathBuf>,
+    config: Option<ProjectConfig>,
+    commands: WatchCommands,
+    listener: Option<(StdTcpListener, Option<String>)>,
+    options: WatcherBuilder,
+    tx: mpsc::UnboundedSender<WatchEvent>,
+    rx: mpsc::UnboundedReceiver<WatchEvent>,
+}
+
+impl Watcher {
+    /// Create a builder for a watcher of the project at the path.
+    pub fn builder(root: impl Into<PathBuf>) -> WatcherBuilder {
+        WatcherBuilder {
+            root: root.into(),
+            debounce: WatcherBuilder::DEFAULT_DEBOUNCE,
+            debounce_paths: Vec::new(),
+            compare_contents: true,
+            paths: Vec::new(),
+            execute: Vec::new(),
+            no_execute: false,
+            stages: Vec::new(),
+            shell: Vec::new(),
+            shell_timeout: None,
+            max_output: None,
+            initial: true,
+            once: false,
+            until_success: false,
+            runs: None,
+            listen: None,
+            cancel: CancellationToken::new(),
+            strict_config: false,
+        }
+    }
+
+    /// Get a controller to trigger runs and stop the watcher from other threads.
+    pub fn controller(&self) -> WatchController {
+        WatchController {
+            tx: self.tx.clone(),
+        }
+    }
+
+    /// Run the watcher on a background thread.
+    pub fn spawn(self) -> Result<WatchHandle> {
+        let controller = self.controller();
+        let thread = thread::Builder::new()
+            .name("watcher".to_string())
+            .spawn(move || self.run())?;
+        Ok(WatchHandle { controller, thread })
+    }
+
+    /// Run the watcher on the current thread until it is stopped.
+    ///
+    /// The commands are run in the project root, the working directory of the process is not
+    /// changed. If the watcher stops on its own (see [`WatcherBuilder::once`],
+    /// [`WatcherBuilder::until_success`] and [`WatcherBuilder::runs`]), a summary of the runs is
+    /// printed and an error is returned if the last run failed.
+    ///
+    /// # Errors
+    /// - If watching the files fails.
+    /// - If the last run of a watcher stopping on its own failed.
+    pub fn run(self) -> Result<()> {
+        runtime::block_on(self.run_async())
+    }
+
+    /// Run the watcher as task of a multi-threaded tokio runtime until it is stopped, see
+    /// [`Watcher::run`].
+    ///
+    /// The commands block the worker thread of the task while they run, other tasks are moved to
+    /// the remaining worker threads.
+    ///
+    /// # Errors
+    /// - If watching the files fails.
+    /// - If the last run of a watcher stopping on its own failed.
+    pub async fn run_async(mut self) -> Result<()> {
+        // whether the commands of each run succeeded
+        let mut outcomes = Vec::new();
+        if self.options.initial {
+            outcomes.push(self.run_commands("Running commands initially..."));
+            if self.is_done(&outcomes, false) {
+                return self.summarize(&outcomes);
+            }
+        } else {
+            print_info("Skipping initial commands.");
+        }
+
+        let cancel = self.options.cancel.clone();
+        let listener = match self.listener.take() {
+            Some((listener, token)) => {
+                let listener = TcpListener::from_std(listener)?;
+                if let Ok(address) = listener.local_addr() {
+                    print_info(format!(
+                        "Listening for triggers on http://{address}/trigger"
+                    ));
+                }
+                Some(tokio::spawn(listen(
+                    listener,
+                    token,
+                    self.tx.clone(),
+                    cancel.clone(),
+                )))
+            }
+            None => None,
+        };
+
+        let result = self.watch_changes(&cancel, &mut outcomes).await;
+        if let Some(listener) = listener {
+            listener.abort();
+        }
+        result?;
+
+        self.summarize(&outcomes)
+    }
+
+    /// Run the commands on changes and triggers until the watcher is stopped or done.
+    async fn watch_changes(
+        &mut self,
+        cancel: &CancellationToken,
+        outcomes: &mut Vec<bool>,
+    ) -> Result<()> {
+        // longer debounce times of paths are waited for in addition to the one of the debouncer
+        let debouncer_timeout = self
+            .debounce_patterns()
+            .into_iter()
+            .map(|(_, debounce)| debounce)
+            .fold(self.options.debounce, Duration::min);
+        let events_tx = self.tx.clone();
+        let mut debouncer = new_debouncer(debouncer_timeout, move |res: DebounceEventResult| {
+            let _ = events_tx.send(WatchEvent::Changes(res));
+        })?;
+
+        let mut watched = WatchedPaths::default();
+        watched.update(
+            debouncer.watcher(),
+            &self.root,
+            self.config.as_ref(),
+            &self.custom_paths,
+            false,
+        );
+        let mut hashes = self.options.compare_contents.then(|| {
+            let mut hashes = ContentHashes::default();
+            task::block_in_place(|| hashes.add_all(watched.paths()));
+            hashes
+        });
+        // the changed paths waiting for their longer debounce time
+        let mut pending = BTreeMap::<PathBuf, time::Instant>::new();
+
+        loop {
+            let next_due = pending.values().min().copied();
+            let event = tokio::select! {
+                event = self.rx.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                () = time::sleep_until(next_due.unwrap_or_else(time::Instant::now)), if next_due.is_some() => {
+                    WatchEvent::Changes(Ok(Vec::new()))
+                }
+                () = cancel.cancelled() => {
+                    print_info("Stopping watcher...");
+                    break;
+                }
+            };
+            let events = match event {
+                WatchEvent::Changes(Ok(events)) => events,
+                WatchEvent::Changes(Err(err)) => return Err(err.into()),
+                WatchEvent::Trigger(peer) => {
+                    let message = match peer {
+                        Some(peer) => format!("Run triggered by {peer}. Running commands..."),
+                        None => "Run triggered. Running commands...".to_string(),
+                    };
+                    outcomes.push(self.run_commands(&message));
+                    if self.is_done(outcomes, true) {
+                        break;
+                    }
+                    continue;
+                }
+                WatchEvent::Stop => {
+                    print_info("Stopping watcher...");
+                    break;
+                }
+            };
+
+            let ignore = self
+                .config
+                .as_ref()
+                .and_then(|config| config.watch.as_ref())
+                .map(|watch| watch.ignore.as_slice())
+                .unwrap_or_default();
+            let patterns = self.debounce_patterns();
+            let now = time::Instant::now();
+            for event in events
+                .iter()
+                .filter(|event| !is_ignored(&self.root, &event.path, ignore))
+            {
+                let debounce = debounce_of(&self.root, &event.path, &patterns)
+                    .unwrap_or(self.options.debounce);
+                pending.insert(
+                    event.path.clone(),
+                    now + debounce.saturating_sub(debouncer_timeout),
+                );
+            }
+            let due = pending
+                .iter()
+                .filter(|(_, due)| **due <= now)
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>();
+            let mut changed = Vec::new();
+            for path in due {
+                pending.remove(&path);
+                let is_changed = match &mut hashes {
+                    Some(hashes) => task::block_in_place(|| hashes.update(&path)),
+                    None => true,
+                };
+                if is_changed {
+                    changed.push(path);
+                }
+            }
+            if changed.is_empty() {
+                continue;
+            }
+
+            if changed
+                .iter()
+                .any(|path| watched.config_files.contains(path))
+            {
+                match get_pack_config(&self.root, self.options.strict_config) {
+                    Ok((new_config, _)) => {
+                        reload(
+                            &self.options,
+                            self.config.as_ref(),
+                            &new_config,
+                            &mut self.commands,
+                        );
+                        self.config = Some(new_config);
+                    }
+                    Err(_) => print_warning(
+                        "Failed to read pack.toml. Keeping the previous configuration.",
+                    ),
+                }
+                watched.update(
+                    debouncer.watcher(),
+                    &self.root,
+                    self.config.as_ref(),
+                    &self.custom_paths,
+                    true,
+                );
+                if let Some(hashes) = &mut hashes {
+                    task::block_in_place(|| hashes.add_all(watched.paths()));
+                }
+            }
+
+            outcomes.push(self.run_commands("Changes have been detected. Running commands..."));
+            if self.is_done(outcomes, true) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The debounce times of the paths given to the builder, followed by the ones of the config.
+    fn debounce_patterns(&self) -> Vec<(String, Duration)> {
+        let configured = self
+            .config
+            .as_ref()
+            .and_then(|config| config.watch.as_ref())
+            .map(|watch| watch.debounce.clone())
+            .unwrap_or_default();
+        self.options
+            .debounce_paths
+            .iter()
+            .cloned()
+            .chain(
+                configured
+                    .into_iter()
+                    .map(|(pattern, millis)| (pattern, Duration::from_millis(millis))),
+            )
+            .collect()
+    }
+
+    /// Run the commands without blocking the other tasks of the runtime, returns whether all of
+    /// them succeeded.
+    fn run_commands(&self, message: &str) -> bool {
+        task::block_in_place(|| self.commands.run(self.options.no_execute, message))
+    }
+
+    /// Whether the watcher stops after the runs with the outcomes.
+    fn is_done(&self, outcomes: &[bool], after_change: bool) -> bool {
+        (self.options.once && after_change)
+            || (self.options.until_success && outcomes.last() == Some(&true))
+            || self.options.runs.is_some_and(|runs| outcomes.len() >= runs)
+    }
+
+    /// Print a summary of the runs of a watcher stopping on its own and fail if the last run
+    /// failed.
+    fn summarize(&self, outcomes: &[bool]) -> Result<()> {
+        if !self.options.is_bounded() {
+            return Ok(());
+        }
+
+        let succeeded = outcomes.iter().filter(|&&success| success).count();
+        print_info(format!(
+            "Finished after {} run(s): {succeeded} succeeded, {} failed.",
+            outcomes.len(),
+            outcomes.len() - succeeded
+        ));
+        if outcomes.last() == Some(&false) || (self.options.until_success && succeeded == 0) {
+            return Err(Error::WatchRunError.into());
+        }
+        Ok(())
+    }
+}
+
+/// Triggers runs and stops a [`Watcher`] from other threads.
+#[derive(Debug, Clone)]
+pub struct WatchController {
+    tx: mpsc::UnboundedSender<WatchEvent>,
+}
+
+impl WatchController {
+    /// Run the commands as if changes were detected.
+    ///
+    /// Returns `false` if the watcher is no longer running.
+    pub fn trigger(&self) -> bool {
+        self.tx.send(WatchEvent::Trigger(None)).is_ok()
+    }
+
+    /// Stop the watcher after the currently running commands.
+    ///
+    /// Use the token passed to [`WatcherBuilder::cancellation`] to also cancel running builds.
+    /// Returns `false` if the watcher is no longer running.
+    pub fn stop(&self) -> bool {
+        self.tx.send(WatchEvent::Stop).is_ok()
+    }
+}
+
+/// Handle of a [`Watcher`] running on a background thread.
+#[derive(Debug)]
+pub struct WatchHandle {
+    controller: WatchController,
+    thread: JoinHandle<Result<()>>,
+}
+
+impl WatchHandle {
+    /// Get a controller of the watcher that can be sent to other threads.
+    pub fn controller(&self) -> WatchController {
+        self.controller.clone()
+    }
+
+    /// Run the commands as if changes were detected.
+    pub fn trigger(&self) -> bool {
+        self.controller.trigger()
+    }
+
+    /// Stop the watcher after the currently running commands.
+    pub fn stop(&self) -> bool {
+        self.controller.stop()
+    }
+
+    /// Check whether the watcher has stopped.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Wait for the watcher to stop.
+    pub fn join(self) -> Result<()> {
+        self.thread
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("The watcher thread panicked")))
+    }
+}
+
+/// An event received by the watch loop.
+#[derive(Debug)]
+enum WatchEvent {
+    /// Changes of the watched files.
+    Changes(DebounceEventResult),
+    /// A run triggered by a controller or an HTTP request from the address.
+    Trigger(Option<SocketAddr>),
+    /// A request to stop the watcher.
+    Stop,
+}
+
+/// Answer HTTP requests on the listener until the token is cancelled, sending a trigger for every
+/// authorized `POST /trigger` request.
+async fn listen(
+    listener: TcpListener,
+    token: Option<String>,
+    tx: mpsc::UnboundedSender<WatchEvent>,
+    cancel: CancellationToken,
+) {
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(_) => continue,
+            },
+            () = cancel.cancelled() => return,
+        };
+        let token = token.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let _ = time::timeout(
+                Duration::from_secs(5),
+                answer_trigger(stream, peer, token.as_deref(), &tx),
+            )
+            .await;
+        });
+    }
+}
+
+/// Read a single HTTP request from the stream and answer it with the status of the trigger.
+async fn answer_trigger(
+    stream: TcpStream,
+    peer: SocketAddr,
+    token: Option<&str>,
+    tx: &mpsc::UnboundedSender<WatchEvent>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut authorization = None;
+    let mut line = String::new();
+    while reader.read_line(&mut line).await? > 0 && !line.trim().is_empty() {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            }
+        }
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next(), parts.next());
+    let status = if target.and_then(|target| target.split('?').next()) != Some("/trigger") {
+        "404 Not Found"
+    } else if method != Some("POST") {
+        "405 Method Not Allowed"
+    } else if token.is_some_and(|token| {
+        authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            != Some(token)
+    }) {
+        print_warning(format!("Rejected unauthorized trigger from {peer}"));
+        "401 Unauthorized"
+    } else if tx.send(WatchEvent::Trigger(Some(peer))).is_err() {
+        "503 Service Unavailable"
+    } else {
+        "202 Accepted"
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{status}\n",
+        status.len() + 1
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// The paths currently watched for a single project.
+#[derive(Debug, Default)]
+struct WatchedPaths {
+    watched: BTreeSet<PathBuf>,
+    missing: BTreeSet<PathBuf>,
+    /// The pack.toml file and the config files it extends.
+    config_files: Vec<PathBuf>,
+}
+
+impl WatchedPaths {
+    /// The watched paths and config files.
+    fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.watched.iter().chain(&self.config_files)
+    }
+
+    /// Start watching the paths of the project that exist and are not watched yet and stop
+    /// watching the ones that are no longer configured.
+    ///
+    /// The `src`, `structures` and `assets-overlays` directories, `pack.png`, `pack.toml`, the
+    /// assets directory and the paths of the `[watch]` section are watched in addition to the
+    /// custom paths.
+    /// Missing custom and configured paths are reported once.
+    fn update(
+        &mut self,
+        watcher: &mut dyn NotifyWatcher,
+        root: &Path,
+        config: Option<&ProjectConfig>,
+        custom_paths: &[PathBuf],
+        reload: bool,
+    ) {
+        let mut paths = vec![
+            (root.join("src"), RecursiveMode::Recursive, false),
+            (root.join("pack.png"), RecursiveMode::NonRecursive, false),
+            (root.join("structures"), RecursiveMode::Recursive, false),
+            (
+                root.join(crate::overlay::SOURCE_DIR),
+                RecursiveMode::Recursive,
+                false,
+            ),
+        ];
+        if let Some(assets) = config
+            .and_then(|config| config.compiler.as_ref())
+            .and_then(|compiler| compiler.assets.as_ref())
+        {
+            paths.push((root.join(assets), RecursiveMode::Recursive, false));
+        }
+        if let Some(config) = config {
+            paths.extend(config.generate.iter().flat_map(|generator| {
+                [&generator.template, &generator.data]
+                    .map(|path| (root.join(path), RecursiveMode::NonRecursive, false))
+            }));
+        }
+        if let Some(watch) = config.and_then(|config| config.watch.as_ref()) {
+            paths.extend(
+                watch
+                    .paths
+                    .iter()
+                    .map(|path| (root.join(path), RecursiveMode::Recursive, true)),
+            );
+        }
+        paths.extend(
+            custom_paths
+                .iter()
+                .map(|path| (path.clone(), RecursiveMode::Recursive, true)),
+        );
+
+        // replacing the file (as many editors do when saving) ends the watch, so it is renewed
+        for config_file in &self.config_files {
+            let _ = watcher.unwatch(config_file);
+        }
+        let toml_path = root.join("pack.toml");
+        self.config_files = vec![toml_path.clone()];
+        self.config_files
+            .extend(ProjectConfig::extended_files(&toml_path));
+        for config_file in &self.config_files {
+            if watcher
+                .watch(config_file, RecursiveMode::NonRecursive)
+                .is_err()
+            {
+                print_warning(format!("Failed to watch {}", config_file.display()));
+            }
+        }
+
+        let stale = self
+            .watched
+            .iter()
+            .filter(|watched| !paths.iter().any(|(path, _, _)| path == *watched))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in stale {
+            let _ = watcher.unwatch(&path);
+            print_info(format!(
+                "Stopped watching {}",
+                path.strip_prefix(root).unwrap_or(&path).display()
+            ));
+            self.watched.remove(&path);
+        }
+
+        for (path, mode, required) in paths {
+            if self.watched.contains(&path) {
+                continue;
+            }
+            if !path.exists() {
+                if required && self.missing.insert(path.clone()) {
+                    print_warning(format!(
+                        "Path {} does not exist. Skipping...",
+                        path.display()
+                    ));
+                }
+                continue;
+            }
+            if watcher.watch(&path, mode).is_err() {
+                print_warning(format!("Failed to watch {}", path.display()));
+                continue;
+            }
+            if reload {
+                print_info(format!(
+                    "Started watching {}",
+                    path.strip_prefix(root).unwrap_or(&path).display()
+                ));
+            }
+            self.missing.remove(&path);
+            self.watched.insert(path);
+        }
+    }
+}
+
+/// The commands run when changes are detected.
+///
+/// The paths of the shulkerscript commands are resolved against the project root and the shell
+/// commands are run in it, the working directory of the process is not changed.
+#[derive(Debug)]
+struct WatchCommands {
+    root: PathBuf,
+    execute: Vec<String>,
+    parsed: Vec<Args>,
+    stages: Vec<(String, Args)>,
+    shell: Vec<String>,
+    limits: CommandLimits,
+}
+
+impl WatchCommands {
+    /// Get the commands from the builder, falling back to the `[watch]` section of the config.
+    fn new(
+        args: &WatcherBuilder,
+        root: &Path,
+        config: Option<&ProjectConfig>,
+    ) -> Result<Self, clap::Error> {
+        let watch_config = config.and_then(|config| config.watch.as_ref());
+        let execute = if !args.execute.is_empty() {
+            args.execute.clone()
+        } else {
+            watch_config
+                .map(|watch| watch.execute.clone())
+                .filter(|execute| !execute.is_empty())
+                .unwrap_or_else(|| vec![WatchConfig::DEFAULT_EXECUTE.to_string()])
+        };
+        let shell = if !args.shell.is_empty() {
+            args.shell.clone()
+        } else {
+            watch_config
+                .map(|watch| watch.shell.clone())
+                .unwrap_or_default()
+        };
+
+        let parsed = execute
+            .iter()
+            .map(|cmd| parse_command(cmd, root))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        let stages = parse_stages(&args.stages, root)?;
+        let limits = LimitsConfig::command_limits(
+            config.and_then(|config| config.limits.as_ref()),
+            args.shell_timeout,
+            args.max_output,
+        );
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            execute,
+            parsed,
+            stages,
+            shell,
+            limits,
+        })
+    }
+
+    /// Run the commands, returns whether all of them succeeded.
+    fn run(&self, no_execute: bool, message: &str) -> bool {
+        print_info(message);
+        if !no_execute {
+            for (index, args) in self.parsed.iter().enumerate() {
+                if args.run().is_err() {
+                    print_error(format!("Error running command: {}", index + 1));
+                    print_error("Not running further commands.");
+                    return false;
+                }
+            }
+        }
+        run_stages(&self.stages) && run_shell_cmds(&self.shell, &self.root, &self.limits)
+    }
+}
+
+/// Apply a changed config to the commands and print what changed.
+fn reload(
+    args: &WatcherBuilder,
+    old_config: Option<&ProjectConfig>,
+    new_config: &ProjectConfig,
+    commands: &mut WatchCommands,
+) {
+    let changes = old_config
+        .map(|old_config| config_diff(old_config, new_config))
+        .unwrap_or_default();
+    if changes.is_empty() {
+        print_info("Reloaded pack.toml without changes.");
+    } else {
+        print_info("Reloaded pack.toml with changes:");
+        for change in changes {
+            print_plain(format!("  {change}"));
+        }
+    }
+
+    match WatchCommands::new(args, &commands.root, Some(new_config)) {
+        Ok(new_commands) => {
+            if new_commands.execute != commands.execute
+                || new_commands.shell != commands.shell
+                || new_commands.limits != commands.limits
+            {
+                print_info("Using the changed commands from now on.");
+            }
+            *commands = new_commands;
+        }
+        Err(err) => {
+            print_error(format!("Invalid command in the [watch] section: {err}"));
+            print_warning("Keeping the previous commands.");
+        }
+    }
+}
+
+/// Get the changed keys of two configs, formatted as `+ key = value`, `- key = value` and
+/// `~ key: old -> new`.
+fn config_diff(old: &ProjectConfig, new: &ProjectConfig) -> Vec<String> {
+    fn flatten(value: &toml::Value, prefix: &str, entries: &mut BTreeMap<String, String>) {
+        match value {
+            toml::Value::Table(table) if !table.is_empty() || prefix.is_empty() => {
+                for (key, value) in table {
+                    let key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    flatten(value, &key, entries);
+                }
+            }
+            value => {
+                entries.insert(prefix.to_string(), value.to_string());
+            }
+        }
+    }
+
+    let entries = |config: &ProjectConfig| {
+        let mut entries = BTreeMap::new();
+        if let Ok(value) = toml::Value::try_from(config) {
+            flatten(&value, "", &mut entries);
+        }
+        entries
+    };
+    let (old, new) = (entries(old), entries(new));
+
+    let mut changes = Vec::new();
+    for (key, old_value) in &old {
+        match new.get(key) {
+            None => changes.push(format!("- {key} = {old_value}").red().to_string()),
+            Some(new_value) if new_value != old_value => changes.push(
+                format!("~ {key}: {old_value} -> {new_value}")
+                    .yellow()
+                    .to_string(),
+            ),
+            Some(_) => {}
+        }
+    }
+    for (key, new_value) in &new {
+        if !old.contains_key(key) {
+            changes.push(format!("+ {key} = {new_value}").green().to_string());
+        }
+    }
+    changes
+}
+
+/// The debounce time of the first pattern matching the path, relative to the root.
+fn debounce_of(root: &Path, path: &Path, patterns: &[(String, Duration)]) -> Option<Duration> {
+    let relative = path.strip_prefix(root).ok()?.to_string_lossy();
+    patterns
+        .iter()
+        .find(|(pattern, _)| util::matches_glob(pattern, &relative))
+        .map(|(_, debounce)| *debounce)
+}
+
+/// The hashes of the contents of the watched files, to skip the events of files whose contents
+/// did not change.
+#[derive(Debug, Default)]
+pub(crate) struct ContentHashes {
+    hashes: BTreeMap<PathBuf, [u8; 32]>,
+}
+
+impl ContentHashes {
+    /// Hash the files at the paths, recursing into folders.
+    pub(crate) fn add_all<'a>(&mut self, paths: impl IntoIterator<Item = &'a PathBuf>) {
+        for path in paths {
+            self.add(path);
+        }
+    }
+
+    fn add(&mut self, path: &Path) {
+        if path.is_dir() {
+            for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+                self.add(&entry.path());
+            }
+        } else if let Some(hash) = hash_file(path) {
+            self.hashes.insert(path.to_path_buf(), hash);
+        }
+    }
+
+    /// Update the hash of the changed path, returns whether its contents changed.
+    ///
+    /// Folders are never changed themselves, as the changes of their files are reported
+    /// separately. Paths that no longer exist are changed if they were hashed before, e.g.
+    /// unlike temporary files.
+    pub(crate) fn update(&mut self, path: &Path) -> bool {
+        if path.is_dir() {
+            return false;
+        }
+        match hash_file(path) {
+            Some(hash) => self.hashes.insert(path.to_path_buf(), hash) != Some(hash),
+            None => {
+                let count = self.hashes.len();
+                self.hashes.retain(|hashed, _| !hashed.starts_with(path));
+                self.hashes.len() != count
+            }
+        }
+    }
+}
+
+fn hash_file(path: &Path) -> Option<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path).ok()?, &mut hasher).ok()?;
+    Some(hasher.finalize().into())
+}
+
+/// Check whether a changed path matches one of the ignore patterns of the `[watch]` section.
+fn is_ignored(root: &Path, path: &Path, ignore: &[String]) -> bool {
+    path.strip_prefix(root).is_ok_and(|relative| {
+        let relative = relative.to_string_lossy();
+        ignore
+            .iter()
+            .any(|pattern| util::matches_glob(pattern, &relative))
+    })
+}
+
+/// Parse a shulkerscript command line without the program name, resolving its relative paths
+/// against the directory.
+///
+/// Aliases and defaults are taken from the targeted project, an alias can expand to multiple
+/// commands.
+fn parse_command(cmd: &str, dir: &Path) -> Result<Vec<Args>, clap::Error> {
+    let prog_name = env::args()
+        .next()
+        .unwrap_or(env!("CARGO_PKG_NAME").to_string());
+    let command = iter::once(prog_name.as_str())
+        .chain(cmd.split_whitespace())
+        .map(OsString::from)
+        .collect();
+    let commands = alias::expand_args(command, dir)
+        .map_err(|err| clap::Error::raw(clap::error::ErrorKind::InvalidSubcommand, err))?;
+
+    commands
+        .into_iter()
+        .map(|command| {
+            let mut args = Args::try_parse_from(defaults::apply(command, dir))?;
+            args.resolve_paths(dir);
+            Ok(args)
+        })
+        .collect()
+}
+
+pub(crate) fn parse_stages(
+    stages: &[String],
+    dir: &Path,
+) -> Result<Vec<(String, Args)>, clap::Error> {
+    let mut parsed = Vec::new();
+    for stage in stages {
+        for args in parse_command(stage, dir)? {
+            parsed.push((stage.clone(), args));
+        }
+    }
+    Ok(parsed)
+}
+
+/// Run the stages one after another, stopping at the first failing one.
+///
+/// Returns whether all stages succeeded.
+pub(crate) fn run_stages(stages: &[(String, Args)]) -> bool {
+    for (index, (name, args)) in stages.iter().enumerate() {
+        print_info(format!(
+            "Running stage {}/{}: {name}",
+            index + 1,
+            stages.len()
+        ));
+        let start = Instant::now();
+        let result = args.run();
+        let elapsed = units::format_duration(start.elapsed());
+        if result.is_err() {
+            print_error(format!("Stage `{name}` failed after {elapsed}"));
+            let skipped = stages.len() - index - 1;
+            if skipped > 0 {
+                print_error(format!("Skipping the remaining {skipped} stage(s)."));
+            }
+            return false;
+        }
+        print_success(format!("Stage `{name}` passed in {elapsed}"));
+    }
+    true
+}
+
+/// Run the shell commands in the directory one after another, stopping at the first failing one.
+///
+/// Returns whether all commands succeeded.
+pub(crate) fn run_shell_cmds(shell_cmds: &[String], dir: &Path, limits: &CommandLimits) -> bool {
+    for (index, cmd) in shell_cmds.iter().enumerate() {
+        let mut command = util::shell_command_group(cmd);
+        command.current_dir(dir);
+        let outcome = util::run_limited(&mut command, limits);
+        match outcome {
+            Ok(CommandOutcome::Exited(status)) if !status.success() => {
+                print_error(format!(
+                    "Shell command {} exited unsuccessfully with status code {}",
+                    index + 1,
+                    status.code().unwrap_or(1)
+                ));
+                print_error("Not running further shell commands.");
+                return false;
+            }
+            Ok(CommandOutcome::TimedOut(elapsed)) => {
+                print_error(format!(
+                    "Shell command {} timed out after {} and was killed",
+                    index + 1,
+                    units::format_duration(elapsed)
+                ));
+                print_error("Not running further shell commands.");
+                return false;
+            }
+            Ok(_) => {}
+            Err(_) => {
+                print_error(format!("Error running shell command: {}", index + 1));
+                print_error("Not running further shell commands.");
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_diff() {
+        let old = toml::from_str::<ProjectConfig>(
+            "[pack]\nname = \"a\"\ndescription = \"\"\nformat = 48\nversion = \"1\"\n[compiler]\noptimize = true\n[watch]\npaths = [\"docs\"]",
+        )
+        .unwrap();
+        let new = toml::from_str::<ProjectConfig>(
+            "[pack]\nname = \"b\"\ndescription = \"\"\nformat = 48\nversion = \"1\"\n[compiler]\nassets = \"assets\"\n[watch]\npaths = [\"docs\"]",
+        )
+        .unwrap();
+
+        let changes = config_diff(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert!(changes[0].contains("- compiler.optimize = true"));
+        assert!(changes[1].contains("~ pack.name: \"a\" -> \"b\""));
+        assert!(changes[2].contains("+ compiler.assets = \"assets\""));
+        assert!(config_diff(&new, &new).is_empty());
+    }
+}
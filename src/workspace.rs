@@ -0,0 +1,501 @@
+//! Workspaces of multiple projects that are built together.
+
+use std::{
+    collections::VecDeque,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use path_absolutize::Absolutize as _;
+
+use crate::{
+    cli::GlobalOptions,
+    config::{ProjectConfig, WorkspaceManifest},
+    error::Error,
+    subcommands::get_pack_config,
+    terminal_output::print_error,
+    util,
+};
+
+/// A workspace and its member projects.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    /// The root directory of the workspace.
+    pub root: PathBuf,
+    /// The members of the workspace, ordered so that every member comes after its dependencies.
+    pub members: Vec<WorkspaceMember>,
+}
+
+/// A project that is a member of a workspace.
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+    /// The root directory of the member project.
+    pub path: PathBuf,
+    /// The config of the member project.
+    pub config: ProjectConfig,
+    /// The indices of the members this member depends on.
+    pub dependencies: Vec<usize>,
+}
+
+impl WorkspaceMember {
+    /// The name of the member project.
+    pub fn name(&self) -> &str {
+        &self.config.pack.name
+    }
+}
+
+/// The outcome of running a task for a workspace member.
+#[derive(Debug)]
+pub enum MemberOutcome<T> {
+    /// The task finished successfully.
+    Finished(T, Duration),
+    /// The task failed.
+    Failed(anyhow::Error, Duration),
+    /// The task was not run, because the task of the named dependency did not succeed.
+    Skipped(String),
+}
+
+impl<T> MemberOutcome<T> {
+    /// Whether the task finished successfully.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Finished(..))
+    }
+}
+
+impl Workspace {
+    /// Find the root of the workspace containing the given path.
+    ///
+    /// The root is the closest ancestor with a pack.toml file defining a `[workspace]` section,
+    /// only the path itself is checked without `discover`.
+    pub fn find_root<P>(path: P, discover: bool) -> Option<PathBuf>
+    where
+        P: AsRef<Path>,
+    {
+        path.as_ref()
+            .absolutize()
+            .ok()?
+            .ancestors()
+            .take(util::discovery_depth(discover))
+            .find(|dir| {
+                read_manifest(&dir.join("pack.toml")).is_some_and(|(m, _)| m.workspace.is_some())
+            })
+            .map(Path::to_path_buf)
+    }
+
+    /// Discover the workspace containing the given path and load all of its members.
+    ///
+    /// The parent directories of the path are only searched without `--no-discover` and the
+    /// configs of the members are loaded with `--strict-config` if given.
+    ///
+    /// # Errors
+    /// - If no workspace contains the path.
+    /// - If a member cannot be loaded.
+    /// - If members depend on each other in a cycle.
+    pub fn discover<P>(path: P, options: &GlobalOptions) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let Some(root) = Self::find_root(path, options.discover()) else {
+            print_error(format!(
+                "No workspace found at {}. Add a [workspace] section with the members to the pack.toml file.",
+                path.display()
+            ));
+            return Err(Error::NoWorkspaceError(path.to_path_buf()))?;
+        };
+        let (manifest, is_project) = read_manifest(&root.join("pack.toml"))
+            .ok_or_else(|| Error::NoWorkspaceError(root.clone()))?;
+        let config = manifest.workspace.unwrap_or_default();
+
+        let mut member_paths = Vec::new();
+        if is_project {
+            member_paths.push(root.clone());
+        }
+        for member in &config.members {
+            match member.strip_suffix('*') {
+                Some(parent) => {
+                    let mut dirs = fs::read_dir(root.join(parent))?
+                        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                        .filter(|dir| dir.join("pack.toml").is_file())
+                        .collect::<Vec<_>>();
+                    dirs.sort();
+                    member_paths.extend(dirs);
+                }
+                None => member_paths.push(root.join(member)),
+            }
+        }
+
+        let mut members = Vec::<WorkspaceMember>::new();
+        let mut paths = Vec::<PathBuf>::new();
+        for member_path in member_paths {
+            let member_path = member_path.absolutize()?.into_owned();
+            if paths.contains(&member_path) {
+                continue;
+            }
+            let (config, _) = get_pack_config(&member_path, options.strict_config)?;
+            paths.push(member_path.clone());
+            members.push(WorkspaceMember {
+                path: member_path,
+                config,
+                dependencies: Vec::new(),
+            });
+        }
+
+        for member in &mut members {
+            for dependency in member.config.dependencies.values() {
+                let Some(dependency_path) = &dependency.path else {
+                    continue;
+                };
+                let dependency_path = member.path.join(dependency_path).absolutize()?.into_owned();
+                if let Some(index) = paths.iter().position(|p| p == &dependency_path) {
+                    member.dependencies.push(index);
+                }
+            }
+        }
+
+        Ok(Self {
+            root,
+            members: sort_members(members)?,
+        })
+    }
+
+    /// Get the index of the member whose directory contains the given path.
+    pub fn member_containing(&self, path: &Path) -> Option<usize> {
+        self.members
+            .iter()
+            .enumerate()
+            .filter(|(_, member)| path.starts_with(&member.path))
+            .max_by_key(|(_, member)| member.path.components().count())
+            .map(|(index, _)| index)
+    }
+
+    /// Get the workspace restricted to the given members and all members depending on them.
+    pub fn affected_by(&self, changed: &[usize]) -> Self {
+        let mut included = vec![false; self.members.len()];
+        for &index in changed {
+            included[index] = true;
+        }
+        // dependents always come after their dependencies
+        for (index, member) in self.members.iter().enumerate() {
+            if member
+                .dependencies
+                .iter()
+                .any(|&dependency| included[dependency])
+            {
+                included[index] = true;
+            }
+        }
+
+        let mut new_index = vec![None; self.members.len()];
+        let mut members = Vec::new();
+        for (index, member) in self.members.iter().enumerate() {
+            if included[index] {
+                new_index[index] = Some(members.len());
+                members.push(WorkspaceMember {
+                    dependencies: member
+                        .dependencies
+                        .iter()
+                        .filter_map(|&dependency| new_index[dependency])
+                        .collect(),
+                    ..member.clone()
+                });
+            }
+        }
+
+        Self {
+            root: self.root.clone(),
+            members,
+        }
+    }
+
+    /// Run a task for every member on up to `jobs` threads.
+    ///
+    /// A member is only started after the tasks of all of its dependencies finished successfully,
+    /// otherwise it is skipped. A panicking task fails its member instead of stopping the other
+    /// threads. The outcomes are returned in the order of the members.
+    pub fn run_parallel<T, F>(&self, jobs: usize, task: F) -> Vec<MemberOutcome<T>>
+    where
+        T: Send,
+        F: Fn(&WorkspaceMember) -> Result<T> + Sync,
+    {
+        let count = self.members.len();
+        let state = Mutex::new(SchedulerState {
+            started: vec![false; count],
+            outcomes: (0..count).map(|_| None).collect::<Vec<_>>(),
+        });
+        let changed = Condvar::new();
+
+        thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, count.max(1)) {
+                scope.spawn(|| loop {
+                    let index = {
+                        let mut state = state.lock().unwrap();
+                        loop {
+                            state.skip_blocked(&self.members);
+                            if state.outcomes.iter().all(Option::is_some) {
+                                changed.notify_all();
+                                return;
+                            }
+                            if let Some(index) = state.next_ready(&self.members) {
+                                state.started[index] = true;
+                                break index;
+                            }
+                            state = changed.wait(state).unwrap();
+                        }
+                    };
+
+                    let member = &self.members[index];
+                    let start = Instant::now();
+                    let outcome = match panic::catch_unwind(AssertUnwindSafe(|| task(member))) {
+                        Ok(Ok(value)) => MemberOutcome::Finished(value, start.elapsed()),
+                        Ok(Err(err)) => MemberOutcome::Failed(err, start.elapsed()),
+                        Err(payload) => {
+                            let message = payload
+                                .downcast_ref::<&str>()
+                                .map(ToString::to_string)
+                                .or_else(|| payload.downcast_ref::<String>().cloned())
+                                .unwrap_or_default();
+                            MemberOutcome::Failed(
+                                Error::MemberPanicError(member.name().to_string(), message).into(),
+                                start.elapsed(),
+                            )
+                        }
+                    };
+
+                    state.lock().unwrap().outcomes[index] = Some(outcome);
+                    changed.notify_all();
+                });
+            }
+        });
+
+        state
+            .into_inner()
+            .unwrap()
+            .outcomes
+            .into_iter()
+            .map(|outcome| outcome.expect("all members are finished or skipped"))
+            .collect()
+    }
+}
+
+struct SchedulerState<T> {
+    started: Vec<bool>,
+    outcomes: Vec<Option<MemberOutcome<T>>>,
+}
+
+impl<T> SchedulerState<T> {
+    /// Get the next member that is not started yet and whose dependencies all finished.
+    fn next_ready(&self, members: &[WorkspaceMember]) -> Option<usize> {
+        (0..members.len()).find(|&index| {
+            !self.started[index]
+                && members[index].dependencies.iter().all(|&dependency| {
+                    self.outcomes[dependency]
+                        .as_ref()
+                        .is_some_and(MemberOutcome::is_finished)
+                })
+        })
+    }
+
+    /// Mark all members as skipped that depend on a member that failed or was skipped.
+    fn skip_blocked(&mut self, members: &[WorkspaceMember]) {
+        for (index, member) in members.iter().enumerate() {
+            if self.started[index] {
+                continue;
+            }
+            let blocking = member.dependencies.iter().find(|&&dependency| {
+                self.outcomes[dependency]
+                    .as_ref()
+                    .is_some_and(|outcome| !outcome.is_finished())
+            });
+            if let Some(&blocking) = blocking {
+                self.started[index] = true;
+                self.outcomes[index] =
+                    Some(MemberOutcome::Skipped(members[blocking].name().to_string()));
+            }
+        }
+    }
+}
+
+/// Read the workspace manifest of a pack.toml file and whether it also defines a project.
+fn read_manifest(toml_path: &Path) -> Option<(WorkspaceManifest, bool)> {
+    let content = fs::read_to_string(toml_path).ok()?;
+    let table = toml::from_str::<toml::Table>(&content).ok()?;
+    let is_project = table.contains_key("pack");
+    let manifest = table.try_into::<WorkspaceManifest>().ok()?;
+    Some((manifest, is_project))
+}
+
+/// Sort the members so that every member comes after its dependencies.
+///
+/// # Errors
+/// - If members depend on each other in a cycle.
+fn sort_members(members: Vec<WorkspaceMember>) -> Result<Vec<WorkspaceMember>> {
+    let count = members.len();
+    let mut remaining_dependencies = members
+        .iter()
+        .map(|member| member.dependencies.len())
+        .collect::<Vec<_>>();
+    let mut queue = (0..count)
+        .filter(|&index| remaining_dependencies[index] == 0)
+        .collect::<VecDeque<_>>();
+
+    let mut order = Vec::with_capacity(count);
+    while let Some(index) = queue.pop_front() {
+        order.push(index);
+        for (dependent, member) in members.iter().enumerate() {
+            for _ in member.dependencies.iter().filter(|&&d| d == index) {
+                remaining_dependencies[dependent] -= 1;
+                if remaining_dependencies[dependent] == 0 {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+    }
+
+    if order.len() < count {
+        let cyclic = (0..count)
+            .filter(|index| !order.contains(index))
+            .map(|index| members[index].name().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        print_error(format!(
+            "The workspace members {cyclic} depend on each other in a cycle."
+        ));
+        Err(Error::CyclicWorkspaceDependencyError(cyclic))?;
+    }
+
+    let mut new_index = vec![0; count];
+    for (position, &index) in order.iter().enumerate() {
+        new_index[index] = position;
+    }
+
+    let mut members = members.into_iter().map(Some).collect::<Vec<_>>();
+    Ok(order
+        .iter()
+        .map(|&index| {
+            let mut member = members[index].take().expect("every member is placed once");
+            for dependency in &mut member.dependencies {
+                *dependency = new_index[*dependency];
+            }
+            member
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Create a member with the name and the indices of its dependencies.
+    fn member(name: &str, dependencies: &[usize]) -> WorkspaceMember {
+        let mut config = ProjectConfig::default();
+        config.pack.name = name.to_string();
+        WorkspaceMember {
+            path: PathBuf::from(name),
+            config,
+            dependencies: dependencies.to_vec(),
+        }
+    }
+
+    fn workspace(members: Vec<WorkspaceMember>) -> Workspace {
+        Workspace {
+            root: PathBuf::from("."),
+            members,
+        }
+    }
+
+    fn names(members: &[WorkspaceMember]) -> Vec<&str> {
+        members.iter().map(WorkspaceMember::name).collect()
+    }
+
+    #[test]
+    fn test_sort_members() {
+        let sorted = sort_members(vec![
+            member("app", &[1, 2]),
+            member("lib", &[2]),
+            member("core", &[]),
+        ])
+        .unwrap();
+        assert_eq!(names(&sorted), ["core", "lib", "app"]);
+        assert_eq!(sorted[1].dependencies, [0]);
+        assert_eq!(sorted[2].dependencies, [1, 0]);
+
+        assert!(
+            sort_members(vec![member("a", &[1]), member("b", &[0]), member("c", &[])]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_affected_by() {
+        let workspace = workspace(vec![
+            member("core", &[]),
+            member("lib", &[0]),
+            member("other", &[]),
+            member("app", &[1, 2]),
+        ]);
+
+        let affected = workspace.affected_by(&[1]);
+        assert_eq!(names(&affected.members), ["lib", "app"]);
+        assert_eq!(affected.members[0].dependencies, Vec::<usize>::new());
+        assert_eq!(affected.members[1].dependencies, [0]);
+
+        assert_eq!(names(&workspace.affected_by(&[0]).members).len(), 3);
+        assert!(workspace.affected_by(&[]).members.is_empty());
+    }
+
+    #[test]
+    fn test_run_parallel_skips_dependents() {
+        let workspace = workspace(vec![
+            member("core", &[]),
+            member("lib", &[0]),
+            member("app", &[1]),
+            member("other", &[]),
+        ]);
+
+        let outcomes = workspace.run_parallel(2, |member| match member.name() {
+            "core" => anyhow::bail!("failed"),
+            "other" => panic!("panicked"),
+            _ => Ok(()),
+        });
+        assert!(matches!(outcomes[0], MemberOutcome::Failed(..)));
+        assert!(matches!(&outcomes[1], MemberOutcome::Skipped(name) if name == "core"));
+        assert!(matches!(&outcomes[2], MemberOutcome::Skipped(name) if name == "lib"));
+        assert!(
+            matches!(&outcomes[3], MemberOutcome::Failed(err, _) if err.to_string().contains("panicked"))
+        );
+    }
+
+    #[test]
+    fn test_run_parallel_order() {
+        let workspace = workspace(
+            (0..8)
+                .map(|index| member(&index.to_string(), &[]))
+                .collect(),
+        );
+        let running = AtomicUsize::new(0);
+
+        let outcomes = workspace.run_parallel(4, |member| {
+            running.fetch_add(1, Ordering::SeqCst);
+            // later members finish first
+            let index = member.name().parse::<u64>()?;
+            thread::sleep(Duration::from_millis(40 - 5 * index));
+            Ok(member.name().to_string())
+        });
+        assert_eq!(running.load(Ordering::SeqCst), 8);
+        let names = outcomes
+            .iter()
+            .map(|outcome| match outcome {
+                MemberOutcome::Finished(name, _) => name.as_str(),
+                _ => panic!("unexpected outcome {outcome:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["0", "1", "2", "3", "4", "5", "6", "7"]);
+    }
+}
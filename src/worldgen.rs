@@ -0,0 +1,274 @@
+//! Validation of the worldgen files of the output, e.g. dimensions, biomes and noise settings.
+//!
+//! Worldgen files change more often between Minecraft versions than other files of datapacks and
+//! invalid ones are only reported in the log of the game when loading a world. The files are
+//! checked for the fields required by the pack format and fields that were removed or renamed in
+//! it.
+
+use std::fmt::Display;
+
+use shulkerscript::shulkerbox::virtual_fs::{VFile, VFolder};
+
+/// The fields of a type of worldgen files that depend on the pack format.
+///
+/// Nested fields are separated by dots.
+struct Schema {
+    /// The folder of the files in the namespace folder.
+    folder: &'static str,
+    /// The fields and the pack format since which they are required.
+    required: &'static [(&'static str, u8)],
+    /// The fields, the pack format in which they were removed and their replacement.
+    removed: &'static [(&'static str, u8, Option<&'static str>)],
+}
+
+const SCHEMAS: &[Schema] = &[
+    Schema {
+        folder: "dimension",
+        required: &[("type", 0), ("generator", 0), ("generator.type", 0)],
+        removed: &[],
+    },
+    Schema {
+        folder: "dimension_type",
+        required: &[
+            ("ultrawarm", 0),
+            ("natural", 0),
+            ("coordinate_scale", 0),
+            ("has_skylight", 0),
+            ("has_ceiling", 0),
+            ("ambient_light", 0),
+            ("piglin_safe", 0),
+            ("bed_works", 0),
+            ("respawn_anchor_works", 0),
+            ("has_raids", 0),
+            ("logical_height", 0),
+            ("infiniburn", 0),
+            ("min_y", 8),
+            ("height", 8),
+            ("monster_spawn_light_level", 10),
+            ("monster_spawn_block_light_limit", 10),
+        ],
+        removed: &[],
+    },
+    Schema {
+        folder: "worldgen/biome",
+        required: &[
+            ("temperature", 0),
+            ("downfall", 0),
+            ("effects", 0),
+            ("spawners", 0),
+            ("spawn_costs", 0),
+            ("carvers", 0),
+            ("features", 0),
+            ("has_precipitation", 12),
+        ],
+        removed: &[
+            ("depth", 8, None),
+            ("scale", 8, None),
+            ("category", 10, None),
+            ("precipitation", 12, Some("has_precipitation")),
+            ("carvers.air", 57, Some("carvers")),
+            ("carvers.liquid", 57, Some("carvers")),
+        ],
+    },
+    Schema {
+        folder: "worldgen/noise_settings",
+        required: &[
+            ("sea_level", 0),
+            ("disable_mob_generation", 0),
+            ("default_block", 0),
+            ("default_fluid", 0),
+            ("noise", 0),
+            ("surface_rule", 0),
+            ("aquifers_enabled", 8),
+            ("ore_veins_enabled", 8),
+            ("legacy_random_source", 9),
+            ("noise_router", 9),
+            ("spawn_target", 10),
+        ],
+        removed: &[
+            ("structures", 9, None),
+            ("noise_caves_enabled", 9, None),
+            ("noodle_caves_enabled", 9, None),
+        ],
+    },
+];
+
+/// A problem of a worldgen file in the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldgenIssue {
+    /// The path of the affected file in the output.
+    pub output_path: String,
+    pub kind: WorldgenIssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorldgenIssueKind {
+    /// The file is not a JSON object, with the parse error.
+    InvalidJson(String),
+    /// A field required by the pack format is missing.
+    MissingField(&'static str),
+    /// A field is no longer read in the pack format, with the field replacing it.
+    RemovedField {
+        field: &'static str,
+        replacement: Option<&'static str>,
+    },
+}
+
+impl WorldgenIssue {
+    /// Whether the file fails to load in game, as opposed to fields that are ignored.
+    pub fn is_error(&self) -> bool {
+        !matches!(self.kind, WorldgenIssueKind::RemovedField { .. })
+    }
+}
+
+impl Display for WorldgenIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            WorldgenIssueKind::InvalidJson(err) => {
+                write!(f, "Invalid worldgen file {}: {err}", self.output_path)
+            }
+            WorldgenIssueKind::MissingField(field) => write!(
+                f,
+                "Missing field \"{field}\" in {} required by the pack format",
+                self.output_path
+            ),
+            WorldgenIssueKind::RemovedField {
+                field,
+                replacement: Some(replacement),
+            } => write!(
+                f,
+                "Field \"{field}\" in {} was replaced by \"{replacement}\" in the pack format",
+                self.output_path
+            ),
+            WorldgenIssueKind::RemovedField {
+                field,
+                replacement: None,
+            } => write!(
+                f,
+                "Field \"{field}\" in {} was removed in the pack format and is ignored",
+                self.output_path
+            ),
+        }
+    }
+}
+
+/// Check the dimensions, dimension types, biomes and noise settings in the `data` folder of the
+/// output against the pack format.
+pub fn validate(folder: &VFolder, pack_format: u8) -> Vec<WorldgenIssue> {
+    let mut issues = Vec::new();
+
+    let Some(data) = folder.get_folder("data") else {
+        return issues;
+    };
+    let mut namespaces = data.get_folders().iter().collect::<Vec<_>>();
+    namespaces.sort_by_key(|(name, _)| name.as_str());
+
+    for (namespace, namespace_folder) in namespaces {
+        for schema in SCHEMAS {
+            let Some(schema_folder) = schema
+                .folder
+                .split('/')
+                .try_fold(namespace_folder, |folder, name| folder.get_folder(name))
+            else {
+                continue;
+            };
+            let mut files = schema_folder.flatten();
+            files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (path, file) in files {
+                if !path.ends_with(".json") {
+                    continue;
+                }
+                let output_path = format!("data/{namespace}/{}/{path}", schema.folder);
+                issues.extend(
+                    validate_file(schema, file, pack_format)
+                        .into_iter()
+                        .map(|kind| WorldgenIssue {
+                            output_path: output_path.clone(),
+                            kind,
+                        }),
+                );
+            }
+        }
+    }
+
+    issues
+}
+
+fn validate_file(schema: &Schema, file: &VFile, pack_format: u8) -> Vec<WorldgenIssueKind> {
+    let json = match serde_json::from_slice::<serde_json::Value>(file.as_bytes()) {
+        Ok(json) if json.is_object() => json,
+        Ok(_) => return vec![WorldgenIssueKind::InvalidJson("not an object".to_string())],
+        Err(err) => return vec![WorldgenIssueKind::InvalidJson(err.to_string())],
+    };
+
+    let removed = schema
+        .removed
+        .iter()
+        .filter(|(field, since, _)| pack_format >= *since && get_field(&json, field).is_some())
+        .map(|&(field, _, replacement)| WorldgenIssueKind::RemovedField { field, replacement });
+    let missing = schema
+        .required
+        .iter()
+        .filter(|(field, since)| pack_format >= *since && get_field(&json, field).is_none())
+        // the replaced field is reported instead
+        .filter(|(field, _)| {
+            !schema.removed.iter().any(|(removed, since, replacement)| {
+                pack_format >= *since
+                    && replacement == &Some(*field)
+                    && get_field(&json, removed).is_some()
+            })
+        })
+        .map(|&(field, _)| WorldgenIssueKind::MissingField(field));
+
+    removed.chain(missing).collect()
+}
+
+/// Get a nested field of the JSON object by its dot separated path.
+fn get_field<'a>(json: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(json, |value, key| value.get(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_biome() {
+        let mut folder = VFolder::new();
+        folder.add_file(
+            "data/pack/worldgen/biome/old.json",
+            VFile::Text(
+                r#"{"precipitation":"rain","temperature":0.8,"downfall":0.4,"effects":{},"spawners":{},"spawn_costs":{},"carvers":{"air":[]},"features":[]}"#
+                    .to_string(),
+            ),
+        );
+        folder.add_file(
+            "data/pack/dimension/broken.json",
+            VFile::Text("{\"type\":".to_string()),
+        );
+
+        let issues = validate(&folder, 61);
+        assert_eq!(issues.len(), 3);
+        assert!(matches!(issues[0].kind, WorldgenIssueKind::InvalidJson(_)));
+        assert_eq!(
+            issues[1].kind,
+            WorldgenIssueKind::RemovedField {
+                field: "precipitation",
+                replacement: Some("has_precipitation")
+            }
+        );
+        assert_eq!(
+            issues[2].kind,
+            WorldgenIssueKind::RemovedField {
+                field: "carvers.air",
+                replacement: Some("carvers")
+            }
+        );
+        assert!(issues[0].is_error() && !issues[1].is_error());
+
+        assert!(validate(&folder, 10)
+            .iter()
+            .all(|issue| issue.output_path.starts_with("data/pack/dimension")));
+    }
+}
@@ -0,0 +1,255 @@
+use shulkerscript_cli::{
+    api::{self, Event},
+    subcommands::BuildArgs,
+    terminal_output::Level,
+    testing::{run, TestProject},
+};
+
+#[test]
+fn test_init_and_build() {
+    let project = TestProject::init("example");
+    assert!(project.read("pack.toml").is_some());
+    assert!(project.read("src/main.shu").is_some());
+
+    let output = project.run(["build"]);
+    assert!(output.success, "{output:?}");
+    assert!(project.path().join("dist/example/pack.mcmeta").is_file());
+    assert!(output.texts(Level::Error).is_empty());
+}
+
+#[test]
+fn test_build_without_pack_toml() {
+    let project = TestProject::empty("missing");
+    let output = project.run(["build", "--no-discover"]);
+    assert!(!output.success);
+    assert!(output.contains(Level::Error, "does not contain a pack.toml"));
+}
+
+#[test]
+fn test_invalid_arguments() {
+    let output = run(["build", "--unknown-option"]);
+    assert!(!output.success);
+    assert!(output.error.unwrap().contains("--unknown-option"));
+}
+
+#[test]
+fn test_fix_deprecated_keys() {
+    let project = TestProject::init("deprecated");
+    let pack_toml = project.read("pack.toml").unwrap();
+    project.write("pack.toml", pack_toml.replace("format =", "pack_format ="));
+
+    let output = project.run(["fix"]);
+    assert!(output.success, "{output:?}");
+    let fixed = project.read("pack.toml").unwrap();
+    assert!(fixed.contains("format =") && !fixed.contains("pack_format"));
+}
+
+#[test]
+fn test_strict_config() {
+    let project = TestProject::init("strict");
+    let pack_toml = project.read("pack.toml").unwrap();
+    project.write(
+        "pack.toml",
+        pack_toml.replace("[pack]", "[pack]\nunknown_key = 1"),
+    );
+
+    let output = project.run(["build", "--strict-config"]);
+    assert!(!output.success);
+    assert!(output.contains(Level::Error, "unknown_key"));
+
+    // the option only applies to the run it was given to
+    let output = project.run(["build"]);
+    assert!(output.success, "{output:?}");
+    assert!(output.contains(Level::Warning, "unknown_key"));
+}
+
+#[test]
+fn test_dry_run_lists_files_as_messages() {
+    let project = TestProject::init("rehearsed");
+    let output = project.run(["build", "--dry-run"]);
+    assert!(output.success, "{output:?}");
+    assert!(output.contains(Level::Info, "pack.mcmeta"));
+    assert!(!project.path().join("dist/rehearsed").exists());
+}
+
+#[cfg(feature = "build-script")]
+#[test]
+fn test_validate_build_script_output() {
+    let project = TestProject::init("validated");
+    project.write(
+        "build.rhai",
+        "write_file(`data/${NAMESPACE}/function/Invalid.mcfunction`, \"say hi\");",
+    );
+
+    let output = project.run(["build"]);
+    assert!(!output.success);
+    assert!(output.contains(Level::Error, "Invalid.mcfunction"));
+    assert!(output.contains(Level::Error, "from the build script"));
+}
+
+#[test]
+fn test_plugins_require_opt_in() {
+    let project = TestProject::init("plugins");
+    let pack_toml = project.read("pack.toml").unwrap();
+    project.write(
+        "pack.toml",
+        format!("{pack_toml}\n[[plugins]]\npath = \"plugins/missing.so\"\n"),
+    );
+
+    let output = project.run(["build"]);
+    assert!(!output.success);
+    assert!(output.contains(Level::Error, "--allow-plugins"));
+    assert!(!project.path().join("dist/plugins").exists());
+
+    let output = project.run(["build", "--allow-plugins", "--remote", "gh:owner/repo"]);
+    assert!(!output.success);
+    assert!(output.error.unwrap().contains("--remote"));
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn test_package_checksum() {
+    let project = TestProject::init("packaged");
+    let output = project.run(["package", "--checksum"]);
+    assert!(output.success, "{output:?}");
+    assert!(project.path().join("dist/packaged.zip").is_file());
+    assert!(project
+        .read("dist/packaged.zip.sha256")
+        .is_some_and(|checksum| checksum.ends_with("  packaged.zip\n")));
+}
+
+#[test]
+fn test_clean() {
+    let project = TestProject::init("cleaned");
+    assert!(project.run(["build"]).success);
+    let output = project.run(["clean"]);
+    assert!(output.success, "{output:?}");
+    assert!(!project.path().join("dist/cleaned").exists());
+}
+
+#[test]
+fn test_init_workspace_member() {
+    let workspace = TestProject::empty("workspace");
+    workspace.write("pack.toml", "[workspace]\nmembers = [] # members\n");
+
+    let output = workspace.run(["init", "--member", "core", "--no-icon"]);
+    assert!(output.success, "{output:?}");
+    assert_eq!(
+        workspace.read("pack.toml").unwrap(),
+        "[workspace]\nmembers = [\"packs/core\"] # members\n"
+    );
+    assert!(workspace
+        .read("packs/core/pack.toml")
+        .unwrap()
+        .starts_with("extends = \"../../base-pack.toml\""));
+    assert!(workspace.read("base-pack.toml").is_some());
+
+    assert!(
+        !workspace
+            .run(["init", "--member", "core", "--no-icon"])
+            .success
+    );
+    let output = workspace.run(["build", "--workspace"]);
+    assert!(output.success, "{output:?}");
+    assert!(workspace
+        .path()
+        .join("packs/core/dist/core/pack.mcmeta")
+        .is_file());
+}
+
+#[test]
+fn test_all_syntax_errors() {
+    let project = TestProject::init("syntax");
+    project.write("src/first.shu", "fn (");
+    project.write("src/second.shu", "fn (");
+
+    let output = project.run(["build", "--all-syntax-errors"]);
+    assert!(!output.success);
+    let failed = output
+        .texts(Level::Error)
+        .into_iter()
+        .filter(|text| text.starts_with("Syntax errors in"))
+        .collect::<Vec<_>>();
+    assert_eq!(failed.len(), 2, "{output:?}");
+    assert!(failed.iter().any(|text| text.ends_with("first.shu")));
+    assert!(failed.iter().any(|text| text.ends_with("second.shu")));
+    assert!(output.contains(Level::Error, "2 of 3 scripts contain syntax errors."));
+}
+
+#[test]
+fn test_api_build_events() {
+    let project = TestProject::init("api");
+    project.write("src/broken.shu", "fn (");
+
+    let task = api::build_project(BuildArgs {
+        path: project.path().to_path_buf(),
+        ..Default::default()
+    })
+    .unwrap();
+    let events = task.events().collect::<Vec<_>>();
+    assert!(task.join().is_err());
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::ProgressBegin { .. })));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::Diagnostic(_))));
+}
+
+#[test]
+fn test_api_build_events_async() {
+    let project = TestProject::init("api-async");
+    project.write("src/broken.shu", "fn (");
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let (events, result) = runtime.block_on(async {
+        let mut task = api::nonblocking::build_project(BuildArgs {
+            path: project.path().to_path_buf(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let mut events = Vec::new();
+        while let Some(event) = task.next_event().await {
+            events.push(event);
+        }
+        (events, task.join().await)
+    });
+    assert!(result.is_err());
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::Diagnostic(_))));
+}
+
+#[test]
+fn test_namespace_collision() {
+    let library = TestProject::init("library");
+    let main = library.read("src/main.shu").unwrap();
+    library.write(
+        "src/main.shu",
+        main.replace("namespace \"library\"", "namespace \"colliding\""),
+    );
+    let project = TestProject::init("colliding");
+    let pack_toml = project.read("pack.toml").unwrap();
+    let dependency = format!("library = {{ path = {:?} }}", library.path());
+    project.write(
+        "pack.toml",
+        format!("{pack_toml}\n[dependencies]\n{dependency}\n"),
+    );
+
+    let output = project.run(["build"]);
+    assert!(!output.success);
+    assert!(output.contains(Level::Error, "Namespace \"colliding\" is declared by"));
+
+    project.write(
+        "pack.toml",
+        format!(
+            "{pack_toml}\n[dependencies]\n{}\n",
+            dependency.replace(" }", ", namespace_prefix = \"vendor_\" }")
+        ),
+    );
+    let output = project.run(["build"]);
+    assert!(output.success, "{output:?}");
+}